@@ -0,0 +1,66 @@
+#!/bin/bash
+
+# Benchmark the two concat paths on synthetic chunks:
+#   copy mode   - one ffprobe per chunk (spread over the CPUs, like --probe-concurrency),
+#                 then a stream-copy FFmpeg run
+#   raw mode    - the stream-copy FFmpeg run only (--raw-concat)
+# It runs the same commands omgrec does, without needing a recorded task.
+#
+# Usage: scripts/bench-concat.sh [CHUNKS] [SECONDS_PER_CHUNK] [SIZE]
+#   e.g. scripts/bench-concat.sh 200 10 1920x1080
+
+set -e  # Exit on error
+
+CHUNKS=${1:-100}
+SECONDS_PER_CHUNK=${2:-10}
+SIZE=${3:-1920x1080}
+FPS=30
+JOBS=$(getconf _NPROCESSORS_ONLN 2>/dev/null || echo 4)
+
+for tool in ffmpeg ffprobe; do
+    if ! command -v "$tool" >/dev/null; then
+        echo "Error: $tool not found on PATH"
+        exit 1
+    fi
+done
+
+WORK_DIR=$(mktemp -d)
+trap 'rm -rf "$WORK_DIR"' EXIT
+
+echo "Generating $CHUNKS chunks of ${SECONDS_PER_CHUNK}s at $SIZE, ${FPS}fps..."
+ffmpeg -v error -f lavfi -i "testsrc2=size=$SIZE:rate=$FPS:duration=$SECONDS_PER_CHUNK" \
+    -c:v libx264 -preset ultrafast -g $((FPS * 2)) -pix_fmt yuv420p "$WORK_DIR/chunk.mp4"
+for i in $(seq -f "%04g" 0 $((CHUNKS - 1))); do
+    cp "$WORK_DIR/chunk.mp4" "$WORK_DIR/chunk_$i.mp4"
+    echo "file '$WORK_DIR/chunk_$i.mp4'" >> "$WORK_DIR/concat_list.txt"
+done
+rm "$WORK_DIR/chunk.mp4"
+echo "Total size: $(du -sh "$WORK_DIR" | cut -f1)"
+
+# Drop the page cache's advantage from the second run by warming it for both
+cat "$WORK_DIR"/chunk_*.mp4 > /dev/null
+
+now() { date +%s.%N; }
+
+echo ""
+echo "Copy mode (probe every chunk, then stream copy)..."
+START=$(now)
+ls "$WORK_DIR"/chunk_*.mp4 | xargs -P "$JOBS" -I{} \
+    ffprobe -v error -show_format -show_streams -print_format json {} > /dev/null
+PROBED=$(now)
+ffmpeg -v error -y -f concat -safe 0 -i "$WORK_DIR/concat_list.txt" -c copy "$WORK_DIR/copy.mp4"
+END=$(now)
+COPY_PROBE=$(echo "$PROBED - $START" | bc)
+COPY_TOTAL=$(echo "$END - $START" | bc)
+
+echo "Raw mode (stream copy only)..."
+START=$(now)
+ffmpeg -v error -y -f concat -safe 0 -i "$WORK_DIR/concat_list.txt" -c copy \
+    -avoid_negative_ts make_zero "$WORK_DIR/raw.mp4"
+END=$(now)
+RAW_TOTAL=$(echo "$END - $START" | bc)
+
+echo ""
+printf "%-10s %10s %10s\n" "mode" "probe (s)" "total (s)"
+printf "%-10s %10.2f %10.2f\n" "copy" "$COPY_PROBE" "$COPY_TOTAL"
+printf "%-10s %10s %10.2f\n" "raw" "-" "$RAW_TOTAL"
@@ -0,0 +1,137 @@
+//! Adaptive quality: trade quality for smoothness when the encoder falls behind
+//!
+//! At every chunk boundary the encoder task reports how full the frame queue is.
+//! A queue that stays mostly full means frames arrive faster than they can be
+//! encoded, so the next chunk is encoded one step cheaper (lower quality level,
+//! or 25% less bitrate). Once the queue stays nearly empty, quality steps back up
+//! toward where the recording started. Changes only happen between chunks, so
+//! every chunk is encoded with a single setting.
+
+use crate::encoder::RateControl;
+
+/// Queue fill (fraction of the byte budget) that counts as falling behind
+pub const STRAINED_BACKLOG: f64 = 0.5;
+/// Queue fill low enough to count as keeping up comfortably
+const RELAXED_BACKLOG: f64 = 0.1;
+/// Consecutive strained chunk boundaries before stepping down
+const STEP_DOWN_AFTER: u32 = 2;
+/// Consecutive relaxed chunk boundaries before stepping back up
+const STEP_UP_AFTER: u32 = 3;
+
+pub struct AdaptiveQuality {
+    min_quality: u8,
+    max_quality: u8,
+    /// Bitrate the recording started with; the ceiling when stepping back up
+    max_bitrate: Option<u64>,
+    strained: u32,
+    relaxed: u32,
+}
+
+impl AdaptiveQuality {
+    /// Quality levels stay within `min_quality..=max_quality`. In bitrate mode the
+    /// starting bitrate is the ceiling and a quarter of it the floor.
+    pub fn new(min_quality: u8, max_quality: u8, start: RateControl) -> Self {
+        Self {
+            min_quality: min_quality.clamp(1, 10),
+            max_quality: max_quality.clamp(min_quality.clamp(1, 10), 10),
+            max_bitrate: match start {
+                RateControl::Bitrate(bitrate) => Some(bitrate),
+                RateControl::Quality(_) | RateControl::Crf(_) => None,
+            },
+            strained: 0,
+            relaxed: 0,
+        }
+    }
+
+    /// Rate control for the next chunk given the queue fill (0.0-1.0) at the
+    /// boundary, or None to keep the current one
+    pub fn next_rate_control(&mut self, current: RateControl, backlog: f64) -> Option<RateControl> {
+        if backlog >= STRAINED_BACKLOG {
+            self.strained += 1;
+            self.relaxed = 0;
+        } else if backlog <= RELAXED_BACKLOG {
+            self.relaxed += 1;
+            self.strained = 0;
+        } else {
+            self.strained = 0;
+            self.relaxed = 0;
+        }
+
+        let next = if self.strained >= STEP_DOWN_AFTER {
+            self.step_down(current)
+        } else if self.relaxed >= STEP_UP_AFTER {
+            self.step_up(current)
+        } else {
+            None
+        };
+
+        if next.is_some() {
+            self.strained = 0;
+            self.relaxed = 0;
+        }
+        next
+    }
+
+    fn step_down(&self, current: RateControl) -> Option<RateControl> {
+        match current {
+            RateControl::Quality(q) if q > self.min_quality => Some(RateControl::Quality(q - 1)),
+            RateControl::Bitrate(bitrate) => {
+                let floor = self.max_bitrate.unwrap_or(bitrate) / 4;
+                let lower = (bitrate * 3 / 4).max(floor);
+                (lower < bitrate).then_some(RateControl::Bitrate(lower))
+            }
+            _ => None,
+        }
+    }
+
+    fn step_up(&self, current: RateControl) -> Option<RateControl> {
+        match current {
+            RateControl::Quality(q) if q < self.max_quality => Some(RateControl::Quality(q + 1)),
+            RateControl::Bitrate(bitrate) => {
+                let ceiling = self.max_bitrate.unwrap_or(bitrate);
+                let higher = (bitrate * 4 / 3).min(ceiling);
+                (higher > bitrate).then_some(RateControl::Bitrate(higher))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_steps_down_on_sustained_backlog_and_recovers() {
+        let mut adaptive = AdaptiveQuality::new(6, 8, RateControl::Quality(8));
+        let mut rc = RateControl::Quality(8);
+
+        // A single full queue is a blip, two in a row is sustained
+        assert_eq!(adaptive.next_rate_control(rc, 0.9), None);
+        rc = adaptive.next_rate_control(rc, 0.9).unwrap();
+        assert_eq!(rc, RateControl::Quality(7));
+
+        for _ in 0..4 {
+            if let Some(next) = adaptive.next_rate_control(rc, 0.9) {
+                rc = next;
+            }
+        }
+        assert_eq!(rc, RateControl::Quality(6), "never below the minimum");
+
+        for _ in 0..2 {
+            assert_eq!(adaptive.next_rate_control(rc, 0.0), None);
+        }
+        assert_eq!(adaptive.next_rate_control(rc, 0.0), Some(RateControl::Quality(7)));
+
+        // Bitrate mode steps by 25% and never exceeds the starting bitrate
+        let mut adaptive = AdaptiveQuality::new(1, 10, RateControl::Bitrate(4_000_000));
+        adaptive.next_rate_control(RateControl::Bitrate(4_000_000), 0.8);
+        assert_eq!(
+            adaptive.next_rate_control(RateControl::Bitrate(4_000_000), 0.8),
+            Some(RateControl::Bitrate(3_000_000))
+        );
+        for _ in 0..3 {
+            assert_eq!(adaptive.next_rate_control(RateControl::Bitrate(4_000_000), 0.0), None);
+        }
+    }
+}
@@ -1,41 +1,211 @@
 use crate::cli::AudioSource;
 use crate::error::{Result, ScreenRecError};
+use chrono::{DateTime, Utc};
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{Device, Stream, StreamConfig};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::mpsc;
 
+/// Audio written beside the chunks on macOS, muxed in later by `merge-audio`
+pub const SESSION_WAV_FILE: &str = "session.wav";
+
+/// When `session.wav` samples arrived, one `AudioTimingPoint` per line, so
+/// `merge-audio` can correct for the audio clock drifting from the wall clock
+pub const SESSION_TIMING_FILE: &str = "session_timing.jsonl";
+
+/// Shortest stretch of continuous audio used to measure drift; over less, the
+/// jitter in callback arrival times outweighs the drift itself
+const MIN_DRIFT_SPAN: f64 = 300.0;
+
+/// Measured rates closer to nominal than this (relative) are left alone
+const DRIFT_TOLERANCE: f64 = 0.000_2;
+
 #[allow(dead_code)]
 pub struct AudioSample {
+    /// Interleaved samples, `channels` per frame
     pub data: Vec<f32>,
     pub sample_rate: u32,
+    pub channels: u16,
+    /// Wall-clock time the buffer reached the callback
+    pub captured_at: DateTime<Utc>,
+}
+
+/// Remix interleaved audio from `from` to `to` channels: a mono target averages
+/// every input channel, a mono source is copied to each output channel, and
+/// otherwise the first `to` channels are kept
+pub fn convert_channels(data: &[f32], from: u16, to: u16) -> Vec<f32> {
+    let (from, to) = (from.max(1) as usize, to.max(1) as usize);
+    if from == to {
+        return data.to_vec();
+    }
+
+    let mut output = Vec::with_capacity(data.len() / from * to);
+    for frame in data.chunks_exact(from) {
+        if to == 1 {
+            output.push(frame.iter().sum::<f32>() / from as f32);
+        } else if from == 1 {
+            output.resize(output.len() + to, frame[0]);
+        } else {
+            output.extend((0..to).map(|channel| frame.get(channel).copied().unwrap_or(0.0)));
+        }
+    }
+    output
 }
 
 pub struct AudioCapture {
     device: Device,
+    device_name: String,
     config: StreamConfig,
+    source: AudioSource,
+    /// `--audio-device`, kept so a reconnect reopens the same device
+    requested_device: Option<String>,
+    /// Channels in each `AudioSample` (`--audio-channels`), whatever the device has
+    output_channels: u16,
+    paused: Option<Arc<AtomicBool>>,
+}
+
+/// Whether an input device name looks like a system-audio loopback device
+fn is_system_audio_device(name: &str) -> bool {
+    #[cfg(target_os = "macos")]
+    {
+        name.contains("Soundflower") || name.contains("BlackHole") || name.contains("Loopback")
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        name.contains("Stereo Mix") || name.contains("What U Hear")
+    }
+
+    // PulseAudio/PipeWire name the loopback of each output "Monitor of <sink>"
+    #[cfg(target_os = "linux")]
+    {
+        name.to_lowercase().contains("monitor")
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        let _ = name;
+        false
+    }
+}
+
+/// An audio input device as reported by the default host
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AudioDeviceInfo {
+    pub name: String,
+    pub is_default: bool,
+    /// Looks like a loopback device usable for `--audio system`
+    pub is_system_audio: bool,
+}
+
+/// List all audio input devices on the default host
+pub fn list_input_devices() -> Vec<AudioDeviceInfo> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
+
+    let Ok(devices) = host.input_devices() else {
+        return Vec::new();
+    };
+
+    devices
+        .filter_map(|device| device.name().ok())
+        .map(|name| AudioDeviceInfo {
+            is_default: default_name.as_deref() == Some(name.as_str()),
+            is_system_audio: is_system_audio_device(&name),
+            name,
+        })
+        .collect()
+}
+
+/// The name in `names` that `wanted` refers to: an exact match, else the only
+/// name containing it (ignoring case), so "blackhole" finds "BlackHole 2ch"
+fn match_device_name<'a>(names: &[&'a str], wanted: &str) -> Option<&'a str> {
+    if let Some(name) = names.iter().find(|name| **name == wanted) {
+        return Some(name);
+    }
+
+    let wanted = wanted.to_lowercase();
+    let mut partial = names.iter().filter(|name| name.to_lowercase().contains(&wanted));
+    match (partial.next(), partial.next()) {
+        (Some(name), None) => Some(name),
+        _ => None,
+    }
 }
 
-/// Enumerate audio devices based on source type
+fn no_such_device(wanted: &str, names: &[&str]) -> ScreenRecError {
+    ScreenRecError::InvalidParameter(format!(
+        "No audio input device matches '{}'. Available: {}",
+        wanted,
+        if names.is_empty() {
+            "(none)".to_string()
+        } else {
+            names.iter().map(|name| format!("\"{}\"", name)).collect::<Vec<_>>().join(", ")
+        }
+    ))
+}
+
+/// Full name of the input device `--audio-device` refers to, or an error listing
+/// the devices there are
+pub fn resolve_input_device(wanted: &str) -> Result<String> {
+    let devices = list_input_devices();
+    let names: Vec<&str> = devices.iter().map(|device| device.name.as_str()).collect();
+    match_device_name(&names, wanted)
+        .map(str::to_string)
+        .ok_or_else(|| no_such_device(wanted, &names))
+}
+
+/// Enumerate audio devices based on source type, with the `requested` device
+/// (if any) ahead of the source's defaults
 fn enumerate_audio_devices(
     host: &cpal::Host,
     source: &AudioSource,
+    requested: Option<&str>,
 ) -> Result<Vec<(Device, String)>> {
     let mut devices = Vec::new();
 
+    if *source == AudioSource::None {
+        return Ok(devices);
+    }
+
+    if let Some(wanted) = requested {
+        let named: Vec<(Device, String)> = host
+            .input_devices()
+            .map(|iter| iter.filter_map(|device| device.name().ok().map(|name| (device, name))).collect())
+            .unwrap_or_default();
+        let names: Vec<&str> = named.iter().map(|(_, name)| name.as_str()).collect();
+        let Some(matched) = match_device_name(&names, wanted).map(str::to_string) else {
+            return Err(no_such_device(wanted, &names));
+        };
+        if let Some((device, name)) = named.into_iter().find(|(_, name)| *name == matched) {
+            log::debug!("Found requested audio device: {}", name);
+            devices.push((device, name));
+        }
+    }
+
     match source {
-        AudioSource::None => return Ok(devices),
+        AudioSource::None => {}
         AudioSource::Mic | AudioSource::Both => {
             // Try default input device first
             if let Some(device) = host.default_input_device() {
                 let name = device.name().unwrap_or_else(|_| "Default Input".to_string());
-                log::debug!("Found default input device: {}", name);
-                devices.push((device, name));
+                if !devices.iter().any(|(_, n)| n == &name) {
+                    log::debug!("Found default input device: {}", name);
+                    devices.push((device, name));
+                }
             }
 
-            // Enumerate all input devices
+            // Enumerate all input devices. A loopback device is only a fallback
+            // for `both`: `--audio mic` must not quietly record system audio
+            // when the default input fails to open.
             if let Ok(input_devices) = host.input_devices() {
                 for device in input_devices {
                     if let Ok(name) = device.name() {
+                        if *source == AudioSource::Mic && is_system_audio_device(&name) {
+                            log::debug!("Skipping loopback device for mic: {}", name);
+                            continue;
+                        }
                         if !devices.iter().any(|(_, n)| n == &name) {
                             log::debug!("Found input device: {}", name);
                             devices.push((device, name));
@@ -46,32 +216,12 @@ fn enumerate_audio_devices(
         }
         AudioSource::System => {
             // Platform-specific system audio device detection
-            #[cfg(target_os = "macos")]
-            {
-                if let Ok(devices_iter) = host.input_devices() {
-                    for device in devices_iter {
-                        if let Ok(name) = device.name() {
-                            if name.contains("Soundflower") ||
-                               name.contains("BlackHole") ||
-                               name.contains("Loopback") {
-                                log::debug!("Found system audio device: {}", name);
-                                devices.push((device, name));
-                            }
-                        }
-                    }
-                }
-            }
-
-            #[cfg(target_os = "windows")]
-            {
-                if let Ok(devices_iter) = host.input_devices() {
-                    for device in devices_iter {
-                        if let Ok(name) = device.name() {
-                            if name.contains("Stereo Mix") ||
-                               name.contains("What U Hear") {
-                                log::debug!("Found system audio device: {}", name);
-                                devices.push((device, name));
-                            }
+            if let Ok(devices_iter) = host.input_devices() {
+                for device in devices_iter {
+                    if let Ok(name) = device.name() {
+                        if is_system_audio_device(&name) && !devices.iter().any(|(_, n)| n == &name) {
+                            log::debug!("Found system audio device: {}", name);
+                            devices.push((device, name));
                         }
                     }
                 }
@@ -92,7 +242,9 @@ fn enumerate_audio_devices(
 }
 
 impl AudioCapture {
-    pub fn new(source: AudioSource) -> Result<Option<Self>> {
+    /// Open the first working device for `source`, trying the device named
+    /// `requested` first (see `resolve_input_device` for how names match)
+    pub fn new(source: AudioSource, requested: Option<&str>) -> Result<Option<Self>> {
         if source == AudioSource::None {
             log::info!("Audio capture disabled");
             return Ok(None);
@@ -103,7 +255,7 @@ impl AudioCapture {
         let host = cpal::default_host();
 
         // Enumerate available devices
-        let available_devices = enumerate_audio_devices(&host, &source)?;
+        let available_devices = enumerate_audio_devices(&host, &source, requested)?;
 
         if available_devices.is_empty() {
             log::warn!("No audio devices found for source: {:?}", source);
@@ -122,7 +274,15 @@ impl AudioCapture {
             match device.default_input_config() {
                 Ok(config) => {
                     log::info!("✓ Successfully initialized audio device: {}", device_name);
-                    return Ok(Some(Self { device, config: config.config() }));
+                    return Ok(Some(Self {
+                        device,
+                        device_name,
+                        config: config.config(),
+                        source,
+                        requested_device: requested.map(str::to_string),
+                        output_channels: 1,
+                        paused: None,
+                    }));
                 }
                 Err(e) => {
                     log::warn!("Failed to initialize device '{}': {}", device_name, e);
@@ -136,6 +296,16 @@ impl AudioCapture {
         Err(ScreenRecError::AudioDeviceUnavailable(tried_devices))
     }
 
+    /// Drop samples while `paused` is set, so audio stays aligned with paused video
+    pub fn set_pause_flag(&mut self, paused: Arc<AtomicBool>) {
+        self.paused = Some(paused);
+    }
+
+    /// Deliver `channels` channels (1 or 2), downmixing or duplicating the device's
+    pub fn set_output_channels(&mut self, channels: u16) {
+        self.output_channels = channels;
+    }
+
     #[allow(dead_code)]
     pub fn sample_rate(&self) -> u32 {
         self.config.sample_rate.0
@@ -146,15 +316,99 @@ impl AudioCapture {
         self.config.channels
     }
 
-    /// Start capturing audio and send samples through the channel
-    pub fn start_capture(self, tx: mpsc::Sender<AudioSample>) -> Result<Stream> {
+    /// Capture audio until `running` is cleared, rebuilding the stream if it dies.
+    ///
+    /// cpal reports a dead stream only through its error callback, so the callback
+    /// raises a flag and this loop re-enumerates devices for the same source and
+    /// reopens the stream, up to `max_reconnects` times with a growing delay.
+    pub fn run_with_reconnect(
+        self,
+        tx: mpsc::Sender<AudioSample>,
+        running: Arc<AtomicBool>,
+        max_reconnects: u32,
+    ) -> Result<()> {
+        let stream_failed = Arc::new(AtomicBool::new(false));
+        let source = self.source;
+        let requested_device = self.requested_device.clone();
+        let output_channels = self.output_channels;
+        let paused = self.paused.clone();
+        let mut device_name = self.device_name.clone();
+        let mut _stream = self.build_stream(tx.clone(), Arc::clone(&stream_failed))?;
+        let mut reconnect_attempts = 0u32;
+
+        while running.load(Ordering::SeqCst) && !tx.is_closed() {
+            std::thread::sleep(Duration::from_millis(200));
+
+            if !stream_failed.swap(false, Ordering::SeqCst) {
+                continue;
+            }
+
+            if reconnect_attempts >= max_reconnects {
+                log::error!(
+                    "Audio stream lost and {} reconnect attempt(s) failed. Continuing without audio.",
+                    reconnect_attempts
+                );
+                return Err(ScreenRecError::AudioError(format!(
+                    "Audio stream on '{}' could not be recovered",
+                    device_name
+                )));
+            }
+
+            reconnect_attempts += 1;
+            let delay = Duration::from_millis(500 * reconnect_attempts as u64);
+            log::warn!(
+                "Audio stream on '{}' died, reconnecting in {}ms (attempt {}/{})",
+                device_name,
+                delay.as_millis(),
+                reconnect_attempts,
+                max_reconnects
+            );
+            std::thread::sleep(delay);
+
+            // Re-enumerate so a device that came back under a new handle is picked up
+            let rebuilt = AudioCapture::new(source, requested_device.as_deref()).and_then(|capture| match capture {
+                Some(mut capture) => {
+                    capture.paused = paused.clone();
+                    capture.output_channels = output_channels;
+                    let name = capture.device_name.clone();
+                    capture
+                        .build_stream(tx.clone(), Arc::clone(&stream_failed))
+                        .map(|stream| (stream, name))
+                }
+                None => Err(ScreenRecError::AudioError("Audio capture disabled".to_string())),
+            });
+
+            match rebuilt {
+                Ok((stream, name)) => {
+                    log::info!("✓ Audio stream reconnected on device: {}", name);
+                    _stream = stream;
+                    device_name = name;
+                    reconnect_attempts = 0;
+                }
+                Err(e) => {
+                    log::warn!("Audio reconnect attempt {} failed: {}", reconnect_attempts, e);
+                    // Try again on the next tick
+                    stream_failed.store(true, Ordering::SeqCst);
+                }
+            }
+        }
+
+        log::info!("Audio capture stopped");
+        Ok(())
+    }
+
+    /// Build and start the input stream, flagging `stream_failed` on stream errors
+    fn build_stream(&self, tx: mpsc::Sender<AudioSample>, stream_failed: Arc<AtomicBool>) -> Result<Stream> {
         let sample_rate = self.config.sample_rate.0;
         let channels = self.config.channels;
+        let output_channels = self.output_channels;
+        let paused = self.paused.clone();
 
         log::info!(
-            "Starting audio capture at {} Hz, {} channels",
+            "Starting audio capture at {} Hz, {} channels (recording {})",
             sample_rate,
-            channels
+            channels,
+            output_channels
         );
 
         let stream = self
@@ -162,25 +416,23 @@ impl AudioCapture {
             .build_input_stream(
                 &self.config,
                 move |data: &[f32], _: &cpal::InputCallbackInfo| {
-                    // Convert to mono if stereo
-                    let mono_data: Vec<f32> = if channels == 2 {
-                        data.chunks_exact(2)
-                            .map(|chunk| (chunk[0] + chunk[1]) / 2.0)
-                            .collect()
-                    } else {
-                        data.to_vec()
-                    };
+                    if paused.as_ref().is_some_and(|p| p.load(Ordering::Relaxed)) {
+                        return;
+                    }
 
                     let sample = AudioSample {
-                        data: mono_data,
+                        data: convert_channels(data, channels, output_channels),
                         sample_rate,
+                        channels: output_channels,
+                        captured_at: Utc::now(),
                     };
 
                     // Try to send, but don't block if receiver is slow
                     let _ = tx.try_send(sample);
                 },
-                |err| {
+                move |err| {
                     log::error!("Audio stream error: {}", err);
+                    stream_failed.store(true, Ordering::SeqCst);
                 },
                 None,
             )
@@ -195,3 +447,284 @@ impl AudioCapture {
         Ok(stream)
     }
 }
+
+/// Streaming writer for a 16-bit PCM WAV file.
+///
+/// The RIFF and data sizes are patched in place by `update_header`, so the file
+/// stays playable if the recorder dies, and an existing file at the same sample
+/// rate is appended to (a task's sessions share one output directory).
+#[cfg(target_os = "macos")]
+pub struct WavWriter {
+    file: std::io::BufWriter<std::fs::File>,
+    sample_rate: u32,
+    channels: u16,
+    data_bytes: u32,
+}
+
+#[cfg(target_os = "macos")]
+impl WavWriter {
+    const HEADER_LEN: u64 = 44;
+
+    /// Open `path` for appending, or create it with a fresh header
+    pub fn open_or_create(path: &std::path::Path, sample_rate: u32, channels: u16) -> Result<Self> {
+        use std::io::{Read, Seek, SeekFrom};
+
+        let mut file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        let mut header = [0u8; Self::HEADER_LEN as usize];
+        let existing = file.metadata()?.len() >= Self::HEADER_LEN && file.read_exact(&mut header).is_ok();
+
+        if existing && &header[0..4] == b"RIFF" && &header[8..12] == b"WAVE" {
+            let existing_channels = u16::from_le_bytes([header[22], header[23]]);
+            let existing_rate = u32::from_le_bytes([header[24], header[25], header[26], header[27]]);
+            if existing_rate != sample_rate {
+                return Err(ScreenRecError::AudioError(format!(
+                    "{} was recorded at {} Hz but the device runs at {} Hz",
+                    path.display(),
+                    existing_rate,
+                    sample_rate
+                )));
+            }
+            if existing_channels != channels {
+                return Err(ScreenRecError::AudioError(format!(
+                    "{} was recorded with {} channel(s) but {} were requested",
+                    path.display(),
+                    existing_channels,
+                    channels
+                )));
+            }
+
+            // Trust the file length over the header in case the last update was lost,
+            // dropping any partial frame
+            let block = 2 * channels as u64;
+            let data_bytes = ((file.metadata()?.len() - Self::HEADER_LEN) / block * block) as u32;
+            file.seek(SeekFrom::Start(Self::HEADER_LEN + data_bytes as u64))?;
+            log::info!("Appending audio to existing {}", path.display());
+
+            return Ok(Self {
+                file: std::io::BufWriter::new(file),
+                sample_rate,
+                channels,
+                data_bytes,
+            });
+        }
+
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        let mut writer = Self {
+            file: std::io::BufWriter::new(file),
+            sample_rate,
+            channels,
+            data_bytes: 0,
+        };
+        writer.update_header()?;
+        Ok(writer)
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    /// Append interleaved samples, clamped and converted to 16-bit PCM
+    pub fn write_samples(&mut self, samples: &[f32]) -> Result<()> {
+        use std::io::Write;
+
+        for sample in samples {
+            let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            self.file.write_all(&value.to_le_bytes())?;
+        }
+        self.data_bytes = self.data_bytes.saturating_add(samples.len() as u32 * 2);
+        Ok(())
+    }
+
+    /// Rewrite the header with the current sizes and return to the end of the data
+    pub fn update_header(&mut self) -> Result<()> {
+        use std::io::{Seek, SeekFrom, Write};
+
+        let block_align = 2 * self.channels;
+        let byte_rate = self.sample_rate * block_align as u32;
+        let mut header = Vec::with_capacity(Self::HEADER_LEN as usize);
+        header.extend_from_slice(b"RIFF");
+        header.extend_from_slice(&(36 + self.data_bytes).to_le_bytes());
+        header.extend_from_slice(b"WAVEfmt ");
+        header.extend_from_slice(&16u32.to_le_bytes()); // fmt chunk size
+        header.extend_from_slice(&1u16.to_le_bytes()); // PCM
+        header.extend_from_slice(&self.channels.to_le_bytes());
+        header.extend_from_slice(&self.sample_rate.to_le_bytes());
+        header.extend_from_slice(&byte_rate.to_le_bytes());
+        header.extend_from_slice(&block_align.to_le_bytes());
+        header.extend_from_slice(&16u16.to_le_bytes()); // bits per sample
+        header.extend_from_slice(b"data");
+        header.extend_from_slice(&self.data_bytes.to_le_bytes());
+
+        self.file.seek(SeekFrom::Start(0))?;
+        self.file.write_all(&header)?;
+        self.file.seek(SeekFrom::Start(Self::HEADER_LEN + self.data_bytes as u64))?;
+        self.file.flush()?;
+        Ok(())
+    }
+
+    /// Frames (samples per channel) in the file so far
+    pub fn frames_written(&self) -> u64 {
+        self.data_bytes as u64 / (2 * self.channels as u64)
+    }
+
+    /// Write the final header and close the file
+    pub fn finish(mut self) -> Result<()> {
+        self.update_header()
+    }
+}
+
+/// `session.wav` frame `sample_index` arrived at `captured_at`
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AudioTimingPoint {
+    pub sample_index: u64,
+    pub captured_at: DateTime<Utc>,
+}
+
+/// Append `point` to the timing sidecar
+#[cfg(target_os = "macos")]
+pub fn append_timing_point(path: &std::path::Path, point: &AudioTimingPoint) -> Result<()> {
+    use std::io::Write;
+
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(path)?;
+    let line = serde_json::to_string(point)
+        .map_err(|e| ScreenRecError::AudioError(format!("Failed to serialize audio timing: {}", e)))?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+/// Read a timing sidecar, skipping lines that don't parse (e.g. a torn last line)
+pub fn read_timing_points(path: &std::path::Path) -> Result<Vec<AudioTimingPoint>> {
+    let contents = std::fs::read_to_string(path)?;
+    Ok(contents.lines().filter_map(|line| serde_json::from_str(line).ok()).collect())
+}
+
+/// The rate the audio device actually delivered, from how many samples arrived
+/// per wall-clock second. Pairs of points with more wall time between them than
+/// audio (a pause, or the gap between sessions) are left out. None when there is
+/// too little continuous audio to tell, or the rate is within tolerance.
+pub fn measured_sample_rate(points: &[AudioTimingPoint], nominal_rate: u32) -> Option<f64> {
+    let mut samples = 0.0;
+    let mut seconds = 0.0;
+
+    for pair in points.windows(2) {
+        let Some(sample_delta) = pair[1].sample_index.checked_sub(pair[0].sample_index) else {
+            continue;
+        };
+        let wall = (pair[1].captured_at - pair[0].captured_at).num_milliseconds() as f64 / 1000.0;
+        let audio = sample_delta as f64 / nominal_rate as f64;
+        // Drift is a fraction of a percent; anything far off is a break, not drift
+        if wall <= 0.0 || (wall - audio).abs() > 1.0 {
+            continue;
+        }
+        samples += sample_delta as f64;
+        seconds += wall;
+    }
+
+    if seconds < MIN_DRIFT_SPAN {
+        return None;
+    }
+    let rate = samples / seconds;
+    ((rate / nominal_rate as f64 - 1.0).abs() > DRIFT_TOLERANCE).then_some(rate)
+}
+
+/// Sample rate in a WAV file's header
+pub fn wav_sample_rate(path: &std::path::Path) -> Result<u32> {
+    use std::io::Read;
+
+    let mut header = [0u8; 28];
+    std::fs::File::open(path)?.read_exact(&mut header)?;
+    if &header[0..4] != b"RIFF" || &header[8..12] != b"WAVE" {
+        return Err(ScreenRecError::AudioError(format!("{} is not a WAV file", path.display())));
+    }
+    Ok(u32::from_le_bytes([header[24], header[25], header[26], header[27]]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_audio_device_names_match_exactly_then_by_unique_substring() {
+        let names = ["MacBook Pro Microphone", "BlackHole 2ch", "BlackHole 16ch", "Loopback Audio"];
+
+        assert_eq!(match_device_name(&names, "BlackHole 2ch"), Some("BlackHole 2ch"));
+        assert_eq!(match_device_name(&names, "loopback"), Some("Loopback Audio"));
+        assert_eq!(match_device_name(&names, "microphone"), Some("MacBook Pro Microphone"));
+        // Ambiguous or unknown names don't pick a device
+        assert_eq!(match_device_name(&names, "blackhole"), None);
+        assert_eq!(match_device_name(&names, "Scarlett"), None);
+
+        let err = no_such_device("Scarlett", &names[..2]).to_string();
+        assert!(err.contains("'Scarlett'"));
+        assert!(err.contains("\"MacBook Pro Microphone\", \"BlackHole 2ch\""));
+    }
+
+    #[test]
+    fn test_channels_are_downmixed_or_duplicated() {
+        let stereo = [0.25, 0.75, -1.0, 1.0];
+        assert_eq!(convert_channels(&stereo, 2, 1), vec![0.5, 0.0]);
+        assert_eq!(convert_channels(&stereo, 2, 2), stereo.to_vec());
+        assert_eq!(convert_channels(&[0.5, -0.5], 1, 2), vec![0.5, 0.5, -0.5, -0.5]);
+        // Surround devices keep front left/right
+        assert_eq!(convert_channels(&[0.1, 0.2, 0.3, 0.4], 4, 2), vec![0.1, 0.2]);
+    }
+
+    #[test]
+    fn test_drift_is_measured_over_continuous_audio_only() {
+        let start = DateTime::parse_from_rfc3339("2025-01-14T10:30:00Z").unwrap().with_timezone(&Utc);
+        let point = |sample_index: u64, ms: i64| AudioTimingPoint {
+            sample_index,
+            captured_at: start + chrono::Duration::milliseconds(ms),
+        };
+
+        // Ten minutes in one-second buffers from a device running 0.1% slow,
+        // then a five minute pause with no samples
+        let mut points: Vec<_> = (0..=600).map(|s| point(s * 47_952, s as i64 * 1000)).collect();
+        points.push(point(601 * 47_952, 901_000));
+        points.push(point(602 * 47_952, 902_000));
+
+        let rate = measured_sample_rate(&points, 48_000).unwrap();
+        assert!((rate - 47_952.0).abs() < 0.5, "measured {}", rate);
+
+        // Too short to tell, or close enough to nominal
+        assert_eq!(measured_sample_rate(&points[..60], 48_000), None);
+        let steady: Vec<_> = (0..=600).map(|s| point(s * 48_000, s as i64 * 1000)).collect();
+        assert_eq!(measured_sample_rate(&steady, 48_000), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn test_wav_writer_appends_across_sessions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.wav");
+
+        let mut writer = WavWriter::open_or_create(&path, 48_000, 1).unwrap();
+        writer.write_samples(&[0.0, 0.5, -0.5, 1.0]).unwrap();
+        writer.finish().unwrap();
+
+        let mut writer = WavWriter::open_or_create(&path, 48_000, 1).unwrap();
+        writer.write_samples(&[0.25, -1.0]).unwrap();
+        writer.finish().unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        assert_eq!(bytes.len(), 44 + 6 * 2);
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()), 36 + 12);
+        assert_eq!(u32::from_le_bytes(bytes[40..44].try_into().unwrap()), 12);
+
+        // A different rate can't be appended without corrupting the timeline
+        assert!(WavWriter::open_or_create(&path, 44_100, 1).is_err());
+        assert!(WavWriter::open_or_create(&path, 48_000, 2).is_err());
+    }
+}
@@ -1,10 +1,31 @@
-use crate::display_info::{get_all_displays_with_bounds, get_display_at_cursor, DisplayInfo};
+use crate::cli::{CaptureRegion, MaskMode, MaskRect, MultiMonitorMode};
+use crate::display_info::{
+    all_displays, display_by_index, display_containing, get_all_displays_with_bounds, get_display_at_cursor,
+    DisplayInfo,
+};
 use crate::error::{Result, ScreenRecError};
+use crate::mask::{apply_masks, frame_masks};
+use crate::overlay::TimestampOverlay;
+use crate::transform::FrameTransform;
+use crate::window_capture::{WindowCapturer, WindowInfo};
 use chrono::{DateTime, Utc};
-use scrap::{Capturer, Display};
+use scrap::Capturer;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 
+/// Capture errors in a row (e.g. after a display sleep/wake) before a single-display,
+/// window or composite recording gives up; each one recreates the capturer after a
+/// doubling backoff
+const CAPTURER_RETRIES: u32 = 5;
+const CAPTURER_RETRY_BACKOFF: Duration = Duration::from_millis(200);
+
+/// Highest frame rate recorded without `--allow-high-fps`
+pub const MAX_STANDARD_FPS: u32 = 60;
+/// Highest frame rate `--allow-high-fps` accepts
+pub const MAX_HIGH_FPS: u32 = 240;
+
 #[derive(Clone)]
 pub struct Frame {
     pub data: Vec<u8>,
@@ -16,13 +37,133 @@ pub struct Frame {
     pub display_index: usize,
 }
 
+/// What a capture run actually achieved, for spotting machines that fall behind
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct CaptureStats {
+    pub frames: u64,
+    pub duration_secs: f64,
+    pub target_fps: u32,
+    pub achieved_fps: f64,
+    /// Loop iterations that took longer than one frame interval
+    pub late_frames: u64,
+    /// Time spent paused, included in `duration_secs` but not in `achieved_fps`
+    pub paused_ms: u64,
+}
+
+impl CaptureStats {
+    fn new(frames: u64, elapsed: Duration, target_fps: u32, late_frames: u64, paused: Duration) -> Self {
+        let duration_secs = elapsed.as_secs_f64();
+        let active_secs = elapsed.saturating_sub(paused).as_secs_f64();
+        let achieved_fps = if active_secs > 0.0 {
+            frames as f64 / active_secs
+        } else {
+            0.0
+        };
+
+        Self {
+            frames,
+            duration_secs,
+            target_fps,
+            achieved_fps,
+            late_frames,
+            paused_ms: paused.as_millis() as u64,
+        }
+    }
+
+    fn log_summary(&self) {
+        log::info!(
+            "Capture duration: {:.2}s, Expected frames at {}fps: {:.0}, Actual captured: {}",
+            self.duration_secs,
+            self.target_fps,
+            self.duration_secs * self.target_fps as f64,
+            self.frames
+        );
+        log::info!(
+            "Achieved {:.1}/{} fps, {} late frames",
+            self.achieved_fps,
+            self.target_fps,
+            self.late_frames
+        );
+        if self.paused_ms > 0 {
+            log::info!("Paused for {:.1}s of the capture", self.paused_ms as f64 / 1000.0);
+        }
+        if self.late_frames > 0 && self.achieved_fps < self.target_fps as f64 * 0.9 {
+            log::warn!(
+                "Capture fell behind: {:.1} fps instead of {} ({} frames took longer than {:.1}ms)",
+                self.achieved_fps,
+                self.target_fps,
+                self.late_frames,
+                1000.0 / self.target_fps as f64
+            );
+        }
+    }
+}
+
+/// Warns once, a few seconds in, when a frame rate above `MAX_STANDARD_FPS` isn't
+/// being reached, instead of only in the end-of-run summary. Displays refreshing
+/// slower than the target (or a capturer that can't keep up) cap what arrives.
+struct FpsShortfallCheck {
+    target_fps: u32,
+    checked: bool,
+}
+
+impl FpsShortfallCheck {
+    /// Active capture time the check waits for before judging the rate
+    const AFTER: Duration = Duration::from_secs(5);
+
+    fn new(target_fps: u32) -> Option<Self> {
+        (target_fps > MAX_STANDARD_FPS).then_some(Self { target_fps, checked: false })
+    }
+
+    fn frame_captured(&mut self, frames: u64, elapsed: Duration, paused: Duration) {
+        let active = elapsed.saturating_sub(paused);
+        if self.checked || active < Self::AFTER {
+            return;
+        }
+        self.checked = true;
+
+        let achieved_fps = frames as f64 / active.as_secs_f64();
+        if achieved_fps < self.target_fps as f64 * 0.75 {
+            log::warn!(
+                "⚠️  Capturing {:.0} fps instead of {}: the display's refresh rate or the capturer can't go this fast; consider a lower --fps",
+                achieved_fps,
+                self.target_fps
+            );
+        }
+    }
+}
+
+/// Time between frames at `fps`. Nanosecond precision: at 144fps whole
+/// microseconds would run the capture fast.
+fn frame_duration(fps: u32) -> Duration {
+    Duration::from_secs(1) / fps
+}
+
+/// Consecutive capture failures, and the backoff before each capturer rebuild
+#[derive(Default)]
+struct CaptureRetry {
+    failures: u32,
+}
+
+impl CaptureRetry {
+    fn succeeded(&mut self) {
+        self.failures = 0;
+    }
+
+    /// Count a failure: how long to wait before recreating the capturer, or None
+    /// once `CAPTURER_RETRIES` rebuilds in a row haven't helped
+    fn failed(&mut self) -> Option<Duration> {
+        self.failures += 1;
+        (self.failures <= CAPTURER_RETRIES).then(|| CAPTURER_RETRY_BACKOFF * 2u32.pow(self.failures - 1))
+    }
+}
+
 struct MonitorSwitchDetector {
     check_interval: Duration,
     last_check: Instant,
     current_display: usize,
     pending_display: Option<usize>,
     pending_count: u8,
-    #[allow(dead_code)]
     displays_info: Vec<DisplayInfo>,
 }
 
@@ -52,15 +193,13 @@ impl MonitorSwitchDetector {
         // Get current cursor position
         let (cursor_x, cursor_y) = get_cursor_position()?;
 
-        // Determine which display the cursor is on
-        let cursor_display = match get_display_at_cursor(cursor_x, cursor_y) {
-            Ok(idx) => idx,
-            Err(_) => {
-                // If we can't determine display, keep current
-                return None;
-            }
-        };
+        // Determine which display the cursor is on (if none, e.g. in a gap, keep current)
+        let cursor_display = display_containing(&self.displays_info, cursor_x, cursor_y)?;
+        self.observe(cursor_display)
+    }
 
+    /// Record that the cursor is on `cursor_display`; switches after two checks in a row there
+    fn observe(&mut self, cursor_display: usize) -> Option<usize> {
         // If cursor is on same display, reset pending
         if cursor_display == self.current_display {
             self.pending_display = None;
@@ -89,21 +228,46 @@ impl MonitorSwitchDetector {
 
         None
     }
+
+    /// Start over on `current_display` after the set of displays changed
+    fn reset(&mut self, displays_info: Vec<DisplayInfo>, current_display: usize) {
+        self.displays_info = displays_info;
+        self.current_display = current_display;
+        self.pending_display = None;
+        self.pending_count = 0;
+    }
+
+    /// A desktop position (the cursor) in pixels of a display's frame
+    fn to_frame_pixels(&self, display: usize, x: i32, y: i32) -> (i32, i32) {
+        self.displays_info
+            .iter()
+            .find(|info| info.index == display)
+            .map_or((x, y), |info| info.to_frame_pixels(x, y))
+    }
 }
 
 pub struct ScreenCapture {
     display_index: usize,
     fps: u32,
     multi_monitor: bool,
+    multi_monitor_mode: MultiMonitorMode,
     monitor_switch_interval: Duration,
+    window: Option<WindowInfo>,
+    window_size: Option<(usize, usize)>,
+    region: Option<CaptureRegion>,
+    show_cursor: bool,
+    paused: Option<Arc<AtomicBool>>,
+    dedup_threshold: Option<usize>,
+    overlay: Option<TimestampOverlay>,
+    masks: Vec<MaskRect>,
+    mask_mode: MaskMode,
+    transform: FrameTransform,
 }
 
 impl ScreenCapture {
     pub fn new(display_index: usize, fps: u32, monitor_switch_interval: Duration) -> Result<Self> {
         // Just validate that the display exists
-        let displays = Display::all().map_err(|e| {
-            ScreenRecError::CaptureError(format!("Failed to enumerate displays: {}", e))
-        })?;
+        let displays = all_displays()?;
 
         if displays.is_empty() {
             return Err(ScreenRecError::CaptureError(
@@ -141,14 +305,53 @@ impl ScreenCapture {
             display_index,
             fps,
             multi_monitor,
+            multi_monitor_mode: MultiMonitorMode::Switch,
             monitor_switch_interval,
+            window: None,
+            window_size: None,
+            region: None,
+            show_cursor: true,
+            paused: None,
+            dedup_threshold: None,
+            overlay: None,
+            masks: Vec::new(),
+            mask_mode: MaskMode::Blackout,
+            transform: FrameTransform::default(),
         })
     }
 
-    fn get_display_size(&self) -> Result<(usize, usize)> {
-        let displays = Display::all().map_err(|e| {
-            ScreenRecError::CaptureError(format!("Failed to enumerate displays: {}", e))
-        })?;
+    /// Capture a single window instead of a display
+    pub fn new_for_window(window: WindowInfo, fps: u32) -> Result<Self> {
+        let capturer = WindowCapturer::new(window.clone())?;
+
+        log::info!(
+            "Window capture configured for '{}' @ {}fps",
+            capturer.window().title,
+            fps
+        );
+
+        Ok(Self {
+            display_index: 0,
+            fps,
+            multi_monitor: false,
+            multi_monitor_mode: MultiMonitorMode::Switch,
+            monitor_switch_interval: Duration::from_secs(1),
+            window: Some(window),
+            window_size: Some((capturer.width(), capturer.height())),
+            region: None,
+            show_cursor: true,
+            paused: None,
+            dedup_threshold: None,
+            overlay: None,
+            masks: Vec::new(),
+            mask_mode: MaskMode::Blackout,
+            transform: FrameTransform::default(),
+        })
+    }
+
+    /// Size of the selected display, in pixels
+    pub fn get_display_size(&self) -> Result<(usize, usize)> {
+        let displays = all_displays()?;
 
         let display = displays.get(self.display_index).ok_or_else(|| {
             ScreenRecError::CaptureError(format!("Display {} not found", self.display_index))
@@ -157,11 +360,121 @@ impl ScreenCapture {
         Ok((display.width(), display.height()))
     }
 
+    /// Crop every frame to `region` of the selected display. The region is tied to
+    /// that display, so this turns off multi-monitor switching.
+    pub fn set_region(&mut self, region: CaptureRegion) {
+        if self.multi_monitor {
+            log::info!("Region capture pins recording to display {}", self.display_index);
+        }
+        self.multi_monitor = false;
+        self.region = Some(region);
+    }
+
+    /// Record only the selected display, without following the cursor across monitors
+    pub fn set_single_display(&mut self) {
+        if self.multi_monitor {
+            log::info!("Multi-monitor tracking disabled; recording display {} only", self.display_index);
+        }
+        self.multi_monitor = false;
+    }
+
+    /// Follow the cursor between displays (switch) or record all of them at once (composite)
+    pub fn set_multi_monitor_mode(&mut self, mode: MultiMonitorMode) {
+        self.multi_monitor_mode = mode;
+    }
+
+    /// Turn cursor drawing on or off (off keeps text under the pointer readable for OCR)
+    pub fn set_show_cursor(&mut self, show_cursor: bool) {
+        self.show_cursor = show_cursor;
+    }
+
+    /// Reuse the previous frame when at most `threshold` sampled pixels changed.
+    /// Frames are still sent at the full rate, they just skip conversion and
+    /// encode as near-empty repeats.
+    pub fn set_dedup_threshold(&mut self, threshold: usize) {
+        self.dedup_threshold = Some(threshold);
+    }
+
+    /// Hide these rectangles (display pixels; window pixels for window capture)
+    /// in every frame before it leaves the capture thread
+    pub fn set_masks(&mut self, masks: Vec<MaskRect>, mode: MaskMode) {
+        self.masks = masks;
+        self.mask_mode = mode;
+    }
+
+    /// Rotate and/or mirror every frame (`--rotate`, `--flip`). Frame sizes
+    /// follow the transform, so 90 and 270 degrees swap width and height.
+    pub fn set_transform(&mut self, transform: FrameTransform) {
+        self.transform = transform;
+    }
+
+    /// `--mask` rectangles in a transformed frame that shows the `width`x`height`
+    /// part of the display starting at `origin`
+    fn transformed_masks(&self, origin: (usize, usize), width: usize, height: usize) -> Vec<MaskRect> {
+        frame_masks(&self.masks, origin, width, height)
+            .iter()
+            .map(|rect| self.transform.map_rect(rect, width, height))
+            .collect()
+    }
+
+    /// A cursor position in a `width`x`height` frame, moved to where the transform puts it
+    fn transform_point(&self, x: i32, y: i32, width: usize, height: usize) -> (i32, i32) {
+        let (x, y) = self.transform.map_point(x as i64, y as i64, width, height);
+        (x as i32, y as i32)
+    }
+
+    /// Burn a timestamp box into every frame, after the cursor is drawn
+    pub fn set_overlay(&mut self, overlay: TimestampOverlay) {
+        self.overlay = Some(overlay);
+    }
+
+    /// Pause capture while `paused` is set: no frames are grabbed or sent, so the
+    /// video timeline doesn't advance
+    pub fn set_pause_flag(&mut self, paused: Arc<AtomicBool>) {
+        self.paused = Some(paused);
+    }
+
+    /// Block while capture is paused, returning how long that was (zero if not paused)
+    fn wait_while_paused(&self, running: &Option<Arc<AtomicBool>>) -> Duration {
+        let Some(paused) = self.paused.as_ref() else {
+            return Duration::ZERO;
+        };
+        if !paused.load(Ordering::SeqCst) {
+            return Duration::ZERO;
+        }
+
+        let paused_at = Instant::now();
+        log::info!("⏸️  Capture paused");
+        while paused.load(Ordering::SeqCst) {
+            // A stop request ends the pause so the recording can finish
+            if running.as_ref().is_some_and(|r| !r.load(Ordering::SeqCst)) {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(50));
+        }
+
+        let paused_for = paused_at.elapsed();
+        log::info!("▶️  Capture resumed after {:.1}s", paused_for.as_secs_f64());
+        paused_for
+    }
+
     pub fn width(&self) -> usize {
+        if let Some((w, _)) = self.window_size {
+            return w;
+        }
+        if let Some(region) = self.region {
+            return region.width;
+        }
         self.get_display_size().map(|(w, _)| w).unwrap_or(1920)
     }
 
     pub fn height(&self) -> usize {
+        if let Some((_, h)) = self.window_size {
+            return h;
+        }
+        if let Some(region) = self.region {
+            return region.height;
+        }
         self.get_display_size().map(|(_, h)| h).unwrap_or(1080)
     }
 
@@ -176,9 +489,7 @@ impl ScreenCapture {
 
     /// Get maximum dimensions across all displays (for encoder initialization)
     pub fn get_max_dimensions(&self) -> Result<(usize, usize)> {
-        let displays = Display::all().map_err(|e| {
-            ScreenRecError::CaptureError(format!("Failed to enumerate displays: {}", e))
-        })?;
+        let displays = all_displays()?;
 
         let (mut max_width, mut max_height) = (0, 0);
         for display in displays {
@@ -191,16 +502,29 @@ impl ScreenCapture {
         Ok((max_width, max_height))
     }
 
+    /// Size of the desktop bounding box around every display (composite mode frame size)
+    pub fn get_composite_size(&self) -> Result<(usize, usize)> {
+        let displays = get_all_displays_with_bounds()?;
+        let (_, _, width, height) = union_bounds(&displays).ok_or_else(|| {
+            ScreenRecError::CaptureError("No display bounds available".to_string())
+        })?;
+        Ok((width, height))
+    }
+
     /// Start capturing frames and send them through the channel
-    /// This runs synchronously in a blocking thread
+    /// This runs synchronously in a blocking thread and returns the achieved frame rate
     pub fn start_capture_sync(
         self,
         tx: std::sync::mpsc::Sender<Frame>,
         target_frames: Option<u64>,
         running: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
-    ) -> Result<()> {
-        // Branch based on whether multi-monitor is enabled
-        if self.multi_monitor {
+    ) -> Result<CaptureStats> {
+        // Branch based on capture target
+        if self.window.is_some() {
+            self.start_capture_window(tx, target_frames, running)
+        } else if self.multi_monitor && self.multi_monitor_mode == MultiMonitorMode::Composite {
+            self.start_capture_composite(tx, target_frames, running)
+        } else if self.multi_monitor {
             self.start_capture_multi_monitor(tx, target_frames, running)
         } else {
             self.start_capture_single_monitor(tx, target_frames, running)
@@ -213,37 +537,45 @@ impl ScreenCapture {
         tx: std::sync::mpsc::Sender<Frame>,
         target_frames: Option<u64>,
         running: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
-    ) -> Result<()> {
+    ) -> Result<CaptureStats> {
         // Create capturer inside this thread (can't be moved between threads)
-        let displays = Display::all().map_err(|e| {
-            ScreenRecError::CaptureError(format!("Failed to enumerate displays: {}", e))
-        })?;
-
-        if self.display_index >= displays.len() {
-            return Err(ScreenRecError::CaptureError(format!(
-                "Display {} not found (only {} displays available)",
-                self.display_index,
-                displays.len()
-            )));
-        }
-
-        let display = displays
-            .into_iter()
-            .nth(self.display_index)
-            .ok_or_else(|| {
-                ScreenRecError::CaptureError(format!("Display {} not found", self.display_index))
-            })?;
+        let display = display_by_index(self.display_index)?;
 
         let mut capturer = Capturer::new(display).map_err(|e| {
             ScreenRecError::CaptureError(format!("Failed to create capturer: {}", e))
         })?;
 
-        let width = capturer.width();
-        let height = capturer.height();
+        let display_width = capturer.width();
+        let display_height = capturer.height();
+
+        // Crop while converting so the full frame is never copied; no region = whole display
+        let region = match self.region {
+            Some(region) => region.clamp_to(display_width, display_height).ok_or_else(|| {
+                ScreenRecError::CaptureError(format!(
+                    "Region {} is outside the {}x{} display",
+                    region, display_width, display_height
+                ))
+            })?,
+            None => CaptureRegion { x: 0, y: 0, width: display_width, height: display_height },
+        };
+        // Frames leave with the transform applied; region sizes stay for the conversion
+        let (width, height) = self.transform.output_size((region.width, region.height));
+        let masks = self.transformed_masks((region.x, region.y), region.width, region.height);
+
+        // Cursor positions are desktop coordinates; this maps them onto the display's pixels
+        let display_bounds = get_all_displays_with_bounds()
+            .ok()
+            .and_then(|displays| displays.into_iter().find(|info| info.index == self.display_index));
 
-        let frame_duration = Duration::from_micros(1_000_000 / self.fps as u64);
+        let mut dedup = self.dedup_threshold.map(|threshold| FrameDedup::new(threshold, self.fps));
+
+        let frame_duration = frame_duration(self.fps);
+        let mut fps_check = FpsShortfallCheck::new(self.fps);
         let mut start_time: Option<Instant> = None;
         let mut frame_count = 0u64;
+        let mut late_frames = 0u64;
+        let mut paused_time = Duration::ZERO;
+        let mut retry = CaptureRetry::default();
 
         log::info!("Starting screen capture...");
         log::info!("Waiting for first frame (grant screen recording permission if prompted)...");
@@ -259,6 +591,13 @@ impl ScreenCapture {
                 }
             }
 
+            // Grab nothing while paused (SIGUSR1), then re-check for stop
+            let paused_for = self.wait_while_paused(&running);
+            if !paused_for.is_zero() {
+                paused_time += paused_for;
+                continue;
+            }
+
             // Check if we should stop (target frames reached)
             if let Some(target) = target_frames {
                 if frame_count >= target {
@@ -271,17 +610,42 @@ impl ScreenCapture {
             let captured_frame = loop {
                 match capturer.frame() {
                     Ok(frame) => {
-                        // Convert BGRA to RGB (removing alpha channel for better compression)
-                        let mut rgb_data = Vec::with_capacity(width * height * 3);
-                        for chunk in frame.chunks_exact(4) {
-                            rgb_data.push(chunk[2]); // R
-                            rgb_data.push(chunk[1]); // G
-                            rgb_data.push(chunk[0]); // B
-                        }
+                        let cursor = if self.show_cursor { get_cursor_position() } else { None };
+                        let unchanged = dedup
+                            .as_mut()
+                            .and_then(|d| d.reuse(&frame, (self.display_index, width, height), cursor));
+
+                        let mut rgb_data = match unchanged {
+                            Some(rgb_data) => rgb_data,
+                            None => {
+                                // Convert BGRA to RGB (removing alpha channel for better compression)
+                                let mut rgb_data = bgra_to_rgb_transformed(
+                                    &frame,
+                                    display_width,
+                                    display_height,
+                                    &region,
+                                    &self.transform,
+                                );
+                                apply_masks(&mut rgb_data, width, height, (0, 0), &masks, self.mask_mode);
+
+                                // Draw cursor on frame, relative to the region
+                                if let Some((cursor_x, cursor_y)) = cursor {
+                                    let (x, y) = display_bounds
+                                        .as_ref()
+                                        .map_or((cursor_x, cursor_y), |info| info.to_frame_pixels(cursor_x, cursor_y));
+                                    let (x, y) = self.transform_point(x - region.x as i32, y - region.y as i32, region.width, region.height);
+                                    draw_cursor(&mut rgb_data, width, height, x, y);
+                                }
+                                if let Some(ref mut dedup) = dedup {
+                                    dedup.remember(&rgb_data);
+                                }
+                                rgb_data
+                            }
+                        };
 
-                        // Draw cursor on frame
-                        if let Some((cursor_x, cursor_y)) = get_cursor_position() {
-                            draw_cursor(&mut rgb_data, width, height, cursor_x, cursor_y);
+                        // Drawn after dedup so reused frames still show the current time
+                        if let Some(ref overlay) = self.overlay {
+                            overlay.draw(&mut rgb_data, width, height, chrono::Local::now());
                         }
 
                         // Start the timer on first successful frame
@@ -290,27 +654,52 @@ impl ScreenCapture {
                             log::info!("First frame captured, recording started!");
                         }
 
-                        break Frame {
+                        break Ok(Frame {
                             data: rgb_data,
                             width,
                             height,
                             timestamp: start_time.unwrap().elapsed(),
                             captured_at: Utc::now(),
                             display_index: self.display_index,
-                        };
+                        });
                     }
                     Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
                         // Frame not ready yet, wait a bit and retry
                         std::thread::sleep(Duration::from_millis(1));
                         // Continue the inner loop to retry capture
                     }
-                    Err(e) => {
+                    Err(e) => break Err(e),
+                }
+            };
+
+            // A failing capturer is recreated a few times before the recording gives up.
+            // Frame counts and timestamps carry on, so PTS stays continuous across the gap.
+            let captured_frame = match captured_frame {
+                Ok(frame) => {
+                    retry.succeeded();
+                    frame
+                }
+                Err(e) => {
+                    let Some(backoff) = retry.failed() else {
                         log::error!("Frame capture error: {}", e);
                         return Err(ScreenRecError::CaptureError(format!(
-                            "Failed to capture frame: {}",
-                            e
+                            "Failed to capture frame after {} attempts to recreate the capturer: {}",
+                            CAPTURER_RETRIES, e
                         )));
+                    };
+                    log::warn!(
+                        "⚠️  Frame capture error: {}; recreating the capturer in {:?} (attempt {}/{})",
+                        e, backoff, retry.failures, CAPTURER_RETRIES
+                    );
+                    std::thread::sleep(backoff);
+                    match recreate_capturer(self.display_index, (display_width, display_height)) {
+                        Ok(new_capturer) => {
+                            capturer = new_capturer;
+                            log::info!("Recreated the capturer for display {}", self.display_index);
+                        }
+                        Err(e) => log::warn!("⚠️  {}", e),
                     }
+                    continue;
                 }
             };
 
@@ -324,22 +713,153 @@ impl ScreenCapture {
             if frame_count % (self.fps as u64) == 0 {
                 log::debug!("Captured {} frames", frame_count);
             }
+            if let Some(check) = fps_check.as_mut() {
+                check.frame_captured(frame_count, start_time.map(|st| st.elapsed()).unwrap_or_default(), paused_time);
+            }
 
             // Maintain frame rate
             let elapsed = frame_start.elapsed();
             if elapsed < frame_duration {
                 std::thread::sleep(frame_duration - elapsed);
+            } else if elapsed > frame_duration {
+                late_frames += 1;
             }
         }
 
         let elapsed_time = start_time.map(|st| st.elapsed()).unwrap_or(Duration::from_secs(0));
         log::info!("Screen capture finished. Total frames: {}", frame_count);
-        log::info!("Capture duration: {:.2}s, Expected frames at {}fps: {:.0}, Actual captured: {}",
-                   elapsed_time.as_secs_f64(),
-                   self.fps,
-                   elapsed_time.as_secs_f64() * self.fps as f64,
-                   frame_count);
-        Ok(())
+        if let Some(ref dedup) = dedup {
+            dedup.log_summary(frame_count);
+        }
+        let stats = CaptureStats::new(frame_count, elapsed_time, self.fps, late_frames, paused_time);
+        stats.log_summary();
+        Ok(stats)
+    }
+
+    /// Window capture path: reads one window's composited content each tick
+    fn start_capture_window(
+        self,
+        tx: std::sync::mpsc::Sender<Frame>,
+        target_frames: Option<u64>,
+        running: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
+    ) -> Result<CaptureStats> {
+        // Follow the window resolved up front by id, even if its title changes
+        let window = self.window.clone().ok_or_else(|| {
+            ScreenRecError::CaptureError("No window selected for window capture".to_string())
+        })?;
+        let mut capturer = WindowCapturer::new(window.clone())?;
+
+        let frame_duration = frame_duration(self.fps);
+        let mut fps_check = FpsShortfallCheck::new(self.fps);
+        let start_time = Instant::now();
+        let mut frame_count = 0u64;
+        let mut late_frames = 0u64;
+        let mut paused_time = Duration::ZERO;
+        let mut retry = CaptureRetry::default();
+
+        log::info!("Starting window capture of '{}'...", capturer.window().title);
+
+        loop {
+            let frame_start = Instant::now();
+
+            // Check if we should stop (Ctrl+C pressed)
+            if let Some(ref running_flag) = running {
+                if !running_flag.load(std::sync::atomic::Ordering::SeqCst) {
+                    log::info!("Stop signal received, finishing capture...");
+                    break;
+                }
+            }
+
+            // Grab nothing while paused (SIGUSR1), then re-check for stop
+            let paused_for = self.wait_while_paused(&running);
+            if !paused_for.is_zero() {
+                paused_time += paused_for;
+                continue;
+            }
+
+            // Check if we should stop (target frames reached)
+            if let Some(target) = target_frames {
+                if frame_count >= target {
+                    log::info!("Target frames reached: {}/{}", frame_count, target);
+                    break;
+                }
+            }
+
+            // Frames keep their real size; the encoder scales/pads them if the window is resized.
+            // A failed grab (e.g. the window is briefly minimized) skips the tick and
+            // recreates the capturer, like the display loop, before giving up.
+            let (mut rgb_data, width, height) = match capturer.frame() {
+                Ok(frame) => {
+                    retry.succeeded();
+                    frame
+                }
+                Err(e) => {
+                    let Some(backoff) = retry.failed() else {
+                        log::error!("Window capture error: {}", e);
+                        return Err(ScreenRecError::CaptureError(format!(
+                            "Failed to capture window after {} attempts to recreate the capturer: {}",
+                            CAPTURER_RETRIES, e
+                        )));
+                    };
+                    log::warn!(
+                        "⚠️  Window capture error: {}; recreating the capturer in {:?} (attempt {}/{})",
+                        e, backoff, retry.failures, CAPTURER_RETRIES
+                    );
+                    std::thread::sleep(backoff);
+                    match WindowCapturer::new(window.clone()) {
+                        Ok(new_capturer) => capturer = new_capturer,
+                        Err(e) => log::warn!("⚠️  {}", e),
+                    }
+                    continue;
+                }
+            };
+            apply_masks(&mut rgb_data, width, height, (0, 0), &self.masks, self.mask_mode);
+            let (width, height) = if self.transform.is_identity() {
+                (width, height)
+            } else {
+                rgb_data = self.transform.apply_rgb(&rgb_data, width, height);
+                self.transform.output_size((width, height))
+            };
+            if let Some(ref overlay) = self.overlay {
+                overlay.draw(&mut rgb_data, width, height, chrono::Local::now());
+            }
+
+            let captured_frame = Frame {
+                data: rgb_data,
+                width,
+                height,
+                timestamp: start_time.elapsed(),
+                captured_at: Utc::now(),
+                display_index: self.display_index,
+            };
+
+            // Send frame through channel
+            if tx.send(captured_frame).is_err() {
+                log::warn!("Frame receiver dropped, stopping capture");
+                break;
+            }
+
+            frame_count += 1;
+            if frame_count % (self.fps as u64) == 0 {
+                log::debug!("Captured {} window frames", frame_count);
+            }
+            if let Some(check) = fps_check.as_mut() {
+                check.frame_captured(frame_count, start_time.elapsed(), paused_time);
+            }
+
+            // Maintain frame rate
+            let elapsed = frame_start.elapsed();
+            if elapsed < frame_duration {
+                std::thread::sleep(frame_duration - elapsed);
+            } else if elapsed > frame_duration {
+                late_frames += 1;
+            }
+        }
+
+        log::info!("Window capture finished. Total frames: {}", frame_count);
+        let stats = CaptureStats::new(frame_count, start_time.elapsed(), self.fps, late_frames, paused_time);
+        stats.log_summary();
+        Ok(stats)
     }
 
     /// Multi-monitor capture path with cursor-based display switching
@@ -348,11 +868,9 @@ impl ScreenCapture {
         tx: std::sync::mpsc::Sender<Frame>,
         target_frames: Option<u64>,
         running: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
-    ) -> Result<()> {
+    ) -> Result<CaptureStats> {
         // Get all displays and create capturers for each
-        let displays = Display::all().map_err(|e| {
-            ScreenRecError::CaptureError(format!("Failed to enumerate displays: {}", e))
-        })?;
+        let displays = all_displays()?;
 
         // Determine initial display based on cursor position
         let initial_display = if let Some((cursor_x, cursor_y)) = get_cursor_position() {
@@ -384,12 +902,18 @@ impl ScreenCapture {
             ScreenRecError::CaptureError(format!("Capturer for display {} not found", current_display_index))
         })?;
 
+        // Size of the current display as captured; frames leave transformed
         let mut width = current_capturer.width();
         let mut height = current_capturer.height();
 
-        let frame_duration = Duration::from_micros(1_000_000 / self.fps as u64);
+        let mut dedup = self.dedup_threshold.map(|threshold| FrameDedup::new(threshold, self.fps));
+
+        let frame_duration = frame_duration(self.fps);
+        let mut fps_check = FpsShortfallCheck::new(self.fps);
         let mut start_time: Option<Instant> = None;
         let mut frame_count = 0u64;
+        let mut late_frames = 0u64;
+        let mut paused_time = Duration::ZERO;
 
         log::info!("Starting multi-monitor screen capture...");
         log::info!("Waiting for first frame (grant screen recording permission if prompted)...");
@@ -405,6 +929,13 @@ impl ScreenCapture {
                 }
             }
 
+            // Grab nothing while paused (SIGUSR1), then re-check for stop
+            let paused_for = self.wait_while_paused(&running);
+            if !paused_for.is_zero() {
+                paused_time += paused_for;
+                continue;
+            }
+
             // Check if we should stop (target frames reached)
             if let Some(target) = target_frames {
                 if frame_count >= target {
@@ -426,23 +957,46 @@ impl ScreenCapture {
             // Capture frame - retry loop for WouldBlock
             let captured_frame = loop {
                 // Get current capturer
-                let current_capturer = capturers.get_mut(&current_display_index).ok_or_else(|| {
-                    ScreenRecError::CaptureError(format!("Capturer for display {} not found", current_display_index))
-                })?;
+                let Some(current_capturer) = capturers.get_mut(&current_display_index) else {
+                    break Err(std::io::Error::new(
+                        std::io::ErrorKind::NotFound,
+                        format!("capturer for display {} not found", current_display_index),
+                    ));
+                };
 
                 match current_capturer.frame() {
                     Ok(frame) => {
-                        // Convert BGRA to RGB (removing alpha channel for better compression)
-                        let mut rgb_data = Vec::with_capacity(width * height * 3);
-                        for chunk in frame.chunks_exact(4) {
-                            rgb_data.push(chunk[2]); // R
-                            rgb_data.push(chunk[1]); // G
-                            rgb_data.push(chunk[0]); // B
-                        }
+                        let cursor = if self.show_cursor { get_cursor_position() } else { None };
+                        let unchanged = dedup
+                            .as_mut()
+                            .and_then(|d| d.reuse(&frame, (current_display_index, width, height), cursor));
+                        let (out_width, out_height) = self.transform.output_size((width, height));
+
+                        let mut rgb_data = match unchanged {
+                            Some(rgb_data) => rgb_data,
+                            None => {
+                                // Convert BGRA to RGB (removing alpha channel for better compression)
+                                let full = CaptureRegion { x: 0, y: 0, width, height };
+                                let mut rgb_data = bgra_to_rgb_transformed(&frame, width, height, &full, &self.transform);
+                                let masks = self.transformed_masks((0, 0), width, height);
+                                apply_masks(&mut rgb_data, out_width, out_height, (0, 0), &masks, self.mask_mode);
+
+                                // Draw cursor on frame, relative to the display it's captured from
+                                if let Some((cursor_x, cursor_y)) = cursor {
+                                    let (x, y) = switch_detector.to_frame_pixels(current_display_index, cursor_x, cursor_y);
+                                    let (x, y) = self.transform_point(x, y, width, height);
+                                    draw_cursor(&mut rgb_data, out_width, out_height, x, y);
+                                }
+                                if let Some(ref mut dedup) = dedup {
+                                    dedup.remember(&rgb_data);
+                                }
+                                rgb_data
+                            }
+                        };
 
-                        // Draw cursor on frame
-                        if let Some((cursor_x, cursor_y)) = get_cursor_position() {
-                            draw_cursor(&mut rgb_data, width, height, cursor_x, cursor_y);
+                        // Drawn after dedup so reused frames still show the current time
+                        if let Some(ref overlay) = self.overlay {
+                            overlay.draw(&mut rgb_data, out_width, out_height, chrono::Local::now());
                         }
 
                         // Start the timer on first successful frame
@@ -451,27 +1005,37 @@ impl ScreenCapture {
                             log::info!("First frame captured, recording started!");
                         }
 
-                        break Frame {
+                        break Ok(Frame {
                             data: rgb_data,
-                            width,
-                            height,
+                            width: out_width,
+                            height: out_height,
                             timestamp: start_time.unwrap().elapsed(),
                             captured_at: Utc::now(),
                             display_index: current_display_index,
-                        };
+                        });
                     }
                     Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
                         // Frame not ready yet, wait a bit and retry
                         std::thread::sleep(Duration::from_millis(1));
                         // Continue the inner loop to retry capture
                     }
-                    Err(e) => {
-                        log::error!("Frame capture error: {}", e);
-                        return Err(ScreenRecError::CaptureError(format!(
-                            "Failed to capture frame: {}",
-                            e
-                        )));
-                    }
+                    Err(e) => break Err(e),
+                }
+            };
+
+            // A display that stops delivering frames may have been unplugged (a dock
+            // disconnect): carry on from the primary display instead of failing
+            let captured_frame = match captured_frame {
+                Ok(frame) => frame,
+                Err(e) => {
+                    let (displays_info, display) = recover_from_disconnect(&mut capturers, current_display_index, e)?;
+                    let capturer = &capturers[&display];
+                    current_display_index = display;
+                    width = capturer.width();
+                    height = capturer.height();
+                    switch_detector.reset(displays_info, display);
+                    log::info!("Continuing on display {} ({}x{})", display, width, height);
+                    continue;
                 }
             };
 
@@ -485,29 +1049,457 @@ impl ScreenCapture {
             if frame_count % (self.fps as u64) == 0 {
                 log::debug!("Captured {} frames", frame_count);
             }
+            if let Some(check) = fps_check.as_mut() {
+                check.frame_captured(frame_count, start_time.map(|st| st.elapsed()).unwrap_or_default(), paused_time);
+            }
 
             // Maintain frame rate
             let elapsed = frame_start.elapsed();
             if elapsed < frame_duration {
                 std::thread::sleep(frame_duration - elapsed);
+            } else if elapsed > frame_duration {
+                late_frames += 1;
             }
         }
 
         let elapsed_time = start_time.map(|st| st.elapsed()).unwrap_or(Duration::from_secs(0));
         log::info!("Multi-monitor screen capture finished. Total frames: {}", frame_count);
-        log::info!("Capture duration: {:.2}s, Expected frames at {}fps: {:.0}, Actual captured: {}",
-                   elapsed_time.as_secs_f64(),
-                   self.fps,
-                   elapsed_time.as_secs_f64() * self.fps as f64,
-                   frame_count);
-        Ok(())
+        if let Some(ref dedup) = dedup {
+            dedup.log_summary(frame_count);
+        }
+        let stats = CaptureStats::new(frame_count, elapsed_time, self.fps, late_frames, paused_time);
+        stats.log_summary();
+        Ok(stats)
     }
+
+    /// Composite capture path: every display is grabbed each tick and copied into
+    /// one frame at its desktop position
+    fn start_capture_composite(
+        self,
+        tx: std::sync::mpsc::Sender<Frame>,
+        target_frames: Option<u64>,
+        running: Option<std::sync::Arc<std::sync::atomic::AtomicBool>>,
+    ) -> Result<CaptureStats> {
+        let displays_info = get_all_displays_with_bounds()?;
+        let (min_x, min_y, union_width, union_height) = union_bounds(&displays_info).ok_or_else(|| {
+            ScreenRecError::CaptureError("No display bounds available".to_string())
+        })?;
+        let scale = canvas_scale(&displays_info);
+        // Same even size the encoder is opened with, so frames don't need scaling
+        let width = union_width & !1;
+        let height = union_height & !1;
+
+        // One capturer per display that has bounds, with its offset in the composite frame
+        let mut tiles = Vec::new();
+        for (index, display) in all_displays()?.into_iter().enumerate() {
+            let Some(info) = displays_info.iter().find(|info| info.index == index) else {
+                log::warn!("Display {} has no bounds; leaving it out of the composite", index);
+                continue;
+            };
+            let capturer = Capturer::new(display).map_err(|e| {
+                ScreenRecError::CaptureError(format!("Failed to create capturer for display {}: {}", index, e))
+            })?;
+            let offset = (to_canvas(info.x - min_x, scale) as usize, to_canvas(info.y - min_y, scale) as usize);
+            tiles.push((index, capturer, offset));
+        }
+
+        if self.dedup_threshold.is_some() {
+            log::warn!("--dedup-threshold is not supported in composite mode; every frame is converted");
+        }
+        log::info!(
+            "Compositing {} displays into one {}x{} frame",
+            tiles.len(),
+            width,
+            height
+        );
+
+        // Last good frame per display, reused while its capturer has nothing new
+        let mut last_frames: Vec<Option<Vec<u8>>> = vec![None; tiles.len()];
+        // Failures per display, and when a failed display's capturer is due to be recreated
+        let mut retries: Vec<(CaptureRetry, Option<Instant>)> = tiles.iter().map(|_| Default::default()).collect();
+
+        let frame_duration = frame_duration(self.fps);
+        let mut fps_check = FpsShortfallCheck::new(self.fps);
+        let mut start_time: Option<Instant> = None;
+        let mut frame_count = 0u64;
+        let mut late_frames = 0u64;
+        let mut paused_time = Duration::ZERO;
+
+        log::info!("Starting composite screen capture...");
+        log::info!("Waiting for first frame (grant screen recording permission if prompted)...");
+
+        loop {
+            let frame_start = Instant::now();
+
+            // Check if we should stop (Ctrl+C pressed)
+            if let Some(ref running_flag) = running {
+                if !running_flag.load(std::sync::atomic::Ordering::SeqCst) {
+                    log::info!("Stop signal received, finishing capture...");
+                    break;
+                }
+            }
+
+            // Grab nothing while paused (SIGUSR1), then re-check for stop
+            let paused_for = self.wait_while_paused(&running);
+            if !paused_for.is_zero() {
+                paused_time += paused_for;
+                continue;
+            }
+
+            // Check if we should stop (target frames reached)
+            if let Some(target) = target_frames {
+                if frame_count >= target {
+                    log::info!("Target frames reached: {}/{}", frame_count, target);
+                    break;
+                }
+            }
+
+            let tiles_and_state = tiles.iter_mut().zip(last_frames.iter_mut()).zip(retries.iter_mut());
+            for (((index, capturer, offset), last_frame), (retry, recreate_at)) in tiles_and_state {
+                let (tile_width, tile_height) = (capturer.width(), capturer.height());
+
+                // A failed display waits out its backoff without holding up the others
+                if let Some(at) = *recreate_at {
+                    if Instant::now() < at {
+                        continue;
+                    }
+                    *recreate_at = None;
+                    match recreate_capturer(*index, (tile_width, tile_height)) {
+                        Ok(new_capturer) => {
+                            *capturer = new_capturer;
+                            log::info!("Recreated the capturer for display {}", index);
+                        }
+                        Err(e) => log::warn!("⚠️  {}", e),
+                    }
+                }
+
+                match capturer.frame() {
+                    Ok(frame) => {
+                        retry.succeeded();
+                        let mut rgb_data = bgra_to_rgb(&frame, tile_width, tile_height);
+                        // Masks are in composite-frame pixels; this tile shows the canvas from its offset
+                        apply_masks(&mut rgb_data, tile_width, tile_height, *offset, &self.masks, self.mask_mode);
+                        *last_frame = Some(rgb_data);
+                    }
+                    Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                        // Nothing new on this display; its last frame is reused
+                    }
+                    Err(e) => {
+                        // Like the single-display loop, but the display's last frame stays
+                        // in the composite while its capturer is recreated
+                        let Some(backoff) = retry.failed() else {
+                            log::error!("Frame capture error on display {}: {}", index, e);
+                            return Err(ScreenRecError::CaptureError(format!(
+                                "Failed to capture frame from display {} after {} attempts to recreate the capturer: {}",
+                                index, CAPTURER_RETRIES, e
+                            )));
+                        };
+                        log::warn!(
+                            "⚠️  Frame capture error on display {}: {}; recreating its capturer in {:?} (attempt {}/{})",
+                            index, e, backoff, retry.failures, CAPTURER_RETRIES
+                        );
+                        *recreate_at = Some(Instant::now() + backoff);
+                    }
+                }
+            }
+
+            // Wait until every display has delivered once, so the first frame has no holes
+            if start_time.is_none() && last_frames.iter().any(Option::is_none) {
+                std::thread::sleep(Duration::from_millis(1));
+                continue;
+            }
+
+            let mut rgb_data = vec![0u8; width * height * 3];
+            for ((_, capturer, offset), last_frame) in tiles.iter().zip(&last_frames) {
+                if let Some(tile) = last_frame {
+                    blit_rgb(&mut rgb_data, width, height, tile, capturer.width(), capturer.height(), *offset);
+                }
+            }
+
+            if self.show_cursor {
+                if let Some((cursor_x, cursor_y)) = get_cursor_position() {
+                    let (x, y) = (to_canvas(cursor_x - min_x, scale), to_canvas(cursor_y - min_y, scale));
+                    draw_cursor(&mut rgb_data, width, height, x, y);
+                }
+            }
+
+            // The canvas is assembled in desktop layout, then rotated as a whole
+            let (out_width, out_height) = self.transform.output_size((width, height));
+            if !self.transform.is_identity() {
+                rgb_data = self.transform.apply_rgb(&rgb_data, width, height);
+            }
+
+            if let Some(ref overlay) = self.overlay {
+                overlay.draw(&mut rgb_data, out_width, out_height, chrono::Local::now());
+            }
+
+            // Start the timer on first successful frame
+            if start_time.is_none() {
+                start_time = Some(Instant::now());
+                log::info!("First frame captured, recording started!");
+            }
+
+            let captured_frame = Frame {
+                data: rgb_data,
+                width: out_width,
+                height: out_height,
+                timestamp: start_time.unwrap().elapsed(),
+                captured_at: Utc::now(),
+                display_index: self.display_index,
+            };
+
+            // Send frame through channel
+            if tx.send(captured_frame).is_err() {
+                log::warn!("Frame receiver dropped, stopping capture");
+                break;
+            }
+
+            frame_count += 1;
+            if frame_count % (self.fps as u64) == 0 {
+                log::debug!("Captured {} composite frames", frame_count);
+            }
+            if let Some(check) = fps_check.as_mut() {
+                check.frame_captured(frame_count, start_time.map(|st| st.elapsed()).unwrap_or_default(), paused_time);
+            }
+
+            // Maintain frame rate
+            let elapsed = frame_start.elapsed();
+            if elapsed < frame_duration {
+                std::thread::sleep(frame_duration - elapsed);
+            } else if elapsed > frame_duration {
+                late_frames += 1;
+            }
+        }
+
+        let elapsed_time = start_time.map(|st| st.elapsed()).unwrap_or(Duration::from_secs(0));
+        log::info!("Composite screen capture finished. Total frames: {}", frame_count);
+        let stats = CaptureStats::new(frame_count, elapsed_time, self.fps, late_frames, paused_time);
+        stats.log_summary();
+        Ok(stats)
+    }
+}
+
+/// A fresh capturer for display `index`, which must still be `size` (the region,
+/// masks and cursor mapping were worked out for that size)
+fn recreate_capturer(index: usize, size: (usize, usize)) -> Result<Capturer> {
+    let capturer = Capturer::new(display_by_index(index)?).map_err(|e| {
+        ScreenRecError::CaptureError(format!("Failed to recreate capturer for display {}: {}", index, e))
+    })?;
+    let new_size = (capturer.width(), capturer.height());
+    if new_size != size {
+        return Err(ScreenRecError::CaptureError(format!(
+            "Display {} is now {}x{} instead of {}x{}",
+            index, new_size.0, new_size.1, size.0, size.1
+        )));
+    }
+    Ok(capturer)
+}
+
+/// Handle a capture error on display `lost`. If the display is gone (fewer
+/// displays than capturers), rebuild the capturers for the displays that remain
+/// and return the new topology and the display to continue on, the primary one.
+/// Any other error is returned as it was.
+fn recover_from_disconnect(
+    capturers: &mut HashMap<usize, Capturer>,
+    lost: usize,
+    error: std::io::Error,
+) -> Result<(Vec<DisplayInfo>, usize)> {
+    let capture_error = |e: &std::io::Error| ScreenRecError::CaptureError(format!("Failed to capture frame: {}", e));
+
+    let displays_info = match get_all_displays_with_bounds() {
+        Ok(displays_info) if displays_info.len() < capturers.len() => displays_info,
+        _ => {
+            log::error!("Frame capture error: {}", error);
+            return Err(capture_error(&error));
+        }
+    };
+
+    log::warn!(
+        "🔌 Display {} stopped capturing ({}); {} display(s) now connected, was {}",
+        lost,
+        error,
+        displays_info.len(),
+        capturers.len()
+    );
+
+    // Display indexes shift when one goes away, so every capturer is rebuilt
+    capturers.clear();
+    for (index, display) in all_displays()?.into_iter().enumerate() {
+        match Capturer::new(display) {
+            Ok(capturer) => {
+                capturers.insert(index, capturer);
+            }
+            Err(e) => log::warn!("⚠️  Failed to create capturer for display {}: {}", index, e),
+        }
+    }
+
+    let display = fallback_display(&displays_info)
+        .filter(|display| capturers.contains_key(display))
+        .or_else(|| capturers.keys().min().copied())
+        .ok_or_else(|| capture_error(&error))?;
+    Ok((displays_info, display))
+}
+
+/// The primary display, or the first one if none is marked primary
+fn fallback_display(displays: &[DisplayInfo]) -> Option<usize> {
+    displays
+        .iter()
+        .find(|display| display.is_primary)
+        .or_else(|| displays.first())
+        .map(|display| display.index)
+}
+
+/// Top-left corner (in desktop coordinates) and pixel size of the smallest desktop
+/// rectangle covering every display, at the composite's [`canvas_scale`]
+fn union_bounds(displays: &[DisplayInfo]) -> Option<(i32, i32, usize, usize)> {
+    let scale = canvas_scale(displays);
+    let min_x = displays.iter().map(|d| d.x).min()?;
+    let min_y = displays.iter().map(|d| d.y).min()?;
+    let max_x = displays.iter().map(|d| d.x + d.desktop_width()).max()?;
+    let max_y = displays.iter().map(|d| d.y + d.desktop_height()).max()?;
+    Some((min_x, min_y, to_canvas(max_x - min_x, scale) as usize, to_canvas(max_y - min_y, scale) as usize))
+}
+
+/// Composite pixels per desktop unit: the sharpest display's, so no display is
+/// downscaled. On mixed-DPI setups the others don't fill their whole slot.
+fn canvas_scale(displays: &[DisplayInfo]) -> f64 {
+    displays.iter().map(|d| d.scale_factor).reduce(f64::max).unwrap_or(1.0)
+}
+
+/// A desktop distance in composite pixels
+fn to_canvas(desktop: i32, scale: f64) -> i32 {
+    (desktop as f64 * scale).round() as i32
+}
+
+/// Copy a packed RGB image into a larger one with its top-left corner at
+/// `offset`, clipping whatever falls outside the destination
+fn blit_rgb(
+    dst: &mut [u8],
+    dst_width: usize,
+    dst_height: usize,
+    src: &[u8],
+    src_width: usize,
+    src_height: usize,
+    offset: (usize, usize),
+) {
+    let (x, y) = offset;
+    if x >= dst_width || y >= dst_height {
+        return;
+    }
+    let row_bytes = src_width.min(dst_width - x) * 3;
+    for row in 0..src_height.min(dst_height - y) {
+        let src_start = row * src_width * 3;
+        let dst_start = ((y + row) * dst_width + x) * 3;
+        let Some(src_row) = src.get(src_start..src_start + row_bytes) else {
+            break;
+        };
+        dst[dst_start..dst_start + row_bytes].copy_from_slice(src_row);
+    }
+}
+
+/// The BGRA bytes of each row of `region` in a `width`x`height` frame from scrap.
+///
+/// scrap may pad each row so the buffer is larger than `width * height * 4`.
+/// The stride is derived from the buffer length and only the first
+/// `width * 4` bytes of each row are treated as pixels. The region must
+/// already be clamped to the frame.
+fn bgra_rows<'a>(
+    frame: &'a [u8],
+    width: usize,
+    height: usize,
+    region: &CaptureRegion,
+) -> impl Iterator<Item = &'a [u8]> {
+    let row_bytes = width * 4;
+    let stride = frame.len().checked_div(height).unwrap_or(row_bytes);
+    if stride != row_bytes {
+        log::trace!("Frame stride {} differs from row width {} bytes", stride, row_bytes);
+    }
+
+    let row_start = region.x * 4;
+    let row_end = (region.x + region.width) * 4;
+    frame
+        .chunks(stride.max(row_bytes))
+        .skip(region.y)
+        .take(region.height)
+        .map(move |row| &row[row_start.min(row.len())..row_end.min(row.len())])
+}
+
+/// Convert a BGRA frame from scrap into tightly packed RGB, skipping row padding
+pub(crate) fn bgra_to_rgb(frame: &[u8], width: usize, height: usize) -> Vec<u8> {
+    bgra_to_rgb_region(frame, width, height, &CaptureRegion { x: 0, y: 0, width, height })
+}
+
+/// Convert a BGRA frame from scrap into tightly packed RGBA, skipping row
+/// padding the same way as [`bgra_to_rgb`] (for screenshots, which keep alpha)
+pub(crate) fn bgra_to_rgba(frame: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut rgba_data = Vec::with_capacity(width * height * 4);
+    for row in bgra_rows(frame, width, height, &CaptureRegion { x: 0, y: 0, width, height }) {
+        for chunk in row.chunks_exact(4) {
+            rgba_data.extend_from_slice(&[chunk[2], chunk[1], chunk[0], chunk[3]]);
+        }
+    }
+    rgba_data
+}
+
+/// Convert `region` of a `width`x`height` BGRA frame into packed RGB with
+/// `transform` applied, writing each pixel straight to its rotated position
+fn bgra_to_rgb_transformed(
+    frame: &[u8],
+    width: usize,
+    height: usize,
+    region: &CaptureRegion,
+    transform: &FrameTransform,
+) -> Vec<u8> {
+    if transform.is_identity() {
+        return bgra_to_rgb_region(frame, width, height, region);
+    }
+
+    let mut rgb_data = vec![0u8; region.width * region.height * 3];
+    for (y, row) in bgra_rows(frame, width, height, region).enumerate() {
+        let (mut dst, step) = transform.row_layout(y, region.width, region.height);
+        for chunk in row.chunks_exact(4) {
+            let i = dst as usize * 3;
+            rgb_data[i] = chunk[2]; // R
+            rgb_data[i + 1] = chunk[1]; // G
+            rgb_data[i + 2] = chunk[0]; // B
+            dst += step;
+        }
+    }
+    rgb_data
+}
+
+/// Convert only `region` of a `width`x`height` BGRA frame into packed RGB.
+/// The region must already be clamped to the frame.
+fn bgra_to_rgb_region(frame: &[u8], width: usize, height: usize, region: &CaptureRegion) -> Vec<u8> {
+    let mut rgb_data = Vec::with_capacity(region.width * region.height * 3);
+    for row in bgra_rows(frame, width, height, region) {
+        for chunk in row.chunks_exact(4) {
+            rgb_data.push(chunk[2]); // R
+            rgb_data.push(chunk[1]); // G
+            rgb_data.push(chunk[0]); // B
+        }
+    }
+    rgb_data
 }
 
 // Store last known cursor position in a static variable
 static LAST_CURSOR_POS: std::sync::RwLock<(i32, i32)> = std::sync::RwLock::new((0, 0));
 
+/// Cursor position in desktop coordinates. Windows asks the system directly, so
+/// it's right even before the first mouse move event arrives; elsewhere this is
+/// the last position reported by the event listener.
 fn get_cursor_position() -> Option<(i32, i32)> {
+    #[cfg(target_os = "windows")]
+    {
+        use windows::Win32::Foundation::POINT;
+        use windows::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+        let mut point = POINT::default();
+        if unsafe { GetCursorPos(&mut point) }.is_ok() {
+            return Some((point.x, point.y));
+        }
+    }
+
     LAST_CURSOR_POS.read().ok().map(|pos| *pos)
 }
 
@@ -517,76 +1509,310 @@ pub fn update_cursor_position(x: i32, y: i32) {
     }
 }
 
+/// Draw the current system cursor with its hotspot at (`cursor_x`, `cursor_y`)
 fn draw_cursor(rgb_data: &mut [u8], width: usize, height: usize, cursor_x: i32, cursor_y: i32) {
-    // Draw a macOS-style cursor (19x25 pixels)
-    // This is a pre-defined pixel art cursor that looks like the macOS pointer
-
-    const CURSOR_WIDTH: i32 = 19;
-    const CURSOR_HEIGHT: i32 = 25;
-
-    // Cursor pixel data (0 = transparent, 1 = black border, 2 = white fill, 3 = shadow)
-    const CURSOR_PIXELS: &[&[u8]] = &[
-        &[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[1, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[1, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[1, 2, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[1, 2, 2, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[1, 2, 2, 2, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[1, 2, 2, 2, 2, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[1, 2, 2, 2, 2, 2, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0],
-        &[1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0],
-        &[1, 2, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0],
-        &[1, 2, 2, 2, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[1, 2, 2, 1, 0, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[1, 2, 1, 0, 0, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[1, 1, 0, 0, 0, 0, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[1, 0, 0, 0, 0, 0, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[0, 0, 0, 0, 0, 0, 0, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[0, 0, 0, 0, 0, 0, 0, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0],
-        &[0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0],
-        &[0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0],
-        &[0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 1, 0, 0, 0, 0, 0, 0, 0],
-        &[0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0],
-    ];
-
-    for dy in 0..CURSOR_HEIGHT {
-        for dx in 0..CURSOR_WIDTH {
-            let x = cursor_x + dx;
-            let y = cursor_y + dy;
-
-            if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
-                continue;
-            }
+    crate::cursor::current_cursor().draw(rgb_data, width, height, cursor_x, cursor_y);
+}
 
-            let pixel = CURSOR_PIXELS[dy as usize][dx as usize];
-            if pixel == 0 {
-                continue; // Transparent
-            }
+/// Every Nth BGRA pixel is compared between frames (prime, so samples don't line up in columns)
+const DEDUP_SAMPLE_STEP: usize = 61;
+
+/// Display index and size, plus the cursor position drawn into the frame
+type DedupKey = ((usize, usize, usize), Option<(i32, i32)>);
+
+/// Reuses the previous frame's RGB data while the screen isn't changing.
+///
+/// Comparing a sample of the raw BGRA pixels is far cheaper than converting the
+/// frame, and an identical frame costs the encoder almost nothing. Sampling can
+/// miss a small change (a blinking caret), so a full conversion is still done
+/// at least once per second.
+struct FrameDedup {
+    threshold: usize,
+    max_reuse: u32,
+    samples: Vec<u32>,
+    key: Option<DedupKey>,
+    rgb: Vec<u8>,
+    reused_in_a_row: u32,
+    reused_total: u64,
+}
 
-            let idx = ((y as usize) * width + (x as usize)) * 3;
-            if idx + 2 >= rgb_data.len() {
-                continue;
-            }
+impl FrameDedup {
+    fn new(threshold: usize, fps: u32) -> Self {
+        Self {
+            threshold,
+            max_reuse: fps.max(1),
+            samples: Vec::new(),
+            key: None,
+            rgb: Vec::new(),
+            reused_in_a_row: 0,
+            reused_total: 0,
+        }
+    }
 
-            match pixel {
-                1 => {
-                    // Black border
-                    rgb_data[idx] = 0;
-                    rgb_data[idx + 1] = 0;
-                    rgb_data[idx + 2] = 0;
+    /// A copy of the previous RGB frame if `bgra` is close enough to it, the
+    /// display is the same and the cursor hasn't moved
+    fn reuse(
+        &mut self,
+        bgra: &[u8],
+        display: (usize, usize, usize),
+        cursor: Option<(i32, i32)>,
+    ) -> Option<Vec<u8>> {
+        let mut changed = 0;
+        let mut sample_count = 0;
+        for (i, pixel) in bgra.chunks_exact(4).step_by(DEDUP_SAMPLE_STEP).enumerate() {
+            let value = u32::from_ne_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]);
+            match self.samples.get_mut(i) {
+                Some(previous) if *previous == value => {}
+                Some(previous) => {
+                    *previous = value;
+                    changed += 1;
                 }
-                2 => {
-                    // White fill
-                    rgb_data[idx] = 255;
-                    rgb_data[idx + 1] = 255;
-                    rgb_data[idx + 2] = 255;
+                None => {
+                    self.samples.push(value);
+                    changed += 1;
                 }
-                _ => {}
             }
+            sample_count = i + 1;
+        }
+        self.samples.truncate(sample_count);
+
+        let key = Some((display, cursor));
+        if changed > self.threshold || key != self.key || self.reused_in_a_row >= self.max_reuse {
+            self.key = key;
+            return None;
+        }
+
+        self.reused_in_a_row += 1;
+        self.reused_total += 1;
+        Some(self.rgb.clone())
+    }
+
+    /// Keep a freshly converted frame for reuse
+    fn remember(&mut self, rgb: &[u8]) {
+        self.rgb.clear();
+        self.rgb.extend_from_slice(rgb);
+        self.reused_in_a_row = 0;
+    }
+
+    fn log_summary(&self, frames: u64) {
+        if frames > 0 {
+            log::info!(
+                "♻️  Reused {} unchanged frames ({:.0}% of {})",
+                self.reused_total,
+                self.reused_total as f64 / frames as f64 * 100.0,
+                frames
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_high_fps_is_checked_once_after_five_active_seconds() {
+        assert!(FpsShortfallCheck::new(60).is_none());
+        let mut check = FpsShortfallCheck::new(144).unwrap();
+
+        // Paused time doesn't count towards the wait
+        check.frame_captured(300, Duration::from_secs(8), Duration::from_secs(4));
+        assert!(!check.checked);
+        check.frame_captured(400, Duration::from_secs(9), Duration::from_secs(4));
+        assert!(check.checked);
+
+        // 144fps frames are timed to the nanosecond, not truncated to 6944us
+        assert_eq!(frame_duration(144), Duration::from_nanos(6_944_444));
+    }
+
+    #[test]
+    fn test_capture_retry_backs_off_then_gives_up() {
+        let mut retry = CaptureRetry::default();
+        let backoffs: Vec<_> = std::iter::from_fn(|| retry.failed()).collect();
+        assert_eq!(backoffs.len(), CAPTURER_RETRIES as usize);
+        assert_eq!(backoffs[0], CAPTURER_RETRY_BACKOFF);
+        assert!(backoffs.windows(2).all(|pair| pair[1] == pair[0] * 2));
+
+        // A good frame starts the count again
+        retry.succeeded();
+        assert_eq!(retry.failed(), Some(CAPTURER_RETRY_BACKOFF));
+    }
+
+    #[test]
+    fn test_monitor_switch_needs_two_checks_on_the_new_display() {
+        let display = |index, x| DisplayInfo {
+            index,
+            width: 1920,
+            height: 1080,
+            x,
+            y: 0,
+            is_primary: index == 0,
+            scale_factor: 1.0,
+        };
+        let mut detector = MonitorSwitchDetector {
+            check_interval: Duration::from_secs(1),
+            last_check: Instant::now(),
+            current_display: 0,
+            pending_display: None,
+            pending_count: 0,
+            displays_info: vec![display(0, 0), display(1, 1920)],
+        };
+
+        // A brief visit to display 1 doesn't switch
+        assert_eq!(detector.observe(1), None);
+        assert_eq!(detector.observe(0), None);
+
+        assert_eq!(detector.observe(1), None);
+        assert_eq!(detector.observe(1), Some(1));
+        assert_eq!(detector.observe(1), None, "already there");
+
+        // The cursor at x=2000 is 80 pixels into display 1
+        let cursor = (2000, 500);
+        assert_eq!(display_containing(&detector.displays_info, cursor.0, cursor.1), Some(1));
+        assert_eq!(detector.to_frame_pixels(1, cursor.0, cursor.1), (80, 500));
+    }
+
+    #[test]
+    fn test_unplugged_display_falls_back_to_the_primary() {
+        let display = |index, is_primary| DisplayInfo { index, width: 2, height: 2, x: 0, y: 0, is_primary, scale_factor: 1.0 };
+        assert_eq!(fallback_display(&[display(0, false), display(1, true)]), Some(1));
+        assert_eq!(fallback_display(&[display(0, false), display(1, false)]), Some(0));
+        assert_eq!(fallback_display(&[]), None);
+
+        // Display 2 went away while the cursor was heading to display 1
+        let mut detector = MonitorSwitchDetector {
+            check_interval: Duration::from_secs(1),
+            last_check: Instant::now(),
+            current_display: 2,
+            pending_display: None,
+            pending_count: 0,
+            displays_info: vec![display(0, false), display(1, true), display(2, false)],
+        };
+        detector.observe(1);
+        detector.reset(vec![display(0, false), display(1, true)], 1);
+        assert_eq!(detector.observe(1), None);
+        assert_eq!(detector.observe(0), None);
+        assert_eq!(detector.observe(0), Some(0));
+    }
+
+    #[test]
+    fn test_composite_places_displays_at_their_desktop_offsets() {
+        // A 4x2 display on the left of a 2x4 primary whose top sits 1px higher
+        let displays = [
+            DisplayInfo { index: 0, width: 2, height: 4, x: 0, y: -1, is_primary: true, scale_factor: 1.0 },
+            DisplayInfo { index: 1, width: 4, height: 2, x: -4, y: 0, is_primary: false, scale_factor: 1.0 },
+        ];
+        assert_eq!(union_bounds(&displays), Some((-4, -1, 6, 4)));
+        assert_eq!(union_bounds(&[]), None);
+
+        // A Retina display (4x4 pixels, 2x2 points) next to a 1x one: the canvas runs at 2x
+        let mixed = [
+            DisplayInfo { index: 0, width: 4, height: 4, x: 0, y: 0, is_primary: true, scale_factor: 2.0 },
+            DisplayInfo { index: 1, width: 2, height: 2, x: 2, y: 0, is_primary: false, scale_factor: 1.0 },
+        ];
+        assert_eq!(union_bounds(&mixed), Some((0, 0, 8, 4)));
+        assert_eq!(to_canvas(mixed[1].x, canvas_scale(&mixed)), 4);
+
+        let (width, height) = (6, 4);
+        let mut canvas = vec![0u8; width * height * 3];
+        blit_rgb(&mut canvas, width, height, &[1; 2 * 4 * 3], 2, 4, (4, 0));
+        blit_rgb(&mut canvas, width, height, &[2; 4 * 2 * 3], 4, 2, (0, 1));
+        let pixels: Vec<u8> = canvas.chunks_exact(3).map(|px| px[0]).collect();
+        assert_eq!(pixels, [0, 0, 0, 0, 1, 1, 2, 2, 2, 2, 1, 1, 2, 2, 2, 2, 1, 1, 0, 0, 0, 0, 1, 1]);
+
+        // A mask in composite pixels covers each tile at its offset, across the seam
+        let mask = [MaskRect { x: 3, y: 1, width: 2, height: 1 }];
+        let mut canvas = vec![0u8; width * height * 3];
+        for (value, tile_width, tile_height, offset) in [(1u8, 2, 4, (4, 0)), (2, 4, 2, (0, 1))] {
+            let mut tile = vec![value; tile_width * tile_height * 3];
+            apply_masks(&mut tile, tile_width, tile_height, offset, &mask, MaskMode::Blackout);
+            blit_rgb(&mut canvas, width, height, &tile, tile_width, tile_height, offset);
+        }
+        let pixels: Vec<u8> = canvas.chunks_exact(3).map(|px| px[0]).collect();
+        assert_eq!(pixels, [0, 0, 0, 0, 1, 1, 2, 2, 2, 0, 0, 1, 2, 2, 2, 2, 1, 1, 0, 0, 0, 0, 1, 1]);
+
+        // Tiles past the edge (an odd desktop trimmed to even) are clipped
+        let mut canvas = vec![0u8; 2 * 2 * 3];
+        blit_rgb(&mut canvas, 2, 2, &[9; 3 * 3 * 3], 3, 3, (1, 1));
+        assert_eq!(canvas.chunks_exact(3).map(|px| px[0]).collect::<Vec<_>>(), [0, 0, 0, 9]);
+        blit_rgb(&mut canvas, 2, 2, &[7; 3], 1, 1, (2, 0));
+        assert_eq!(canvas.chunks_exact(3).map(|px| px[0]).collect::<Vec<_>>(), [0, 0, 0, 9]);
+    }
+
+    #[test]
+    fn test_bgra_to_rgb_skips_row_padding() {
+        // 2x2 frame with 8 bytes of padding at the end of each row
+        let width = 2;
+        let height = 2;
+        let stride = width * 4 + 8;
+        let mut frame = vec![0xAAu8; stride * height];
+        for y in 0..height {
+            for x in 0..width {
+                let i = y * stride + x * 4;
+                let v = (y * width + x) as u8;
+                frame[i..i + 4].copy_from_slice(&[v, v + 10, v + 20, 255]);
+            }
+        }
+
+        let rgb = bgra_to_rgb(&frame, width, height);
+        assert_eq!(rgb.len(), width * height * 3);
+        assert_eq!(rgb, vec![20, 10, 0, 21, 11, 1, 22, 12, 2, 23, 13, 3]);
+
+        // Screenshots keep alpha but must skip the same padding
+        let rgba = bgra_to_rgba(&frame, width, height);
+        assert_eq!(rgba.len(), width * height * 4);
+        assert_eq!(&rgba[8..], &[22, 12, 2, 255, 23, 13, 3, 255]);
+    }
+
+    #[test]
+    fn test_region_is_clamped_and_cropped() {
+        let region = "2,1,100,100".parse::<CaptureRegion>().unwrap();
+        assert!("1,2,3".parse::<CaptureRegion>().is_err());
+        assert!("0,0,0,10".parse::<CaptureRegion>().is_err());
+
+        // 5x4 display: clamped to 3x3 from (2,1), then rounded down to even
+        let region = region.clamp_to(5, 4).unwrap();
+        assert_eq!(region, CaptureRegion { x: 2, y: 1, width: 2, height: 2 });
+        assert!(CaptureRegion { x: 5, y: 0, width: 2, height: 2 }.clamp_to(5, 4).is_none());
+
+        // Pixel value encodes its position: B = y * 5 + x
+        let (width, height) = (5, 4);
+        let mut frame = vec![0u8; width * height * 4];
+        for (i, pixel) in frame.chunks_exact_mut(4).enumerate() {
+            pixel[0] = i as u8;
+        }
+
+        let rgb = bgra_to_rgb_region(&frame, width, height, &region);
+        let blues: Vec<u8> = rgb.chunks_exact(3).map(|p| p[2]).collect();
+        assert_eq!(blues, vec![7, 8, 12, 13]);
+    }
+
+    #[test]
+    fn test_dedup_reuses_static_frames_until_something_changes() {
+        let mut dedup = FrameDedup::new(0, 3);
+        let display = (0, 100, 100);
+        let mut bgra = vec![0u8; 100 * 100 * 4];
+
+        // First frame is always converted
+        assert!(dedup.reuse(&bgra, display, Some((5, 5))).is_none());
+        dedup.remember(&[1, 2, 3]);
+
+        assert_eq!(dedup.reuse(&bgra, display, Some((5, 5))), Some(vec![1, 2, 3]));
+
+        // Cursor moved: the old frame has the cursor in the wrong place
+        assert!(dedup.reuse(&bgra, display, Some((6, 5))).is_none());
+        dedup.remember(&[4, 5, 6]);
+
+        // A sampled pixel changed (pixel 0 is always sampled)
+        bgra[0] = 255;
+        assert!(dedup.reuse(&bgra, display, Some((6, 5))).is_none());
+        dedup.remember(&[7, 8, 9]);
+
+        // Reused at most once per second's worth of frames (3 here)
+        for _ in 0..3 {
+            assert!(dedup.reuse(&bgra, display, Some((6, 5))).is_some());
         }
+        assert!(dedup.reuse(&bgra, display, Some((6, 5))).is_none());
+        assert_eq!(dedup.reused_total, 4);
     }
 }
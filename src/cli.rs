@@ -13,19 +13,57 @@ pub struct Cli {
     /// Enable verbose logging
     #[arg(short, long, global = true)]
     pub verbose: bool,
+
+    /// Log line format: text (default) or json (one object per line, for log pipelines)
+    #[arg(long, global = true, default_value = "text")]
+    pub log_format: LogFormat,
+
+    /// Also append progress as newline-delimited JSON to this file (or e.g. /dev/fd/3)
+    #[arg(long, global = true)]
+    pub progress_json: Option<PathBuf>,
 }
 
+// Parsed once at startup, so Record's many flags needn't be boxed
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand, Debug)]
 pub enum Commands {
     /// Capture a screenshot
     Screenshot {
-        /// Output file path (supports .png, .jpg, .jpeg)
+        /// Output file path (supports .png, .jpg, .jpeg, .webp, .avif); output directory in watch mode
         #[arg(short, long, default_value = "screenshot.png")]
         output: PathBuf,
 
         /// Display to capture (0 for primary display)
         #[arg(short, long, default_value = "0")]
         display: usize,
+
+        /// Capture every display into one image laid out like the desktop (gaps are black)
+        #[arg(long, conflicts_with = "display")]
+        all_displays: bool,
+
+        /// Watch mode: take a screenshot every N seconds until stopped
+        #[arg(long)]
+        interval: Option<f64>,
+
+        /// Stop watch mode after this many screenshots instead of waiting for Ctrl+C
+        #[arg(long, requires = "interval")]
+        count: Option<usize>,
+
+        /// Image format for watch-mode screenshots: png, jpg, webp or avif
+        #[arg(long, default_value = "png", requires = "interval")]
+        format: ScreenshotFormat,
+
+        /// Quality for lossy formats (JPEG, AVIF), 1-100
+        #[arg(long)]
+        quality: Option<u8>,
+
+        /// Only take watch-mode screenshots when there was recent input activity
+        #[arg(long, requires = "interval")]
+        activity_only: bool,
+
+        /// Seconds without input after which the user is considered idle (with --activity-only)
+        #[arg(long, default_value = "60")]
+        idle_threshold: u64,
     },
 
     /// Record screen video with audio
@@ -38,10 +76,22 @@ pub enum Commands {
         #[arg(short, long, default_value = "0")]
         duration: u64,
 
-        /// Frames per second
+        /// Count down this many seconds on stderr before capture starts (not counted in the recording)
+        #[arg(long, default_value = "0")]
+        start_delay: u64,
+
+        /// Wait until this local time (e.g. 2024-06-01T22:00:00, or RFC 3339 with an offset) before capture starts
+        #[arg(long, conflicts_with = "start_delay")]
+        start_at: Option<String>,
+
+        /// Frames per second (1-60, or up to 240 with --allow-high-fps)
         #[arg(short, long, default_value = "30")]
         fps: u32,
 
+        /// Allow --fps above 60, e.g. 120 for gameplay on a high-refresh display; the display must refresh at least that fast
+        #[arg(long)]
+        allow_high_fps: bool,
+
         /// Audio source: none, system, mic, or both
         #[arg(short, long, default_value = "system")]
         audio: AudioSource,
@@ -50,11 +100,23 @@ pub enum Commands {
         #[arg(long)]
         no_audio: bool,
 
-        /// Video width (0 for native screen resolution)
+        /// How many times to reopen the audio stream if the device drops out mid-recording
+        #[arg(long, default_value = "3")]
+        audio_reconnect_attempts: u32,
+
+        /// Audio input to record from, by name as shown by list-devices (a unique part of the name is enough)
+        #[arg(long)]
+        audio_device: Option<String>,
+
+        /// Audio channels to record: 1 (mono, downmixed) or 2 (stereo; a mono device is duplicated)
+        #[arg(long, default_value = "1")]
+        audio_channels: u16,
+
+        /// Video width; frames are scaled to it (0 for native screen resolution, or to follow --height)
         #[arg(long, default_value = "0")]
         width: u32,
 
-        /// Video height (0 for native screen resolution)
+        /// Video height; frames are scaled to it (0 for native screen resolution, or to follow --width)
         #[arg(long, default_value = "0")]
         height: u32,
 
@@ -66,6 +128,38 @@ pub enum Commands {
         #[arg(short, long, default_value = "10")]
         quality: u8,
 
+        /// Target average bitrate instead of constant quality, e.g. 4M or 2500k
+        #[arg(long, conflicts_with = "quality")]
+        bitrate: Option<String>,
+
+        /// Constant rate factor (0-51, lower is better quality), used as is instead of mapping --quality; not for bitrate-only encoders such as VideoToolbox
+        #[arg(long, conflicts_with_all = ["bitrate", "adaptive_quality"])]
+        crf: Option<u8>,
+
+        /// Encoder speed preset: ultrafast, fast, medium, slow, or veryslow (defaults per encoder)
+        #[arg(long)]
+        preset: Option<Preset>,
+
+        /// Settings for a kind of content: screen (static UI), motion (gameplay, video) or archival (near-lossless HEVC); --codec, --quality, --bitrate, --crf and --preset given on the command line or in the config file still win
+        #[arg(long)]
+        quality_preset: Option<QualityPreset>,
+
+        /// Seconds between keyframes (default 2): shorter seeks faster in review tools, longer makes smaller files
+        #[arg(long)]
+        keyframe_interval: Option<u32>,
+
+        /// Lossless H.264 (libx264 at QP 0 with 4:4:4 chroma); colours may still be off by one level from the RGB to YUV conversion. Files are many times larger than usual
+        #[arg(long, conflicts_with_all = ["bitrate", "crf", "codec", "encoder", "quality_preset", "adaptive_quality"])]
+        lossless: bool,
+
+        /// Color matrix for RGB to YUV: bt601 or bt709 (default: bt709 from 720p up, otherwise bt601)
+        #[arg(long)]
+        colorspace: Option<Colorspace>,
+
+        /// Longest side of the video in pixels; larger captures are scaled down to fit. Hardware encoders also scale down past their own limit (4096 for H.264)
+        #[arg(long)]
+        max_encode_dimension: Option<u32>,
+
         /// Track mouse and keyboard interactions
         #[arg(long)]
         track_interactions: bool,
@@ -74,6 +168,26 @@ pub enum Commands {
         #[arg(long)]
         track_mouse_moves: bool,
 
+        /// Record at most this many mouse-move samples per second with --track-mouse-moves (0 = every move)
+        #[arg(long, default_value = "30")]
+        move_sample_hz: u32,
+
+        /// Merge wheel events less than this many milliseconds apart into one scroll event (0 = log every wheel event)
+        #[arg(long, default_value = "100")]
+        scroll_debounce_ms: u64,
+
+        /// Save tracked interactions as interactions.json, a flat interactions.csv, or both
+        #[arg(long, default_value = "json")]
+        interactions_format: InteractionsFormat,
+
+        /// PRIVACY: on paste (Cmd/Ctrl+V), read the clipboard to log the pasted content's type and text length (the content itself is never stored)
+        #[arg(long)]
+        capture_clipboard: bool,
+
+        /// PRIVACY: log typed characters as "text" events (Shift/Caps Lock applied, US layout) instead of raw key presses. Everything typed, including passwords, ends up in the interaction log
+        #[arg(long)]
+        capture_text: bool,
+
         /// Recording type: task or always_on
         #[arg(long, default_value = "always_on")]
         recording_type: RecordingType,
@@ -86,13 +200,161 @@ pub enum Commands {
         #[arg(long, default_value = "10")]
         chunk_duration: u64,
 
+        /// Roll to a new chunk once it reaches this size instead of after --chunk-duration, e.g. 50MB
+        #[arg(long, conflicts_with = "chunk_duration")]
+        chunk_size: Option<ByteSize>,
+
+        /// Chunk file name (without .mp4); tokens: {ts} start time, {index} zero-padded chunk number, {task} task ID
+        #[arg(long, default_value = "{ts}_{index}")]
+        chunk_name_pattern: ChunkNamePattern,
+
+        /// always_on: delete the oldest chunks once the output directory exceeds this size, e.g. 20GB
+        #[arg(long)]
+        max_disk_usage: Option<ByteSize>,
+
+        /// Encrypt each finished chunk with AES-256-GCM into a .mp4.enc file and delete the plaintext (see `decrypt`)
+        #[arg(long)]
+        encrypt: bool,
+
+        /// File holding the --encrypt key as 64 hex digits (default: the OMGREC_ENCRYPTION_KEY environment variable)
+        #[arg(long, requires = "encrypt")]
+        key_file: Option<PathBuf>,
+
+        /// Append each frame's metadata (timestamp, pts, keyframe, display) to this file as JSON lines while recording
+        #[arg(long)]
+        frames_jsonl: Option<PathBuf>,
+
+        /// Also write captured frames, still uncompressed RGB, into this directory (I/O heavy: a 1080p PNG is several MB)
+        #[arg(long)]
+        dump_frames: Option<PathBuf>,
+
+        /// With --dump-frames: keep every Nth frame
+        #[arg(long, default_value = "1", requires = "dump_frames")]
+        dump_every: u64,
+
+        /// With --dump-frames: png or rgb (raw RGB24 bytes, size in the file name)
+        #[arg(long, default_value = "png", requires = "dump_frames")]
+        frame_format: FrameFormat,
+
+        /// Capture a single window whose title contains this text (instead of a display)
+        #[arg(long)]
+        window_title: Option<String>,
+
+        /// Capture the window with this native id (CGWindowID on macOS, HWND on Windows); see list-devices
+        #[arg(long, conflicts_with = "window_title")]
+        window_id: Option<u64>,
+
+        /// Capture only this rectangle of the display: x,y,width,height in pixels
+        #[arg(long, conflicts_with_all = ["window_title", "window_id"])]
+        region: Option<CaptureRegion>,
+
+        /// Hide this rectangle of the display in every frame: x,y,width,height in pixels (repeatable)
+        #[arg(long)]
+        mask: Vec<MaskRect>,
+
+        /// How --mask rectangles are hidden: blackout (default) or blur
+        #[arg(long, default_value = "blackout")]
+        mask_mode: MaskMode,
+
+        /// Don't draw the mouse cursor onto frames (keeps text under it intact for OCR)
+        #[arg(long)]
+        no_cursor: bool,
+
+        /// Rotate frames clockwise: 0, 90, 180 or 270 degrees (90 and 270 swap width and height)
+        #[arg(long, default_value = "0")]
+        rotate: Rotation,
+
+        /// Mirror frames after rotating: none, h (left-right) or v (upside down)
+        #[arg(long, default_value = "none")]
+        flip: Flip,
+
+        /// Reuse the previous frame when at most this many sampled pixels changed, so static screens encode almost for free (0 = only unchanged samples)
+        #[arg(long)]
+        dedup_threshold: Option<usize>,
+
+        /// Burn the wall-clock time (and task ID in task mode) into a corner of every frame
+        #[arg(long)]
+        overlay_timestamp: bool,
+
+        /// Corner for --overlay-timestamp: top-left, top-right, bottom-left or bottom-right
+        #[arg(long, default_value = "bottom-right", requires = "overlay_timestamp")]
+        overlay_position: OverlayPosition,
+
         /// Monitor switch check interval in seconds (only used with multiple monitors)
         #[arg(long, default_value = "1.0")]
         monitor_switch_interval: f64,
 
+        /// With multiple monitors: switch (follow the cursor, default) or composite (record all side by side)
+        #[arg(long, default_value = "switch")]
+        multi_monitor_mode: MultiMonitorMode,
+
         /// Path to ffmpeg binary (defaults to system ffmpeg)
         #[arg(long)]
         ffmpeg_path: Option<PathBuf>,
+
+        /// Video codec: h264, hevc or av1 (smallest files; slow without a GPU encoder)
+        #[arg(long, default_value = "h264")]
+        codec: Codec,
+
+        /// Use only this encoder (e.g. libx264 or h264_nvenc) instead of the GPU-first fallback chain
+        #[arg(long, conflicts_with = "safe_mode")]
+        encoder: Option<String>,
+
+        /// Print the encoders available for --codec, in fallback order, and exit (hardware ones are test-encoded first)
+        #[arg(long)]
+        list_encoders: bool,
+
+        /// MP4 chunk layout on macOS: fragmented (survives crashes, default) or regular (faststart, like Windows/Linux)
+        #[arg(long)]
+        mp4_layout: Option<Mp4Layout>,
+
+        /// Rotate interactions.jsonl to a new segment after this many megabytes (task mode)
+        #[arg(long)]
+        interactions_max_size_mb: Option<u64>,
+
+        /// Memory budget for frames waiting on the encoder, e.g. 512M or 2G (default: 300 frames at the capture resolution)
+        #[arg(long)]
+        frame_buffer_bytes: Option<ByteSize>,
+
+        /// Frames that may wait for the encoder, sized at the capture resolution (default: 300, or 60 with --safe-mode)
+        #[arg(long, conflicts_with = "frame_buffer_bytes")]
+        buffer_frames: Option<usize>,
+
+        /// What capture does when the frame buffer is full: block (keep every frame, default) or drop-oldest (never stall capture)
+        #[arg(long, default_value = "block")]
+        on_overflow: OverflowPolicy,
+
+        /// Lower quality (or bitrate) at chunk boundaries while the encoder can't keep up, and raise it again once it catches up
+        #[arg(long)]
+        adaptive_quality: bool,
+
+        /// Lowest quality --adaptive-quality may step down to
+        #[arg(long, default_value = "3", requires = "adaptive_quality")]
+        min_quality: u8,
+
+        /// Highest quality --adaptive-quality may step back up to (defaults to --quality)
+        #[arg(long, requires = "adaptive_quality")]
+        max_quality: Option<u8>,
+
+        /// Filter for resizing frames that don't match the encoder size: bilinear (smooth text, default) or nearest (faster)
+        #[arg(long, default_value = "bilinear")]
+        scale_filter: ScaleFilter,
+
+        /// Known-good troubleshooting profile: software H.264 (libx264), a single display, a small frame buffer and verbose logging
+        #[arg(long)]
+        safe_mode: bool,
+
+        /// Serve live progress as JSON at http://127.0.0.1:<PORT>/status while recording
+        #[arg(long)]
+        status_port: Option<u16>,
+
+        /// Read defaults from this config file instead of ~/.omega/config.toml
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Print the effective settings (command line > config file > built-in defaults) as TOML and exit
+        #[arg(long)]
+        print_config: bool,
     },
 
     /// Concatenate video chunks for a completed task recording
@@ -108,6 +370,45 @@ pub enum Commands {
         /// Path to ffmpeg binary (defaults to system ffmpeg)
         #[arg(long)]
         ffmpeg_path: Option<PathBuf>,
+
+        /// Skip probing: don't ffprobe each chunk, just stream copy them with FFmpeg's concat demuxer (single-resolution tasks only)
+        #[arg(long)]
+        raw_concat: bool,
+
+        /// Validate the chunks and print the concat list and FFmpeg command without deleting or writing anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Chunks to validate with ffprobe at the same time (defaults to the CPU count)
+        #[arg(long)]
+        probe_concurrency: Option<usize>,
+
+        /// Also decode every frame of each chunk and skip chunks with decode errors (slow)
+        #[arg(long, conflicts_with = "raw_concat")]
+        deep_validate: bool,
+
+        /// After concatenating, measure SSIM and PSNR against a reference and record them in metadata.json
+        #[arg(long)]
+        verify: bool,
+
+        /// Video to compare against with --verify (default: the first chunk, against the start of the output)
+        #[arg(long, requires = "verify")]
+        verify_reference: Option<PathBuf>,
+    },
+
+    /// Mux a task's session.wav into its concatenated video (macOS audio)
+    MergeAudio {
+        /// Task ID whose final.mp4 and session.wav should be merged
+        #[arg(short, long)]
+        task_id: String,
+
+        /// Output file path (defaults to final_with_audio.mp4 in output directory)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Path to ffmpeg binary (defaults to system ffmpeg)
+        #[arg(long)]
+        ffmpeg_path: Option<PathBuf>,
     },
 
     /// Inspect recording sessions for a task
@@ -116,6 +417,179 @@ pub enum Commands {
         #[arg(short, long)]
         task_id: String,
     },
+
+    /// Check every chunk of a task with ffprobe (the same checks concat runs) and report its health
+    Doctor {
+        /// Task ID whose chunks to check
+        #[arg(short, long)]
+        task_id: String,
+
+        /// Delete the database rows of missing or invalid chunks (the files are left alone)
+        #[arg(long)]
+        fix: bool,
+
+        /// Path to ffmpeg binary (defaults to system ffmpeg); ffprobe is looked up next to it
+        #[arg(long)]
+        ffmpeg_path: Option<PathBuf>,
+    },
+
+    /// Re-hash every chunk of a task and compare with the SHA-256 recorded when it was finished
+    VerifyIntegrity {
+        /// Task ID whose chunks to verify
+        #[arg(short, long)]
+        task_id: String,
+    },
+
+    /// Decrypt a task's --encrypt chunks back to .mp4 files, e.g. before concat
+    Decrypt {
+        /// Task ID whose chunks to decrypt
+        #[arg(short, long)]
+        task_id: String,
+
+        /// File holding the key as 64 hex digits (default: the OMGREC_ENCRYPTION_KEY environment variable)
+        #[arg(long)]
+        key_file: Option<PathBuf>,
+    },
+
+    /// Emit a Gantt-style JSON timeline of active, paused and idle recording time
+    Timeline {
+        /// Task ID whose sessions to include
+        #[arg(short, long, required_unless_present = "date")]
+        task_id: Option<String>,
+
+        /// Include every session that started on this local date (YYYY-MM-DD)
+        #[arg(short, long, conflicts_with = "task_id")]
+        date: Option<String>,
+
+        /// Seconds without frames inside a session before it counts as paused
+        #[arg(long, default_value = "5.0")]
+        gap_threshold: f64,
+
+        /// Write the JSON here instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Write a WebVTT subtitle file showing the focused window over a task's final video
+    ExportSubtitles {
+        /// Task ID whose interactions.jsonl to read
+        #[arg(short, long)]
+        task_id: String,
+
+        /// Output file (defaults to final.vtt in the task's directory)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Export a recording or chunk as an animated GIF or APNG
+    #[command(alias = "gif")]
+    ToGif {
+        /// Input video file (a chunk or a concatenated recording)
+        #[arg(short, long, required_unless_present = "task_id")]
+        input: Option<PathBuf>,
+
+        /// Use this task's final.mp4 (from `concat`) as the input
+        #[arg(long, conflicts_with = "input")]
+        task_id: Option<String>,
+
+        /// Output file (.gif, or .png/.apng for APNG)
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Start of the clip in seconds from the beginning of the input
+        #[arg(long)]
+        start: Option<f64>,
+
+        /// Length of the clip in seconds (defaults to the rest of the input)
+        #[arg(long)]
+        duration: Option<f64>,
+
+        /// Frames per second of the animation (GIFs are capped at 25)
+        #[arg(short, long, default_value = "10")]
+        fps: u32,
+
+        /// Output width in pixels, height follows the aspect ratio (0 keeps the source width)
+        #[arg(short, long, default_value = "640")]
+        width: u32,
+
+        /// Animation format: gif or apng (defaults to the output file extension)
+        #[arg(long)]
+        format: Option<AnimationFormat>,
+
+        /// Path to ffmpeg binary (defaults to system ffmpeg)
+        #[arg(long)]
+        ffmpeg_path: Option<PathBuf>,
+    },
+
+    /// Re-encode a recording (e.g. final.mp4) into a smaller or web-friendly copy
+    Transcode {
+        /// Input video file
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Output file (must differ from the input)
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Constant rate factor: lower is better quality and bigger files (0-51 for h264/hevc, 0-63 for vp9)
+        #[arg(long, default_value = "23")]
+        crf: u8,
+
+        /// Resize with an FFmpeg scale expression, e.g. 1280:-2 (-2 keeps the aspect ratio)
+        #[arg(long)]
+        scale: Option<String>,
+
+        /// Output codec: h264, hevc or vp9
+        #[arg(long, default_value = "h264")]
+        codec: TranscodeCodec,
+
+        /// Path to ffmpeg binary (defaults to system ffmpeg)
+        #[arg(long)]
+        ffmpeg_path: Option<PathBuf>,
+    },
+
+    /// Print duration, resolution, codecs, bitrate and frame rate of a media file
+    Probe {
+        /// File to inspect (a chunk, final.mp4 or any other video)
+        input: PathBuf,
+
+        /// Print ffprobe's full JSON (format and streams) instead of a summary
+        #[arg(long)]
+        json: bool,
+
+        /// Path to ffmpeg binary (ffprobe is looked up next to it)
+        #[arg(long)]
+        ffmpeg_path: Option<PathBuf>,
+    },
+
+    /// Delete old recordings, plus database rows and chunk files that lost their counterpart
+    Cleanup {
+        /// Delete chunks recorded more than this many days ago (without it, only orphans are cleaned)
+        #[arg(long)]
+        older_than_days: Option<u32>,
+
+        /// Print what would be deleted without deleting anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Guided first-run setup: FFmpeg, permissions, a test capture and audio devices
+    Setup {
+        /// Where to write the default config (defaults to ~/.omega/config.toml)
+        #[arg(long)]
+        config: Option<PathBuf>,
+
+        /// Accept the default answer for every prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+
+    /// List displays and audio input devices, to pick values for --display and --audio
+    ListDevices {
+        /// Print JSON instead of a table
+        #[arg(long)]
+        json: bool,
+    },
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -132,54 +606,869 @@ pub enum RecordingType {
     AlwaysOn,
 }
 
-impl std::str::FromStr for AudioSource {
-    type Err = String;
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Codec {
+    H264,
+    Hevc,
+    Av1,
+}
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "none" => Ok(AudioSource::None),
-            "system" => Ok(AudioSource::System),
-            "mic" => Ok(AudioSource::Mic),
-            "both" => Ok(AudioSource::Both),
-            _ => Err(format!(
-                "Invalid audio source: {}. Use: none, system, mic, or both",
-                s
-            )),
-        }
-    }
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationFormat {
+    Gif,
+    Apng,
 }
 
-impl std::fmt::Display for AudioSource {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// Image format for numbered watch-mode screenshots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScreenshotFormat {
+    #[default]
+    Png,
+    Jpeg,
+    Webp,
+    Avif,
+}
+
+impl ScreenshotFormat {
+    /// File extension; saving picks the encoder from it
+    pub fn extension(self) -> &'static str {
         match self {
-            AudioSource::None => write!(f, "none"),
-            AudioSource::System => write!(f, "system"),
-            AudioSource::Mic => write!(f, "mic"),
-            AudioSource::Both => write!(f, "both"),
+            ScreenshotFormat::Png => "png",
+            ScreenshotFormat::Jpeg => "jpg",
+            ScreenshotFormat::Webp => "webp",
+            ScreenshotFormat::Avif => "avif",
         }
     }
 }
 
-impl std::str::FromStr for RecordingType {
-    type Err = String;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_lowercase().as_str() {
-            "task" => Ok(RecordingType::Task),
-            "always_on" | "always-on" | "alwayson" => Ok(RecordingType::AlwaysOn),
-            _ => Err(format!(
-                "Invalid recording type: {}. Use: task or always_on",
-                s
-            )),
+impl AnimationFormat {
+    /// Infer the format from an output file extension
+    pub fn from_path(path: &std::path::Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "gif" => Some(AnimationFormat::Gif),
+            "png" | "apng" => Some(AnimationFormat::Apng),
+            _ => None,
         }
     }
 }
 
-impl std::fmt::Display for RecordingType {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            RecordingType::Task => write!(f, "task"),
-            RecordingType::AlwaysOn => write!(f, "always_on"),
+/// Output codec for `transcode`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TranscodeCodec {
+    H264,
+    Hevc,
+    Vp9,
+}
+
+/// A bundle of codec, quality, preset and keyframe settings for a kind of content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QualityPreset {
+    /// Mostly static UI
+    Screen,
+    /// Gameplay and video
+    Motion,
+    /// Near-lossless HEVC for keeping
+    Archival,
+}
+
+/// RGB → YUV matrix, tagged on the video so players convert back the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Colorspace {
+    /// Standard definition; what recordings used before `--colorspace`
+    #[default]
+    Bt601,
+    /// HD, the default from 720p up
+    Bt709,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Preset {
+    Ultrafast,
+    Fast,
+    Medium,
+    Slow,
+    Veryslow,
+}
+
+/// A rectangle of a display, in display pixels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaptureRegion {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl CaptureRegion {
+    /// Clamp to a `display_width`x`display_height` display and round the size down
+    /// to even numbers for H.264. None if nothing usable is left.
+    pub fn clamp_to(self, display_width: usize, display_height: usize) -> Option<Self> {
+        if self.x >= display_width || self.y >= display_height {
+            return None;
+        }
+
+        let width = self.width.min(display_width - self.x) & !1;
+        let height = self.height.min(display_height - self.y) & !1;
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        Some(CaptureRegion { x: self.x, y: self.y, width, height })
+    }
+}
+
+/// A rectangle to hide in every frame, in pixels of the captured display
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MaskRect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl MaskRect {
+    /// The part of the mask inside a `width`x`height` frame, None if it misses it
+    pub fn clamp_to(self, width: usize, height: usize) -> Option<Self> {
+        if self.x >= width || self.y >= height {
+            return None;
+        }
+
+        Some(MaskRect {
+            x: self.x,
+            y: self.y,
+            width: self.width.min(width - self.x),
+            height: self.height.min(height - self.y),
+        })
+    }
+}
+
+/// How `--mask` rectangles are hidden
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MaskMode {
+    #[default]
+    Blackout,
+    Blur,
+}
+
+/// File format for `--dump-frames`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FrameFormat {
+    #[default]
+    Png,
+    /// Bare RGB24 bytes, row by row
+    Rgb,
+}
+
+/// What gets recorded when more than one display is connected
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MultiMonitorMode {
+    /// Record the display the cursor is on, switching as it moves
+    #[default]
+    Switch,
+    /// Record every display in one frame, laid out as they are on the desktop
+    Composite,
+}
+
+/// How the macOS subprocess encoder lays out each MP4 chunk.
+///
+/// Fragmented is the default because it streams from a pipe and leaves a playable
+/// file if FFmpeg or the recorder dies mid-chunk. Regular files need the `moov` box
+/// written at the end, so an interrupted chunk is lost, but they seek faster and
+/// match the library encoder's output on Windows/Linux.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Mp4Layout {
+    #[default]
+    Fragmented,
+    Regular,
+}
+
+/// How frames are resized when their size doesn't match the encoder (e.g. a smaller
+/// secondary monitor in multi-monitor mode). Bilinear keeps downscaled text from
+/// shimmering; nearest is cheaper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ScaleFilter {
+    Nearest,
+    #[default]
+    Bilinear,
+}
+
+/// Clockwise rotation applied to captured frames (`--rotate`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rotation {
+    #[default]
+    None,
+    Cw90,
+    Cw180,
+    Cw270,
+}
+
+/// Mirroring applied to captured frames after `--rotate` (`--flip`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Flip {
+    #[default]
+    None,
+    /// Left-right mirror
+    Horizontal,
+    /// Upside down mirror
+    Vertical,
+}
+
+/// Frame corner for the `--overlay-timestamp` box
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverlayPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+/// File format(s) the tracked interactions are saved in when recording stops
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum InteractionsFormat {
+    #[default]
+    Json,
+    Csv,
+    Both,
+}
+
+impl InteractionsFormat {
+    pub fn writes_json(self) -> bool {
+        matches!(self, InteractionsFormat::Json | InteractionsFormat::Both)
+    }
+
+    pub fn writes_csv(self) -> bool {
+        matches!(self, InteractionsFormat::Csv | InteractionsFormat::Both)
+    }
+}
+
+/// How log lines are written to stderr
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LogFormat {
+    #[default]
+    Text,
+    /// `{"timestamp":...,"level":...,"target":...,"message":...}` per line
+    Json,
+}
+
+/// What happens to a new frame when the encoder queue is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OverflowPolicy {
+    /// Wait for the encoder; no frame is lost but capture stalls
+    #[default]
+    Block,
+    /// Discard the oldest queued frames to make room
+    DropOldest,
+}
+
+/// How chunk files are named, e.g. `{ts}_{index}`: `{ts}` is the chunk's start
+/// time (`2025-01-14_10-30-00`), `{index}` its number in the session padded to
+/// four digits and `{task}` the task ID (`always_on` without one)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkNamePattern(pub String);
+
+impl ChunkNamePattern {
+    pub const TOKENS: [&'static str; 3] = ["{ts}", "{index}", "{task}"];
+
+    /// The file name of chunk `index`, started at `start`
+    pub fn render(&self, start: chrono::DateTime<chrono::Local>, index: i64, task: Option<&str>) -> String {
+        let name = self
+            .0
+            .replace("{ts}", &start.format("%Y-%m-%d_%H-%M-%S").to_string())
+            .replace("{index}", &format!("{:04}", index))
+            .replace("{task}", task.unwrap_or("always_on"));
+        format!("{}.mp4", name)
+    }
+
+    /// Whether every chunk of a session gets a different name
+    pub fn has_index(&self) -> bool {
+        self.0.contains("{index}")
+    }
+}
+
+impl Default for ChunkNamePattern {
+    fn default() -> Self {
+        ChunkNamePattern("{ts}_{index}".to_string())
+    }
+}
+
+/// A size in bytes, written as a number with an optional K, M or G suffix (powers of 1024)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteSize(pub u64);
+
+impl std::str::FromStr for AudioSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(AudioSource::None),
+            "system" => Ok(AudioSource::System),
+            "mic" => Ok(AudioSource::Mic),
+            "both" => Ok(AudioSource::Both),
+            _ => Err(format!(
+                "Invalid audio source: {}. Use: none, system, mic, or both",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for AudioSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AudioSource::None => write!(f, "none"),
+            AudioSource::System => write!(f, "system"),
+            AudioSource::Mic => write!(f, "mic"),
+            AudioSource::Both => write!(f, "both"),
+        }
+    }
+}
+
+impl std::str::FromStr for RecordingType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "task" => Ok(RecordingType::Task),
+            "always_on" | "always-on" | "alwayson" => Ok(RecordingType::AlwaysOn),
+            _ => Err(format!(
+                "Invalid recording type: {}. Use: task or always_on",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for RecordingType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RecordingType::Task => write!(f, "task"),
+            RecordingType::AlwaysOn => write!(f, "always_on"),
+        }
+    }
+}
+
+impl std::str::FromStr for Codec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "h264" | "avc" => Ok(Codec::H264),
+            "hevc" | "h265" => Ok(Codec::Hevc),
+            "av1" => Ok(Codec::Av1),
+            _ => Err(format!("Invalid codec: {}. Use: h264, hevc or av1", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Codec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Codec::H264 => write!(f, "h264"),
+            Codec::Hevc => write!(f, "hevc"),
+            Codec::Av1 => write!(f, "av1"),
+        }
+    }
+}
+
+/// Parse `x,y,width,height` with a non-zero size; `what` names it in errors
+fn parse_rect(s: &str, what: &str) -> Result<(usize, usize, usize, usize), String> {
+    let parts: Vec<usize> = s
+        .split(',')
+        .map(|part| part.trim().parse::<usize>())
+        .collect::<Result<_, _>>()
+        .map_err(|_| format!("Invalid {}: {}. Use: x,y,width,height", what, s))?;
+
+    match parts.as_slice() {
+        &[x, y, width, height] if width > 0 && height > 0 => Ok((x, y, width, height)),
+        _ => Err(format!("Invalid {}: {}. Use: x,y,width,height with a non-zero size", what, s)),
+    }
+}
+
+impl std::str::FromStr for CaptureRegion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y, width, height) = parse_rect(s, "region")?;
+        Ok(CaptureRegion { x, y, width, height })
+    }
+}
+
+impl std::fmt::Display for CaptureRegion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{} at ({}, {})", self.width, self.height, self.x, self.y)
+    }
+}
+
+impl std::str::FromStr for MaskRect {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (x, y, width, height) = parse_rect(s, "mask")?;
+        Ok(MaskRect { x, y, width, height })
+    }
+}
+
+impl std::fmt::Display for MaskRect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{} at ({}, {})", self.width, self.height, self.x, self.y)
+    }
+}
+
+impl std::str::FromStr for MaskMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "blackout" | "black" => Ok(MaskMode::Blackout),
+            "blur" => Ok(MaskMode::Blur),
+            _ => Err(format!("Invalid mask mode: {}. Use: blackout or blur", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for MaskMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MaskMode::Blackout => write!(f, "blackout"),
+            MaskMode::Blur => write!(f, "blur"),
+        }
+    }
+}
+
+impl std::str::FromStr for FrameFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "png" => Ok(FrameFormat::Png),
+            "rgb" | "raw" => Ok(FrameFormat::Rgb),
+            _ => Err(format!("Invalid frame format: {}. Use: png or rgb", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for FrameFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameFormat::Png => write!(f, "png"),
+            FrameFormat::Rgb => write!(f, "rgb"),
+        }
+    }
+}
+
+impl std::str::FromStr for MultiMonitorMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "switch" => Ok(MultiMonitorMode::Switch),
+            "composite" => Ok(MultiMonitorMode::Composite),
+            _ => Err(format!("Invalid multi-monitor mode: {}. Use: switch or composite", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for MultiMonitorMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MultiMonitorMode::Switch => write!(f, "switch"),
+            MultiMonitorMode::Composite => write!(f, "composite"),
+        }
+    }
+}
+
+impl std::str::FromStr for ByteSize {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("Invalid size: {}. Use a number with an optional K, M or G suffix, e.g. 512M", s);
+
+        let trimmed = s.trim();
+        let trimmed = trimmed
+            .strip_suffix(['b', 'B'])
+            .filter(|rest| rest.ends_with(|c: char| c.is_ascii_alphabetic()))
+            .unwrap_or(trimmed);
+        let (number, multiplier) = match trimmed.char_indices().last() {
+            Some((i, 'k' | 'K')) => (&trimmed[..i], 1u64 << 10),
+            Some((i, 'm' | 'M')) => (&trimmed[..i], 1u64 << 20),
+            Some((i, 'g' | 'G')) => (&trimmed[..i], 1u64 << 30),
+            _ => (trimmed, 1),
+        };
+
+        let value: f64 = number.trim().parse().map_err(|_| invalid())?;
+        let bytes = (value * multiplier as f64).round();
+        if !bytes.is_finite() || bytes < 1.0 {
+            return Err(invalid());
+        }
+
+        Ok(ByteSize(bytes as u64))
+    }
+}
+
+impl std::str::FromStr for ChunkNamePattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() || s.contains(['/', '\\']) {
+            return Err(format!("Invalid chunk name pattern: {:?}. It must be a non-empty file name", s));
+        }
+
+        // Whatever is left once the known tokens are gone must not look like a token
+        let mut rest = s.to_string();
+        for token in ChunkNamePattern::TOKENS {
+            rest = rest.replace(token, "");
+        }
+        if rest.contains(['{', '}']) {
+            return Err(format!(
+                "Invalid chunk name pattern: {}. Known tokens: {}",
+                s,
+                ChunkNamePattern::TOKENS.join(", ")
+            ));
+        }
+
+        Ok(ChunkNamePattern(s.to_string()))
+    }
+}
+
+impl std::fmt::Display for ChunkNamePattern {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("Invalid log format: {}. Use: text or json", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for LogFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LogFormat::Text => write!(f, "text"),
+            LogFormat::Json => write!(f, "json"),
+        }
+    }
+}
+
+impl std::str::FromStr for OverflowPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "block" => Ok(OverflowPolicy::Block),
+            "drop-oldest" | "drop_oldest" => Ok(OverflowPolicy::DropOldest),
+            _ => Err(format!("Invalid overflow policy: {}. Use: block or drop-oldest", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for OverflowPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverflowPolicy::Block => write!(f, "block"),
+            OverflowPolicy::DropOldest => write!(f, "drop-oldest"),
+        }
+    }
+}
+
+impl std::fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const UNITS: [(&str, u64); 3] = [("GiB", 1 << 30), ("MiB", 1 << 20), ("KiB", 1 << 10)];
+        match UNITS.iter().find(|(_, size)| self.0 >= *size) {
+            Some((unit, size)) => write!(f, "{:.1} {}", self.0 as f64 / *size as f64, unit),
+            None => write!(f, "{} B", self.0),
+        }
+    }
+}
+
+impl std::str::FromStr for Mp4Layout {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "fragmented" | "frag" => Ok(Mp4Layout::Fragmented),
+            "regular" | "faststart" => Ok(Mp4Layout::Regular),
+            _ => Err(format!("Invalid MP4 layout: {}. Use: fragmented or regular", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Mp4Layout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mp4Layout::Fragmented => write!(f, "fragmented"),
+            Mp4Layout::Regular => write!(f, "regular"),
+        }
+    }
+}
+
+impl std::str::FromStr for ScaleFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "nearest" => Ok(ScaleFilter::Nearest),
+            "bilinear" => Ok(ScaleFilter::Bilinear),
+            _ => Err(format!("Invalid scale filter: {}. Use: nearest or bilinear", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for ScaleFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScaleFilter::Nearest => write!(f, "nearest"),
+            ScaleFilter::Bilinear => write!(f, "bilinear"),
+        }
+    }
+}
+
+impl std::str::FromStr for Rotation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "0" => Ok(Rotation::None),
+            "90" => Ok(Rotation::Cw90),
+            "180" => Ok(Rotation::Cw180),
+            "270" => Ok(Rotation::Cw270),
+            _ => Err(format!("Invalid rotation: {}. Use: 0, 90, 180 or 270", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Rotation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rotation::None => write!(f, "0"),
+            Rotation::Cw90 => write!(f, "90"),
+            Rotation::Cw180 => write!(f, "180"),
+            Rotation::Cw270 => write!(f, "270"),
+        }
+    }
+}
+
+impl std::str::FromStr for Flip {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "none" => Ok(Flip::None),
+            "h" | "horizontal" => Ok(Flip::Horizontal),
+            "v" | "vertical" => Ok(Flip::Vertical),
+            _ => Err(format!("Invalid flip: {}. Use: none, h or v", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Flip {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Flip::None => write!(f, "none"),
+            Flip::Horizontal => write!(f, "h"),
+            Flip::Vertical => write!(f, "v"),
+        }
+    }
+}
+
+impl std::str::FromStr for OverlayPosition {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().replace('_', "-").as_str() {
+            "top-left" => Ok(OverlayPosition::TopLeft),
+            "top-right" => Ok(OverlayPosition::TopRight),
+            "bottom-left" => Ok(OverlayPosition::BottomLeft),
+            "bottom-right" => Ok(OverlayPosition::BottomRight),
+            _ => Err(format!(
+                "Invalid overlay position: {}. Use: top-left, top-right, bottom-left or bottom-right",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for OverlayPosition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverlayPosition::TopLeft => write!(f, "top-left"),
+            OverlayPosition::TopRight => write!(f, "top-right"),
+            OverlayPosition::BottomLeft => write!(f, "bottom-left"),
+            OverlayPosition::BottomRight => write!(f, "bottom-right"),
+        }
+    }
+}
+
+impl std::str::FromStr for InteractionsFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "json" => Ok(InteractionsFormat::Json),
+            "csv" => Ok(InteractionsFormat::Csv),
+            "both" => Ok(InteractionsFormat::Both),
+            _ => Err(format!("Invalid interactions format: {}. Use: json, csv or both", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for InteractionsFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InteractionsFormat::Json => write!(f, "json"),
+            InteractionsFormat::Csv => write!(f, "csv"),
+            InteractionsFormat::Both => write!(f, "both"),
+        }
+    }
+}
+
+impl std::str::FromStr for QualityPreset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "screen" => Ok(QualityPreset::Screen),
+            "motion" => Ok(QualityPreset::Motion),
+            "archival" => Ok(QualityPreset::Archival),
+            _ => Err(format!("Invalid quality preset: {}. Use: screen, motion or archival", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for QualityPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QualityPreset::Screen => write!(f, "screen"),
+            QualityPreset::Motion => write!(f, "motion"),
+            QualityPreset::Archival => write!(f, "archival"),
+        }
+    }
+}
+
+impl std::str::FromStr for Colorspace {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bt601" => Ok(Colorspace::Bt601),
+            "bt709" => Ok(Colorspace::Bt709),
+            _ => Err(format!("Invalid colorspace: {}. Use: bt601 or bt709", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Colorspace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Colorspace::Bt601 => write!(f, "bt601"),
+            Colorspace::Bt709 => write!(f, "bt709"),
+        }
+    }
+}
+
+impl std::str::FromStr for Preset {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ultrafast" => Ok(Preset::Ultrafast),
+            "fast" => Ok(Preset::Fast),
+            "medium" => Ok(Preset::Medium),
+            "slow" => Ok(Preset::Slow),
+            "veryslow" => Ok(Preset::Veryslow),
+            _ => Err(format!(
+                "Invalid preset: {}. Use: ultrafast, fast, medium, slow, or veryslow",
+                s
+            )),
+        }
+    }
+}
+
+impl std::fmt::Display for Preset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Preset::Ultrafast => write!(f, "ultrafast"),
+            Preset::Fast => write!(f, "fast"),
+            Preset::Medium => write!(f, "medium"),
+            Preset::Slow => write!(f, "slow"),
+            Preset::Veryslow => write!(f, "veryslow"),
+        }
+    }
+}
+
+impl std::str::FromStr for AnimationFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "gif" => Ok(AnimationFormat::Gif),
+            "apng" | "png" => Ok(AnimationFormat::Apng),
+            _ => Err(format!("Invalid animation format: {}. Use: gif or apng", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for AnimationFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnimationFormat::Gif => write!(f, "gif"),
+            AnimationFormat::Apng => write!(f, "apng"),
+        }
+    }
+}
+
+impl std::str::FromStr for ScreenshotFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "png" => Ok(ScreenshotFormat::Png),
+            "jpg" | "jpeg" => Ok(ScreenshotFormat::Jpeg),
+            "webp" => Ok(ScreenshotFormat::Webp),
+            "avif" => Ok(ScreenshotFormat::Avif),
+            _ => Err(format!("Invalid screenshot format: {}. Use: png, jpg, webp or avif", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for ScreenshotFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ScreenshotFormat::Png => write!(f, "png"),
+            ScreenshotFormat::Jpeg => write!(f, "jpg"),
+            ScreenshotFormat::Webp => write!(f, "webp"),
+            ScreenshotFormat::Avif => write!(f, "avif"),
+        }
+    }
+}
+
+impl std::str::FromStr for TranscodeCodec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "h264" | "avc" => Ok(TranscodeCodec::H264),
+            "hevc" | "h265" => Ok(TranscodeCodec::Hevc),
+            "vp9" => Ok(TranscodeCodec::Vp9),
+            _ => Err(format!("Invalid codec: {}. Use: h264, hevc or vp9", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for TranscodeCodec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TranscodeCodec::H264 => write!(f, "h264"),
+            TranscodeCodec::Hevc => write!(f, "hevc"),
+            TranscodeCodec::Vp9 => write!(f, "vp9"),
         }
     }
 }
@@ -0,0 +1,365 @@
+//! Default flags from `~/.omega/config.toml`
+//!
+//! The `[record]` table sets defaults for `omgrec record` (the file `omgrec setup`
+//! writes already has one). Precedence is CLI > config file > built-in default:
+//! a value is only taken from the file when the flag was not given on the command
+//! line, which clap reports through the argument's value source, so passing a
+//! flag with its default value still overrides the file.
+//!
+//! Values taken from the file count as explicit, like typed flags: a
+//! `--quality-preset` profile only fills what neither set.
+
+use crate::cli::{
+    AudioSource, ByteSize, Codec, Commands, Flip, InteractionsFormat, MaskMode, Mp4Layout, OverlayPosition, Preset,
+    Rotation, ScaleFilter,
+};
+use crate::error::{Result, ScreenRecError};
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct ConfigFile {
+    #[serde(default)]
+    record: Defaults,
+}
+
+/// `[record]` defaults; every key is optional and named like its flag (with underscores)
+#[derive(Debug, Default, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Defaults {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fps: Option<u32>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display", skip_serializing_if = "Option::is_none")]
+    audio: Option<AudioSource>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    audio_reconnect_attempts: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    width: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    height: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    display: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quality: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bitrate: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    crf: Option<u8>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display", skip_serializing_if = "Option::is_none")]
+    preset: Option<Preset>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display", skip_serializing_if = "Option::is_none")]
+    codec: Option<Codec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    encoder: Option<String>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display", skip_serializing_if = "Option::is_none")]
+    mp4_layout: Option<Mp4Layout>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display", skip_serializing_if = "Option::is_none")]
+    scale_filter: Option<ScaleFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    chunk_duration: Option<u64>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "byte_size", skip_serializing_if = "Option::is_none")]
+    chunk_size: Option<ByteSize>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "byte_size", skip_serializing_if = "Option::is_none")]
+    max_disk_usage: Option<ByteSize>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "byte_size", skip_serializing_if = "Option::is_none")]
+    frame_buffer_bytes: Option<ByteSize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    track_interactions: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    track_mouse_moves: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    move_sample_hz: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scroll_debounce_ms: Option<u64>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display", skip_serializing_if = "Option::is_none")]
+    interactions_format: Option<InteractionsFormat>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display", skip_serializing_if = "Option::is_none")]
+    mask_mode: Option<MaskMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    no_cursor: Option<bool>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display", skip_serializing_if = "Option::is_none")]
+    rotate: Option<Rotation>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display", skip_serializing_if = "Option::is_none")]
+    flip: Option<Flip>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    overlay_timestamp: Option<bool>,
+    #[serde(default, deserialize_with = "parse", serialize_with = "display", skip_serializing_if = "Option::is_none")]
+    overlay_position: Option<OverlayPosition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ffmpeg_path: Option<PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status_port: Option<u16>,
+}
+
+/// Read the config file. An explicit `--config` path must exist; the default
+/// `~/.omega/config.toml` is optional. Returns the defaults and the file they came from.
+pub fn load(path: Option<&Path>) -> Result<(Defaults, Option<PathBuf>)> {
+    let path = match path {
+        Some(path) if !path.exists() => {
+            return Err(ScreenRecError::ConfigError(format!("Config file not found: {}", path.display())))
+        }
+        Some(path) => path.to_path_buf(),
+        None => match crate::setup::default_config_path() {
+            Ok(path) if path.exists() => path,
+            _ => return Ok((Defaults::default(), None)),
+        },
+    };
+
+    let contents = std::fs::read_to_string(&path)
+        .map_err(|e| ScreenRecError::ConfigError(format!("Failed to read {}: {}", path.display(), e)))?;
+    let file: ConfigFile = toml::from_str(&contents)
+        .map_err(|e| ScreenRecError::ConfigError(format!("Invalid config {}: {}", path.display(), e)))?;
+    Ok((file.record, Some(path)))
+}
+
+/// Fill the `record` flags that weren't given on the command line from `defaults`.
+/// Returns the ids of the flags that were filled.
+pub fn apply(command: &mut Commands, matches: &ArgMatches, defaults: &Defaults) -> Vec<&'static str> {
+    let (Commands::Record {
+        output,
+        fps,
+        audio,
+        audio_reconnect_attempts,
+        width,
+        height,
+        display,
+        quality,
+        bitrate,
+        crf,
+        preset,
+        codec,
+        encoder,
+        mp4_layout,
+        scale_filter,
+        chunk_duration,
+        chunk_size,
+        max_disk_usage,
+        frame_buffer_bytes,
+        track_interactions,
+        track_mouse_moves,
+        move_sample_hz,
+        scroll_debounce_ms,
+        interactions_format,
+        mask_mode,
+        no_cursor,
+        rotate,
+        flip,
+        overlay_timestamp,
+        overlay_position,
+        ffmpeg_path,
+        status_port,
+        ..
+    }, Some(matches)) = (command, matches.subcommand_matches("record"))
+    else {
+        return Vec::new();
+    };
+    let from_cli = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+    let mut filled = Vec::new();
+
+    // Flags with a built-in default take the config value as is...
+    macro_rules! fill {
+        ($($field:ident),*) => {$(
+            if let (Some(value), false) = (defaults.$field.clone(), from_cli(stringify!($field))) {
+                *$field = value;
+                filled.push(stringify!($field));
+            }
+        )*};
+    }
+    // ...optional ones become Some(value)
+    macro_rules! fill_some {
+        ($($field:ident),*) => {$(
+            if let (Some(value), false) = (defaults.$field.clone(), from_cli(stringify!($field))) {
+                *$field = Some(value);
+                filled.push(stringify!($field));
+            }
+        )*};
+    }
+
+    fill!(fps, audio, audio_reconnect_attempts, width, height, display, quality, chunk_duration);
+    fill!(track_interactions, track_mouse_moves, move_sample_hz, scroll_debounce_ms, interactions_format);
+    fill!(mask_mode, no_cursor, overlay_timestamp, overlay_position, scale_filter, codec);
+    fill!(rotate, flip);
+    fill_some!(output, bitrate, crf, preset, mp4_layout, chunk_size, max_disk_usage, frame_buffer_bytes);
+    fill_some!(encoder, ffmpeg_path, status_port);
+
+    // A mode chosen on the command line beats the other mode from the file
+    if from_cli("quality") {
+        *bitrate = None;
+        *crf = None;
+    }
+    if from_cli("bitrate") {
+        *crf = None;
+    }
+    if from_cli("crf") {
+        *bitrate = None;
+    }
+    if from_cli("chunk_duration") {
+        *chunk_size = None;
+    }
+    filled
+}
+
+/// The effective `record` settings as a `[record]` table, for `--print-config`
+pub fn render(command: &Commands, source: Option<&Path>) -> Result<String> {
+    let Commands::Record {
+        output,
+        fps,
+        audio,
+        audio_reconnect_attempts,
+        width,
+        height,
+        display,
+        quality,
+        bitrate,
+        crf,
+        preset,
+        codec,
+        encoder,
+        mp4_layout,
+        scale_filter,
+        chunk_duration,
+        chunk_size,
+        max_disk_usage,
+        frame_buffer_bytes,
+        track_interactions,
+        track_mouse_moves,
+        move_sample_hz,
+        scroll_debounce_ms,
+        interactions_format,
+        mask_mode,
+        no_cursor,
+        rotate,
+        flip,
+        overlay_timestamp,
+        overlay_position,
+        ffmpeg_path,
+        status_port,
+        ..
+    } = command
+    else {
+        return Err(ScreenRecError::InvalidParameter("--print-config only applies to record".to_string()));
+    };
+
+    let record = Defaults {
+        output: output.clone(),
+        fps: Some(*fps),
+        audio: Some(*audio),
+        audio_reconnect_attempts: Some(*audio_reconnect_attempts),
+        width: Some(*width),
+        height: Some(*height),
+        display: Some(*display),
+        // Only one rate control mode is in effect
+        quality: (bitrate.is_none() && crf.is_none()).then_some(*quality),
+        bitrate: bitrate.clone(),
+        crf: if bitrate.is_none() { *crf } else { None },
+        preset: *preset,
+        codec: Some(*codec),
+        encoder: encoder.clone(),
+        mp4_layout: *mp4_layout,
+        scale_filter: Some(*scale_filter),
+        chunk_duration: chunk_size.is_none().then_some(*chunk_duration),
+        chunk_size: *chunk_size,
+        max_disk_usage: *max_disk_usage,
+        frame_buffer_bytes: *frame_buffer_bytes,
+        track_interactions: Some(*track_interactions),
+        track_mouse_moves: Some(*track_mouse_moves),
+        move_sample_hz: Some(*move_sample_hz),
+        scroll_debounce_ms: Some(*scroll_debounce_ms),
+        interactions_format: Some(*interactions_format),
+        mask_mode: Some(*mask_mode),
+        no_cursor: Some(*no_cursor),
+        rotate: Some(*rotate),
+        flip: Some(*flip),
+        overlay_timestamp: Some(*overlay_timestamp),
+        overlay_position: Some(*overlay_position),
+        ffmpeg_path: ffmpeg_path.clone(),
+        status_port: *status_port,
+    };
+
+    let table = toml::to_string(&ConfigFile { record })
+        .map_err(|e| ScreenRecError::ConfigError(format!("Failed to render config: {}", e)))?;
+    let source = match source {
+        Some(path) => path.display().to_string(),
+        None => "no config file".to_string(),
+    };
+    Ok(format!("# Effective record settings (command line > {} > built-in defaults)\n{}", source, table))
+}
+
+/// Deserialize one of the CLI's FromStr types from its flag spelling
+fn parse<'de, D, T>(deserializer: D) -> std::result::Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = String>,
+{
+    let value = String::deserialize(deserializer)?;
+    value.parse().map(Some).map_err(serde::de::Error::custom)
+}
+
+fn display<S, T>(value: &Option<T>, serializer: S) -> std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: Display,
+{
+    match value {
+        Some(value) => serializer.collect_str(value),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// Sizes in the largest K/M/G unit that divides them, so they parse back
+fn byte_size<S: Serializer>(value: &Option<ByteSize>, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+    let Some(ByteSize(bytes)) = *value else {
+        return serializer.serialize_none();
+    };
+    let unit = [("G", 1u64 << 30), ("M", 1 << 20), ("K", 1 << 10)]
+        .into_iter()
+        .find(|(_, size)| bytes % size == 0);
+    match unit {
+        Some((suffix, size)) => serializer.collect_str(&format_args!("{}{}", bytes / size, suffix)),
+        None => serializer.collect_str(&bytes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use clap::{CommandFactory, FromArgMatches};
+
+    #[test]
+    fn test_command_line_beats_config_file() {
+        let file: ConfigFile = toml::from_str(
+            "[record]\nfps = 15\nquality = 6\nchunk_size = \"50M\"\ncodec = \"hevc\"\nffmpeg_path = \"/opt/ffmpeg\"\n",
+        )
+        .unwrap();
+
+        // --fps at its built-in value still wins; --chunk-duration wins over the file's chunk_size
+        let args = ["omgrec", "record", "--fps", "30", "--chunk-duration", "20"];
+        let matches = Cli::command().get_matches_from(args);
+        let mut cli = Cli::from_arg_matches(&matches).unwrap();
+        let filled = apply(&mut cli.command, &matches, &file.record);
+        assert_eq!(filled, ["quality", "codec", "chunk_size", "ffmpeg_path"]);
+
+        let Commands::Record { fps, quality, codec, chunk_duration, chunk_size, ffmpeg_path, .. } = &cli.command else {
+            panic!("not a record command");
+        };
+        assert_eq!(*fps, 30);
+        assert_eq!(*quality, 6);
+        assert_eq!(*codec, Codec::Hevc);
+        assert_eq!((*chunk_duration, *chunk_size), (20, None));
+        assert_eq!(ffmpeg_path.as_deref(), Some(Path::new("/opt/ffmpeg")));
+
+        let rendered = render(&cli.command, None).unwrap();
+        assert!(rendered.contains("fps = 30"));
+        assert!(rendered.contains("codec = \"hevc\""));
+
+        assert!(toml::from_str::<ConfigFile>("[record]\nfsp = 30\n").is_err(), "typos are rejected");
+        assert!(toml::from_str::<ConfigFile>("[record]\ncodec = \"h266\"\n").is_err());
+    }
+}
@@ -0,0 +1,560 @@
+//! System cursor image for drawing onto captured frames
+//!
+//! The pointer shape and hotspot are read from the OS (NSCursor on macOS,
+//! GetCursorInfo/GetIconInfo on Windows) and cached until the cursor changes,
+//! so the bitmap is only fetched again when the user's pointer actually
+//! switches shape. When the shape can't be read, a built-in arrow is used.
+//!
+//! On macOS the change check uses `CGSCurrentCursorSeed`, private CoreGraphics
+//! SPI with no public equivalent. It is looked up at runtime, so a macOS release
+//! without it just gets the arrow. `NSCursor` is read from the capture thread,
+//! since the CLI has no AppKit main thread to hand it to; reads only happen
+//! under the cache lock, one at a time, when the seed says the shape changed.
+
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// A cursor bitmap ready to blend onto RGB frames
+#[derive(Debug)]
+pub struct CursorImage {
+    pub width: usize,
+    pub height: usize,
+    /// Pixel inside the image that sits exactly at the pointer position
+    pub hotspot_x: usize,
+    pub hotspot_y: usize,
+    /// Premultiplied RGBA, `width * height * 4` bytes, top row first
+    pub rgba: Vec<u8>,
+}
+
+impl CursorImage {
+    /// The built-in macOS-style arrow (19x25 pixels), hotspot at its tip
+    fn arrow() -> Self {
+        // Cursor pixel data (0 = transparent, 1 = black border, 2 = white fill)
+        const CURSOR_PIXELS: &[&[u8]] = &[
+            &[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[1, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[1, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[1, 2, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[1, 2, 2, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[1, 2, 2, 2, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[1, 2, 2, 2, 2, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[1, 2, 2, 2, 2, 2, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0],
+            &[1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 0, 0, 0, 0, 0, 0],
+            &[1, 2, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0],
+            &[1, 2, 2, 2, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[1, 2, 2, 1, 0, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[1, 2, 1, 0, 0, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[1, 1, 0, 0, 0, 0, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[1, 0, 0, 0, 0, 0, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[0, 0, 0, 0, 0, 0, 0, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[0, 0, 0, 0, 0, 0, 0, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0, 0],
+            &[0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0],
+            &[0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 2, 1, 0, 0, 0, 0, 0, 0, 0],
+            &[0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 1, 0, 0, 0, 0, 0, 0, 0],
+            &[0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0],
+        ];
+
+        let rgba = CURSOR_PIXELS
+            .iter()
+            .flat_map(|row| row.iter())
+            .flat_map(|pixel| match pixel {
+                1 => [0, 0, 0, 255],
+                2 => [255, 255, 255, 255],
+                _ => [0, 0, 0, 0],
+            })
+            .collect();
+
+        CursorImage {
+            width: CURSOR_PIXELS[0].len(),
+            height: CURSOR_PIXELS.len(),
+            hotspot_x: 0,
+            hotspot_y: 0,
+            rgba,
+        }
+    }
+
+    /// Blend the cursor onto a packed RGB frame with its hotspot at (`cursor_x`, `cursor_y`)
+    pub fn draw(&self, rgb_data: &mut [u8], width: usize, height: usize, cursor_x: i32, cursor_y: i32) {
+        let left = cursor_x - self.hotspot_x as i32;
+        let top = cursor_y - self.hotspot_y as i32;
+
+        for dy in 0..self.height {
+            let y = top + dy as i32;
+            if y < 0 || y >= height as i32 {
+                continue;
+            }
+
+            for dx in 0..self.width {
+                let x = left + dx as i32;
+                if x < 0 || x >= width as i32 {
+                    continue;
+                }
+
+                let src = &self.rgba[(dy * self.width + dx) * 4..][..4];
+                let alpha = src[3] as u32;
+                if alpha == 0 {
+                    continue; // Transparent
+                }
+
+                let idx = ((y as usize) * width + (x as usize)) * 3;
+                if idx + 2 >= rgb_data.len() {
+                    continue;
+                }
+
+                // Premultiplied "over": src + dst * (1 - alpha)
+                for c in 0..3 {
+                    let dst = rgb_data[idx + c] as u32;
+                    rgb_data[idx + c] = (src[c] as u32 + dst * (255 - alpha) / 255).min(255) as u8;
+                }
+            }
+        }
+    }
+}
+
+struct CachedCursor {
+    /// Platform token that changes whenever the cursor shape does
+    key: u64,
+    image: Arc<CursorImage>,
+}
+
+static CURSOR_CACHE: Mutex<Option<CachedCursor>> = Mutex::new(None);
+static ARROW: OnceLock<Arc<CursorImage>> = OnceLock::new();
+
+fn arrow() -> Arc<CursorImage> {
+    ARROW.get_or_init(|| Arc::new(CursorImage::arrow())).clone()
+}
+
+/// The current system cursor, refetched only when the OS reports a new shape
+pub fn current_cursor() -> Arc<CursorImage> {
+    let Some(key) = platform::cursor_key() else {
+        return arrow();
+    };
+
+    let Ok(mut cache) = CURSOR_CACHE.lock() else {
+        return arrow();
+    };
+    cached_cursor(&mut cache, key, platform::fetch_cursor)
+}
+
+/// The cached image while `key` is unchanged, otherwise `fetch`ed and cached
+fn cached_cursor(
+    cache: &mut Option<CachedCursor>,
+    key: u64,
+    fetch: impl FnOnce(u64) -> Option<CursorImage>,
+) -> Arc<CursorImage> {
+    if let Some(cached) = cache.as_ref().filter(|cached| cached.key == key) {
+        return cached.image.clone();
+    }
+
+    let image = match fetch(key) {
+        Some(image) => {
+            log::debug!(
+                "Cursor changed: {}x{} with hotspot ({}, {})",
+                image.width,
+                image.height,
+                image.hotspot_x,
+                image.hotspot_y
+            );
+            Arc::new(image)
+        }
+        None => {
+            log::debug!("Could not read the system cursor image, using the built-in arrow");
+            arrow()
+        }
+    };
+
+    // Failures are cached too, so an unreadable cursor isn't retried every frame
+    *cache = Some(CachedCursor { key, image: image.clone() });
+    image
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::CursorImage;
+    use core_graphics::base::{kCGBitmapByteOrder32Big, kCGImageAlphaPremultipliedLast};
+    use core_graphics::geometry::{CGPoint, CGRect, CGSize};
+    use std::ffi::{c_char, c_void, CStr};
+    use std::sync::OnceLock;
+
+    type Id = *mut c_void;
+    type Sel = *mut c_void;
+
+    // NSCursor lives in AppKit
+    #[link(name = "AppKit", kind = "framework")]
+    extern "C" {}
+
+    #[link(name = "objc")]
+    extern "C" {
+        fn objc_getClass(name: *const c_char) -> Id;
+        fn sel_registerName(name: *const c_char) -> Sel;
+        fn objc_msgSend();
+        fn objc_autoreleasePoolPush() -> *mut c_void;
+        fn objc_autoreleasePoolPop(pool: *mut c_void);
+    }
+
+    extern "C" {
+        fn dlsym(handle: *mut c_void, symbol: *const c_char) -> *mut c_void;
+    }
+
+    /// dlfcn.h's RTLD_DEFAULT: search every loaded image
+    const RTLD_DEFAULT: *mut c_void = -2isize as *mut c_void;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGImageGetWidth(image: Id) -> usize;
+        fn CGImageGetHeight(image: Id) -> usize;
+        fn CGColorSpaceCreateDeviceRGB() -> Id;
+        fn CGColorSpaceRelease(space: Id);
+        fn CGBitmapContextCreate(
+            data: *mut c_void,
+            width: usize,
+            height: usize,
+            bits_per_component: usize,
+            bytes_per_row: usize,
+            space: Id,
+            bitmap_info: u32,
+        ) -> Id;
+        fn CGContextDrawImage(context: Id, rect: CGRect, image: Id);
+        fn CGContextRelease(context: Id);
+    }
+
+    /// `CGSCurrentCursorSeed`, which bumps every time the system cursor changes.
+    /// Private SPI, so it's resolved at runtime rather than linked against.
+    fn cursor_seed() -> Option<unsafe extern "C" fn() -> i32> {
+        static SEED: OnceLock<Option<unsafe extern "C" fn() -> i32>> = OnceLock::new();
+        *SEED.get_or_init(|| {
+            let symbol = unsafe { dlsym(RTLD_DEFAULT, c"CGSCurrentCursorSeed".as_ptr()) };
+            if symbol.is_null() {
+                log::debug!("CGSCurrentCursorSeed is not available; drawing the built-in arrow");
+                return None;
+            }
+            Some(unsafe { std::mem::transmute::<*mut c_void, unsafe extern "C" fn() -> i32>(symbol) })
+        })
+    }
+
+    pub fn cursor_key() -> Option<u64> {
+        let seed = cursor_seed()?;
+        Some(unsafe { seed() } as u32 as u64)
+    }
+
+    pub fn fetch_cursor(_key: u64) -> Option<CursorImage> {
+        unsafe {
+            let pool = objc_autoreleasePoolPush();
+            let image = read_system_cursor();
+            objc_autoreleasePoolPop(pool);
+            image
+        }
+    }
+
+    unsafe fn sel(selector: &CStr) -> Sel {
+        sel_registerName(selector.as_ptr())
+    }
+
+    unsafe fn send_id(receiver: Id, selector: &CStr) -> Id {
+        let send: unsafe extern "C" fn(Id, Sel) -> Id = std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        send(receiver, sel(selector))
+    }
+
+    unsafe fn send_point(receiver: Id, selector: &CStr) -> CGPoint {
+        let send: unsafe extern "C" fn(Id, Sel) -> CGPoint =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        send(receiver, sel(selector))
+    }
+
+    unsafe fn send_size(receiver: Id, selector: &CStr) -> CGSize {
+        let send: unsafe extern "C" fn(Id, Sel) -> CGSize =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        send(receiver, sel(selector))
+    }
+
+    unsafe fn read_system_cursor() -> Option<CursorImage> {
+        let cursor_class = objc_getClass(c"NSCursor".as_ptr());
+        if cursor_class.is_null() {
+            return None;
+        }
+
+        let cursor = send_id(cursor_class, c"currentSystemCursor");
+        if cursor.is_null() {
+            return None;
+        }
+
+        let ns_image = send_id(cursor, c"image");
+        if ns_image.is_null() {
+            return None;
+        }
+
+        let hotspot = send_point(cursor, c"hotSpot");
+        let size = send_size(ns_image, c"size");
+
+        // [image CGImageForProposedRect:NULL context:nil hints:nil] (not owned by us)
+        let for_rect: unsafe extern "C" fn(Id, Sel, *mut c_void, Id, Id) -> Id =
+            std::mem::transmute(objc_msgSend as unsafe extern "C" fn());
+        let cg_image = for_rect(
+            ns_image,
+            sel(c"CGImageForProposedRect:context:hints:"),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        );
+        if cg_image.is_null() {
+            return None;
+        }
+
+        let width = CGImageGetWidth(cg_image);
+        let height = CGImageGetHeight(cg_image);
+        if width == 0 || height == 0 || size.width <= 0.0 || size.height <= 0.0 {
+            return None;
+        }
+
+        // Draw into our own buffer so the pixel format is always premultiplied RGBA
+        let mut rgba = vec![0u8; width * height * 4];
+        let space = CGColorSpaceCreateDeviceRGB();
+        let context = CGBitmapContextCreate(
+            rgba.as_mut_ptr() as *mut c_void,
+            width,
+            height,
+            8,
+            width * 4,
+            space,
+            kCGImageAlphaPremultipliedLast | kCGBitmapByteOrder32Big,
+        );
+        CGColorSpaceRelease(space);
+        if context.is_null() {
+            return None;
+        }
+
+        let bounds = CGRect::new(&CGPoint::new(0.0, 0.0), &CGSize::new(width as f64, height as f64));
+        CGContextDrawImage(context, bounds, cg_image);
+        CGContextRelease(context);
+
+        // NSCursor reports the hotspot in points; the bitmap may be @2x
+        let scale_x = width as f64 / size.width;
+        let scale_y = height as f64 / size.height;
+
+        Some(CursorImage {
+            width,
+            height,
+            hotspot_x: ((hotspot.x * scale_x).max(0.0) as usize).min(width - 1),
+            hotspot_y: ((hotspot.y * scale_y).max(0.0) as usize).min(height - 1),
+            rgba,
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::CursorImage;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::Graphics::Gdi::{
+        DeleteObject, GetDC, GetDIBits, GetObjectW, ReleaseDC, BITMAP, BITMAPINFO,
+        BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, HBITMAP,
+    };
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetCursorInfo, GetIconInfo, CURSORINFO, HICON, ICONINFO,
+    };
+
+    /// The HCURSOR handle; each cursor shape has its own
+    pub fn cursor_key() -> Option<u64> {
+        let mut info = CURSORINFO {
+            cbSize: std::mem::size_of::<CURSORINFO>() as u32,
+            ..Default::default()
+        };
+        unsafe { GetCursorInfo(&mut info) }.ok()?;
+
+        if info.hCursor.is_invalid() {
+            return None;
+        }
+        Some(info.hCursor.0 as u64)
+    }
+
+    pub fn fetch_cursor(key: u64) -> Option<CursorImage> {
+        let mut icon = ICONINFO::default();
+        unsafe { GetIconInfo(HICON(key as isize), &mut icon) }.ok()?;
+
+        let image = unsafe { read_icon(&icon) };
+
+        // GetIconInfo hands us copies of both bitmaps
+        unsafe {
+            if !icon.hbmColor.is_invalid() {
+                let _ = DeleteObject(icon.hbmColor);
+            }
+            let _ = DeleteObject(icon.hbmMask);
+        }
+
+        image
+    }
+
+    unsafe fn read_icon(icon: &ICONINFO) -> Option<CursorImage> {
+        let (width, height, rgba) = if !icon.hbmColor.is_invalid() {
+            let (bgra, width, height) = read_bitmap(icon.hbmColor)?;
+
+            // Older color cursors leave alpha empty and rely on the AND mask
+            let has_alpha = bgra.chunks_exact(4).any(|p| p[3] != 0);
+            let mask = if has_alpha { None } else { read_bitmap(icon.hbmMask) };
+
+            let mut rgba = Vec::with_capacity(width * height * 4);
+            for (i, p) in bgra.chunks_exact(4).enumerate() {
+                let alpha = match &mask {
+                    None => p[3] as u32,
+                    Some((mask, _, _)) if mask.get(i * 4).copied().unwrap_or(0) != 0 => 0,
+                    Some(_) => 255,
+                };
+                rgba.extend_from_slice(&[
+                    (p[2] as u32 * alpha / 255) as u8,
+                    (p[1] as u32 * alpha / 255) as u8,
+                    (p[0] as u32 * alpha / 255) as u8,
+                    alpha as u8,
+                ]);
+            }
+            (width, height, rgba)
+        } else {
+            // Monochrome: the mask holds the AND bitmap above the XOR bitmap
+            let (bits, width, double_height) = read_bitmap(icon.hbmMask)?;
+            let height = double_height / 2;
+            let plane = width * height;
+
+            let mut rgba = Vec::with_capacity(plane * 4);
+            for i in 0..plane {
+                let and = bits[i * 4] != 0;
+                let xor = bits[(plane + i) * 4] != 0;
+                rgba.extend_from_slice(match (and, xor) {
+                    (true, false) => &[0, 0, 0, 0],
+                    (false, true) => &[255, 255, 255, 255],
+                    // Black, and "invert screen" (I-beam) approximated as black
+                    _ => &[0, 0, 0, 255],
+                });
+            }
+            (width, height, rgba)
+        };
+
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        Some(CursorImage {
+            width,
+            height,
+            hotspot_x: (icon.xHotspot as usize).min(width - 1),
+            hotspot_y: (icon.yHotspot as usize).min(height - 1),
+            rgba,
+        })
+    }
+
+    /// Read a GDI bitmap as top-down 32-bit BGRA
+    unsafe fn read_bitmap(bitmap: HBITMAP) -> Option<(Vec<u8>, usize, usize)> {
+        let mut header = BITMAP::default();
+        let read = GetObjectW(
+            bitmap,
+            std::mem::size_of::<BITMAP>() as i32,
+            Some(&mut header as *mut BITMAP as *mut std::ffi::c_void),
+        );
+        if read == 0 {
+            return None;
+        }
+
+        let width = header.bmWidth.max(0) as usize;
+        let height = header.bmHeight.unsigned_abs() as usize;
+        if width == 0 || height == 0 {
+            return None;
+        }
+
+        let mut info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width as i32,
+                biHeight: -(height as i32), // Negative height = top-down rows
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut buffer = vec![0u8; width * height * 4];
+        let screen_dc = GetDC(HWND::default());
+        let lines = GetDIBits(
+            screen_dc,
+            bitmap,
+            0,
+            height as u32,
+            Some(buffer.as_mut_ptr() as *mut std::ffi::c_void),
+            &mut info,
+            DIB_RGB_COLORS,
+        );
+        ReleaseDC(HWND::default(), screen_dc);
+
+        if lines == 0 {
+            return None;
+        }
+        Some((buffer, width, height))
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+mod platform {
+    use super::CursorImage;
+
+    pub fn cursor_key() -> Option<u64> {
+        None
+    }
+
+    pub fn fetch_cursor(_key: u64) -> Option<CursorImage> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_is_drawn_at_its_hotspot() {
+        // 2x2 cursor: opaque red, half-transparent white (premultiplied), two clear pixels
+        let cursor = CursorImage {
+            width: 2,
+            height: 2,
+            hotspot_x: 1,
+            hotspot_y: 1,
+            rgba: vec![255, 0, 0, 255, 128, 128, 128, 128, 0, 0, 0, 0, 0, 0, 0, 0],
+        };
+
+        let (width, height) = (4, 4);
+        let mut rgb = vec![0u8; width * height * 3];
+        cursor.draw(&mut rgb, width, height, 2, 2);
+
+        // The image's top-left lands one pixel up and left of the pointer
+        let pixel = |x: usize, y: usize| &rgb[(y * width + x) * 3..][..3];
+        assert_eq!(pixel(1, 1), &[255, 0, 0]);
+        assert_eq!(pixel(2, 1), &[128, 128, 128]);
+        assert_eq!(pixel(2, 2), &[0, 0, 0]);
+
+        // Clipped at the frame edge: only the clear bottom-right pixel is on screen
+        let mut edge = vec![7u8; width * height * 3];
+        cursor.draw(&mut edge, width, height, 0, 0);
+        assert!(edge.iter().all(|&v| v == 7));
+
+        let arrow = CursorImage::arrow();
+        assert_eq!(arrow.rgba.len(), arrow.width * arrow.height * 4);
+        assert_eq!((arrow.hotspot_x, arrow.hotspot_y), (0, 0));
+    }
+
+    #[test]
+    fn test_cursor_is_fetched_only_when_its_key_changes() {
+        let image = |width| CursorImage { width, height: 1, hotspot_x: 0, hotspot_y: 0, rgba: vec![0; width * 4] };
+        let mut cache = None;
+
+        assert_eq!(cached_cursor(&mut cache, 1, |_| Some(image(3))).width, 3);
+        let same_key = cached_cursor(&mut cache, 1, |_| panic!("fetched again for an unchanged cursor"));
+        assert_eq!(same_key.width, 3);
+        assert_eq!(cached_cursor(&mut cache, 2, |key| Some(image(key as usize))).width, 2);
+
+        // A cursor that can't be read is drawn as the arrow, and not retried until it changes
+        let unreadable = cached_cursor(&mut cache, 3, |_| None);
+        assert!(Arc::ptr_eq(&unreadable, &arrow()));
+        assert!(Arc::ptr_eq(&cached_cursor(&mut cache, 3, |_| panic!("retried")), &arrow()));
+    }
+}
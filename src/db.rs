@@ -75,6 +75,10 @@ impl Database {
                 device_name TEXT NOT NULL,
                 started_at TIMESTAMP NOT NULL,
                 ended_at TIMESTAMP,
+                captured_frames INTEGER,
+                achieved_fps REAL,
+                late_frames INTEGER,
+                paused_ms INTEGER,
                 created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
             )
             "#,
@@ -93,6 +97,10 @@ impl Database {
                 chunk_index INTEGER,
                 session_id INTEGER,
                 fps INTEGER,
+                encoder_name TEXT,
+                quality INTEGER,
+                sha256 TEXT,
+                encryption_nonce TEXT,
                 created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
                 FOREIGN KEY (session_id) REFERENCES recording_sessions(id)
             )
@@ -122,6 +130,16 @@ impl Database {
         .execute(&self.pool)
         .await?;
 
+        // Sessions are always looked up by task (concat, total recording time)
+        sqlx::query(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_recording_sessions_task_id
+            ON recording_sessions(task_id)
+            "#,
+        )
+        .execute(&self.pool)
+        .await?;
+
         sqlx::query(
             r#"
             CREATE INDEX IF NOT EXISTS idx_frames_video_chunk_id
@@ -191,11 +209,38 @@ impl Database {
                 .await?;
         }
 
-        if !chunk_column_names.contains(&"fps".to_string()) {
-            log::info!("Adding fps column to video_chunks table");
-            sqlx::query("ALTER TABLE video_chunks ADD COLUMN fps INTEGER")
-                .execute(&self.pool)
-                .await?;
+        for (column, column_type) in [("fps", "INTEGER"), ("encoder_name", "TEXT"), ("quality", "INTEGER"), ("sha256", "TEXT"),
+            ("encryption_nonce", "TEXT")] {
+            if !chunk_column_names.contains(&column.to_string()) {
+                log::info!("Adding {} column to video_chunks table", column);
+                sqlx::query(&format!("ALTER TABLE video_chunks ADD COLUMN {} {}", column, column_type))
+                    .execute(&self.pool)
+                    .await?;
+            }
+        }
+
+        // Migration: Add capture stats columns to recording_sessions if they don't exist
+        let session_columns: Vec<(i64, String, String, i64, Option<String>, i64)> =
+            sqlx::query_as("PRAGMA table_info(recording_sessions)")
+            .fetch_all(&self.pool)
+            .await
+            .unwrap_or_default();
+
+        let session_column_names: Vec<String> = session_columns.iter().map(|(_, name, _, _, _, _)| name.clone()).collect();
+        log::debug!("Existing recording_sessions table columns: {:?}", session_column_names);
+
+        for (column, column_type) in [
+            ("captured_frames", "INTEGER"),
+            ("achieved_fps", "REAL"),
+            ("late_frames", "INTEGER"),
+            ("paused_ms", "INTEGER"),
+        ] {
+            if !session_column_names.contains(&column.to_string()) {
+                log::info!("Adding {} column to recording_sessions table", column);
+                sqlx::query(&format!("ALTER TABLE recording_sessions ADD COLUMN {} {}", column, column_type))
+                    .execute(&self.pool)
+                    .await?;
+            }
         }
 
         Ok(())
@@ -248,11 +293,35 @@ impl Database {
         Ok(())
     }
 
+    /// Store what the capture loop achieved for a session
+    pub async fn set_session_capture_stats(
+        &self,
+        session_id: i64,
+        captured_frames: u64,
+        achieved_fps: f64,
+        late_frames: u64,
+        paused_ms: u64,
+    ) -> Result<()> {
+        sqlx::query(
+            "UPDATE recording_sessions SET captured_frames = ?1, achieved_fps = ?2, late_frames = ?3, paused_ms = ?4 WHERE id = ?5",
+        )
+        .bind(captured_frames as i64)
+        .bind(achieved_fps)
+        .bind(late_frames as i64)
+        .bind(paused_ms as i64)
+        .bind(session_id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Get all recording sessions for a task_id
     pub async fn get_sessions_for_task(&self, task_id: &str) -> Result<Vec<RecordingSessionInfo>> {
         let rows = sqlx::query_as::<_, RecordingSessionInfo>(
             r#"
-            SELECT id, task_id, device_name, started_at, ended_at, created_at
+            SELECT id, task_id, device_name, started_at, ended_at,
+                   captured_frames, achieved_fps, late_frames, paused_ms, created_at
             FROM recording_sessions
             WHERE task_id = ?1
             ORDER BY started_at ASC
@@ -265,6 +334,64 @@ impl Database {
         Ok(rows)
     }
 
+    /// Get all recording sessions that started within [from, to), across tasks
+    pub async fn get_sessions_between(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<RecordingSessionInfo>> {
+        let rows = sqlx::query_as::<_, RecordingSessionInfo>(
+            r#"
+            SELECT id, task_id, device_name, started_at, ended_at,
+                   captured_frames, achieved_fps, late_frames, paused_ms, created_at
+            FROM recording_sessions
+            WHERE started_at >= ?1 AND started_at < ?2
+            ORDER BY started_at ASC
+            "#,
+        )
+        .bind(from)
+        .bind(to)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Get the capture timestamps of every frame recorded in a session, in order
+    pub async fn get_frame_timestamps_for_session(&self, session_id: i64) -> Result<Vec<DateTime<Utc>>> {
+        let rows = sqlx::query_scalar::<_, DateTime<Utc>>(
+            r#"
+            SELECT f.timestamp
+            FROM frames f
+            JOIN video_chunks vc ON f.video_chunk_id = vc.id
+            WHERE vc.session_id = ?1
+            ORDER BY f.timestamp ASC
+            "#,
+        )
+        .bind(session_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// Highest PTS stored for any frame of a task, None if it has no frames yet
+    pub async fn get_max_pts_for_task(&self, task_id: &str) -> Result<Option<i64>> {
+        let max_pts: Option<i64> = sqlx::query_scalar(
+            r#"
+            SELECT MAX(f.pts)
+            FROM frames f
+            JOIN video_chunks vc ON f.video_chunk_id = vc.id
+            WHERE vc.task_id = ?1
+            "#,
+        )
+        .bind(task_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(max_pts)
+    }
+
     /// Get total recording time for a task_id (sum of all session durations)
     pub async fn get_total_recording_time(&self, task_id: &str) -> Result<f64> {
         let total_seconds: Option<f64> = sqlx::query_scalar(
@@ -293,6 +420,8 @@ impl Database {
         chunk_index: Option<i64>,
         session_id: Option<i64>,
         fps: Option<i64>,
+        encoder_name: Option<&str>,
+        quality: Option<i64>,
     ) -> Result<i64> {
         // Retry logic for database locking issues
         const MAX_RETRIES: u32 = 5;
@@ -301,7 +430,7 @@ impl Database {
         let mut attempt = 0;
         loop {
             match sqlx::query(
-                "INSERT INTO video_chunks (file_path, device_name, recording_type, task_id, chunk_index, session_id, fps) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                "INSERT INTO video_chunks (file_path, device_name, recording_type, task_id, chunk_index, session_id, fps, encoder_name, quality) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
             )
             .bind(file_path)
             .bind(device_name)
@@ -310,6 +439,8 @@ impl Database {
             .bind(chunk_index)
             .bind(session_id)
             .bind(fps)
+            .bind(encoder_name)
+            .bind(quality)
             .execute(&self.pool)
             .await {
                 Ok(result) => return Ok(result.last_insert_rowid()),
@@ -524,7 +655,8 @@ impl Database {
     pub async fn get_chunks_by_task_id(&self, task_id: &str) -> Result<Vec<VideoChunkInfo>> {
         let rows = sqlx::query_as::<_, VideoChunkInfo>(
             r#"
-            SELECT id, file_path, device_name, recording_type, task_id, chunk_index, created_at, fps
+            SELECT id, file_path, device_name, recording_type, task_id, chunk_index, created_at, session_id, fps,
+                   encoder_name, quality, sha256, encryption_nonce
             FROM video_chunks
             WHERE task_id = ?1
             ORDER BY created_at ASC
@@ -537,6 +669,22 @@ impl Database {
         Ok(rows)
     }
 
+    /// Get every video chunk, oldest first
+    pub async fn get_all_chunks(&self) -> Result<Vec<VideoChunkInfo>> {
+        let rows = sqlx::query_as::<_, VideoChunkInfo>(
+            r#"
+            SELECT id, file_path, device_name, recording_type, task_id, chunk_index, created_at, session_id, fps,
+                   encoder_name, quality, sha256, encryption_nonce
+            FROM video_chunks
+            ORDER BY created_at ASC
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows)
+    }
+
     /// Get all frames for a specific task_id across all chunks
     pub async fn get_frames_by_task_id(&self, task_id: &str) -> Result<Vec<FrameInfo>> {
         let rows = sqlx::query_as::<_, FrameInfo>(
@@ -566,6 +714,60 @@ impl Database {
 
         Ok(rows)
     }
+
+    /// Delete a video chunk and its frames. Returns false if there was no such chunk.
+    pub async fn delete_chunk(&self, video_chunk_id: i64) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM frames WHERE video_chunk_id = ?1")
+            .bind(video_chunk_id)
+            .execute(&mut *tx)
+            .await?;
+        let result = sqlx::query("DELETE FROM video_chunks WHERE id = ?1")
+            .bind(video_chunk_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Store the SHA-256 of the finished chunk recorded to `file_path`
+    pub async fn set_chunk_sha256(&self, file_path: &str, sha256: &str) -> Result<bool> {
+        let result = sqlx::query("UPDATE video_chunks SET sha256 = ?1 WHERE file_path = ?2")
+            .bind(sha256)
+            .bind(file_path)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Point the chunk recorded to `old_path` at `new_path` after it was encrypted
+    /// (with its nonce) or decrypted (with None)
+    pub async fn set_chunk_file(&self, old_path: &str, new_path: &str, encryption_nonce: Option<&str>) -> Result<bool> {
+        let result = sqlx::query("UPDATE video_chunks SET file_path = ?1, encryption_nonce = ?2 WHERE file_path = ?3")
+            .bind(new_path)
+            .bind(encryption_nonce)
+            .bind(old_path)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delete the chunk (and its frames) recorded to `file_path`, e.g. after evicting the file
+    pub async fn delete_chunk_by_path(&self, file_path: &str) -> Result<bool> {
+        let id: Option<i64> = sqlx::query_scalar("SELECT id FROM video_chunks WHERE file_path = ?1")
+            .bind(file_path)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        match id {
+            Some(id) => self.delete_chunk(id).await,
+            None => Ok(false),
+        }
+    }
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -595,7 +797,18 @@ pub struct VideoChunkInfo {
     pub task_id: Option<String>,
     pub chunk_index: Option<i64>,
     pub created_at: DateTime<Utc>,
+    /// Recording session the chunk belongs to, None for chunks from before sessions were tracked
+    pub session_id: Option<i64>,
+    /// Frame rate the chunk was encoded at
     pub fps: Option<i64>,
+    /// Encoder that opened the chunk, e.g. libx264
+    pub encoder_name: Option<String>,
+    /// Quality level (1-10), None for bitrate or --crf recordings
+    pub quality: Option<i64>,
+    /// SHA-256 of the finished file, None while recording or for chunks from before checksums
+    pub sha256: Option<String>,
+    /// Nonce (hex) the chunk was encrypted under with --encrypt, None for plain chunks
+    pub encryption_nonce: Option<String>,
 }
 
 #[derive(Debug, sqlx::FromRow)]
@@ -606,5 +819,98 @@ pub struct RecordingSessionInfo {
     pub device_name: String,
     pub started_at: DateTime<Utc>,
     pub ended_at: Option<DateTime<Utc>>,
+    /// Capture stats, None for sessions recorded before they were tracked
+    pub captured_frames: Option<i64>,
+    pub achieved_fps: Option<f64>,
+    pub late_frames: Option<i64>,
+    /// Time spent paused (SIGUSR1) inside the session, in milliseconds
+    pub paused_ms: Option<i64>,
     pub created_at: DateTime<Utc>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_session_lifecycle_and_total_recording_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(dir.path().join("test.db")).await.unwrap();
+
+        let t0 = DateTime::parse_from_rfc3339("2025-01-14T10:00:00Z").unwrap().with_timezone(&Utc);
+        let at = |secs: i64| t0 + chrono::Duration::seconds(secs);
+
+        let first = db.create_recording_session("demo", "host", at(0)).await.unwrap();
+        db.end_recording_session(first, at(90)).await.unwrap();
+        // A later end time must not overwrite the first one
+        db.end_recording_session(first, at(500)).await.unwrap();
+
+        let second = db.create_recording_session("demo", "host", at(120)).await.unwrap();
+        db.end_recording_session(second, at(150)).await.unwrap();
+
+        // Still recording: not counted in the total
+        db.create_recording_session("demo", "host", at(200)).await.unwrap();
+        db.create_recording_session("other", "host", at(0)).await.unwrap();
+        db.checkpoint_wal().await.unwrap();
+
+        let sessions = db.get_sessions_for_task("demo").await.unwrap();
+        assert_eq!(sessions.len(), 3);
+        assert_eq!(sessions[0].id, first);
+        assert_eq!(sessions[0].ended_at, Some(at(90)));
+        assert_eq!(sessions[2].ended_at, None);
+
+        let total = db.get_total_recording_time("demo").await.unwrap();
+        assert!((total - 120.0).abs() < 0.01, "total was {}", total);
+        assert_eq!(db.get_total_recording_time("missing").await.unwrap(), 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_chunk_keeps_its_encoder_settings() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(dir.path().join("test.db")).await.unwrap();
+
+        db.insert_video_chunk("/a.mp4", "host", Some("task"), Some("demo"), Some(0), None, Some(30), Some("libx264"), Some(8))
+            .await
+            .unwrap();
+        // Bitrate mode has no quality level
+        db.insert_video_chunk("/b.mp4", "host", Some("task"), Some("demo"), Some(1), None, Some(30), Some("h264_nvenc"), None)
+            .await
+            .unwrap();
+
+        assert!(db.set_chunk_sha256("/a.mp4", "ba7816bf").await.unwrap());
+        assert!(!db.set_chunk_sha256("/missing.mp4", "ba7816bf").await.unwrap());
+
+        let chunks = db.get_chunks_by_task_id("demo").await.unwrap();
+        assert_eq!(chunks[0].sha256.as_deref(), Some("ba7816bf"));
+        assert_eq!(chunks[1].sha256, None);
+        assert_eq!(chunks[0].encryption_nonce, None);
+        assert_eq!(chunks[0].encoder_name.as_deref(), Some("libx264"));
+        assert_eq!(chunks[0].quality, Some(8));
+        assert_eq!(chunks[1].encoder_name.as_deref(), Some("h264_nvenc"));
+        assert_eq!(chunks[1].quality, None);
+
+        assert!(db.set_chunk_file("/a.mp4", "/a.mp4.enc", Some("00ff")).await.unwrap());
+        let chunks = db.get_chunks_by_task_id("demo").await.unwrap();
+        assert_eq!(chunks[0].file_path, "/a.mp4.enc");
+        assert_eq!(chunks[0].encryption_nonce.as_deref(), Some("00ff"));
+    }
+
+    #[tokio::test]
+    async fn test_max_pts_for_task_spans_its_chunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::new(dir.path().join("test.db")).await.unwrap();
+        assert_eq!(db.get_max_pts_for_task("demo").await.unwrap(), None);
+
+        for (path, task, pts) in [("/a.mp4", "demo", [0, 1]), ("/b.mp4", "demo", [2, 3]), ("/c.mp4", "other", [40, 41])] {
+            db.insert_video_chunk(path, "host", Some("task"), Some(task), Some(0), None, Some(30), None, None)
+                .await
+                .unwrap();
+            for pts in pts {
+                db.insert_frame("host", None, pts == 0, Some(pts), None, Some(0), Some(1920), Some(1080)).await.unwrap();
+            }
+        }
+
+        assert_eq!(db.get_max_pts_for_task("demo").await.unwrap(), Some(3));
+        assert_eq!(db.get_max_pts_for_task("missing").await.unwrap(), None);
+    }
+}
@@ -1,100 +1,315 @@
+//! Display enumeration shared by every command
+//!
+//! A display index (`--display N`, the `display_index` stored with frames) always
+//! means entry N of scrap's display list, because scrap is what both `screenshot`
+//! and `record` capture with. Anything that needs more than scrap offers, like
+//! display bounds for multi-monitor switching, is looked up per scrap entry here
+//! so it can't drift out of that order.
+
 #[cfg(target_os = "macos")]
-use core_graphics::display::CGDisplay;
+use core_graphics::display::{CGDirectDisplayID, CGDisplay};
 use scrap::Display;
 use crate::error::ScreenRecError;
 
-#[derive(Debug, Clone)]
+/// All displays in capture order; index N here is `--display N`
+pub fn all_displays() -> Result<Vec<Display>, ScreenRecError> {
+    Display::all().map_err(|e| {
+        ScreenRecError::CaptureError(format!("Failed to enumerate displays: {}{}", e, wayland_hint()))
+    })
+}
+
+/// scrap captures through X11 only; a Wayland session needs XWayland and `DISPLAY`
+#[cfg(target_os = "linux")]
+fn wayland_hint() -> &'static str {
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    if wayland && std::env::var_os("DISPLAY").is_none() {
+        " (Wayland session without DISPLAY: capture needs an X11 session or XWayland)"
+    } else {
+        ""
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn wayland_hint() -> &'static str {
+    ""
+}
+
+/// The display a `--display` index refers to
+pub fn display_by_index(index: usize) -> Result<Display, ScreenRecError> {
+    let displays = all_displays()?;
+
+    if displays.is_empty() {
+        return Err(ScreenRecError::CaptureError(
+            "No displays found".to_string(),
+        ));
+    }
+
+    let count = displays.len();
+    displays.into_iter().nth(index).ok_or_else(|| {
+        ScreenRecError::CaptureError(format!(
+            "Display {} not found (only {} displays available)",
+            index, count
+        ))
+    })
+}
+
+/// CoreGraphics IDs of the online displays, in the order scrap lists them
+/// (scrap's `Display::all` is built from this same call on macOS)
+#[cfg(target_os = "macos")]
+fn online_display_ids() -> Result<Vec<CGDirectDisplayID>, ScreenRecError> {
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGGetOnlineDisplayList(
+            max_displays: u32,
+            online_displays: *mut CGDirectDisplayID,
+            display_count: *mut u32,
+        ) -> i32;
+    }
+
+    let mut ids = [0 as CGDirectDisplayID; 16];
+    let mut count = 0u32;
+    let err = unsafe { CGGetOnlineDisplayList(ids.len() as u32, ids.as_mut_ptr(), &mut count) };
+    if err != 0 {
+        return Err(ScreenRecError::CaptureError(format!(
+            "Failed to list online displays (CGError {})",
+            err
+        )));
+    }
+
+    Ok(ids[..count as usize].to_vec())
+}
+
+/// A display and where it sits on the desktop.
+///
+/// `x` and `y` are desktop coordinates, the space cursor positions are reported
+/// in: points on macOS, pixels on Windows. `width` and `height` are the size of
+/// the captured frame in pixels. On a Retina display the two differ by
+/// `scale_factor`, the number of frame pixels per desktop unit (2.0 for a
+/// typical Retina panel, 1.0 otherwise).
+#[derive(Debug, Clone, serde::Serialize)]
 pub struct DisplayInfo {
     pub index: usize,
     pub width: usize,
     pub height: usize,
     pub x: i32,
     pub y: i32,
-    #[allow(dead_code)]
     pub is_primary: bool,
+    pub scale_factor: f64,
+}
+
+impl DisplayInfo {
+    /// Width in desktop coordinates
+    pub fn desktop_width(&self) -> i32 {
+        (self.width as f64 / self.scale_factor).round() as i32
+    }
+
+    /// Height in desktop coordinates
+    pub fn desktop_height(&self) -> i32 {
+        (self.height as f64 / self.scale_factor).round() as i32
+    }
+
+    /// A desktop position (e.g. the cursor) in frame pixels, relative to this display's top-left corner
+    pub fn to_frame_pixels(&self, x: i32, y: i32) -> (i32, i32) {
+        (
+            ((x - self.x) as f64 * self.scale_factor).round() as i32,
+            ((y - self.y) as f64 * self.scale_factor).round() as i32,
+        )
+    }
+}
+
+/// Frame pixels per desktop unit for a display `pixels` wide and `desktop` units wide
+#[cfg_attr(not(any(target_os = "macos", target_os = "windows")), allow(dead_code))]
+fn scale_factor(pixels: usize, desktop: f64) -> f64 {
+    let scale = pixels as f64 / desktop;
+    if scale.is_finite() && scale > 0.0 {
+        scale
+    } else {
+        1.0
+    }
 }
 
 /// Get all displays with their bounds information
 #[cfg(target_os = "macos")]
 pub fn get_all_displays_with_bounds() -> Result<Vec<DisplayInfo>, ScreenRecError> {
-    let displays = Display::all().map_err(|e| {
-        ScreenRecError::CaptureError(format!("Failed to enumerate displays: {}", e))
-    })?;
+    let displays = all_displays()?;
+    let ids = online_display_ids()?;
 
     let mut display_infos = Vec::new();
 
     for (index, display) in displays.iter().enumerate() {
-        // Get CGDisplay for this display index
-        let cg_display = CGDisplay::new(index as u32);
+        // Map the scrap index to its CoreGraphics display (an index is not a display ID)
+        let Some(&id) = ids.get(index) else {
+            log::warn!("No CoreGraphics display for display {}; skipping its bounds", index);
+            continue;
+        };
+        let cg_display = CGDisplay::new(id);
         let bounds = cg_display.bounds();
 
+        // scrap captures backing pixels while CoreGraphics bounds (and cursor
+        // positions) are in points; the ratio is the display's backing scale factor
         display_infos.push(DisplayInfo {
             index,
             width: display.width(),
             height: display.height(),
             x: bounds.origin.x as i32,
             y: bounds.origin.y as i32,
-            is_primary: index == 0, // Primary display is typically index 0
+            is_primary: cg_display.is_main(),
+            scale_factor: scale_factor(display.width(), bounds.size.width),
         });
     }
 
     Ok(display_infos)
 }
 
+/// A monitor's desktop rectangle as `EnumDisplayMonitors` reports it
+#[cfg(target_os = "windows")]
+struct MonitorRect {
+    x: i32,
+    y: i32,
+    width: usize,
+    height: usize,
+    is_primary: bool,
+}
+
+/// Every monitor on the desktop, in `EnumDisplayMonitors` order
+#[cfg(target_os = "windows")]
+fn enum_monitors() -> Result<Vec<MonitorRect>, ScreenRecError> {
+    use windows::Win32::Foundation::{BOOL, LPARAM, RECT};
+    use windows::Win32::Graphics::Gdi::{EnumDisplayMonitors, GetMonitorInfoW, HDC, HMONITOR, MONITORINFO};
+    use windows::Win32::UI::WindowsAndMessaging::MONITORINFOF_PRIMARY;
+
+    unsafe extern "system" fn collect(monitor: HMONITOR, _hdc: HDC, _clip: *mut RECT, lparam: LPARAM) -> BOOL {
+        let monitors = &mut *(lparam.0 as *mut Vec<MonitorRect>);
+        let mut info = MONITORINFO {
+            cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+        if GetMonitorInfoW(monitor, &mut info).as_bool() {
+            let rect = info.rcMonitor;
+            monitors.push(MonitorRect {
+                x: rect.left,
+                y: rect.top,
+                width: (rect.right - rect.left).max(0) as usize,
+                height: (rect.bottom - rect.top).max(0) as usize,
+                is_primary: info.dwFlags & MONITORINFOF_PRIMARY != 0,
+            });
+        }
+        BOOL(1)
+    }
+
+    let mut monitors: Vec<MonitorRect> = Vec::new();
+    let ok = unsafe {
+        EnumDisplayMonitors(HDC::default(), None, Some(collect), LPARAM(&mut monitors as *mut Vec<MonitorRect> as isize))
+    };
+    if !ok.as_bool() {
+        return Err(ScreenRecError::CaptureError("Failed to enumerate monitors".to_string()));
+    }
+
+    Ok(monitors)
+}
+
+/// Pair scrap's displays with the monitors Windows enumerates. scrap lists DXGI
+/// outputs, whose order can differ from `EnumDisplayMonitors`, so each display
+/// takes the first unused monitor of the same size and only then the next unused
+/// one. With equal sizes (or DPI scaling hiding them) Windows' order is kept.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn assign_monitors(display_sizes: &[(usize, usize)], monitor_sizes: &[(usize, usize)]) -> Vec<Option<usize>> {
+    let mut used = vec![false; monitor_sizes.len()];
+    display_sizes
+        .iter()
+        .map(|size| {
+            let same_size = (0..monitor_sizes.len()).find(|&i| !used[i] && monitor_sizes[i] == *size);
+            let pick = same_size.or_else(|| (0..monitor_sizes.len()).find(|&i| !used[i]))?;
+            used[pick] = true;
+            Some(pick)
+        })
+        .collect()
+}
+
 /// Get all displays with their bounds information (Windows implementation)
 #[cfg(target_os = "windows")]
 pub fn get_all_displays_with_bounds() -> Result<Vec<DisplayInfo>, ScreenRecError> {
-    let displays = Display::all().map_err(|e| {
-        ScreenRecError::CaptureError(format!("Failed to enumerate displays: {}", e))
-    })?;
+    let displays = all_displays()?;
+    let monitors = enum_monitors()?;
+
+    let display_sizes: Vec<_> = displays.iter().map(|d| (d.width(), d.height())).collect();
+    let monitor_sizes: Vec<_> = monitors.iter().map(|m| (m.width, m.height)).collect();
+    let assignment = assign_monitors(&display_sizes, &monitor_sizes);
 
     let mut display_infos = Vec::new();
 
     for (index, display) in displays.iter().enumerate() {
-        // On Windows, scrap doesn't provide position info, so we default to (0, 0)
-        // This is a limitation but shouldn't affect single-display recording
+        let Some(monitor) = assignment[index].map(|i| &monitors[i]) else {
+            log::warn!("No monitor found for display {}; skipping its bounds", index);
+            continue;
+        };
+
+        // Capture size comes from scrap (physical pixels); the position from Windows,
+        // which reports scaled coordinates unless the process is DPI aware
         display_infos.push(DisplayInfo {
             index,
             width: display.width(),
             height: display.height(),
-            x: 0,
-            y: 0,
-            is_primary: index == 0, // Primary display is typically index 0
+            x: monitor.x,
+            y: monitor.y,
+            is_primary: monitor.is_primary,
+            scale_factor: scale_factor(display.width(), monitor.width as f64),
         });
     }
 
     Ok(display_infos)
 }
 
-/// Determine which display contains the given cursor position (macOS)
-#[cfg(target_os = "macos")]
-pub fn get_display_at_cursor(cursor_x: i32, cursor_y: i32) -> Result<usize, ScreenRecError> {
-    let displays = get_all_displays_with_bounds()?;
-
-    // Find which display contains the cursor
-    for display in &displays {
-        let x_min = display.x;
-        let x_max = display.x + display.width as i32;
-        let y_min = display.y;
-        let y_max = display.y + display.height as i32;
+/// Get all displays with their bounds information (Linux implementation)
+///
+/// scrap captures X11 screens, and a screen's root window already spans every
+/// monitor attached to it, so the usual single screen sits at the origin and
+/// the cursor never has another display to switch to. Each X screen has its own
+/// coordinate space; any extra ones are placed to the right so bounds never
+/// overlap. X11 works in pixels.
+#[cfg(target_os = "linux")]
+pub fn get_all_displays_with_bounds() -> Result<Vec<DisplayInfo>, ScreenRecError> {
+    let mut x = 0;
+    let mut display_infos = Vec::new();
 
-        if cursor_x >= x_min && cursor_x < x_max && cursor_y >= y_min && cursor_y < y_max {
-            return Ok(display.index);
-        }
+    for (index, display) in all_displays()?.iter().enumerate() {
+        display_infos.push(DisplayInfo {
+            index,
+            width: display.width(),
+            height: display.height(),
+            x,
+            y: 0,
+            is_primary: index == 0,
+            scale_factor: 1.0,
+        });
+        x += display.width() as i32;
     }
 
-    // If cursor is not on any display (shouldn't happen), return primary
-    Ok(0)
+    Ok(display_infos)
 }
 
-/// Determine which display contains the given cursor position (Windows)
-/// Note: Windows implementation returns primary display as we don't have
-/// accurate position information without additional Windows API calls
-#[cfg(target_os = "windows")]
-pub fn get_display_at_cursor(_cursor_x: i32, _cursor_y: i32) -> Result<usize, ScreenRecError> {
-    // On Windows, without proper display position info, we default to primary display
-    // This is a simplified implementation that works for single-monitor setups
-    Ok(0)
+/// The display whose bounds contain the point (in desktop coordinates), if any
+pub fn display_containing(displays: &[DisplayInfo], x: i32, y: i32) -> Option<usize> {
+    displays
+        .iter()
+        .find(|display| {
+            x >= display.x
+                && x < display.x + display.desktop_width()
+                && y >= display.y
+                && y < display.y + display.desktop_height()
+        })
+        .map(|display| display.index)
+}
+
+/// Determine which display contains the given cursor position
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+pub fn get_display_at_cursor(cursor_x: i32, cursor_y: i32) -> Result<usize, ScreenRecError> {
+    let displays = get_all_displays_with_bounds()?;
+
+    // If cursor is not on any display (shouldn't happen), return primary
+    Ok(display_containing(&displays, cursor_x, cursor_y)
+        .or_else(|| displays.iter().find(|display| display.is_primary).map(|display| display.index))
+        .unwrap_or(0))
 }
 
 #[cfg(test)]
@@ -106,7 +321,54 @@ mod tests {
         // This test will only pass if displays are available
         if let Ok(displays) = get_all_displays_with_bounds() {
             assert!(!displays.is_empty());
-            assert!(displays[0].is_primary);
+            assert_eq!(displays.iter().filter(|d| d.is_primary).count(), 1);
+
+            // Monitors sit side by side on the desktop, never on top of each other
+            for (i, a) in displays.iter().enumerate() {
+                for b in &displays[i + 1..] {
+                    let overlaps = a.x < b.x + b.desktop_width()
+                        && b.x < a.x + a.desktop_width()
+                        && a.y < b.y + b.desktop_height()
+                        && b.y < a.y + a.desktop_height();
+                    assert!(!overlaps, "displays {} and {} overlap: {:?} {:?}", a.index, b.index, a, b);
+                }
+            }
         }
     }
+
+    #[test]
+    fn test_hit_testing_and_monitor_assignment() {
+        let display = |index, x, y, width, height| DisplayInfo {
+            index,
+            width,
+            height,
+            x,
+            y,
+            is_primary: index == 0,
+            scale_factor: 1.0,
+        };
+        // A 1920x1080 primary with a 2560x1440 monitor to its left
+        let displays = [display(0, 0, 0, 1920, 1080), display(1, -2560, -360, 2560, 1440)];
+
+        assert_eq!(display_containing(&displays, 100, 100), Some(0));
+        assert_eq!(display_containing(&displays, -1, 0), Some(1));
+        assert_eq!(display_containing(&displays, 1920, 0), None);
+
+        // A Retina laptop (2880x1800 pixels, 1440x900 points) with a 1x monitor to its right
+        let retina = [
+            DisplayInfo { scale_factor: scale_factor(2880, 1440.0), ..display(0, 0, 0, 2880, 1800) },
+            display(1, 1440, 0, 1920, 1080),
+        ];
+        assert_eq!(display_containing(&retina, 1500, 10), Some(1));
+        assert_eq!(display_containing(&retina, 1439, 899), Some(0));
+        assert_eq!(retina[0].to_frame_pixels(700, 450), (1400, 900));
+        assert_eq!(retina[1].to_frame_pixels(1500, 10), (60, 10));
+        assert_eq!(scale_factor(1920, 0.0), 1.0);
+
+        // DXGI lists the big monitor first, EnumDisplayMonitors the primary first
+        assert_eq!(assign_monitors(&[(2560, 1440), (1920, 1080)], &[(1920, 1080), (2560, 1440)]), [Some(1), Some(0)]);
+        // No size matches (DPI scaling): keep the enumeration order
+        assert_eq!(assign_monitors(&[(3840, 2160), (1920, 1080)], &[(2560, 1440), (1536, 864)]), [Some(0), Some(1)]);
+        assert_eq!(assign_monitors(&[(1920, 1080), (1920, 1080)], &[(1920, 1080)]), [Some(0), None]);
+    }
 }
@@ -1,14 +1,26 @@
-#[cfg(target_os = "macos")]
+use crate::adaptive::{AdaptiveQuality, STRAINED_BACKLOG};
 use crate::audio::AudioSample;
+#[cfg(not(target_os = "macos"))]
 use crate::capture::Frame;
+use crate::cli::{ChunkNamePattern, Codec, Colorspace, Preset, QualityPreset, ScaleFilter};
 use crate::db::Database;
+use crate::encryption::{self, EncryptionKey};
 use crate::error::{Result, ScreenRecError};
+use crate::frame_dump::FrameDumper;
+use crate::frame_log::FrameLog;
+use crate::frame_queue::FrameReceiver;
+use crate::integrity;
+use crate::latency::{EncodeLatency, LatencyStats};
 #[cfg(not(target_os = "macos"))]
-use ffmpeg_next as ffmpeg;
+use crate::scaling::scale_and_pad_frame;
+use crate::status::SharedStatus;
+#[cfg(not(target_os = "macos"))]
+use crate::yuv;
 #[cfg(not(target_os = "macos"))]
-use std::path::Path;
-use std::path::PathBuf;
-use std::sync::Arc;
+use ffmpeg_next as ffmpeg;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
 use tokio::sync::mpsc;
 
 #[allow(dead_code)]
@@ -16,6 +28,11 @@ pub struct RecordingOutput {
     pub video_file: PathBuf,
 }
 
+/// Called with each chunk's path and index once the chunk file is complete
+/// (`Recorder::on_chunk_finished`). It runs on the encoder task, so slow work
+/// such as an upload should be handed off rather than done inline.
+pub type ChunkFinished = Arc<dyn Fn(&Path, i64) + Send + Sync>;
+
 pub struct FrameMetadata {
     pub is_keyframe: bool,
     pub pts: Option<i64>,
@@ -42,6 +59,432 @@ pub struct EncoderInfo {
     pub priority: u8,  // Lower is higher priority
 }
 
+/// Which encoders the GPU-first fallback chain may pick from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EncoderChoice {
+    /// Every encoder in the priority list, GPU first
+    Auto,
+    /// Software encoders only (`--safe-mode`)
+    SoftwareOnly,
+    /// Exactly this encoder (`--encoder`), never falling back to another
+    Named(String),
+}
+
+/// The encoders in `priority_list` that `choice` allows and `is_available` finds,
+/// keeping hardware encoders only if `hardware_works` (the test encode) passes.
+/// A named encoder that is unknown or missing is an error rather than a silent fallback.
+pub(crate) fn select_encoders(
+    priority_list: Vec<EncoderInfo>,
+    choice: &EncoderChoice,
+    is_available: impl Fn(&str) -> bool,
+    hardware_works: impl Fn(&str) -> bool,
+) -> Result<Vec<EncoderInfo>> {
+    if let EncoderChoice::Named(name) = choice {
+        let known = priority_list.iter().map(|e| e.name.as_str()).collect::<Vec<_>>().join(", ");
+        let Some(encoder_info) = priority_list.iter().find(|e| e.name == *name).cloned() else {
+            return Err(ScreenRecError::InvalidParameter(format!(
+                "Unknown encoder '{}' for this codec; choose one of: {}",
+                name, known
+            )));
+        };
+        if !is_available(name) {
+            return Err(ScreenRecError::HardwareEncoderUnavailable(format!(
+                "Encoder '{}' is not available on this system (see --list-encoders)",
+                name
+            )));
+        }
+        if encoder_info.encoder_type != EncoderType::Software && !hardware_works(name) {
+            return Err(ScreenRecError::HardwareEncoderUnavailable(format!(
+                "Encoder '{}' is built into FFmpeg but failed a test encode (no usable GPU?)",
+                name
+            )));
+        }
+        return Ok(vec![encoder_info]);
+    }
+
+    let mut available = Vec::new();
+    for encoder_info in priority_list {
+        if *choice == EncoderChoice::SoftwareOnly && encoder_info.encoder_type != EncoderType::Software {
+            log::debug!("Encoder '{}' skipped (software only)", encoder_info.name);
+            continue;
+        }
+        if !is_available(&encoder_info.name) {
+            log::debug!("Encoder '{}' not available", encoder_info.name);
+        } else if encoder_info.encoder_type != EncoderType::Software && !hardware_works(&encoder_info.name) {
+            log::info!("Encoder '{}' skipped: it failed a test encode", encoder_info.name);
+        } else {
+            log::debug!("Encoder '{}' is available", encoder_info.name);
+            available.push(encoder_info);
+        }
+    }
+    Ok(available)
+}
+
+/// Side of the blank clip hardware encoders are tried with
+pub(crate) const PROBE_SIZE: u32 = 64;
+
+/// Frames in the test clip
+pub(crate) const PROBE_FRAMES: usize = 2;
+
+/// Test-encode results by encoder name. Registration in FFmpeg doesn't mean the
+/// GPU behind an encoder is there (nvenc without an NVIDIA card, VideoToolbox
+/// without entitlements), so each hardware encoder is tried once per process
+/// and dead ones drop out of the fallback chain before the first chunk.
+static HARDWARE_PROBES: OnceLock<Mutex<HashMap<String, bool>>> = OnceLock::new();
+
+/// Whether `name` passed its test encode, running `probe` the first time it's asked
+pub(crate) fn hardware_encoder_works(name: &str, probe: impl FnOnce() -> bool) -> bool {
+    let mut probes = HARDWARE_PROBES.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+    *probes.entry(name.to_string()).or_insert_with(|| {
+        let started = std::time::Instant::now();
+        let works = probe();
+        log::info!(
+            "Probed hardware encoder '{}': {} ({} ms)",
+            name,
+            if works { "works" } else { "failed" },
+            started.elapsed().as_millis()
+        );
+        works
+    })
+}
+
+/// Hardware encoders probed so far and whether each worked, by name
+pub fn hardware_probe_results() -> Vec<(String, bool)> {
+    let Some(probes) = HARDWARE_PROBES.get() else {
+        return Vec::new();
+    };
+    let mut results: Vec<_> = probes.lock().unwrap_or_else(|e| e.into_inner()).iter().map(|(n, w)| (n.clone(), *w)).collect();
+    results.sort();
+    results
+}
+
+/// Encoders this machine can use for `codec` under `choice`, in fallback order.
+/// Backs `--list-encoders` and the up-front check of `--encoder`.
+pub fn available_encoders(
+    codec: Codec,
+    choice: &EncoderChoice,
+    #[cfg(target_os = "macos")] ffmpeg_path: &str,
+) -> Result<Vec<EncoderInfo>> {
+    #[cfg(target_os = "macos")]
+    {
+        crate::encoder_subprocess::get_available_encoders(ffmpeg_path, codec, choice)
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        ffmpeg::init().map_err(|e| {
+            ScreenRecError::EncodingError(format!("Failed to initialize FFmpeg: {}", e))
+        })?;
+        get_available_encoders(codec, choice)
+    }
+}
+
+/// How the encoder spends bits: constant quality or a target average bitrate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateControl {
+    /// Quality level 1-10, mapped to each encoder's CRF/CQ scale
+    Quality(u8),
+    /// Average bitrate in bits per second, capped with maxrate/bufsize
+    Bitrate(u64),
+    /// Raw constant rate factor from `--crf`, bypassing the quality mapping
+    Crf(u8),
+}
+
+impl RateControl {
+    /// The 1-10 quality level, if the encoder is in quality mode
+    pub fn quality(&self) -> Option<u8> {
+        match *self {
+            RateControl::Quality(quality) => Some(quality),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for RateControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            RateControl::Quality(quality) => write!(f, "quality {}/10", quality),
+            RateControl::Bitrate(bitrate) => write!(f, "{} kbps average", bitrate / 1000),
+            RateControl::Crf(crf) => write!(f, "CRF {}", crf),
+        }
+    }
+}
+
+/// Encoder settings beyond rate control and preset, set by `--quality-preset`,
+/// `--keyframe-interval`, `--lossless`, `--colorspace` and `--max-encode-dimension`. The default leaves
+/// every encoder as it is configured without them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EncoderTuning {
+    /// Seconds between keyframes (default 2, or 1 for Media Foundation)
+    pub keyframe_interval: Option<u32>,
+    /// libx264 `-tune`, e.g. "stillimage"
+    pub tune: Option<&'static str>,
+    /// B-frames for libx264, libx265 and NVENC (otherwise 0, except for
+    /// libx264 on macOS, which keeps FFmpeg's default)
+    pub bframes: Option<u32>,
+    /// libx264 at QP 0 with 4:4:4 chroma instead of the rate control
+    pub lossless: bool,
+    /// Matrix frames are converted to YUV with, and tagged with
+    pub colorspace: Colorspace,
+    /// Longest side the video may have; larger frames are scaled down to fit
+    pub max_dimension: Option<u32>,
+}
+
+impl EncoderTuning {
+    /// Frames between keyframes at `fps`
+    pub fn gop_size(&self, fps: u32) -> u32 {
+        fps * self.keyframe_interval.unwrap_or(2)
+    }
+}
+
+/// Largest width or height `encoder_name` opens at. Hardware H.264 encoders
+/// (and older VideoToolbox profiles) stop at 4096, which a 6K display exceeds;
+/// HEVC and AV1 hardware goes to 8192. Software encoders have no practical limit.
+pub(crate) fn max_encoder_dimension(encoder_name: &str) -> Option<usize> {
+    match encoder_name {
+        "h264_videotoolbox" | "h264_nvenc" | "h264_qsv" | "h264_amf" | "h264_vaapi" => Some(4096),
+        "hevc_videotoolbox" | "hevc_nvenc" | "hevc_qsv" | "hevc_amf" | "hevc_vaapi" | "av1_nvenc"
+        | "av1_qsv" | "av1_amf" | "av1_vaapi" => Some(8192),
+        _ => None,
+    }
+}
+
+/// Size to open `encoder_name` at for `width`x`height` frames: scaled down to
+/// the encoder's limit and `--max-encode-dimension`, whichever is smaller.
+/// Frames are then fitted to it by `scale_and_pad_frame`.
+pub(crate) fn encode_size(encoder_name: &str, width: usize, height: usize, tuning: EncoderTuning) -> (usize, usize) {
+    let requested = tuning.max_dimension.map(|max| max as usize);
+    let Some(limit) = max_encoder_dimension(encoder_name).into_iter().chain(requested).min() else {
+        return (width, height);
+    };
+    let (fit_width, fit_height) = crate::scaling::fit_within((width, height), limit);
+    if (fit_width, fit_height) != (width, height) {
+        if requested == Some(limit) {
+            log::info!("Scaling {}x{} to {}x{} for --max-encode-dimension", width, height, fit_width, fit_height);
+        } else {
+            log::warn!(
+                "⚠️  {}x{} is larger than {} supports ({} max); encoding at {}x{}",
+                width, height, encoder_name, limit, fit_width, fit_height
+            );
+        }
+    }
+    (fit_width, fit_height)
+}
+
+/// `--colorspace` when it isn't given: BT.709 for HD (720p and up), BT.601 below
+pub fn default_colorspace(height: usize) -> Colorspace {
+    if height >= 720 {
+        Colorspace::Bt709
+    } else {
+        Colorspace::Bt601
+    }
+}
+
+/// What a `--quality-preset` stands for; flags given on the command line win
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QualityProfile {
+    pub codec: Codec,
+    pub rate_control: RateControl,
+    pub preset: Preset,
+    pub tuning: EncoderTuning,
+}
+
+impl QualityProfile {
+    pub fn for_preset(preset: QualityPreset) -> Self {
+        match preset {
+            // Mostly static UI: long GOPs and a lower quality cost little sharpness
+            QualityPreset::Screen => Self {
+                codec: Codec::H264,
+                rate_control: RateControl::Quality(6),
+                preset: Preset::Medium,
+                tuning: EncoderTuning {
+                    keyframe_interval: Some(10),
+                    tune: Some("stillimage"),
+                    bframes: None,
+                    lossless: false,
+                    colorspace: Colorspace::default(),
+                    max_dimension: None,
+                },
+            },
+            // Gameplay and video: B-frames and more bits for constant motion
+            QualityPreset::Motion => Self {
+                codec: Codec::H264,
+                rate_control: RateControl::Quality(9),
+                preset: Preset::Fast,
+                tuning: EncoderTuning {
+                    keyframe_interval: Some(2),
+                    tune: Some("film"),
+                    bframes: Some(3),
+                    lossless: false,
+                    colorspace: Colorspace::default(),
+                    max_dimension: None,
+                },
+            },
+            // Near-lossless HEVC, as small as a slow preset gets it
+            QualityPreset::Archival => Self {
+                codec: Codec::Hevc,
+                rate_control: RateControl::Quality(10),
+                preset: Preset::Slow,
+                tuning: EncoderTuning {
+                    keyframe_interval: Some(10),
+                    tune: None,
+                    bframes: Some(3),
+                    lossless: false,
+                    colorspace: Colorspace::default(),
+                    max_dimension: None,
+                },
+            },
+        }
+    }
+}
+
+/// When the chunked encoder closes the current chunk and starts a new one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChunkPolicy {
+    /// Roll after this many seconds of frames
+    ByDuration(u64),
+    /// Roll once the chunk file reaches this many bytes. Every chunk is a fresh
+    /// encoder, so the next one always opens on a keyframe.
+    BySize(u64),
+}
+
+impl ChunkPolicy {
+    fn is_full(&self, frames_in_chunk: u64, fps: u32, chunk_bytes: u64) -> bool {
+        match *self {
+            ChunkPolicy::ByDuration(secs) => frames_in_chunk >= fps as u64 * secs,
+            // Never roll an empty chunk, however small the limit
+            ChunkPolicy::BySize(bytes) => frames_in_chunk > 0 && chunk_bytes >= bytes,
+        }
+    }
+}
+
+impl std::fmt::Display for ChunkPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ChunkPolicy::ByDuration(secs) => write!(f, "{}-second chunks", secs),
+            ChunkPolicy::BySize(bytes) => write!(f, "{} chunks", crate::cli::ByteSize(*bytes)),
+        }
+    }
+}
+
+/// Parse a bitrate such as "4M", "2500k" or "4000000" into bits per second
+pub fn parse_bitrate(s: &str) -> Result<u64> {
+    let invalid = || {
+        ScreenRecError::InvalidParameter(format!(
+            "Invalid bitrate '{}'. Use a number with an optional k or M suffix, e.g. 4M or 2500k",
+            s
+        ))
+    };
+
+    let trimmed = s.trim();
+    let (number, multiplier) = match trimmed.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&trimmed[..i], 1_000.0),
+        Some((i, 'm' | 'M')) => (&trimmed[..i], 1_000_000.0),
+        _ => (trimmed, 1.0),
+    };
+
+    let value: f64 = number.parse().map_err(|_| invalid())?;
+    let bits = (value * multiplier).round();
+    if !bits.is_finite() || bits < 1.0 {
+        return Err(invalid());
+    }
+
+    Ok(bits as u64)
+}
+
+/// Map a `--preset` to an encoder's native option, or None if it has no presets.
+/// Returns the option name and value, e.g. ("preset", "p4") for nvenc.
+pub(crate) fn native_preset(encoder_name: &str, preset: Preset) -> Option<(&'static str, String)> {
+    match encoder_name {
+        "libx264" | "libx265" => Some(("preset", preset.to_string())),
+        // SVT-AV1 presets are numbers, 0 (slowest) to 13
+        "libsvtav1" => {
+            let level = match preset {
+                Preset::Ultrafast => "12",
+                Preset::Fast => "10",
+                Preset::Medium => "8",
+                Preset::Slow => "6",
+                Preset::Veryslow => "4",
+            };
+            Some(("preset", level.to_string()))
+        }
+        "h264_nvenc" | "hevc_nvenc" | "av1_nvenc" => {
+            let level = match preset {
+                Preset::Ultrafast => "p1",
+                Preset::Fast => "p3",
+                Preset::Medium => "p4",
+                Preset::Slow => "p5",
+                Preset::Veryslow => "p7",
+            };
+            Some(("preset", level.to_string()))
+        }
+        "h264_qsv" | "hevc_qsv" | "av1_qsv" => {
+            let level = match preset {
+                Preset::Ultrafast => "veryfast",
+                _ => return Some(("preset", preset.to_string())),
+            };
+            Some(("preset", level.to_string()))
+        }
+        "h264_amf" | "hevc_amf" | "av1_amf" => {
+            let level = match preset {
+                Preset::Ultrafast | Preset::Fast => "speed",
+                Preset::Medium => "balanced",
+                Preset::Slow | Preset::Veryslow => "quality",
+            };
+            Some(("quality", level.to_string()))
+        }
+        // VideoToolbox, VAAPI and Media Foundation have no preset namespace
+        _ => None,
+    }
+}
+
+/// Highest `--crf` accepted (the x264/x265 scale; hardware CQ/QP scales match it)
+pub const MAX_CRF: u8 = 51;
+
+/// SVT-AV1's CRF runs 0-63; stretch the 0-51 CRF over it so quality levels match
+pub(crate) fn svtav1_crf(crf: u8) -> u8 {
+    ((crf as u32 * 63 + 25) / 51) as u8
+}
+
+/// Software AV1 is several times slower than x264. Warn (once) when it ends up
+/// encoding, since a busy CPU will drop frames.
+pub(crate) fn warn_if_software_av1(encoder_name: &str) {
+    static WARNED: std::sync::Once = std::sync::Once::new();
+    if encoder_name == "libsvtav1" {
+        WARNED.call_once(|| {
+            log::warn!(
+                "⚠️  Encoding AV1 on the CPU (libsvtav1); expect high CPU use and dropped frames at high resolutions. \
+                 Use --codec h264 or hevc for live capture and transcode to AV1 later if this machine has no AV1 GPU encoder"
+            );
+        });
+    }
+}
+
+/// Whether `--crf` can drive the encoder. VideoToolbox only does average bitrate
+/// and Media Foundation has its own 0-100 quality scale.
+pub(crate) fn supports_crf(encoder_name: &str) -> bool {
+    !matches!(encoder_name, "h264_videotoolbox" | "hevc_videotoolbox" | "h264_mf")
+}
+
+/// Drop the encoders `--crf` can't drive, failing if that leaves none
+pub(crate) fn encoders_for_rate_control(encoders: Vec<EncoderInfo>, rate_control: RateControl) -> Result<Vec<EncoderInfo>> {
+    let RateControl::Crf(crf) = rate_control else {
+        return Ok(encoders);
+    };
+
+    let (usable, skipped): (Vec<_>, Vec<_>) = encoders.into_iter().partition(|e| supports_crf(&e.name));
+    for encoder in &skipped {
+        log::info!("Encoder '{}' has no CRF mode, skipping it for --crf {}", encoder.name, crf);
+    }
+    if usable.is_empty() && !skipped.is_empty() {
+        return Err(ScreenRecError::InvalidParameter(format!(
+            "--crf needs a CRF-capable encoder, but only {} is available; use --quality or --bitrate instead",
+            skipped.iter().map(|e| e.name.as_str()).collect::<Vec<_>>().join(", ")
+        )));
+    }
+    Ok(usable)
+}
+
 // VideoEncoder struct is only used on Windows/Linux
 // macOS uses SubprocessEncoder from encoder_subprocess.rs
 #[cfg(not(target_os = "macos"))]
@@ -58,60 +501,123 @@ pub struct VideoEncoder {
     last_packet_keyframe: bool,
     last_packet_pts: Option<i64>,
     last_packet_dts: Option<i64>,
+    bytes_written: u64, // Video packet bytes muxed into this chunk
     encoder_info: EncoderInfo,  // Track which encoder is being used
+    codec: Codec,
+    rate_control: RateControl,
+    preset: Option<Preset>,
+    tuning: EncoderTuning,
+    encoder_choice: EncoderChoice,
+    scale_filter: ScaleFilter,
+    audio: Option<AudioTrack>,
 }
 
 /// Get platform-specific encoder priority list (GPU first)
 #[cfg(not(target_os = "macos"))]
-fn get_encoder_priority_list() -> Vec<EncoderInfo> {
+fn get_encoder_priority_list(codec: Codec) -> Vec<EncoderInfo> {
     #[cfg(target_os = "windows")]
     {
-        vec![
-            EncoderInfo { name: "h264_nvenc".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 0 },
-            EncoderInfo { name: "h264_qsv".to_string(), encoder_type: EncoderType::HardwareCpu, priority: 1 },
-            EncoderInfo { name: "h264_amf".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 2 },
-            EncoderInfo { name: "libx264".to_string(), encoder_type: EncoderType::Software, priority: 10 },
-            EncoderInfo { name: "h264".to_string(), encoder_type: EncoderType::Software, priority: 11 },
-        ]
-    }
-
-    #[cfg(target_os = "macos")]
-    {
-        vec![
-            EncoderInfo { name: "h264_videotoolbox".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 0 },
-            EncoderInfo { name: "libx264".to_string(), encoder_type: EncoderType::Software, priority: 10 },
-            EncoderInfo { name: "h264".to_string(), encoder_type: EncoderType::Software, priority: 11 },
-        ]
+        match codec {
+            Codec::H264 => vec![
+                EncoderInfo { name: "h264_nvenc".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 0 },
+                EncoderInfo { name: "h264_qsv".to_string(), encoder_type: EncoderType::HardwareCpu, priority: 1 },
+                EncoderInfo { name: "h264_amf".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 2 },
+                EncoderInfo { name: "libx264".to_string(), encoder_type: EncoderType::Software, priority: 10 },
+            ],
+            Codec::Hevc => vec![
+                EncoderInfo { name: "hevc_nvenc".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 0 },
+                EncoderInfo { name: "hevc_qsv".to_string(), encoder_type: EncoderType::HardwareCpu, priority: 1 },
+                EncoderInfo { name: "hevc_amf".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 2 },
+                EncoderInfo { name: "libx265".to_string(), encoder_type: EncoderType::Software, priority: 10 },
+            ],
+            Codec::Av1 => vec![
+                EncoderInfo { name: "av1_nvenc".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 0 },
+                EncoderInfo { name: "av1_qsv".to_string(), encoder_type: EncoderType::HardwareCpu, priority: 1 },
+                EncoderInfo { name: "av1_amf".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 2 },
+                EncoderInfo { name: "libsvtav1".to_string(), encoder_type: EncoderType::Software, priority: 10 },
+            ],
+        }
     }
 
     #[cfg(target_os = "linux")]
     {
-        vec![
-            EncoderInfo { name: "h264_vaapi".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 0 },
-            EncoderInfo { name: "h264_nvenc".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 1 },
-            EncoderInfo { name: "libx264".to_string(), encoder_type: EncoderType::Software, priority: 10 },
-            EncoderInfo { name: "h264".to_string(), encoder_type: EncoderType::Software, priority: 11 },
-        ]
+        match codec {
+            Codec::H264 => vec![
+                EncoderInfo { name: "h264_vaapi".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 0 },
+                EncoderInfo { name: "h264_nvenc".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 1 },
+                EncoderInfo { name: "libx264".to_string(), encoder_type: EncoderType::Software, priority: 10 },
+            ],
+            Codec::Hevc => vec![
+                EncoderInfo { name: "hevc_vaapi".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 0 },
+                EncoderInfo { name: "hevc_nvenc".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 1 },
+                EncoderInfo { name: "libx265".to_string(), encoder_type: EncoderType::Software, priority: 10 },
+            ],
+            Codec::Av1 => vec![
+                EncoderInfo { name: "av1_vaapi".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 0 },
+                EncoderInfo { name: "av1_nvenc".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 1 },
+                EncoderInfo { name: "libsvtav1".to_string(), encoder_type: EncoderType::Software, priority: 10 },
+            ],
+        }
     }
 }
 
-/// Get available encoders sorted by priority
+/// Get available encoders sorted by priority, limited by `choice`
+///
+/// Only real encoder names belong in the priority list: FFmpeg has no encoder
+/// registered as plain `h264`, so a generic fallback entry would never resolve.
 #[cfg(not(target_os = "macos"))]
-fn get_available_encoders() -> Vec<EncoderInfo> {
-    let priority_list = get_encoder_priority_list();
-    let mut available = Vec::new();
+fn get_available_encoders(codec: Codec, choice: &EncoderChoice) -> Result<Vec<EncoderInfo>> {
+    let available = select_encoders(
+        get_encoder_priority_list(codec),
+        choice,
+        |name| ffmpeg::encoder::find_by_name(name).is_some(),
+        |name| hardware_encoder_works(name, || test_encode(name)),
+    )?;
 
-    for encoder_info in priority_list {
-        if ffmpeg::encoder::find_by_name(&encoder_info.name).is_some() {
-            log::debug!("Encoder '{}' is available", encoder_info.name);
-            available.push(encoder_info);
-        } else {
-            log::debug!("Encoder '{}' not available", encoder_info.name);
+    log::info!("Available encoders: {:?}", available.iter().map(|e| &e.name).collect::<Vec<_>>());
+    Ok(available)
+}
+
+/// Open `encoder_name` at a tiny size and push a few blank frames through it
+#[cfg(not(target_os = "macos"))]
+fn test_encode(encoder_name: &str) -> bool {
+    let mut encoder = match try_init_encoder_once(
+        encoder_name,
+        PROBE_SIZE,
+        PROBE_SIZE,
+        30,
+        RateControl::Quality(5),
+        None,
+        EncoderTuning::default(),
+    ) {
+        Ok(encoder) => encoder,
+        Err(e) => {
+            log::debug!("Test encode with '{}' failed to open: {}", encoder_name, e);
+            return false;
+        }
+    };
+
+    for pts in 0..PROBE_FRAMES as i64 {
+        let mut frame = ffmpeg::frame::Video::new(ffmpeg::format::Pixel::YUV420P, PROBE_SIZE, PROBE_SIZE);
+        for plane in 0..3 {
+            frame.data_mut(plane).fill(if plane == 0 { 16 } else { 128 });
         }
+        frame.set_pts(Some(pts));
+        if let Err(e) = encoder.send_frame(&frame) {
+            log::debug!("Test encode with '{}' rejected a frame: {}", encoder_name, e);
+            return false;
+        }
+    }
+    if encoder.send_eof().is_err() {
+        return false;
     }
 
-    log::info!("Available encoders: {:?}", available.iter().map(|e| &e.name).collect::<Vec<_>>());
-    available
+    let mut packet = ffmpeg::Packet::empty();
+    let mut packets = 0;
+    while encoder.receive_packet(&mut packet).is_ok() {
+        packets += 1;
+    }
+    packets > 0
 }
 
 /// Retry configuration for encoder initialization
@@ -148,85 +654,364 @@ impl RetryConfig {
 #[cfg(not(target_os = "macos"))]
 fn configure_encoder_options(
     encoder_name: &str,
-    quality: u8,
+    rate_control: RateControl,
+    preset: Option<Preset>,
+    tuning: EncoderTuning,
     fps: u32,
     opts: &mut ffmpeg::Dictionary,
 ) {
     match encoder_name {
+        "libx264" if tuning.lossless => {
+            // Lossless is far more work per frame, so keep up with the fastest preset
+            opts.set("qp", "0");
+            opts.set("preset", "ultrafast");
+            opts.set("profile", "high444");
+            let gop_size = tuning.gop_size(fps).to_string();
+            opts.set("g", &gop_size);
+            opts.set("keyint_min", &gop_size);
+            opts.set("bf", "0");
+            opts.set("sc_threshold", "0");
+            opts.set("movflags", "+faststart");
+        }
         "libx264" => {
-            let crf = VideoEncoder::quality_to_crf(quality);
-            opts.set("crf", &crf.to_string());
+            match rate_control {
+                RateControl::Quality(_) => {
+                    let crf = VideoEncoder::crf_value(rate_control);
+                    opts.set("crf", &crf.to_string());
+                    opts.set("qmin", "10");
+                    opts.set("qmax", "25");
+                    opts.set("crf_max", "18");
+                }
+                // An explicit --crf is passed through without the quality clamps
+                RateControl::Crf(crf) => opts.set("crf", &crf.to_string()),
+                RateControl::Bitrate(bitrate) => set_vbv_bitrate(opts, bitrate),
+            }
             opts.set("preset", "slow");
             opts.set("profile", "high");
-            let gop_size = (fps * 2).to_string();
+            if let Some(tune) = tuning.tune {
+                opts.set("tune", tune);
+            }
+            let gop_size = tuning.gop_size(fps).to_string();
             opts.set("g", &gop_size);
             opts.set("keyint_min", &gop_size);
-            opts.set("bf", "0");
+            opts.set("bf", &tuning.bframes.unwrap_or(0).to_string());
             opts.set("refs", "3");
             opts.set("sc_threshold", "0");
-            opts.set("qmin", "10");
-            opts.set("qmax", "25");
-            opts.set("crf_max", "18");
             opts.set("movflags", "+faststart");
         }
         "h264_videotoolbox" => {
-            let crf = VideoEncoder::quality_to_crf(quality);
-            opts.set("q:v", &crf.to_string());
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let crf = VideoEncoder::crf_value(rate_control);
+                    opts.set("q:v", &crf.to_string());
+                }
+                RateControl::Bitrate(bitrate) => opts.set("b", &bitrate.to_string()),
+            }
             opts.set("profile", "high");
             opts.set("allow_sw", "1");
-            let gop_size = (fps * 2).to_string();
+            let gop_size = tuning.gop_size(fps).to_string();
             opts.set("g", &gop_size);
         }
         "h264_nvenc" => {
-            let crf = VideoEncoder::quality_to_crf(quality);
-            opts.set("cq", &crf.to_string());
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let crf = VideoEncoder::crf_value(rate_control);
+                    opts.set("cq", &crf.to_string());
+                }
+                RateControl::Bitrate(bitrate) => {
+                    opts.set("rc", "vbr");
+                    set_vbv_bitrate(opts, bitrate);
+                }
+            }
             opts.set("preset", "p4");
             opts.set("tune", "hq");
             opts.set("profile", "high");
-            let gop_size = (fps * 2).to_string();
+            let gop_size = tuning.gop_size(fps).to_string();
             opts.set("g", &gop_size);
-            opts.set("bf", "0");
+            opts.set("bf", &tuning.bframes.unwrap_or(0).to_string());
         }
         "h264_qsv" => {
-            let crf = VideoEncoder::quality_to_crf(quality);
-            opts.set("global_quality", &crf.to_string());
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let crf = VideoEncoder::crf_value(rate_control);
+                    opts.set("global_quality", &crf.to_string());
+                }
+                // QSV picks VBR when a bitrate is set without global_quality
+                RateControl::Bitrate(bitrate) => set_vbv_bitrate(opts, bitrate),
+            }
             opts.set("preset", "medium");
-            let gop_size = (fps * 2).to_string();
+            let gop_size = tuning.gop_size(fps).to_string();
             opts.set("g", &gop_size);
         }
         "h264_amf" => {
-            let crf = VideoEncoder::quality_to_crf(quality);
-            opts.set("qp_i", &crf.to_string());
-            opts.set("qp_p", &crf.to_string());
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let crf = VideoEncoder::crf_value(rate_control);
+                    opts.set("qp_i", &crf.to_string());
+                    opts.set("qp_p", &crf.to_string());
+                }
+                RateControl::Bitrate(bitrate) => {
+                    opts.set("rc", "vbr_peak");
+                    set_vbv_bitrate(opts, bitrate);
+                }
+            }
             opts.set("quality", "quality");
             opts.set("profile", "high");
-            let gop_size = (fps * 2).to_string();
+            let gop_size = tuning.gop_size(fps).to_string();
             opts.set("gops_per_idr", "1");
             opts.set("keyint_min", &gop_size);
         }
         "h264_vaapi" => {
-            let crf = VideoEncoder::quality_to_crf(quality);
-            opts.set("qp", &crf.to_string());
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let crf = VideoEncoder::crf_value(rate_control);
+                    opts.set("qp", &crf.to_string());
+                }
+                RateControl::Bitrate(bitrate) => {
+                    opts.set("rc_mode", "VBR");
+                    set_vbv_bitrate(opts, bitrate);
+                }
+            }
             opts.set("quality", "1");
-            let gop_size = (fps * 2).to_string();
+            let gop_size = tuning.gop_size(fps).to_string();
             opts.set("g", &gop_size);
         }
         "h264_mf" => {
             // Windows Media Foundation encoder
-            opts.set("rate_control", "quality");
-            let mf_quality = ((quality as f32 / 10.0) * 100.0).min(100.0) as i32;
-            opts.set("quality", &mf_quality.to_string());
+            match rate_control {
+                RateControl::Quality(quality) => {
+                    opts.set("rate_control", "quality");
+                    let mf_quality = ((quality as f32 / 10.0) * 100.0).min(100.0) as i32;
+                    opts.set("quality", &mf_quality.to_string());
+                }
+                // Never selected for --crf (see supports_crf)
+                RateControl::Crf(_) => {}
+                RateControl::Bitrate(bitrate) => {
+                    opts.set("rate_control", "u_vbr");
+                    opts.set("b", &bitrate.to_string());
+                }
+            }
             opts.set("low_latency", "1");
-            let gop_size = fps.to_string();
+            let gop_size = tuning.keyframe_interval.map_or(fps, |_| tuning.gop_size(fps)).to_string();
+            opts.set("g", &gop_size);
+        }
+        "libx265" => {
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let crf = VideoEncoder::crf_value(rate_control);
+                    opts.set("crf", &crf.to_string());
+                }
+                RateControl::Bitrate(bitrate) => set_vbv_bitrate(opts, bitrate),
+            }
+            let gop_size = tuning.gop_size(fps);
+            opts.set("preset", "medium");
+            opts.set(
+                "x265-params",
+                &format!(
+                    "keyint={}:min-keyint={}:bframes={}:scenecut=0:log-level=error",
+                    gop_size,
+                    gop_size,
+                    tuning.bframes.unwrap_or(0)
+                ),
+            );
+        }
+        "hevc_videotoolbox" => {
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let crf = VideoEncoder::crf_value(rate_control);
+                    opts.set("q:v", &crf.to_string());
+                }
+                RateControl::Bitrate(bitrate) => opts.set("b", &bitrate.to_string()),
+            }
+            opts.set("profile", "main");
+            opts.set("allow_sw", "1");
+            let gop_size = tuning.gop_size(fps).to_string();
+            opts.set("g", &gop_size);
+        }
+        "hevc_nvenc" => {
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let crf = VideoEncoder::crf_value(rate_control);
+                    opts.set("cq", &crf.to_string());
+                }
+                RateControl::Bitrate(bitrate) => {
+                    opts.set("rc", "vbr");
+                    set_vbv_bitrate(opts, bitrate);
+                }
+            }
+            opts.set("preset", "p4");
+            opts.set("tune", "hq");
+            opts.set("profile", "main");
+            let gop_size = tuning.gop_size(fps).to_string();
+            opts.set("g", &gop_size);
+            opts.set("bf", &tuning.bframes.unwrap_or(0).to_string());
+        }
+        "hevc_qsv" => {
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let crf = VideoEncoder::crf_value(rate_control);
+                    opts.set("global_quality", &crf.to_string());
+                }
+                RateControl::Bitrate(bitrate) => set_vbv_bitrate(opts, bitrate),
+            }
+            opts.set("preset", "medium");
+            let gop_size = tuning.gop_size(fps).to_string();
+            opts.set("g", &gop_size);
+        }
+        "hevc_amf" => {
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let crf = VideoEncoder::crf_value(rate_control);
+                    opts.set("qp_i", &crf.to_string());
+                    opts.set("qp_p", &crf.to_string());
+                }
+                RateControl::Bitrate(bitrate) => {
+                    opts.set("rc", "vbr_peak");
+                    set_vbv_bitrate(opts, bitrate);
+                }
+            }
+            opts.set("quality", "quality");
+            opts.set("gops_per_idr", "1");
+        }
+        "hevc_vaapi" => {
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let crf = VideoEncoder::crf_value(rate_control);
+                    opts.set("qp", &crf.to_string());
+                }
+                RateControl::Bitrate(bitrate) => {
+                    opts.set("rc_mode", "VBR");
+                    set_vbv_bitrate(opts, bitrate);
+                }
+            }
+            let gop_size = tuning.gop_size(fps).to_string();
             opts.set("g", &gop_size);
         }
+        "libsvtav1" => {
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let crf = svtav1_crf(VideoEncoder::crf_value(rate_control));
+                    opts.set("crf", &crf.to_string());
+                }
+                RateControl::Bitrate(bitrate) => opts.set("b", &bitrate.to_string()),
+            }
+            // Preset 10 keeps a CPU close to real time at 1080p; lower is slower and smaller
+            opts.set("preset", "10");
+            opts.set("g", &tuning.gop_size(fps).to_string());
+        }
+        "av1_nvenc" => {
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let crf = VideoEncoder::crf_value(rate_control);
+                    opts.set("cq", &crf.to_string());
+                }
+                RateControl::Bitrate(bitrate) => {
+                    opts.set("rc", "vbr");
+                    set_vbv_bitrate(opts, bitrate);
+                }
+            }
+            opts.set("preset", "p4");
+            opts.set("tune", "hq");
+            opts.set("g", &tuning.gop_size(fps).to_string());
+            opts.set("bf", &tuning.bframes.unwrap_or(0).to_string());
+        }
+        "av1_qsv" => {
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let q = av1_qindex(VideoEncoder::crf_value(rate_control));
+                    opts.set("global_quality", &q.to_string());
+                }
+                RateControl::Bitrate(bitrate) => set_vbv_bitrate(opts, bitrate),
+            }
+            opts.set("preset", "medium");
+            opts.set("g", &tuning.gop_size(fps).to_string());
+        }
+        "av1_amf" => {
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let q = av1_qindex(VideoEncoder::crf_value(rate_control));
+                    opts.set("qp_i", &q.to_string());
+                    opts.set("qp_p", &q.to_string());
+                }
+                RateControl::Bitrate(bitrate) => {
+                    opts.set("rc", "vbr_peak");
+                    set_vbv_bitrate(opts, bitrate);
+                }
+            }
+            opts.set("quality", "quality");
+            opts.set("g", &tuning.gop_size(fps).to_string());
+        }
+        "av1_vaapi" => {
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let q = av1_qindex(VideoEncoder::crf_value(rate_control));
+                    opts.set("qp", &q.to_string());
+                }
+                RateControl::Bitrate(bitrate) => {
+                    opts.set("rc_mode", "VBR");
+                    set_vbv_bitrate(opts, bitrate);
+                }
+            }
+            opts.set("g", &tuning.gop_size(fps).to_string());
+        }
         _ => {
             // Generic fallback
-            let crf = VideoEncoder::quality_to_crf(quality);
-            opts.set("crf", &crf.to_string());
+            match rate_control {
+                RateControl::Quality(_) | RateControl::Crf(_) => {
+                    let crf = VideoEncoder::crf_value(rate_control);
+                    opts.set("crf", &crf.to_string());
+                }
+                RateControl::Bitrate(bitrate) => set_vbv_bitrate(opts, bitrate),
+            }
             opts.set("preset", "medium");
         }
     }
+
+    // Override the encoder's default preset when one was requested
+    if let Some(preset) = preset {
+        match native_preset(encoder_name, preset) {
+            Some((key, value)) => opts.set(key, &value),
+            None => log::debug!("Encoder '{}' has no presets, ignoring --preset {}", encoder_name, preset),
+        }
+    }
+}
+
+/// Tag the stream with the matrix `yuv` converted with, so players convert back
+/// the same way. The conversion is full range.
+#[cfg(not(target_os = "macos"))]
+fn set_color_tags(opts: &mut ffmpeg::Dictionary, colorspace: Colorspace) {
+    let name = match colorspace {
+        Colorspace::Bt601 => "smpte170m",
+        Colorspace::Bt709 => "bt709",
+    };
+    opts.set("colorspace", name);
+    opts.set("color_primaries", name);
+    opts.set("color_trc", name);
+    opts.set("color_range", "pc");
+}
+
+/// Frame format the encoder is fed: 4:4:4 when lossless, so chroma isn't subsampled
+#[cfg(not(target_os = "macos"))]
+fn pixel_format(tuning: EncoderTuning) -> ffmpeg::format::Pixel {
+    if tuning.lossless {
+        ffmpeg::format::Pixel::YUV444P
+    } else {
+        ffmpeg::format::Pixel::YUV420P
+    }
+}
+
+/// Hardware AV1 encoders take a 0-255 quantizer index; stretch the 0-51 CRF over it
+#[cfg(not(target_os = "macos"))]
+fn av1_qindex(crf: u8) -> u32 {
+    (crf as u32 * 255 + 25) / 51
+}
+
+/// Average bitrate mode: cap the peak at the same rate with a two-second VBV buffer
+#[cfg(not(target_os = "macos"))]
+fn set_vbv_bitrate(opts: &mut ffmpeg::Dictionary, bitrate: u64) {
+    opts.set("b", &bitrate.to_string());
+    opts.set("maxrate", &bitrate.to_string());
+    opts.set("bufsize", &(bitrate * 2).to_string());
 }
 
 /// Single attempt to initialize encoder (no retries)
@@ -236,7 +1021,9 @@ fn try_init_encoder_once(
     width: u32,
     height: u32,
     fps: u32,
-    quality: u8,
+    rate_control: RateControl,
+    preset: Option<Preset>,
+    tuning: EncoderTuning,
 ) -> Result<ffmpeg::encoder::Video> {
     // Find encoder
     let codec = ffmpeg::encoder::find_by_name(encoder_name)
@@ -252,13 +1039,14 @@ fn try_init_encoder_once(
     // Configure encoder
     video_encoder.set_width(width);
     video_encoder.set_height(height);
-    video_encoder.set_format(ffmpeg::format::Pixel::YUV420P);
+    video_encoder.set_format(pixel_format(tuning));
     video_encoder.set_time_base(ffmpeg::Rational::new(1, fps as i32));
     video_encoder.set_frame_rate(Some(ffmpeg::Rational::new(fps as i32, 1)));
 
     // Set encoder-specific options
     let mut opts = ffmpeg::Dictionary::new();
-    configure_encoder_options(encoder_name, quality, fps, &mut opts);
+    configure_encoder_options(encoder_name, rate_control, preset, tuning, fps, &mut opts);
+    set_color_tags(&mut opts, tuning.colorspace);
 
     // Open encoder - this is where resource conflicts occur
     let encoder = video_encoder.open_with(opts)
@@ -278,13 +1066,16 @@ fn try_init_encoder_once(
 #[cfg(not(target_os = "macos"))]
 fn try_init_encoder_with_retries(
     encoder_name: &str,
+    codec: Codec,
     width: u32,
     height: u32,
     fps: u32,
-    quality: u8,
+    rate_control: RateControl,
+    preset: Option<Preset>,
+    tuning: EncoderTuning,
     retry_config: &RetryConfig,
 ) -> Result<(ffmpeg::encoder::Video, EncoderInfo)> {
-    let encoder_info = get_encoder_priority_list()
+    let encoder_info = get_encoder_priority_list(codec)
         .into_iter()
         .find(|e| e.name == encoder_name)
         .unwrap_or_else(|| EncoderInfo {
@@ -303,7 +1094,7 @@ fn try_init_encoder_with_retries(
             std::thread::sleep(std::time::Duration::from_millis(delay_ms));
         }
 
-        match try_init_encoder_once(encoder_name, width, height, fps, quality) {
+        match try_init_encoder_once(encoder_name, width, height, fps, rate_control, preset, tuning) {
             Ok(encoder) => {
                 if attempt > 0 {
                     log::info!("Encoder '{}' initialized successfully on retry {}", encoder_name, attempt);
@@ -339,7 +1130,13 @@ impl VideoEncoder {
         width: usize,
         height: usize,
         fps: u32,
-        quality: u8,
+        rate_control: RateControl,
+        codec: Codec,
+        preset: Option<Preset>,
+        tuning: EncoderTuning,
+        encoder_choice: &EncoderChoice,
+        scale_filter: ScaleFilter,
+        audio_channels: Option<u16>,
         pts_offset: i64,
         on_chunk_created: Option<F>,
     ) -> Result<Self>
@@ -375,11 +1172,11 @@ impl VideoEncoder {
         // GPU-first encoder selection with retry logic and fallback chain
         log::info!("Initializing encoder with GPU-first priority");
 
-        let available_encoders = get_available_encoders();
+        let available_encoders = encoders_for_rate_control(get_available_encoders(codec, encoder_choice)?, rate_control)?;
         if available_encoders.is_empty() {
-            return Err(ScreenRecError::EncodingError(
-                "No H.264 encoders available on this system".to_string()
-            ));
+            return Err(ScreenRecError::EncodingError(format!(
+                "No {} encoders available on this system", codec
+            )));
         }
 
         let retry_config = RetryConfig::default();
@@ -388,18 +1185,23 @@ impl VideoEncoder {
         // Try each encoder in priority order with retries
         let mut encoder_result = None;
         let mut selected_encoder_info = None;
+        let mut encoder_size = (width, height);
 
         for encoder_info in &available_encoders {
             log::info!("Attempting encoder: {} (type: {:?}, priority: {})",
                       encoder_info.name, encoder_info.encoder_type, encoder_info.priority);
             tried_encoders.push(encoder_info.name.clone());
 
+            let (encode_width, encode_height) = encode_size(&encoder_info.name, width, height, tuning);
             match try_init_encoder_with_retries(
                 &encoder_info.name,
-                width as u32,
-                height as u32,
+                codec,
+                encode_width as u32,
+                encode_height as u32,
                 fps,
-                quality,
+                rate_control,
+                preset,
+                tuning,
                 &retry_config,
             ) {
                 Ok((encoder, info)) => {
@@ -407,6 +1209,7 @@ impl VideoEncoder {
                               info.name, info.encoder_type);
                     encoder_result = Some(encoder);
                     selected_encoder_info = Some(info);
+                    encoder_size = (encode_width, encode_height);
                     break;
                 }
                 Err(e) => {
@@ -424,31 +1227,47 @@ impl VideoEncoder {
 
         let encoder_info = selected_encoder_info.unwrap();
         let encoder_name = encoder_info.name.clone();
+        let (width, height) = encoder_size;
+        warn_if_software_av1(&encoder_name);
 
         // Get codec for stream setup
-        let codec = ffmpeg::encoder::find_by_name(&encoder_name)
+        let stream_codec = ffmpeg::encoder::find_by_name(&encoder_name)
             .ok_or_else(|| ScreenRecError::EncodingError("Selected encoder codec not found".to_string()))?;
 
         log::info!("Using encoder: {} (type: {:?})", encoder_name, encoder_info.encoder_type);
 
         // Create video stream
-        let mut stream = octx.add_stream(codec).map_err(|e| {
+        let mut stream = octx.add_stream(stream_codec).map_err(|e| {
             ScreenRecError::EncodingError(format!("Failed to add stream: {}", e))
         })?;
         let stream_index = stream.index();
 
-        // Copy encoder parameters to stream first
+        // Copy encoder parameters to stream first (this carries the codec id,
+        // so HEVC chunks are tagged as hevc and concat can stream-copy them)
         stream.set_parameters(&encoder);
 
         // Then set time_base (1/90000 is MP4 standard) and frame rate AFTER parameters
         stream.set_time_base(ffmpeg::Rational(1, 90000));
         stream.set_avg_frame_rate(ffmpeg::Rational(fps as i32, 1));
 
+        // Optional AAC track, added before the header like the video stream
+        let mut audio = match audio_channels {
+            Some(channels) => Some(AudioTrack::new(&mut octx, channels)?),
+            None => None,
+        };
+
         // Write header
         octx.write_header().map_err(|e| {
             ScreenRecError::EncodingError(format!("Failed to write header: {}", e))
         })?;
 
+        // The muxer may pick its own audio time base when writing the header
+        if let Some(track) = audio.as_mut() {
+            if let Some(audio_stream) = octx.stream(track.stream_index) {
+                track.stream_time_base = audio_stream.time_base();
+            }
+        }
+
         log::info!("MP4 encoder initialized: {}", output_path.display());
 
         // Call callback if provided
@@ -471,7 +1290,15 @@ impl VideoEncoder {
             last_packet_keyframe: false,
             last_packet_pts: None,
             last_packet_dts: None,
+            bytes_written: 0,
             encoder_info,
+            codec,
+            rate_control,
+            preset,
+            tuning,
+            encoder_choice: encoder_choice.clone(),
+            scale_filter,
+            audio,
         })
     }
 
@@ -479,7 +1306,8 @@ impl VideoEncoder {
     fn try_recover_encoder(&mut self, error: &ScreenRecError) -> Result<()> {
         log::error!("Encoder failure detected: {}. Attempting recovery...", error);
 
-        let available_encoders = get_available_encoders();
+        let available_encoders =
+            encoders_for_rate_control(get_available_encoders(self.codec, &self.encoder_choice)?, self.rate_control)?;
         let current_priority = self.encoder_info.priority;
 
         // Find next encoder with lower priority (higher number)
@@ -498,7 +1326,9 @@ impl VideoEncoder {
                     self.width as u32,
                     self.height as u32,
                     self.fps,
-                    8, // Default quality for recovery
+                    self.rate_control,
+                    self.preset,
+                    self.tuning,
                 ) {
                     Ok(new_encoder) => {
                         log::info!("✓ Successfully switched to fallback encoder: {}", fallback_info.name);
@@ -526,21 +1356,23 @@ impl VideoEncoder {
         }
     }
 
-    pub fn encode_frame(&mut self, frame: Frame) -> Result<FrameMetadata> {
+    /// Encode one frame. `force_keyframe` makes it an I-frame, so a chunk can be
+    /// cut here and still be stream-copied by concat.
+    pub fn encode_frame(&mut self, frame: Frame, force_keyframe: bool) -> Result<FrameMetadata> {
         let Frame { data, width, height, display_index, .. } = frame;
 
         // If frame dimensions don't match encoder dimensions, we need to scale/pad
         let processed_data = if width != self.width || height != self.height {
             log::debug!("Frame dimensions {}x{} don't match encoder {}x{}, scaling/padding",
                        width, height, self.width, self.height);
-            Self::scale_and_pad_frame(&data, width, height, self.width, self.height)?
+            scale_and_pad_frame(&data, width, height, self.width, self.height, self.scale_filter)?
         } else {
             data
         };
 
-        // Create YUV420P frame
+        // Create the YUV frame (YUV420P, or YUV444P when lossless)
         let mut yuv_frame = ffmpeg::frame::Video::new(
-            ffmpeg::format::Pixel::YUV420P,
+            pixel_format(self.tuning),
             self.width as u32,
             self.height as u32,
         );
@@ -550,9 +1382,12 @@ impl VideoEncoder {
         // pts_offset is only used for tracking logical frame numbers
         let pts = self.frame_count as i64;
         yuv_frame.set_pts(Some(pts));
+        if force_keyframe {
+            yuv_frame.set_kind(ffmpeg::picture::Type::I);
+        }
 
-        // Convert RGB to YUV420P
-        Self::rgb_to_yuv420p(&processed_data, self.width, self.height, &mut yuv_frame)?;
+        // Convert RGB to YUV
+        Self::rgb_to_yuv(&processed_data, self.width, self.height, self.tuning, &mut yuv_frame)?;
 
         // Send frame to encoder with recovery on failure
         match self.encoder.send_frame(&yuv_frame) {
@@ -606,6 +1441,7 @@ impl VideoEncoder {
             self.last_packet_keyframe = encoded.is_key();
             self.last_packet_pts = encoded.pts();
             self.last_packet_dts = encoded.dts();
+            self.bytes_written += encoded.size() as u64;
 
             encoded.set_stream(self.stream_index);
 
@@ -624,12 +1460,30 @@ impl VideoEncoder {
         Ok(())
     }
 
+    /// Mux captured audio into this chunk's AAC track (no-op without one)
+    pub fn encode_audio(&mut self, sample: &AudioSample) -> Result<()> {
+        match self.audio.as_mut() {
+            Some(track) => track.push(sample, &mut self.octx),
+            None => Ok(()),
+        }
+    }
+
     /// Get the next logical frame number that should be used for the next chunk
     /// This is for tracking purposes, not for PTS (each chunk starts PTS at 0)
     pub fn get_next_pts(&self) -> i64 {
         self.pts_offset + (self.frame_count as i64)
     }
 
+    /// Encoded video bytes written to this chunk so far
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// FFmpeg name of the encoder in use, e.g. h264_nvenc
+    pub fn encoder_name(&self) -> &str {
+        &self.encoder_info.name
+    }
+
     pub fn finish(mut self) -> Result<RecordingOutput> {
         log::info!("Finishing encoding, total frames: {}", self.frame_count);
 
@@ -640,6 +1494,11 @@ impl VideoEncoder {
 
         self.receive_packets()?;
 
+        // Flush buffered audio frames so the tail of the chunk isn't silent
+        if let Some(track) = self.audio.as_mut() {
+            track.finish(&mut self.octx)?;
+        }
+
         // Write trailer
         self.octx.write_trailer().map_err(|e| {
             ScreenRecError::EncodingError(format!("Failed to write trailer: {}", e))
@@ -652,117 +1511,60 @@ impl VideoEncoder {
         })
     }
 
-    fn rgb_to_yuv420p(
+    fn rgb_to_yuv(
         rgb: &[u8],
         width: usize,
         height: usize,
+        tuning: EncoderTuning,
         yuv_frame: &mut ffmpeg::frame::Video,
     ) -> Result<()> {
         // Get strides
         let y_stride = yuv_frame.stride(0);
-        let u_stride = yuv_frame.stride(1);
-
-        // Use integer arithmetic for much faster conversion
-        unsafe {
-            let y_ptr = yuv_frame.data_mut(0).as_mut_ptr();
-            let u_ptr = yuv_frame.data_mut(1).as_mut_ptr();
-            let v_ptr = yuv_frame.data_mut(2).as_mut_ptr();
-
-            // Process Y plane (all pixels)
-            for y in 0..height {
-                let y_row_offset = y * y_stride;
-                let rgb_row_offset = y * width * 3;
-
-                for x in 0..width {
-                    let rgb_idx = rgb_row_offset + x * 3;
-                    let r = rgb[rgb_idx] as i32;
-                    let g = rgb[rgb_idx + 1] as i32;
-                    let b = rgb[rgb_idx + 2] as i32;
-
-                    // Y = 0.299*R + 0.587*G + 0.114*B (using fixed-point arithmetic)
-                    let y_val = ((77 * r + 150 * g + 29 * b) >> 8) as u8;
-                    *y_ptr.add(y_row_offset + x) = y_val;
-                }
-            }
+        let uv_stride = yuv_frame.stride(1);
 
-            // Process U and V planes (2x2 subsampling)
-            for y in (0..height).step_by(2) {
-                let uv_y = y / 2;
-                let uv_row_offset = uv_y * u_stride;
-                let rgb_row_offset = y * width * 3;
-
-                for x in (0..width).step_by(2) {
-                    let rgb_idx = rgb_row_offset + x * 3;
-                    let r = rgb[rgb_idx] as i32;
-                    let g = rgb[rgb_idx + 1] as i32;
-                    let b = rgb[rgb_idx + 2] as i32;
-
-                    let uv_x = x / 2;
-
-                    // U = -0.169*R - 0.331*G + 0.500*B + 128
-                    let u_val = (((-43 * r - 85 * g + 128 * b) >> 8) + 128).clamp(0, 255) as u8;
-                    *u_ptr.add(uv_row_offset + uv_x) = u_val;
-
-                    // V = 0.500*R - 0.419*G - 0.081*B + 128
-                    let v_val = (((128 * r - 107 * g - 21 * b) >> 8) + 128).clamp(0, 255) as u8;
-                    *v_ptr.add(uv_row_offset + uv_x) = v_val;
-                }
+        // Each plane is a separate allocation, but `data_mut` borrows the whole frame,
+        // so take raw parts to hand all three planes to the converter at once
+        let mut plane = |index: usize| {
+            let data = yuv_frame.data_mut(index);
+            (data.as_mut_ptr(), data.len())
+        };
+        let (y_ptr, y_len) = plane(0);
+        let (u_ptr, u_len) = plane(1);
+        let (v_ptr, v_len) = plane(2);
+
+        // SAFETY: the pointers and lengths come from the frame's own planes, which
+        // don't overlap and outlive this call
+        let planes = unsafe {
+            yuv::YuvPlanes {
+                y: std::slice::from_raw_parts_mut(y_ptr, y_len),
+                y_stride,
+                u: std::slice::from_raw_parts_mut(u_ptr, u_len),
+                v: std::slice::from_raw_parts_mut(v_ptr, v_len),
+                uv_stride,
             }
+        };
+
+        let matrix = match tuning.colorspace {
+            Colorspace::Bt601 => &yuv::BT601,
+            Colorspace::Bt709 => &yuv::BT709,
+        };
+        if tuning.lossless {
+            yuv::rgb_to_yuv444p(rgb, width, height, matrix, planes);
+        } else {
+            yuv::rgb_to_yuv420p(rgb, width, height, matrix, planes);
         }
 
         Ok(())
     }
 
-    /// Scale and pad frame to target dimensions (center with black bars)
-    fn scale_and_pad_frame(
-        rgb: &[u8],
-        src_width: usize,
-        src_height: usize,
-        target_width: usize,
-        target_height: usize,
-    ) -> Result<Vec<u8>> {
-        // Calculate scaling to fit within target while preserving aspect ratio
-        let width_ratio = target_width as f32 / src_width as f32;
-        let height_ratio = target_height as f32 / src_height as f32;
-        let scale_ratio = width_ratio.min(height_ratio);
-
-        let scaled_width = (src_width as f32 * scale_ratio) as usize;
-        let scaled_height = (src_height as f32 * scale_ratio) as usize;
-
-        // Calculate padding to center the scaled image
-        let pad_x = (target_width - scaled_width) / 2;
-        let pad_y = (target_height - scaled_height) / 2;
-
-        // Create black canvas
-        let mut result = vec![0u8; target_width * target_height * 3];
-
-        // Simple nearest-neighbor scaling and placement
-        for target_y in 0..target_height {
-            for target_x in 0..target_width {
-                // Check if we're in the scaled image area
-                if target_x >= pad_x && target_x < pad_x + scaled_width &&
-                   target_y >= pad_y && target_y < pad_y + scaled_height {
-                    // Map to source coordinates
-                    let src_x = ((target_x - pad_x) as f32 / scale_ratio) as usize;
-                    let src_y = ((target_y - pad_y) as f32 / scale_ratio) as usize;
-
-                    // Bounds check
-                    if src_x < src_width && src_y < src_height {
-                        let src_idx = (src_y * src_width + src_x) * 3;
-                        let dst_idx = (target_y * target_width + target_x) * 3;
-
-                        if src_idx + 2 < rgb.len() && dst_idx + 2 < result.len() {
-                            result[dst_idx] = rgb[src_idx];         // R
-                            result[dst_idx + 1] = rgb[src_idx + 1]; // G
-                            result[dst_idx + 2] = rgb[src_idx + 2]; // B
-                        }
-                    }
-                }
-                // Else: leave as black (already initialized to 0)
-            }
+    /// CRF for the constant-quality modes: `--crf` as given, otherwise mapped from the quality level
+    fn crf_value(rate_control: RateControl) -> u8 {
+        match rate_control {
+            RateControl::Crf(crf) => crf,
+            RateControl::Quality(quality) => Self::quality_to_crf(quality),
+            // Bitrate mode sets no CRF; this is only a placeholder
+            RateControl::Bitrate(_) => Self::quality_to_crf(5),
         }
-
-        Ok(result)
     }
 
     fn quality_to_crf(quality: u8) -> u8 {
@@ -790,11 +1592,231 @@ impl VideoEncoder {
     }
 }
 
+/// Sample rate of the AAC track muxed into each chunk
+#[cfg(not(target_os = "macos"))]
+const AUDIO_SAMPLE_RATE: u32 = 48_000;
+
+/// AAC audio stream muxed alongside the video in a chunk.
+/// Like video PTS, audio PTS restarts at 0 in every chunk file.
+#[cfg(not(target_os = "macos"))]
+struct AudioTrack {
+    encoder: ffmpeg::encoder::Audio,
+    stream_index: usize,
+    stream_time_base: ffmpeg::Rational,
+    channels: u16,
+    layout: ffmpeg::ChannelLayout,
+    frame_size: usize, // Samples per channel in each encoder frame
+    resampler: Option<LinearResampler>,
+    pending: Vec<f32>, // Resampled interleaved samples waiting to fill an encoder frame
+    samples_written: i64,
+}
+
+#[cfg(not(target_os = "macos"))]
+impl AudioTrack {
+    /// Add a mono or stereo AAC stream to the output. Must be called before the header is written.
+    fn new(octx: &mut ffmpeg::format::context::Output, channels: u16) -> Result<Self> {
+        let aac = ffmpeg::encoder::find(ffmpeg::codec::Id::AAC)
+            .ok_or_else(|| ScreenRecError::EncodingError("AAC encoder not found".to_string()))?;
+
+        let mut audio_encoder = ffmpeg::codec::context::Context::new_with_codec(aac)
+            .encoder()
+            .audio()
+            .map_err(|e| ScreenRecError::EncodingError(format!("Failed to get audio encoder: {}", e)))?;
+
+        let layout = if channels >= 2 {
+            ffmpeg::ChannelLayout::STEREO
+        } else {
+            ffmpeg::ChannelLayout::MONO
+        };
+        audio_encoder.set_rate(AUDIO_SAMPLE_RATE as i32);
+        audio_encoder.set_channel_layout(layout);
+        audio_encoder.set_format(ffmpeg::format::Sample::F32(ffmpeg::format::sample::Type::Planar));
+        audio_encoder.set_bit_rate(128_000);
+        audio_encoder.set_time_base((1, AUDIO_SAMPLE_RATE as i32));
+
+        let encoder = audio_encoder
+            .open_with(ffmpeg::Dictionary::new())
+            .map_err(|e| ScreenRecError::EncodingError(format!("Failed to open AAC encoder: {}", e)))?;
+
+        let mut stream = octx.add_stream(aac).map_err(|e| {
+            ScreenRecError::EncodingError(format!("Failed to add audio stream: {}", e))
+        })?;
+        stream.set_parameters(&encoder);
+        stream.set_time_base((1, AUDIO_SAMPLE_RATE as i32));
+
+        // AAC reports a fixed frame size (1024); fall back to it if the encoder doesn't
+        let frame_size = match encoder.frame_size() {
+            0 => 1024,
+            size => size as usize,
+        };
+
+        Ok(Self {
+            encoder,
+            stream_index: stream.index(),
+            stream_time_base: ffmpeg::Rational(1, AUDIO_SAMPLE_RATE as i32),
+            channels: channels.clamp(1, 2),
+            layout,
+            frame_size,
+            resampler: None,
+            pending: Vec::new(),
+            samples_written: 0,
+        })
+    }
+
+    /// Resample captured audio to the encoder rate and encode every full frame
+    fn push(&mut self, sample: &AudioSample, octx: &mut ffmpeg::format::context::Output) -> Result<()> {
+        let remixed;
+        let data = if sample.channels == self.channels {
+            &sample.data
+        } else {
+            remixed = crate::audio::convert_channels(&sample.data, sample.channels, self.channels);
+            &remixed
+        };
+
+        if sample.sample_rate == AUDIO_SAMPLE_RATE {
+            self.pending.extend_from_slice(data);
+        } else {
+            // The capture rate can change if the audio device was reopened
+            if self.resampler.as_ref().map(|r| r.input_rate) != Some(sample.sample_rate) {
+                log::debug!("Resampling audio {} Hz -> {} Hz", sample.sample_rate, AUDIO_SAMPLE_RATE);
+                self.resampler = Some(LinearResampler::new(sample.sample_rate, AUDIO_SAMPLE_RATE, self.channels));
+            }
+            if let Some(resampler) = self.resampler.as_mut() {
+                resampler.process(data, &mut self.pending);
+            }
+        }
+
+        let frame_len = self.frame_size * self.channels as usize;
+        while self.pending.len() >= frame_len {
+            let samples: Vec<f32> = self.pending.drain(..frame_len).collect();
+            self.send_frame(&samples, octx)?;
+        }
+
+        Ok(())
+    }
+
+    fn send_frame(&mut self, samples: &[f32], octx: &mut ffmpeg::format::context::Output) -> Result<()> {
+        let channels = self.channels as usize;
+        let frames = samples.len() / channels;
+        let mut frame = ffmpeg::frame::Audio::new(
+            ffmpeg::format::Sample::F32(ffmpeg::format::sample::Type::Planar),
+            frames,
+            self.layout,
+        );
+        // Planar: one plane per channel
+        for channel in 0..channels {
+            let plane = &mut frame.plane_mut::<f32>(channel)[..frames];
+            for (i, value) in plane.iter_mut().enumerate() {
+                *value = samples[i * channels + channel];
+            }
+        }
+        frame.set_rate(AUDIO_SAMPLE_RATE);
+        frame.set_pts(Some(self.samples_written));
+        self.samples_written += frames as i64;
+
+        self.encoder.send_frame(&frame).map_err(|e| {
+            ScreenRecError::EncodingError(format!("Failed to send audio frame: {}", e))
+        })?;
+
+        self.receive_packets(octx)
+    }
+
+    fn receive_packets(&mut self, octx: &mut ffmpeg::format::context::Output) -> Result<()> {
+        let mut encoded = ffmpeg::Packet::empty();
+        while self.encoder.receive_packet(&mut encoded).is_ok() {
+            encoded.set_stream(self.stream_index);
+            encoded.rescale_ts(ffmpeg::Rational(1, AUDIO_SAMPLE_RATE as i32), self.stream_time_base);
+
+            // Interleaved writes keep audio and video packets ordered by DTS
+            encoded.write_interleaved(octx).map_err(|e| {
+                ScreenRecError::EncodingError(format!("Failed to write audio packet: {}", e))
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Encode the partial last frame and drain the encoder. Call before write_trailer.
+    fn finish(&mut self, octx: &mut ffmpeg::format::context::Output) -> Result<()> {
+        if !self.pending.is_empty() {
+            // AAC accepts a short final frame
+            let samples = std::mem::take(&mut self.pending);
+            self.send_frame(&samples, octx)?;
+        }
+
+        self.encoder.send_eof().map_err(|e| {
+            ScreenRecError::EncodingError(format!("Failed to send audio EOF: {}", e))
+        })?;
+        self.receive_packets(octx)?;
+
+        log::info!(
+            "Audio track finished: {:.1}s of audio",
+            self.samples_written as f64 / AUDIO_SAMPLE_RATE as f64
+        );
+        Ok(())
+    }
+}
+
+/// Streaming linear-interpolation resampler for interleaved f32 audio
+#[cfg(not(target_os = "macos"))]
+struct LinearResampler {
+    input_rate: u32,
+    channels: usize,
+    step: f64,     // Input frames advanced per output frame
+    position: f64, // Read position relative to `previous`
+    previous: Option<Vec<f32>>, // Last input frame of the previous block
+}
+
+#[cfg(not(target_os = "macos"))]
+impl LinearResampler {
+    fn new(input_rate: u32, output_rate: u32, channels: u16) -> Self {
+        Self {
+            input_rate,
+            channels: channels.max(1) as usize,
+            step: input_rate as f64 / output_rate as f64,
+            position: 0.0,
+            previous: None,
+        }
+    }
+
+    fn process(&mut self, input: &[f32], output: &mut Vec<f32>) {
+        let channels = self.channels;
+        let frames = input.len() / channels;
+        if frames == 0 {
+            return;
+        }
+
+        // Frame 0 is `previous` (the first frame on the first block), frames 1..=len are this block
+        let previous = self.previous.take().unwrap_or_else(|| input[..channels].to_vec());
+        let sample_at = |i: usize, channel: usize| {
+            if i == 0 {
+                previous[channel]
+            } else {
+                input[(i - 1) * channels + channel]
+            }
+        };
+        let len = frames as f64;
+
+        while self.position < len {
+            let index = self.position as usize;
+            let frac = (self.position - index as f64) as f32;
+            for channel in 0..channels {
+                let a = sample_at(index, channel);
+                let b = sample_at(index + 1, channel);
+                output.push(a + (b - a) * frac);
+            }
+            self.position += self.step;
+        }
+
+        self.position -= len;
+        self.previous = Some(input[(frames - 1) * channels..frames * channels].to_vec());
+    }
+}
+
 /// Process frames from the capture channel and encode them
 #[allow(dead_code)]
 #[cfg(not(target_os = "macos"))]
 pub async fn process_frames(
-    mut rx: mpsc::Receiver<Frame>,
+    mut rx: FrameReceiver,
     mut encoder: VideoEncoder,
     db: Option<Arc<Database>>,
     device_name: Option<String>,
@@ -804,8 +1826,9 @@ pub async fn process_frames(
     while let Some(frame) = rx.recv().await {
         let captured_at = frame.captured_at;
 
-        // Encode frame and get metadata
-        let metadata = encoder.encode_frame(frame)?;
+        // Encode frame and get metadata (the file must open on a keyframe)
+        let force_keyframe = encoder.frame_count == 0;
+        let metadata = encoder.encode_frame(frame, force_keyframe)?;
 
         // Insert frame into database with metadata if enabled
         if let (Some(ref db), Some(ref device)) = (&db, &device_name) {
@@ -830,36 +1853,184 @@ pub async fn process_frames(
     encoder.finish()
 }
 
+/// With adaptive quality on, pick the rate control for the next chunk from how far
+/// the encoder has fallen behind
+fn adapt_rate_control(
+    adaptive: &mut Option<AdaptiveQuality>,
+    rate_control: &mut RateControl,
+    backlog: f64,
+) {
+    let Some(adaptive) = adaptive.as_mut() else {
+        return;
+    };
+
+    if let Some(next) = adaptive.next_rate_control(*rate_control, backlog) {
+        if backlog >= STRAINED_BACKLOG {
+            log::warn!(
+                "⚠️  Encoder falling behind (frame queue {:.0}% full): {} -> {} for the next chunk",
+                backlog * 100.0,
+                rate_control,
+                next
+            );
+        } else {
+            log::info!(
+                "✅ Encoder caught up (frame queue {:.0}% full): {} -> {} for the next chunk",
+                backlog * 100.0,
+                rate_control,
+                next
+            );
+        }
+        *rate_control = next;
+    }
+}
+
+/// Where chunk `index` goes. A name that is already taken (a pattern without
+/// `{index}`, or a clock that went backwards) gets a numeric suffix instead of
+/// overwriting the earlier chunk.
+fn next_chunk_path(dir: &Path, pattern: &ChunkNamePattern, index: i64, task_id: Option<&str>) -> PathBuf {
+    let path = dir.join(pattern.render(chrono::Local::now(), index, task_id));
+    if !path.exists() {
+        return path;
+    }
+
+    let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("chunk").to_string();
+    let free = (1..)
+        .map(|n| dir.join(format!("{}-{}.mp4", stem, n)))
+        .find(|candidate| !candidate.exists())
+        .unwrap_or(path);
+    log::warn!("⚠️  Chunk name {}.mp4 already exists, writing {} instead", stem, free.display());
+    free
+}
+
+/// Publish encoder progress for `--status-port`
+fn update_status(status: &Option<SharedStatus>, chunk_index: i64, frames_encoded: u64) {
+    if let Some(Ok(mut status)) = status.as_ref().map(|status| status.lock()) {
+        status.chunk_index = chunk_index;
+        status.frames_encoded = frames_encoded;
+    }
+}
+
+/// Take a pending request for a new chunk (SIGHUP or `POST /rotate`)
+fn take_rotation_request(status: &Option<SharedStatus>) -> bool {
+    match status.as_ref().map(|status| status.lock()) {
+        Some(Ok(mut status)) => status.take_rotation_request(),
+        _ => false,
+    }
+}
+
+/// Hash a finished chunk into its `.sha256` sidecar and database row
+async fn record_checksum(db: &Option<Arc<Database>>, video_file: &Path) {
+    // Hashing reads the whole chunk, so it runs off the async workers
+    let chunk = video_file.to_path_buf();
+    let hashed = tokio::task::spawn_blocking(move || integrity::write_checksum(&chunk))
+        .await
+        .unwrap_or_else(|e| Err(std::io::Error::other(e).into()));
+    let sha256 = match hashed {
+        Ok(sha256) => sha256,
+        Err(e) => {
+            log::warn!("⚠️  Could not checksum {}: {}", video_file.display(), e);
+            return;
+        }
+    };
+    if let Some(db) = db {
+        if let Err(e) = db.set_chunk_sha256(&video_file.to_string_lossy(), &sha256).await {
+            log::error!("Failed to store chunk checksum in database: {}", e);
+        }
+    }
+}
+
+/// Encrypt a finished chunk with `--encrypt`, record its checksum and hand it to
+/// `on_chunk_finished`. Returns the chunk as it now is on disk.
+async fn finalize_chunk(
+    db: &Option<Arc<Database>>,
+    encryption_key: Option<&EncryptionKey>,
+    on_chunk_finished: &Option<ChunkFinished>,
+    chunk_index: i64,
+    mut output: RecordingOutput,
+) -> RecordingOutput {
+    if let Some(key) = encryption_key {
+        // Encryption reads and rewrites the whole chunk, so it runs off the async workers
+        let (chunk, key) = (output.video_file.clone(), key.clone());
+        let encrypted = tokio::task::spawn_blocking(move || encryption::encrypt_chunk(&chunk, &key))
+            .await
+            .unwrap_or_else(|e| Err(std::io::Error::other(e).into()));
+        match encrypted {
+            Ok((encrypted, nonce)) => {
+                if let Some(db) = db {
+                    let old_path = output.video_file.to_string_lossy();
+                    if let Err(e) = db.set_chunk_file(&old_path, &encrypted.to_string_lossy(), Some(&nonce)).await {
+                        log::error!("Failed to store encrypted chunk in database: {}", e);
+                    }
+                }
+                output.video_file = encrypted;
+            }
+            Err(e) => log::error!("❌ {} was left unencrypted: {}", output.video_file.display(), e),
+        }
+    }
+    record_checksum(db, &output.video_file).await;
+    if let Some(on_chunk_finished) = on_chunk_finished {
+        on_chunk_finished(&output.video_file, chunk_index);
+    }
+    output
+}
+
 /// Process frames with chunking support
 pub async fn process_frames_chunked(
-    mut rx: mpsc::Receiver<Frame>,
+    mut rx: FrameReceiver,
     base_output_dir: PathBuf,
     width: usize,
     height: usize,
     fps: u32,
-    quality: u8,
-    chunk_duration_secs: u64,
+    mut rate_control: RateControl,
+    codec: Codec,
+    preset: Option<Preset>,
+    tuning: EncoderTuning,
+    encoder_choice: EncoderChoice,
+    scale_filter: ScaleFilter,
+    mut adaptive: Option<AdaptiveQuality>,
+    chunk_policy: ChunkPolicy,
+    chunk_name_pattern: ChunkNamePattern,
+    max_disk_usage: Option<u64>,
+    encryption_key: Option<EncryptionKey>,
+    mut frame_log: Option<FrameLog>,
+    mut frame_dump: Option<FrameDumper>,
+    on_chunk_finished: Option<ChunkFinished>,
     db: Option<Arc<Database>>,
     device_name: Option<String>,
     recording_type: Option<String>,
     task_id: Option<String>,
     session_id: Option<i64>,
     mut shutdown_rx: Option<tokio::sync::oneshot::Receiver<()>>,
+    status: Option<SharedStatus>,
     #[cfg(target_os = "macos")] ffmpeg_path: Option<String>,
-) -> Result<Vec<RecordingOutput>> {
-    log::info!("Starting chunked frame processing with {}-second chunks", chunk_duration_secs);
+    #[cfg(target_os = "macos")] mp4_layout: crate::cli::Mp4Layout,
+    #[cfg(not(target_os = "macos"))] mut audio_rx: Option<mpsc::Receiver<AudioSample>>,
+    #[cfg(not(target_os = "macos"))] audio_channels: u16,
+) -> Result<(Vec<RecordingOutput>, Option<LatencyStats>)> {
+    log::info!("Starting chunked frame processing with {}", chunk_policy);
 
     let mut chunk_outputs = Vec::new();
     let mut chunk_index = 0i64;
-    let frames_per_chunk = (fps as u64) * chunk_duration_secs;
     let mut frames_in_current_chunk = 0u64;
     let mut next_pts_offset = 0i64; // Track continuous PTS across chunks
     let mut total_frames_encoded = 0u64;
+    let mut latency = EncodeLatency::new();
+
+    // A task recorded again after an interruption carries on from its last frame,
+    // so PTS (and the frame offsets derived from them) keep increasing across sessions
+    if let (Some(db), Some(task_id)) = (&db, &task_id) {
+        match db.get_max_pts_for_task(task_id).await {
+            Ok(Some(max_pts)) => {
+                next_pts_offset = max_pts + 1;
+                log::info!("Resuming task {} at PTS {}", task_id, next_pts_offset);
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("Could not look up the last PTS of task {}, starting at 0: {}", task_id, e),
+        }
+    }
 
     // Create first chunk
-    let now = chrono::Local::now();
-    let chunk_filename = format!("{}.mp4", now.format("%Y-%m-%d_%H-%M-%S"));
-    let chunk_path = base_output_dir.join(&chunk_filename);
+    let chunk_path = next_chunk_path(&base_output_dir, &chunk_name_pattern, chunk_index, task_id.as_deref());
 
     log::info!("Creating chunk {}: {} (PTS offset: {})", chunk_index, chunk_path.display(), next_pts_offset);
 
@@ -875,10 +2046,16 @@ pub async fn process_frames_chunked(
             width,
             height,
             fps,
-            quality,
+            rate_control,
+            codec,
+            preset,
+            tuning,
+            &encoder_choice,
+            scale_filter,
             next_pts_offset,
             None::<fn(&str)>,
             ffmpeg,
+            mp4_layout,
         )?
     };
 
@@ -888,7 +2065,13 @@ pub async fn process_frames_chunked(
         width,
         height,
         fps,
-        quality,
+        rate_control,
+        codec,
+        preset,
+        tuning,
+        &encoder_choice,
+        scale_filter,
+        audio_rx.is_some().then_some(audio_channels),
         next_pts_offset,
         None::<fn(&str)>,
     )?;
@@ -903,6 +2086,8 @@ pub async fn process_frames_chunked(
             Some(chunk_index),
             session_id,
             Some(fps as i64),
+            Some(current_encoder.encoder_name()),
+            rate_control.quality().map(i64::from),
         ).await {
             log::error!("Failed to insert video chunk into database: {}", e);
         }
@@ -916,8 +2101,12 @@ pub async fn process_frames_chunked(
                     if let Some(frame) = frame_opt {
                         let captured_at = frame.captured_at;
 
-                        // Check if we need to start a new chunk
-                        if frames_in_current_chunk >= frames_per_chunk {
+                        // Check if we need to start a new chunk; an empty one is already new
+                        let rotate = take_rotation_request(&status) && frames_in_current_chunk > 0;
+                        if rotate {
+                            log::info!("Starting a new chunk on request");
+                        }
+                        if rotate || chunk_policy.is_full(frames_in_current_chunk, fps, current_encoder.bytes_written()) {
                             log::info!("Finishing chunk {} with {} frames", chunk_index, frames_in_current_chunk);
 
                             // Get next PTS before finishing encoder
@@ -925,15 +2114,19 @@ pub async fn process_frames_chunked(
 
                             // Finish current encoder
                             let output = current_encoder.finish()?;
+                            let output =
+                                finalize_chunk(&db, encryption_key.as_ref(), &on_chunk_finished, chunk_index, output)
+                                    .await;
                             chunk_outputs.push(output);
+                            adapt_rate_control(&mut adaptive, &mut rate_control, rx.backlog());
 
                             // Start new chunk
                             chunk_index += 1;
                             frames_in_current_chunk = 0;
+                            update_status(&status, chunk_index, total_frames_encoded);
 
-                            let now = chrono::Local::now();
-                            let chunk_filename = format!("{}.mp4", now.format("%Y-%m-%d_%H-%M-%S"));
-                            let chunk_path = base_output_dir.join(&chunk_filename);
+                            let chunk_path =
+                                next_chunk_path(&base_output_dir, &chunk_name_pattern, chunk_index, task_id.as_deref());
 
                             log::info!("Creating chunk {}: {} (PTS offset: {})", chunk_index, chunk_path.display(), next_pts_offset);
 
@@ -949,10 +2142,16 @@ pub async fn process_frames_chunked(
                                     width,
                                     height,
                                     fps,
-                                    quality,
+                                    rate_control,
+                                    codec,
+                                    preset,
+                                    tuning,
+                                    &encoder_choice,
+                                    scale_filter,
                                     next_pts_offset,
                                     None::<fn(&str)>,
                                     ffmpeg,
+                                    mp4_layout,
                                 )?;
                             }
 
@@ -963,7 +2162,13 @@ pub async fn process_frames_chunked(
                                     width,
                                     height,
                                     fps,
-                                    quality,
+                                    rate_control,
+                                    codec,
+                                    preset,
+                                    tuning,
+                                    &encoder_choice,
+                                    scale_filter,
+                                    audio_rx.is_some().then_some(audio_channels),
                                     next_pts_offset,
                                     None::<fn(&str)>,
                                 )?;
@@ -979,16 +2184,41 @@ pub async fn process_frames_chunked(
                                     Some(chunk_index),
                                     session_id,
                                     Some(fps as i64),
+                                    Some(current_encoder.encoder_name()),
+                                    rate_control.quality().map(i64::from),
                                 ).await {
                                     log::error!("Failed to insert video chunk into database: {}", e);
                                 }
                             }
+
+                            // Keep the output directory under --max-disk-usage
+                            if let Some(max_bytes) = max_disk_usage {
+                                crate::retention::enforce_disk_budget(&base_output_dir, &chunk_path, max_bytes, db.as_ref()).await;
+                            }
+                        }
+
+                        // Mux audio that arrived since the last frame
+                        #[cfg(not(target_os = "macos"))]
+                        if let Some(ref mut audio_rx) = audio_rx {
+                            while let Ok(sample) = audio_rx.try_recv() {
+                                current_encoder.encode_audio(&sample)?;
+                            }
+                        }
+
+                        // Tee off the RGB frame before the encoder converts it to YUV
+                        if let Some(frame_dump) = frame_dump.as_mut() {
+                            frame_dump.offer(&frame);
                         }
 
                         // Encode frame and get metadata
-                        let metadata = current_encoder.encode_frame(frame)?;
+                        let metadata = current_encoder.encode_frame(frame, frames_in_current_chunk == 0)?;
                         frames_in_current_chunk += 1;
                         total_frames_encoded += 1;
+                        update_status(&status, chunk_index, total_frames_encoded);
+                        latency.frame_encoded(captured_at, rx.queued_frames());
+                        if let Some(frame_log) = frame_log.as_mut() {
+                            frame_log.append(total_frames_encoded - 1, chunk_index, captured_at, &metadata);
+                        }
 
                         // Insert frame into database with metadata if enabled
                         if let (Some(ref db), Some(ref device)) = (&db, &device_name) {
@@ -1024,8 +2254,12 @@ pub async fn process_frames_chunked(
             if let Some(frame) = rx.recv().await {
                 let captured_at = frame.captured_at;
 
-                // Check if we need to start a new chunk
-                if frames_in_current_chunk >= frames_per_chunk {
+                // Check if we need to start a new chunk; an empty one is already new
+                let rotate = take_rotation_request(&status) && frames_in_current_chunk > 0;
+                if rotate {
+                    log::info!("Starting a new chunk on request");
+                }
+                if rotate || chunk_policy.is_full(frames_in_current_chunk, fps, current_encoder.bytes_written()) {
                     log::debug!("Starting new chunk - total frames encoded so far: {}", total_frames_encoded);
                     log::info!("Finishing chunk {} with {} frames", chunk_index, frames_in_current_chunk);
 
@@ -1034,15 +2268,18 @@ pub async fn process_frames_chunked(
 
                     // Finish current encoder
                     let output = current_encoder.finish()?;
+                    let output =
+                        finalize_chunk(&db, encryption_key.as_ref(), &on_chunk_finished, chunk_index, output).await;
                     chunk_outputs.push(output);
+                    adapt_rate_control(&mut adaptive, &mut rate_control, rx.backlog());
 
                     // Start new chunk
                     chunk_index += 1;
                     frames_in_current_chunk = 0;
+                    update_status(&status, chunk_index, total_frames_encoded);
 
-                    let now = chrono::Local::now();
-                    let chunk_filename = format!("{}.mp4", now.format("%Y-%m-%d_%H-%M-%S"));
-                    let chunk_path = base_output_dir.join(&chunk_filename);
+                    let chunk_path =
+                        next_chunk_path(&base_output_dir, &chunk_name_pattern, chunk_index, task_id.as_deref());
 
                     log::info!("Creating chunk {}: {} (PTS offset: {})", chunk_index, chunk_path.display(), next_pts_offset);
 
@@ -1058,10 +2295,16 @@ pub async fn process_frames_chunked(
                             width,
                             height,
                             fps,
-                            quality,
+                            rate_control,
+                            codec,
+                            preset,
+                            tuning,
+                            &encoder_choice,
+                            scale_filter,
                             next_pts_offset,
                             None::<fn(&str)>,
                             ffmpeg,
+                            mp4_layout,
                         )?;
                     }
 
@@ -1072,7 +2315,13 @@ pub async fn process_frames_chunked(
                             width,
                             height,
                             fps,
-                            quality,
+                            rate_control,
+                            codec,
+                            preset,
+                            tuning,
+                            &encoder_choice,
+                            scale_filter,
+                            audio_rx.is_some().then_some(audio_channels),
                             next_pts_offset,
                             None::<fn(&str)>,
                         )?;
@@ -1088,16 +2337,41 @@ pub async fn process_frames_chunked(
                             Some(chunk_index),
                             session_id,
                             Some(fps as i64),
+                            Some(current_encoder.encoder_name()),
+                            rate_control.quality().map(i64::from),
                         ).await {
                             log::error!("Failed to insert video chunk into database: {}", e);
                         }
                     }
+
+                    // Keep the output directory under --max-disk-usage
+                    if let Some(max_bytes) = max_disk_usage {
+                        crate::retention::enforce_disk_budget(&base_output_dir, &chunk_path, max_bytes, db.as_ref()).await;
+                    }
+                }
+
+                // Mux audio that arrived since the last frame
+                #[cfg(not(target_os = "macos"))]
+                if let Some(ref mut audio_rx) = audio_rx {
+                    while let Ok(sample) = audio_rx.try_recv() {
+                        current_encoder.encode_audio(&sample)?;
+                    }
                 }
 
-                // Encode frame and get metadata
-                let metadata = current_encoder.encode_frame(frame)?;
+                // Tee off the RGB frame before the encoder converts it to YUV
+                if let Some(frame_dump) = frame_dump.as_mut() {
+                    frame_dump.offer(&frame);
+                }
+
+                // Encode frame and get metadata (each chunk opens on a keyframe)
+                let metadata = current_encoder.encode_frame(frame, frames_in_current_chunk == 0)?;
                 frames_in_current_chunk += 1;
                 total_frames_encoded += 1;
+                update_status(&status, chunk_index, total_frames_encoded);
+                latency.frame_encoded(captured_at, rx.queued_frames());
+                if let Some(frame_log) = frame_log.as_mut() {
+                    frame_log.append(total_frames_encoded - 1, chunk_index, captured_at, &metadata);
+                }
 
                 // Log every second worth of frames
                 if total_frames_encoded % fps as u64 == 0 {
@@ -1135,32 +2409,440 @@ pub async fn process_frames_chunked(
         }
     }
 
+    // Mux any audio still queued into the last chunk
+    #[cfg(not(target_os = "macos"))]
+    if let Some(ref mut audio_rx) = audio_rx {
+        while let Ok(sample) = audio_rx.try_recv() {
+            current_encoder.encode_audio(&sample)?;
+        }
+    }
+
     // Finish the last chunk
     log::info!("Finishing final chunk {} with {} frames", chunk_index, frames_in_current_chunk);
     let output = current_encoder.finish()?;
+    let output = finalize_chunk(&db, encryption_key.as_ref(), &on_chunk_finished, chunk_index, output).await;
     chunk_outputs.push(output);
 
+    if let Some(frame_dump) = frame_dump {
+        // Waits for the dump's writer thread to empty its queue
+        if let Err(e) = tokio::task::spawn_blocking(move || frame_dump.finish()).await {
+            log::error!("Frame dump writer failed: {}", e);
+        }
+    }
+
     log::info!("===== ENCODING COMPLETE =====");
     log::info!("Total frames encoded: {}", total_frames_encoded);
     log::info!("Chunks created: {}", chunk_outputs.len());
-    log::info!("Expected frames per chunk: {}", frames_per_chunk);
+    log::info!("Chunk policy: {}", chunk_policy);
     log::info!("============================");
-    Ok(chunk_outputs)
+    Ok((chunk_outputs, latency.stats()))
 }
 
-/// Process audio samples (currently just logs them, can be extended to save audio file)
+/// Write captured audio to a WAV file next to the chunks.
+///
+/// The subprocess encoder has no audio input, so `merge-audio` muxes this file
+/// into the concatenated video afterwards. About once a second the arrival time
+/// of the current sample is appended to the timing sidecar next to it, which
+/// `merge-audio` uses to correct for audio clock drift.
 #[cfg(target_os = "macos")]
-pub async fn process_audio(mut rx: mpsc::Receiver<AudioSample>) -> Result<()> {
-    log::info!("Starting audio processing");
+pub async fn process_audio(mut rx: mpsc::Receiver<AudioSample>, wav_path: PathBuf) -> Result<()> {
+    use crate::audio::{append_timing_point, AudioTimingPoint, WavWriter, SESSION_TIMING_FILE};
 
+    log::info!("Starting audio processing, writing to {}", wav_path.display());
+    let timing_path = wav_path.with_file_name(SESSION_TIMING_FILE);
+    let mut timing_failed = false;
+
+    let mut writer: Option<WavWriter> = None;
+    let mut writer_failed = false;
+    let mut skipped_rate: Option<u32> = None;
     let mut sample_count = 0u64;
-    while let Some(_sample) = rx.recv().await {
+
+    while let Some(sample) = rx.recv().await {
         sample_count += 1;
+        if writer_failed {
+            continue;
+        }
+
+        if writer.is_none() {
+            match WavWriter::open_or_create(&wav_path, sample.sample_rate, sample.channels) {
+                Ok(w) => writer = Some(w),
+                Err(e) => {
+                    log::warn!("Could not write {}: {}. Audio will not be saved.", wav_path.display(), e);
+                    println!("⚠️  Audio will not be saved: {}", e);
+                    writer_failed = true;
+                    continue;
+                }
+            }
+        }
+        let Some(w) = writer.as_mut() else { continue };
+
+        // A reconnect can land on a device at another rate; those samples don't fit the file
+        if sample.sample_rate != w.sample_rate() {
+            if skipped_rate != Some(sample.sample_rate) {
+                log::warn!(
+                    "Audio device switched to {} Hz but {} is {} Hz; dropping samples",
+                    sample.sample_rate,
+                    wav_path.display(),
+                    w.sample_rate()
+                );
+                skipped_rate = Some(sample.sample_rate);
+            }
+            continue;
+        }
+
+        let sample_index = w.frames_written();
+        if sample.channels == w.channels() {
+            w.write_samples(&sample.data)?;
+        } else {
+            w.write_samples(&crate::audio::convert_channels(&sample.data, sample.channels, w.channels()))?;
+        }
+
         if sample_count % 100 == 0 {
+            // Keep the header current so an interrupted recording is still readable
+            w.update_header()?;
             log::debug!("Received {} audio samples", sample_count);
+
+            let point = AudioTimingPoint { sample_index, captured_at: sample.captured_at };
+            if !timing_failed {
+                if let Err(e) = append_timing_point(&timing_path, &point) {
+                    log::warn!("Could not write {}: {}. Audio drift won't be corrected.", timing_path.display(), e);
+                    timing_failed = true;
+                }
+            }
         }
     }
 
+    if let Some(w) = writer {
+        w.finish()?;
+        log::info!("Audio saved to: {}", wav_path.display());
+    }
+
     log::info!("Audio processing finished, total samples: {}", sample_count);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn test_explicit_crf_skips_quality_clamps() {
+        let x264 = |rate_control| {
+            let mut opts = ffmpeg::Dictionary::new();
+            configure_encoder_options("libx264", rate_control, None, EncoderTuning::default(), 30, &mut opts);
+            ["crf", "qmin", "qmax", "crf_max"].map(|key| opts.get(key).map(str::to_string))
+        };
+
+        for crf in [0, 30, 51] {
+            assert_eq!(x264(RateControl::Crf(crf)), [Some(crf.to_string()), None, None, None]);
+        }
+        let [crf, qmin, qmax, crf_max] = x264(RateControl::Quality(5));
+        assert!(crf.is_some());
+        assert_eq!((qmin.as_deref(), qmax.as_deref(), crf_max.as_deref()), (Some("10"), Some("25"), Some("18")));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn test_encoder_fallback_chain_ends_with_software() {
+        for (codec, software) in [(Codec::H264, "libx264"), (Codec::Hevc, "libx265"), (Codec::Av1, "libsvtav1")] {
+            let list = get_encoder_priority_list(codec);
+            assert!(!list.is_empty());
+            assert!(list.iter().all(|e| e.name != "h264" && e.name != "hevc" && e.name != "av1"));
+            assert!(list.windows(2).all(|w| w[0].priority < w[1].priority));
+
+            let last = list.last().unwrap();
+            assert_eq!(last.name, software);
+            assert_eq!(last.encoder_type, EncoderType::Software);
+        }
+    }
+
+    #[test]
+    fn test_pinned_encoder_never_falls_back() {
+        let list = || {
+            vec![
+                EncoderInfo { name: "h264_nvenc".to_string(), encoder_type: EncoderType::HardwareGpu, priority: 0 },
+                EncoderInfo { name: "libx264".to_string(), encoder_type: EncoderType::Software, priority: 10 },
+            ]
+        };
+        let names = |encoders: Vec<EncoderInfo>| encoders.into_iter().map(|e| e.name).collect::<Vec<_>>();
+        let everything = |_: &str| true;
+        let no_gpu = |_: &str| false;
+
+        assert_eq!(names(select_encoders(list(), &EncoderChoice::Auto, everything, everything).unwrap()), ["h264_nvenc", "libx264"]);
+        assert_eq!(names(select_encoders(list(), &EncoderChoice::SoftwareOnly, everything, everything).unwrap()), ["libx264"]);
+
+        let pinned = EncoderChoice::Named("libx264".to_string());
+        assert_eq!(names(select_encoders(list(), &pinned, everything, no_gpu).unwrap()), ["libx264"]);
+
+        // Missing or unknown is an error, not a quiet switch to another encoder
+        let nvenc = EncoderChoice::Named("h264_nvenc".to_string());
+        assert!(matches!(
+            select_encoders(list(), &nvenc, |name| name == "libx264", everything),
+            Err(ScreenRecError::HardwareEncoderUnavailable(_))
+        ));
+        let unknown = EncoderChoice::Named("libx265".to_string());
+        assert!(matches!(select_encoders(list(), &unknown, everything, everything), Err(ScreenRecError::InvalidParameter(_))));
+
+        // Registered but dead: skipped up front, and an error when pinned
+        assert_eq!(names(select_encoders(list(), &EncoderChoice::Auto, everything, no_gpu).unwrap()), ["libx264"]);
+        assert!(matches!(
+            select_encoders(list(), &nvenc, everything, no_gpu),
+            Err(ScreenRecError::HardwareEncoderUnavailable(_))
+        ));
+    }
+
+    #[test]
+    fn test_hardware_probe_runs_once() {
+        let runs = std::sync::atomic::AtomicUsize::new(0);
+        let probe = || {
+            runs.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            false
+        };
+        assert!(!hardware_encoder_works("test_probe_once", probe));
+        assert!(!hardware_encoder_works("test_probe_once", probe));
+        assert_eq!(runs.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert!(hardware_probe_results().contains(&("test_probe_once".to_string(), false)));
+    }
+
+    #[test]
+    fn test_encode_size_fits_encoder_limit() {
+        let tuning = EncoderTuning::default();
+        assert_eq!(encode_size("h264_videotoolbox", 6016, 3384, tuning), (4096, 2304));
+        assert_eq!(encode_size("hevc_nvenc", 6016, 3384, tuning), (6016, 3384));
+        assert_eq!(encode_size("libx264", 6016, 3384, tuning), (6016, 3384));
+
+        let capped = EncoderTuning { max_dimension: Some(1920), ..tuning };
+        assert_eq!(encode_size("libx264", 2560, 1440, capped), (1920, 1080));
+        assert_eq!(encode_size("h264_nvenc", 6016, 3384, capped), (1920, 1080));
+    }
+
+    #[test]
+    fn test_crf_skips_bitrate_only_encoders() {
+        let encoder = |name: &str, encoder_type| EncoderInfo { name: name.to_string(), encoder_type, priority: 0 };
+        let names = |encoders: Vec<EncoderInfo>| encoders.into_iter().map(|e| e.name).collect::<Vec<_>>();
+        let mac = || vec![encoder("h264_videotoolbox", EncoderType::HardwareGpu), encoder("libx264", EncoderType::Software)];
+
+        assert_eq!(encoders_for_rate_control(mac(), RateControl::Quality(8)).unwrap().len(), 2);
+        assert_eq!(names(encoders_for_rate_control(mac(), RateControl::Crf(16)).unwrap()), ["libx264"]);
+
+        let only_hardware = vec![encoder("hevc_videotoolbox", EncoderType::HardwareGpu)];
+        assert!(matches!(
+            encoders_for_rate_control(only_hardware, RateControl::Crf(16)),
+            Err(ScreenRecError::InvalidParameter(_))
+        ));
+
+        // SVT-AV1 gets the same CRF stretched over its 0-63 scale
+        assert_eq!([0, 23, 51].map(svtav1_crf), [0, 28, 63]);
+        assert!(supports_crf("libsvtav1"));
+        assert_eq!(native_preset("libsvtav1", Preset::Medium), Some(("preset", "8".to_string())));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn test_linear_resampler_keeps_duration_and_level() {
+        let mut resampler = LinearResampler::new(44_100, 48_000, 1);
+        let mut output = Vec::new();
+
+        // One second of a constant signal, delivered in capture-sized blocks
+        for _ in 0..100 {
+            resampler.process(&[0.5; 441], &mut output);
+        }
+
+        assert!((output.len() as i64 - 48_000).abs() <= 1, "got {} samples", output.len());
+        // Interpolating a constant signal must not ramp in from silence
+        assert!(output.iter().all(|s| (s - 0.5).abs() < 1e-6));
+
+        // Stereo frames stay interleaved, each channel interpolated on its own
+        let mut resampler = LinearResampler::new(44_100, 48_000, 2);
+        let mut output = Vec::new();
+        resampler.process(&[0.25, -0.75].repeat(441), &mut output);
+        assert_eq!(output.len() & 1, 0);
+        assert!(output.chunks_exact(2).all(|frame| frame == [0.25, -0.75]));
+    }
+
+    #[test]
+    fn test_quality_presets_bundle_codec_and_keyframes() {
+        assert_eq!(EncoderTuning::default().gop_size(30), 60);
+
+        let screen = QualityProfile::for_preset(QualityPreset::Screen);
+        assert_eq!(screen.codec, Codec::H264);
+        assert_eq!(screen.tuning.gop_size(30), 300);
+        assert_eq!(screen.tuning.tune, Some("stillimage"));
+
+        let archival = QualityProfile::for_preset(QualityPreset::Archival);
+        assert_eq!(archival.codec, Codec::Hevc);
+        assert_eq!(archival.preset, Preset::Slow);
+        assert!(archival.rate_control.quality() > screen.rate_control.quality());
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn test_keyframe_interval_sets_the_gop() {
+        let gop = |encoder_name, tuning| {
+            let mut opts = ffmpeg::Dictionary::new();
+            configure_encoder_options(encoder_name, RateControl::Quality(5), None, tuning, 30, &mut opts);
+            ["g", "keyint_min"].map(|key| opts.get(key).map(str::to_string))
+        };
+        let every = |seconds| EncoderTuning { keyframe_interval: Some(seconds), ..EncoderTuning::default() };
+
+        assert_eq!(every(5).gop_size(24), 120);
+        assert_eq!(gop("libx264", EncoderTuning::default()), [Some("60".into()), Some("60".into())]);
+        assert_eq!(gop("libx264", every(1)), [Some("30".into()), Some("30".into())]);
+        // Media Foundation keeps its 1s default unless an interval is given
+        assert_eq!(gop("h264_mf", EncoderTuning::default())[0].as_deref(), Some("30"));
+        assert_eq!(gop("h264_mf", every(4))[0].as_deref(), Some("120"));
+
+        // --keyframe-interval beats the preset's, the way `record` merges them
+        let screen = QualityProfile::for_preset(QualityPreset::Screen).tuning;
+        let overridden = EncoderTuning { keyframe_interval: Some(1).or(screen.keyframe_interval), ..screen };
+        assert_eq!(gop("libx264", screen)[0].as_deref(), Some("300"));
+        assert_eq!(gop("libx264", overridden)[0].as_deref(), Some("30"));
+    }
+
+    #[test]
+    fn test_parse_bitrate() {
+        assert_eq!(parse_bitrate("4M").unwrap(), 4_000_000);
+        assert_eq!(parse_bitrate("2500k").unwrap(), 2_500_000);
+        assert_eq!(parse_bitrate("1.5M").unwrap(), 1_500_000);
+        assert_eq!(parse_bitrate("800000").unwrap(), 800_000);
+
+        for bad in ["4Mbps", "", "M", "0", "-2M", "fast"] {
+            assert!(
+                matches!(parse_bitrate(bad), Err(ScreenRecError::InvalidParameter(_))),
+                "{} should be rejected",
+                bad
+            );
+        }
+    }
+
+    #[test]
+    fn test_chunk_policy_rolls_on_duration_or_size() {
+        let by_duration = ChunkPolicy::ByDuration(10);
+        assert!(!by_duration.is_full(299, 30, u64::MAX));
+        assert!(by_duration.is_full(300, 30, 0));
+
+        let by_size = ChunkPolicy::BySize(50 << 20);
+        assert!(!by_size.is_full(10_000, 30, (50 << 20) - 1));
+        assert!(by_size.is_full(1, 30, 50 << 20));
+        // A chunk with no frames yet is never rolled
+        assert!(!by_size.is_full(0, 30, u64::MAX));
+    }
+
+    #[test]
+    fn test_chunk_names_never_collide() {
+        use chrono::TimeZone;
+
+        let start = chrono::Local.with_ymd_and_hms(2025, 1, 14, 10, 30, 0).unwrap();
+        let default: ChunkNamePattern = "{ts}_{index}".parse().unwrap();
+        assert_eq!(default.render(start, 7, Some("demo")), "2025-01-14_10-30-00_0007.mp4");
+        let custom: ChunkNamePattern = "{task}-{index}".parse().unwrap();
+        assert_eq!(custom.render(start, 12, None), "always_on-0012.mp4");
+        assert!("{ts}_{chunk}".parse::<ChunkNamePattern>().is_err());
+        assert!("../{ts}".parse::<ChunkNamePattern>().is_err());
+
+        // Two chunks in the same second with no {index} get distinct files
+        let dir = tempfile::tempdir().unwrap();
+        let fixed: ChunkNamePattern = "{task}".parse().unwrap();
+        let first = next_chunk_path(dir.path(), &fixed, 0, Some("demo"));
+        assert_eq!(first, dir.path().join("demo.mp4"));
+        std::fs::write(&first, b"chunk").unwrap();
+        assert_eq!(next_chunk_path(dir.path(), &fixed, 1, Some("demo")), dir.path().join("demo-1.mp4"));
+    }
+
+    #[tokio::test]
+    async fn test_chunk_finished_hook_gets_each_final_chunk_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let finished = Arc::new(Mutex::new(Vec::new()));
+        let hook: ChunkFinished = {
+            let finished = Arc::clone(&finished);
+            Arc::new(move |path: &Path, index| finished.lock().unwrap().push((path.to_path_buf(), index)))
+        };
+        let hook = Some(hook);
+
+        let mut chunks = Vec::new();
+        for index in 0..2 {
+            let video_file = dir.path().join(format!("chunk{}.mp4", index));
+            std::fs::write(&video_file, b"chunk").unwrap();
+            let output = finalize_chunk(&None, None, &hook, index, RecordingOutput { video_file }).await;
+            assert!(integrity::sidecar_path(&output.video_file).exists(), "hook ran before the checksum");
+            chunks.push((output.video_file, index));
+        }
+        assert_eq!(*finished.lock().unwrap(), chunks);
+
+        // With --encrypt the hook is handed the .mp4.enc, never the deleted plaintext
+        let key_file = dir.path().join("key");
+        std::fs::write(&key_file, "01".repeat(32)).unwrap();
+        let key = EncryptionKey::load(Some(&key_file)).unwrap();
+        let video_file = dir.path().join("chunk2.mp4");
+        std::fs::write(&video_file, b"chunk").unwrap();
+        let output = finalize_chunk(&None, Some(&key), &hook, 2, RecordingOutput { video_file }).await;
+        assert_eq!(output.video_file, dir.path().join("chunk2.mp4.enc"));
+        assert_eq!(finished.lock().unwrap().last(), Some(&(output.video_file, 2)));
+        assert_eq!(finished.lock().unwrap().len(), 3);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn test_every_chunk_starts_on_a_keyframe() {
+        // Checked with ffprobe, like concat does; skipped where FFmpeg isn't installed
+        let ffprobe = crate::ffmpeg_utils::find_ffprobe_binary("ffmpeg");
+        if std::process::Command::new(&ffprobe).arg("-version").output().is_err() {
+            eprintln!("⚠️  Skipping chunk keyframe check: ffprobe not on PATH");
+            return;
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let (width, height, fps) = (64, 48, 10);
+        let mut pts_offset = 0;
+
+        for chunk in 0..2 {
+            let path = dir.path().join(format!("chunk{}.mp4", chunk));
+            let mut encoder = VideoEncoder::new_with_pts_offset(
+                &path,
+                width,
+                height,
+                fps,
+                RateControl::Quality(5),
+                Codec::H264,
+                None,
+                EncoderTuning::default(),
+                &EncoderChoice::SoftwareOnly,
+                ScaleFilter::Bilinear,
+                None,
+                pts_offset,
+                None::<fn(&str)>,
+            )
+            .unwrap();
+
+            for i in 0..15 {
+                let frame = Frame {
+                    data: vec![(i * 16) as u8; width * height * 3],
+                    width,
+                    height,
+                    timestamp: std::time::Duration::ZERO,
+                    captured_at: chrono::Utc::now(),
+                    display_index: 0,
+                };
+                encoder.encode_frame(frame, i == 0).unwrap();
+            }
+            pts_offset = encoder.get_next_pts();
+            let output = encoder.finish().unwrap();
+
+            // Flags of the first video packet, e.g. "K__" for a keyframe
+            let probe = std::process::Command::new(&ffprobe)
+                .args(["-v", "error", "-select_streams", "v:0", "-show_entries", "packet=flags"])
+                .args(["-read_intervals", "%+#1", "-of", "csv=p=0"])
+                .arg(&output.video_file)
+                .output()
+                .unwrap();
+            let flags = String::from_utf8_lossy(&probe.stdout);
+            assert!(
+                flags.trim_start().starts_with('K'),
+                "chunk {} starts with packet flags {:?}",
+                chunk,
+                flags
+            );
+        }
+    }
+}
@@ -6,8 +6,14 @@
 
 #[cfg(target_os = "macos")]
 use crate::capture::Frame;
-use crate::encoder::{EncoderInfo, EncoderType, FrameMetadata, RecordingOutput};
+use crate::cli::{Codec, Colorspace, Mp4Layout, Preset, ScaleFilter};
+use crate::encoder::{
+    encode_size, encoders_for_rate_control, hardware_encoder_works, native_preset, select_encoders, svtav1_crf,
+    warn_if_software_av1, EncoderChoice, EncoderInfo, EncoderTuning, EncoderType, FrameMetadata, RateControl,
+    RecordingOutput,
+};
 use crate::error::{Result, ScreenRecError};
+use crate::scaling::scale_and_pad_frame;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::{Child, Command, Stdio};
@@ -23,6 +29,7 @@ pub struct SubprocessEncoder {
     frame_count: u64,
     pts_offset: i64,
     gop_size: u32,
+    scale_filter: ScaleFilter,
     #[allow(dead_code)]
     encoder_info: EncoderInfo,
     #[allow(dead_code)]
@@ -35,10 +42,16 @@ impl SubprocessEncoder {
         width: usize,
         height: usize,
         fps: u32,
-        quality: u8,
+        rate_control: RateControl,
+        codec: Codec,
+        preset: Option<Preset>,
+        tuning: EncoderTuning,
+        encoder_choice: &EncoderChoice,
+        scale_filter: ScaleFilter,
         pts_offset: i64,
         on_chunk_created: Option<F>,
         ffmpeg_path: &str,
+        mp4_layout: Mp4Layout,
     ) -> Result<Self>
     where
         F: FnOnce(&str),
@@ -60,11 +73,11 @@ impl SubprocessEncoder {
         };
 
         // Get available encoders
-        let available_encoders = get_available_encoders(ffmpeg_path)?;
+        let available_encoders = encoders_for_rate_control(get_available_encoders(ffmpeg_path, codec, encoder_choice)?, rate_control)?;
         if available_encoders.is_empty() {
-            return Err(ScreenRecError::EncodingError(
-                "No H.264 encoders available on this system".to_string()
-            ));
+            return Err(ScreenRecError::EncodingError(format!(
+                "No {} encoders available on this system", codec
+            )));
         }
 
         // Try each encoder in priority order
@@ -77,6 +90,7 @@ impl SubprocessEncoder {
                 encoder_info.priority
             );
 
+            let (width, height) = encode_size(&encoder_info.name, width, height, tuning);
             match spawn_ffmpeg_encoder(
                 ffmpeg_path,
                 &encoder_info.name,
@@ -84,13 +98,18 @@ impl SubprocessEncoder {
                 width,
                 height,
                 fps,
-                quality,
+                rate_control,
+                preset,
+                tuning,
+                mp4_layout,
             ) {
                 Ok((process, stdin)) => {
                     log::info!("✓ Successfully initialized encoder: {} ({:?})",
                               encoder_info.name, encoder_info.encoder_type);
+                    warn_if_software_av1(&encoder_info.name);
 
-                    let gop_size = fps * 2; // GOP size is 2 seconds
+                    // Keyframes are read off the frame count, so this must match -g
+                    let gop_size = tuning.gop_size(fps);
 
                     // Call callback if provided
                     if let Some(callback) = on_chunk_created {
@@ -109,6 +128,7 @@ impl SubprocessEncoder {
                         frame_count: 0,
                         pts_offset,
                         gop_size,
+                        scale_filter,
                         encoder_info: encoder_info.clone(),
                         ffmpeg_path: ffmpeg_path.to_string(),
                     });
@@ -125,8 +145,14 @@ impl SubprocessEncoder {
         }))
     }
 
-    pub fn encode_frame(&mut self, frame: Frame) -> Result<FrameMetadata> {
+    /// Encode one frame. FFmpeg is spawned to make the first frame a keyframe;
+    /// later frames can't be forced through the pipe, so `force_keyframe` only
+    /// matters for the first one.
+    pub fn encode_frame(&mut self, frame: Frame, force_keyframe: bool) -> Result<FrameMetadata> {
         let Frame { data, width, height, display_index, .. } = frame;
+        if force_keyframe && self.frame_count > 0 {
+            log::debug!("Can't force a keyframe mid-chunk with the subprocess encoder (frame {})", self.frame_count);
+        }
 
         // If frame dimensions don't match encoder dimensions, we need to scale/pad
         let processed_data = if width != self.width || height != self.height {
@@ -137,7 +163,7 @@ impl SubprocessEncoder {
                 self.width,
                 self.height
             );
-            scale_and_pad_frame(&data, width, height, self.width, self.height)?
+            scale_and_pad_frame(&data, width, height, self.width, self.height, self.scale_filter)?
         } else {
             data
         };
@@ -194,6 +220,17 @@ impl SubprocessEncoder {
         self.pts_offset + (self.frame_count as i64)
     }
 
+    /// Size of the chunk file so far. FFmpeg writes as it encodes, so this trails
+    /// the frames sent by the encoder's lookahead.
+    pub fn bytes_written(&self) -> u64 {
+        std::fs::metadata(&self.output_path).map(|m| m.len()).unwrap_or(0)
+    }
+
+    /// FFmpeg name of the encoder in use, e.g. h264_videotoolbox
+    pub fn encoder_name(&self) -> &str {
+        &self.encoder_info.name
+    }
+
     pub fn finish(mut self) -> Result<RecordingOutput> {
         log::info!("Finishing encoding, total frames: {}", self.frame_count);
 
@@ -239,28 +276,32 @@ impl SubprocessEncoder {
 }
 
 /// Get platform-specific encoder priority list for macOS
-fn get_encoder_priority_list() -> Vec<EncoderInfo> {
-    vec![
-        EncoderInfo {
-            name: "h264_videotoolbox".to_string(),
+fn get_encoder_priority_list(codec: Codec) -> Vec<EncoderInfo> {
+    let (hardware, software) = match codec {
+        Codec::H264 => (Some("h264_videotoolbox"), "libx264"),
+        Codec::Hevc => (Some("hevc_videotoolbox"), "libx265"),
+        // VideoToolbox has no AV1 encoder
+        Codec::Av1 => (None, "libsvtav1"),
+    };
+
+    let mut encoders = Vec::new();
+    if let Some(hardware) = hardware {
+        encoders.push(EncoderInfo {
+            name: hardware.to_string(),
             encoder_type: EncoderType::HardwareGpu,
             priority: 0,
-        },
-        EncoderInfo {
-            name: "libx264".to_string(),
-            encoder_type: EncoderType::Software,
-            priority: 10,
-        },
-        EncoderInfo {
-            name: "h264".to_string(),
-            encoder_type: EncoderType::Software,
-            priority: 11,
-        },
-    ]
+        });
+    }
+    encoders.push(EncoderInfo {
+        name: software.to_string(),
+        encoder_type: EncoderType::Software,
+        priority: 10,
+    });
+    encoders
 }
 
-/// Get available encoders by checking FFmpeg
-fn get_available_encoders(ffmpeg_path: &str) -> Result<Vec<EncoderInfo>> {
+/// Get available encoders by checking FFmpeg, limited by `choice`
+pub(crate) fn get_available_encoders(ffmpeg_path: &str, codec: Codec, choice: &EncoderChoice) -> Result<Vec<EncoderInfo>> {
     log::info!("Detecting available encoders...");
 
     let output = Command::new(ffmpeg_path)
@@ -277,19 +318,14 @@ fn get_available_encoders(ffmpeg_path: &str) -> Result<Vec<EncoderInfo>> {
     }
 
     let output_str = String::from_utf8_lossy(&output.stdout);
-    let priority_list = get_encoder_priority_list();
-    let mut available = Vec::new();
-
-    for encoder_info in priority_list {
-        // Check if encoder is in the output
-        if output_str.contains(&format!(" {} ", encoder_info.name))
-            || output_str.contains(&format!("{}  ", encoder_info.name)) {
-            log::debug!("Encoder '{}' is available", encoder_info.name);
-            available.push(encoder_info);
-        } else {
-            log::debug!("Encoder '{}' not available", encoder_info.name);
-        }
-    }
+
+    // Check if encoder is in the output
+    let available = select_encoders(
+        get_encoder_priority_list(codec),
+        choice,
+        |name| output_str.contains(&format!(" {} ", name)) || output_str.contains(&format!("{}  ", name)),
+        |name| hardware_encoder_works(name, || test_encode(ffmpeg_path, name)),
+    )?;
 
     log::info!(
         "Available encoders: {:?}",
@@ -299,6 +335,45 @@ fn get_available_encoders(ffmpeg_path: &str) -> Result<Vec<EncoderInfo>> {
     Ok(available)
 }
 
+/// Pipe a few blank frames through `encoder_name` and throw the result away
+fn test_encode(ffmpeg_path: &str, encoder_name: &str) -> bool {
+    let size = crate::encoder::PROBE_SIZE;
+    let child = Command::new(ffmpeg_path)
+        .args(["-hide_banner", "-loglevel", "error", "-f", "rawvideo", "-pixel_format", "rgb24"])
+        .args(["-video_size", &format!("{}x{}", size, size), "-framerate", "30", "-i", "pipe:0"])
+        .args(["-c:v", encoder_name, "-f", "null", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn();
+    let mut child = match child {
+        Ok(child) => child,
+        Err(e) => {
+            log::debug!("Test encode with '{}' failed to start: {}", encoder_name, e);
+            return false;
+        }
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let frames = vec![0u8; (size * size * 3) as usize * crate::encoder::PROBE_FRAMES];
+        // A write error means FFmpeg already gave up; its exit status says why
+        let _ = stdin.write_all(&frames);
+    }
+
+    match child.wait_with_output() {
+        Ok(output) if output.status.success() => true,
+        Ok(output) => {
+            log::debug!(
+                "Test encode with '{}' failed: {}",
+                encoder_name,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            false
+        }
+        Err(_) => false,
+    }
+}
+
 /// Spawn FFmpeg process for encoding
 fn spawn_ffmpeg_encoder(
     ffmpeg_path: &str,
@@ -307,9 +382,12 @@ fn spawn_ffmpeg_encoder(
     width: usize,
     height: usize,
     fps: u32,
-    quality: u8,
+    rate_control: RateControl,
+    preset: Option<Preset>,
+    tuning: EncoderTuning,
+    mp4_layout: Mp4Layout,
 ) -> Result<(Child, std::io::BufWriter<std::process::ChildStdin>)> {
-    let gop_size = fps * 2;
+    let gop_size = tuning.gop_size(fps);
 
     // Build encoder-specific arguments
     let mut args = vec![
@@ -329,15 +407,15 @@ fn spawn_ffmpeg_encoder(
         "cfr".to_string(), // Force constant frame rate - duplicate/drop frames as needed
         "-r".to_string(),
         fps.to_string(), // Output frame rate
+        "-force_key_frames".to_string(),
+        "expr:eq(n,0)".to_string(), // Every chunk opens on a keyframe, so concat can stream-copy
     ];
 
-    // Add encoder-specific quality parameters
+    // Add encoder-specific rate control parameters
     match encoder_name {
         "h264_videotoolbox" => {
-            let bitrate = quality_to_bitrate(quality, width, height, fps);
+            args.extend(videotoolbox_bitrate_args(rate_control, width, height, fps));
             args.extend_from_slice(&[
-                "-b:v".to_string(),
-                bitrate,
                 "-profile:v".to_string(),
                 "high".to_string(),
                 "-allow_sw".to_string(),
@@ -346,11 +424,22 @@ fn spawn_ffmpeg_encoder(
                 gop_size.to_string(),
             ]);
         }
+        "libx264" if tuning.lossless => {
+            // Lossless is far more work per frame, so keep up with the fastest preset
+            args.extend_from_slice(&[
+                "-qp".to_string(),
+                "0".to_string(),
+                "-preset".to_string(),
+                "ultrafast".to_string(),
+                "-profile:v".to_string(),
+                "high444".to_string(),
+                "-g".to_string(),
+                gop_size.to_string(),
+            ]);
+        }
         "libx264" => {
-            let crf = quality_to_crf(quality);
+            args.extend(software_rate_args(rate_control));
             args.extend_from_slice(&[
-                "-crf".to_string(),
-                crf.to_string(),
                 "-preset".to_string(),
                 "slow".to_string(),
                 "-profile:v".to_string(),
@@ -358,13 +447,56 @@ fn spawn_ffmpeg_encoder(
                 "-g".to_string(),
                 gop_size.to_string(),
             ]);
+            if let Some(tune) = tuning.tune {
+                args.extend_from_slice(&["-tune".to_string(), tune.to_string()]);
+            }
+            if let Some(bframes) = tuning.bframes {
+                args.extend_from_slice(&["-bf".to_string(), bframes.to_string()]);
+            }
+        }
+        "hevc_videotoolbox" => {
+            args.extend(videotoolbox_bitrate_args(rate_control, width, height, fps));
+            args.extend_from_slice(&[
+                "-profile:v".to_string(),
+                "main".to_string(),
+                "-allow_sw".to_string(),
+                "1".to_string(),
+                "-g".to_string(),
+                gop_size.to_string(),
+                "-tag:v".to_string(),
+                "hvc1".to_string(), // QuickTime only plays HEVC tagged as hvc1
+            ]);
+        }
+        "libx265" => {
+            args.extend(software_rate_args(rate_control));
+            args.extend_from_slice(&[
+                "-preset".to_string(),
+                "medium".to_string(),
+                "-x265-params".to_string(),
+                format!(
+                    "keyint={}:min-keyint={}:bframes={}:scenecut=0:log-level=error",
+                    gop_size,
+                    gop_size,
+                    tuning.bframes.unwrap_or(0)
+                ),
+                "-tag:v".to_string(),
+                "hvc1".to_string(),
+            ]);
+        }
+        "libsvtav1" => {
+            args.extend(svtav1_rate_args(rate_control));
+            args.extend_from_slice(&[
+                // Preset 10 keeps a CPU close to real time at 1080p; lower is slower and smaller
+                "-preset".to_string(),
+                "10".to_string(),
+                "-g".to_string(),
+                gop_size.to_string(),
+            ]);
         }
         _ => {
             // Generic fallback
-            let crf = quality_to_crf(quality);
+            args.extend(software_rate_args(rate_control));
             args.extend_from_slice(&[
-                "-crf".to_string(),
-                crf.to_string(),
                 "-preset".to_string(),
                 "medium".to_string(),
                 "-g".to_string(),
@@ -373,12 +505,24 @@ fn spawn_ffmpeg_encoder(
         }
     }
 
-    // Add output format parameters
+    // Override the encoder's default preset when one was requested
+    if let Some(preset) = preset {
+        match native_preset(encoder_name, preset) {
+            Some((key, value)) => set_arg(&mut args, key, value),
+            None => log::debug!("Encoder '{}' has no presets, ignoring --preset {}", encoder_name, preset),
+        }
+    }
+
+    // FFmpeg converts the RGB input itself; use the requested matrix and tag it
+    args.extend(color_args(tuning.colorspace));
+
+    // Add output format parameters (see Mp4Layout for fragmented vs regular)
     args.extend_from_slice(&[
         "-pix_fmt".to_string(),
-        "yuv420p".to_string(),
+        // 4:4:4 keeps lossless chroma exact
+        if tuning.lossless { "yuv444p" } else { "yuv420p" }.to_string(),
         "-movflags".to_string(),
-        "frag_keyframe+empty_moov".to_string(),
+        movflags(mp4_layout).to_string(),
         "-f".to_string(),
         "mp4".to_string(),
         output_path.to_str().unwrap().to_string(),
@@ -405,6 +549,83 @@ fn spawn_ffmpeg_encoder(
     Ok((child, buffered_stdin))
 }
 
+/// Convert with `colorspace`'s matrix (limited range, FFmpeg's default) and tag
+/// the stream with it so players convert back the same way
+fn color_args(colorspace: Colorspace) -> Vec<String> {
+    let (matrix, tag) = match colorspace {
+        Colorspace::Bt601 => ("bt601", "smpte170m"),
+        Colorspace::Bt709 => ("bt709", "bt709"),
+    };
+    vec![
+        "-vf".to_string(),
+        format!("scale=out_color_matrix={}:out_range=tv", matrix),
+        "-colorspace".to_string(),
+        tag.to_string(),
+        "-color_primaries".to_string(),
+        tag.to_string(),
+        "-color_trc".to_string(),
+        tag.to_string(),
+        "-color_range".to_string(),
+        "tv".to_string(),
+    ]
+}
+
+/// FFmpeg `-movflags` for a chunk layout
+fn movflags(layout: Mp4Layout) -> &'static str {
+    match layout {
+        Mp4Layout::Fragmented => "frag_keyframe+empty_moov",
+        Mp4Layout::Regular => "+faststart",
+    }
+}
+
+/// Set `-flag value`, replacing the value if the flag is already present
+fn set_arg(args: &mut Vec<String>, flag: &str, value: String) {
+    let flag = format!("-{}", flag);
+    match args.iter().position(|arg| *arg == flag) {
+        Some(i) if i + 1 < args.len() => args[i + 1] = value,
+        _ => args.extend([flag, value]),
+    }
+}
+
+/// VideoToolbox only does average bitrate, so quality mode is converted to a bitrate
+fn videotoolbox_bitrate_args(rate_control: RateControl, width: usize, height: usize, fps: u32) -> Vec<String> {
+    let bitrate = match rate_control {
+        RateControl::Quality(quality) => quality_to_bitrate(quality, width, height, fps),
+        RateControl::Bitrate(bitrate) => bitrate.to_string(),
+        // Never selected for --crf (see supports_crf)
+        RateControl::Crf(_) => return Vec::new(),
+    };
+    vec!["-b:v".to_string(), bitrate]
+}
+
+/// CRF for quality mode (or as given with --crf), or average bitrate capped with maxrate/bufsize
+fn software_rate_args(rate_control: RateControl) -> Vec<String> {
+    match rate_control {
+        RateControl::Quality(quality) => {
+            vec!["-crf".to_string(), quality_to_crf(quality).to_string()]
+        }
+        RateControl::Crf(crf) => vec!["-crf".to_string(), crf.to_string()],
+        RateControl::Bitrate(bitrate) => vec![
+            "-b:v".to_string(),
+            bitrate.to_string(),
+            "-maxrate".to_string(),
+            bitrate.to_string(),
+            "-bufsize".to_string(),
+            (bitrate * 2).to_string(),
+        ],
+    }
+}
+
+/// SVT-AV1 takes a CRF on its own 0-63 scale, and a plain target bitrate
+fn svtav1_rate_args(rate_control: RateControl) -> Vec<String> {
+    let crf = match rate_control {
+        RateControl::Quality(quality) => quality_to_crf(quality),
+        RateControl::Crf(crf) => crf,
+        RateControl::Bitrate(bitrate) => return vec!["-b:v".to_string(), bitrate.to_string()],
+    };
+    vec!["-crf".to_string(), svtav1_crf(crf).to_string()]
+}
+
 /// Convert quality (1-10) to bitrate for VideoToolbox
 /// Quality 1 = 2 Mbps, Quality 10 = 20 Mbps (scales with resolution)
 fn quality_to_bitrate(quality: u8, width: usize, height: usize, fps: u32) -> String {
@@ -426,58 +647,3 @@ fn quality_to_crf(quality: u8) -> u8 {
     let mapped = 42 - q * 3;
     mapped.clamp(12, 35) as u8
 }
-
-/// Scale and pad frame to target dimensions (center with black bars)
-fn scale_and_pad_frame(
-    rgb: &[u8],
-    src_width: usize,
-    src_height: usize,
-    target_width: usize,
-    target_height: usize,
-) -> Result<Vec<u8>> {
-    // Calculate scaling to fit within target while preserving aspect ratio
-    let width_ratio = target_width as f32 / src_width as f32;
-    let height_ratio = target_height as f32 / src_height as f32;
-    let scale_ratio = width_ratio.min(height_ratio);
-
-    let scaled_width = (src_width as f32 * scale_ratio) as usize;
-    let scaled_height = (src_height as f32 * scale_ratio) as usize;
-
-    // Calculate padding to center the scaled image
-    let pad_x = (target_width - scaled_width) / 2;
-    let pad_y = (target_height - scaled_height) / 2;
-
-    // Create black canvas
-    let mut result = vec![0u8; target_width * target_height * 3];
-
-    // Simple nearest-neighbor scaling and placement
-    for target_y in 0..target_height {
-        for target_x in 0..target_width {
-            // Check if we're in the scaled image area
-            if target_x >= pad_x
-                && target_x < pad_x + scaled_width
-                && target_y >= pad_y
-                && target_y < pad_y + scaled_height
-            {
-                // Map to source coordinates
-                let src_x = ((target_x - pad_x) as f32 / scale_ratio) as usize;
-                let src_y = ((target_y - pad_y) as f32 / scale_ratio) as usize;
-
-                // Bounds check
-                if src_x < src_width && src_y < src_height {
-                    let src_idx = (src_y * src_width + src_x) * 3;
-                    let dst_idx = (target_y * target_width + target_x) * 3;
-
-                    if src_idx + 2 < rgb.len() && dst_idx + 2 < result.len() {
-                        result[dst_idx] = rgb[src_idx]; // R
-                        result[dst_idx + 1] = rgb[src_idx + 1]; // G
-                        result[dst_idx + 2] = rgb[src_idx + 2]; // B
-                    }
-                }
-            }
-            // Else: leave as black (already initialized to 0)
-        }
-    }
-
-    Ok(result)
-}
@@ -0,0 +1,175 @@
+//! AES-256-GCM encryption of finished chunks (`--encrypt`)
+//!
+//! Once a chunk is finished it is read whole, encrypted under a fresh random
+//! 96-bit nonce and written next to itself as `<chunk>.mp4.enc`: the nonce
+//! followed by the ciphertext and its tag. The file is written under a temporary
+//! name, synced and renamed; only then is the plaintext deleted and the
+//! chunk's database row pointed at the encrypted file, with the nonce alongside.
+//! `omgrec decrypt --task-id <id>` turns a task's chunks back into `.mp4` files.
+//!
+//! Only the video chunks are encrypted; audio, interaction logs and the database
+//! are not. Chunks are encrypted in memory, so streaming encryption of very
+//! large chunks is left for later.
+
+use crate::error::{Result, ScreenRecError};
+use aes_gcm::aead::{AeadCore, AeadInPlace, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce, Tag};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Environment variable the key is read from when there is no `--key-file`
+pub const KEY_ENV: &str = "OMGREC_ENCRYPTION_KEY";
+/// Extension added to encrypted chunks (`.mp4.enc`)
+pub const ENCRYPTED_EXTENSION: &str = "enc";
+const NONCE_BYTES: usize = 12;
+const TAG_BYTES: usize = 16;
+
+/// A 256-bit key, written as 64 hex digits in the key file or environment
+#[derive(Clone)]
+pub struct EncryptionKey([u8; 32]);
+
+impl std::fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EncryptionKey(..)")
+    }
+}
+
+impl EncryptionKey {
+    /// Read the key from `key_file`, or from `OMGREC_ENCRYPTION_KEY` without one
+    pub fn load(key_file: Option<&Path>) -> Result<Self> {
+        let hex = match key_file {
+            Some(path) => std::fs::read_to_string(path).map_err(|e| {
+                ScreenRecError::ConfigError(format!("Could not read key file {}: {}", path.display(), e))
+            })?,
+            None => std::env::var(KEY_ENV).map_err(|_| {
+                ScreenRecError::ConfigError(format!("Encryption needs a key: pass --key-file or set {}", KEY_ENV))
+            })?,
+        };
+        Self::from_hex(hex.trim())
+    }
+
+    fn from_hex(hex: &str) -> Result<Self> {
+        let invalid = || ScreenRecError::ConfigError("Encryption key must be 64 hex digits (32 bytes)".to_string());
+        if hex.len() != 64 || !hex.is_ascii() {
+            return Err(invalid());
+        }
+        let mut key = [0u8; 32];
+        for (byte, pair) in key.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            let pair = std::str::from_utf8(pair).map_err(|_| invalid())?;
+            *byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+        }
+        Ok(Self(key))
+    }
+
+    fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new(&self.0.into())
+    }
+}
+
+/// Whether `path` is an encrypted chunk (`.mp4.enc`)
+pub fn is_encrypted(path: &Path) -> bool {
+    path.extension().and_then(|ext| ext.to_str()) == Some(ENCRYPTED_EXTENSION)
+}
+
+/// `<chunk>.mp4.enc` for `<chunk>.mp4`
+pub fn encrypted_path(chunk: &Path) -> PathBuf {
+    let mut name = chunk.as_os_str().to_owned();
+    name.push(".");
+    name.push(ENCRYPTED_EXTENSION);
+    PathBuf::from(name)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Write `parts` to `path` through a `.partial` file that is synced and then
+/// renamed, so a crash or full disk never leaves a truncated `path` behind
+fn write_atomically(path: &Path, parts: &[&[u8]]) -> Result<()> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+
+    let written = (|| {
+        let mut file = std::fs::File::create(&partial)?;
+        for part in parts {
+            file.write_all(part)?;
+        }
+        file.sync_all()
+    })();
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&partial);
+        return Err(e.into());
+    }
+    std::fs::rename(&partial, path)?;
+    Ok(())
+}
+
+/// Encrypt `chunk` to `<chunk>.enc` and delete the plaintext once the encrypted
+/// file is safely on disk. Returns the encrypted file and the nonce in hex.
+pub fn encrypt_chunk(chunk: &Path, key: &EncryptionKey) -> Result<(PathBuf, String)> {
+    // Encrypted in place, so only one copy of the chunk is held in memory
+    let mut buffer = std::fs::read(chunk)?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let tag = key
+        .cipher()
+        .encrypt_in_place_detached(&nonce, b"", &mut buffer)
+        .map_err(|_| ScreenRecError::EncryptionError(format!("Failed to encrypt {}", chunk.display())))?;
+
+    let encrypted = encrypted_path(chunk);
+    write_atomically(&encrypted, &[&nonce, &buffer, &tag])?;
+    std::fs::remove_file(chunk)?;
+    Ok((encrypted, to_hex(&nonce)))
+}
+
+/// Decrypt a `.mp4.enc` chunk back to `.mp4` and delete the encrypted file.
+/// Returns the decrypted file and the nonce it was encrypted under, in hex.
+pub fn decrypt_chunk(encrypted: &Path, key: &EncryptionKey) -> Result<(PathBuf, String)> {
+    let mut contents = std::fs::read(encrypted)?;
+    if contents.len() < NONCE_BYTES + TAG_BYTES {
+        return Err(ScreenRecError::EncryptionError(format!("{} is too short to be encrypted", encrypted.display())));
+    }
+    let tag = Tag::clone_from_slice(&contents[contents.len() - TAG_BYTES..]);
+    contents.truncate(contents.len() - TAG_BYTES);
+    let nonce = Nonce::clone_from_slice(&contents[..NONCE_BYTES]);
+    let body = &mut contents[NONCE_BYTES..];
+    key.cipher().decrypt_in_place_detached(&nonce, b"", body, &tag).map_err(|_| {
+        ScreenRecError::EncryptionError(format!("Could not decrypt {} (wrong key or altered file)", encrypted.display()))
+    })?;
+
+    let decrypted = encrypted.with_extension("");
+    write_atomically(&decrypted, &[body])?;
+    std::fs::remove_file(encrypted)?;
+    Ok((decrypted, to_hex(&nonce)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_round_trips_only_with_its_key() {
+        assert!(EncryptionKey::from_hex("abcd").is_err());
+        assert!(EncryptionKey::from_hex(&"zz".repeat(32)).is_err());
+        let key = EncryptionKey::from_hex(&"01".repeat(32)).unwrap();
+        let wrong_key = EncryptionKey::from_hex(&"02".repeat(32)).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let chunk = dir.path().join("2025-01-14_10-30-00_0000.mp4");
+        std::fs::write(&chunk, b"not really an mp4").unwrap();
+
+        let (encrypted, nonce) = encrypt_chunk(&chunk, &key).unwrap();
+        assert_eq!(encrypted, dir.path().join("2025-01-14_10-30-00_0000.mp4.enc"));
+        assert!(is_encrypted(&encrypted) && !chunk.exists());
+        assert_eq!(nonce.len(), NONCE_BYTES * 2);
+
+        // A wrong key leaves the encrypted file alone
+        assert!(decrypt_chunk(&encrypted, &wrong_key).is_err());
+        assert!(encrypted.exists());
+
+        assert_eq!(decrypt_chunk(&encrypted, &key).unwrap(), (chunk.clone(), nonce));
+        assert_eq!(std::fs::read(&chunk).unwrap(), b"not really an mp4");
+        assert!(!encrypted.exists());
+        assert!(!dir.path().join("2025-01-14_10-30-00_0000.mp4.enc.partial").exists());
+    }
+}
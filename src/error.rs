@@ -47,6 +47,9 @@ pub enum ScreenRecError {
 
     #[error("Encoder failure during recording: {0}")]
     EncoderRuntimeFailure(String),
+
+    #[error("Encryption error: {0}")]
+    EncryptionError(String),
 }
 
 impl From<anyhow::Error> for ScreenRecError {
@@ -0,0 +1,245 @@
+//! Export recordings as animated GIF or APNG through FFmpeg
+//!
+//! GIFs use the two-pass palette approach: `palettegen` builds a 256-color
+//! palette tuned to the clip, then `paletteuse` maps every frame onto it. This
+//! avoids the banding and dithering noise of FFmpeg's generic GIF palette.
+
+use crate::cli::AnimationFormat;
+use crate::error::{Result, ScreenRecError};
+use crate::ffmpeg_utils;
+use std::path::Path;
+
+/// GIF frame delays are whole centiseconds and many viewers slow down anything
+/// faster, so higher rates only make the file bigger
+const MAX_GIF_FPS: u32 = 25;
+
+/// Convert `input` into an animated GIF or APNG at `output`.
+/// `width` scales the output (keeping aspect ratio); None keeps the source size.
+/// `start` and `duration` (seconds) cut a clip out of the input.
+pub fn export_animation(
+    ffmpeg_binary: &str,
+    input: &Path,
+    output: &Path,
+    format: AnimationFormat,
+    fps: u32,
+    width: Option<u32>,
+    start: Option<f64>,
+    duration: Option<f64>,
+) -> Result<()> {
+    if !input.exists() {
+        return Err(ScreenRecError::InvalidParameter(format!(
+            "Input file not found: {}",
+            input.display()
+        )));
+    }
+    if fps == 0 {
+        return Err(ScreenRecError::InvalidParameter(
+            "FPS must be greater than 0".to_string(),
+        ));
+    }
+    let fps = if format == AnimationFormat::Gif && fps > MAX_GIF_FPS {
+        log::warn!("GIFs play at most {}fps, using {} instead of {}", MAX_GIF_FPS, MAX_GIF_FPS, fps);
+        MAX_GIF_FPS
+    } else {
+        fps
+    };
+
+    // Check the clip against the source before spending time encoding
+    let ffprobe = ffmpeg_utils::find_ffprobe_binary(ffmpeg_binary);
+    let source_duration = ffmpeg_utils::probe_video_duration(&ffprobe, input)?;
+    let clip_duration = clip_duration(source_duration, start.unwrap_or(0.0), duration)?;
+
+    let filter = scale_filter(fps, width);
+    // Seek before -i so FFmpeg jumps straight to the clip
+    let mut input_args = trim_args(start, duration);
+    input_args.extend(["-i".into(), path_arg(input)?]);
+
+    match format {
+        AnimationFormat::Gif => {
+            let palette_path = std::env::temp_dir().join(format!(
+                "omgrec_palette_{}.png",
+                std::process::id()
+            ));
+
+            println!("🔄 [PROGRESS] Generating palette...");
+            let mut palette_args: Vec<String> = vec!["-y".into()];
+            palette_args.extend(input_args.iter().cloned());
+            palette_args.extend([
+                "-vf".into(),
+                format!("{},palettegen=stats_mode=diff", filter),
+                path_arg(&palette_path)?,
+            ]);
+
+            let result = run_ffmpeg(ffmpeg_binary, &palette_args, None, "Generating palette").and_then(|_| {
+                println!("🔄 [PROGRESS] Encoding GIF...");
+                let mut gif_args: Vec<String> = vec!["-y".into()];
+                gif_args.extend(input_args.iter().cloned());
+                gif_args.extend([
+                    "-i".into(),
+                    path_arg(&palette_path)?,
+                    "-lavfi".into(),
+                    format!(
+                        "{}[x];[x][1:v]paletteuse=dither=bayer:bayer_scale=5:diff_mode=rectangle",
+                        filter
+                    ),
+                    "-loop".into(),
+                    "0".into(),
+                    path_arg(output)?,
+                ]);
+                run_ffmpeg(ffmpeg_binary, &gif_args, clip_duration, "Encoding GIF")
+            });
+
+            let _ = std::fs::remove_file(&palette_path);
+            result?;
+        }
+        AnimationFormat::Apng => {
+            println!("🔄 [PROGRESS] Encoding APNG...");
+            let mut apng_args: Vec<String> = vec!["-y".into()];
+            apng_args.extend(input_args);
+            apng_args.extend([
+                "-vf".into(),
+                filter,
+                "-plays".into(),
+                "0".into(),
+                "-f".into(),
+                "apng".into(),
+                path_arg(output)?,
+            ]);
+            run_ffmpeg(ffmpeg_binary, &apng_args, clip_duration, "Encoding APNG")?;
+        }
+    }
+
+    validate_output(output)
+}
+
+/// Length of the requested clip, after checking it lies within the source.
+/// Without a known source length the request is taken as is.
+fn clip_duration(source_duration: Option<f64>, start: f64, duration: Option<f64>) -> Result<Option<f64>> {
+    if start < 0.0 {
+        return Err(ScreenRecError::InvalidParameter(
+            "Start must not be negative".to_string(),
+        ));
+    }
+    if duration.is_some_and(|duration| duration <= 0.0) {
+        return Err(ScreenRecError::InvalidParameter(
+            "Duration must be greater than 0".to_string(),
+        ));
+    }
+
+    let Some(source_duration) = source_duration else {
+        return Ok(duration);
+    };
+    if start >= source_duration {
+        return Err(ScreenRecError::InvalidParameter(format!(
+            "Start {:.1}s is past the end of the {:.1}s video",
+            start, source_duration
+        )));
+    }
+
+    let remaining = source_duration - start;
+    match duration {
+        // Allow for rounding in the probed duration
+        Some(duration) if duration > remaining + 0.05 => Err(ScreenRecError::InvalidParameter(format!(
+            "A {:.1}s clip from {:.1}s runs past the end of the {:.1}s video",
+            duration, start, source_duration
+        ))),
+        Some(duration) => Ok(Some(duration)),
+        None => Ok(Some(remaining)),
+    }
+}
+
+/// `-ss`/`-t` input options for the requested clip
+fn trim_args(start: Option<f64>, duration: Option<f64>) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(start) = start {
+        args.extend(["-ss".into(), format!("{:.3}", start)]);
+    }
+    if let Some(duration) = duration {
+        args.extend(["-t".into(), format!("{:.3}", duration)]);
+    }
+    args
+}
+
+/// fps + optional lanczos downscale, shared by both passes
+fn scale_filter(fps: u32, width: Option<u32>) -> String {
+    match width {
+        Some(width) => format!("fps={},scale={}:-1:flags=lanczos", fps, width),
+        None => format!("fps={}", fps),
+    }
+}
+
+fn path_arg(path: &Path) -> Result<String> {
+    path.to_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| ScreenRecError::InvalidParameter(format!("Invalid path: {}", path.display())))
+}
+
+/// Run FFmpeg, reporting progress against `clip_duration` seconds of output
+fn run_ffmpeg(ffmpeg_binary: &str, args: &[String], clip_duration: Option<f64>, label: &str) -> Result<()> {
+    // -progress must come before the output path, which is the last argument
+    let mut args = args.to_vec();
+    let output_arg = args.pop().unwrap_or_default();
+    args.extend(["-progress".into(), "pipe:1".into(), "-nostats".into(), output_arg]);
+
+    let result = ffmpeg_utils::run_with_progress(ffmpeg_binary, &args, clip_duration, label)?;
+
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        log::error!("FFmpeg stderr: {}", stderr);
+        return Err(ScreenRecError::EncodingError(format!(
+            "FFmpeg export failed: {}",
+            stderr
+        )));
+    }
+
+    Ok(())
+}
+
+/// Same sanity check as concat: the file must exist and not be suspiciously small
+pub fn validate_output(output: &Path) -> Result<()> {
+    if !output.exists() {
+        return Err(ScreenRecError::EncodingError(
+            "FFmpeg did not create output file".to_string(),
+        ));
+    }
+
+    let file_size = std::fs::metadata(output).map(|m| m.len()).unwrap_or(0);
+    if file_size < 1024 {
+        log::error!("Output file is only {} bytes, removing corrupted file", file_size);
+        std::fs::remove_file(output).ok();
+        return Err(ScreenRecError::EncodingError(format!(
+            "FFmpeg produced invalid output file ({} bytes)",
+            file_size
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scale_filter() {
+        assert_eq!(scale_filter(10, None), "fps=10");
+        assert_eq!(scale_filter(15, Some(640)), "fps=15,scale=640:-1:flags=lanczos");
+    }
+
+    #[test]
+    fn test_clip_must_fit_in_source() {
+        assert_eq!(clip_duration(Some(30.0), 0.0, None).unwrap(), Some(30.0));
+        assert_eq!(clip_duration(Some(30.0), 10.0, None).unwrap(), Some(20.0));
+        assert_eq!(clip_duration(Some(30.0), 10.0, Some(5.0)).unwrap(), Some(5.0));
+        assert_eq!(clip_duration(Some(30.0), 25.0, Some(5.0)).unwrap(), Some(5.0));
+        assert_eq!(clip_duration(None, 10.0, Some(5.0)).unwrap(), Some(5.0));
+
+        assert!(clip_duration(Some(30.0), 30.0, None).is_err());
+        assert!(clip_duration(Some(30.0), 25.0, Some(10.0)).is_err());
+        assert!(clip_duration(Some(30.0), -1.0, None).is_err());
+        assert!(clip_duration(None, 0.0, Some(0.0)).is_err());
+
+        assert_eq!(trim_args(Some(1.5), Some(4.0)), ["-ss", "1.500", "-t", "4.000"]);
+        assert!(trim_args(None, None).is_empty());
+    }
+}
@@ -1,6 +1,8 @@
 use crate::error::{Result, ScreenRecError};
-use std::path::PathBuf;
-use std::process::Command;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 /// Finds the FFmpeg binary, either from the provided path or from the system PATH
 pub fn find_ffmpeg_binary(provided_path: Option<&PathBuf>) -> Result<String> {
@@ -133,6 +135,429 @@ pub fn validate_ffmpeg(ffmpeg_path: &str) -> Result<String> {
     }
 }
 
+/// Make sure the input has a video stream and return its duration in seconds (if known)
+pub fn probe_video_duration(ffprobe: &str, input: &Path) -> Result<Option<f64>> {
+    let result = Command::new(ffprobe)
+        .args(["-v", "error", "-select_streams", "v:0"])
+        .args(["-show_entries", "stream=codec_type:format=duration", "-of", "json"])
+        .arg(input)
+        .output()
+        .map_err(|e| ScreenRecError::EncodingError(format!("Failed to run ffprobe: {}", e)))?;
+
+    if !result.status.success() {
+        return Err(ScreenRecError::InvalidParameter(format!(
+            "Cannot read {}: {}",
+            input.display(),
+            String::from_utf8_lossy(&result.stderr).trim()
+        )));
+    }
+
+    let json: serde_json::Value = serde_json::from_slice(&result.stdout).unwrap_or_default();
+    let has_video = json
+        .get("streams")
+        .and_then(|streams| streams.as_array())
+        .is_some_and(|streams| !streams.is_empty());
+    if !has_video {
+        return Err(ScreenRecError::InvalidParameter(format!(
+            "{} has no video stream",
+            input.display()
+        )));
+    }
+
+    Ok(json
+        .get("format")
+        .and_then(|format| format.get("duration"))
+        .and_then(|duration| duration.as_str())
+        .and_then(|duration| duration.parse().ok()))
+}
+
+/// Run `ffprobe -show_format -show_streams` on a file and return its JSON
+pub fn probe_json(ffprobe: &str, input: &Path) -> Result<serde_json::Value> {
+    let result = Command::new(ffprobe)
+        .args(["-v", "error", "-print_format", "json", "-show_format", "-show_streams"])
+        .arg(input)
+        .output()
+        .map_err(|e| ScreenRecError::EncodingError(format!("Failed to run ffprobe: {}", e)))?;
+
+    if !result.status.success() {
+        return Err(ScreenRecError::InvalidParameter(format!(
+            "Cannot read {}: {}",
+            input.display(),
+            String::from_utf8_lossy(&result.stderr).trim()
+        )));
+    }
+
+    serde_json::from_slice(&result.stdout)
+        .map_err(|e| ScreenRecError::EncodingError(format!("Invalid ffprobe output for {}: {}", input.display(), e)))
+}
+
+/// The parts of ffprobe's output people usually want; missing fields are None
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize)]
+pub struct MediaInfo {
+    pub format: Option<String>,
+    pub duration: Option<f64>,
+    pub size: Option<u64>,
+    pub bit_rate: Option<u64>,
+    pub stream_count: usize,
+    pub video_codec: Option<String>,
+    pub width: Option<u64>,
+    pub height: Option<u64>,
+    pub fps: Option<f64>,
+    pub audio_codec: Option<String>,
+    pub sample_rate: Option<u64>,
+    pub channels: Option<u64>,
+}
+
+impl MediaInfo {
+    /// Read the first video and audio stream and the container from [`probe_json`] output
+    pub fn from_probe_json(json: &serde_json::Value) -> Self {
+        // ffprobe writes most numbers as strings
+        let number = |value: &serde_json::Value| value.as_str().and_then(|s| s.parse().ok()).or(value.as_f64());
+        let text = |value: &serde_json::Value| value.as_str().filter(|s| !s.is_empty()).map(str::to_string);
+
+        let streams = json["streams"].as_array().map(Vec::as_slice).unwrap_or_default();
+        let stream = |codec_type: &str| streams.iter().find(|stream| stream["codec_type"] == codec_type);
+        let video = stream("video");
+        let audio = stream("audio");
+        let format = &json["format"];
+
+        MediaInfo {
+            format: text(&format["format_name"]),
+            duration: number(&format["duration"]),
+            size: number(&format["size"]).map(|size| size as u64),
+            bit_rate: number(&format["bit_rate"]).map(|rate| rate as u64),
+            stream_count: streams.len(),
+            video_codec: video.and_then(|v| text(&v["codec_name"])),
+            width: video.and_then(|v| v["width"].as_u64()),
+            height: video.and_then(|v| v["height"].as_u64()),
+            fps: video.and_then(|v| {
+                parse_frame_rate(&v["avg_frame_rate"]).or_else(|| parse_frame_rate(&v["r_frame_rate"]))
+            }),
+            audio_codec: audio.and_then(|a| text(&a["codec_name"])),
+            sample_rate: audio.and_then(|a| number(&a["sample_rate"])).map(|rate| rate as u64),
+            channels: audio.and_then(|a| a["channels"].as_u64()),
+        }
+    }
+}
+
+/// `"30000/1001"` as frames per second; `"0/0"` (unknown) is None
+fn parse_frame_rate(value: &serde_json::Value) -> Option<f64> {
+    let (num, den) = value.as_str()?.split_once('/')?;
+    let (num, den): (f64, f64) = (num.parse().ok()?, den.parse().ok()?);
+    (num > 0.0 && den > 0.0).then(|| num / den)
+}
+
+/// How closely a video matches a reference, averaged over the frames compared
+#[derive(Debug, Default, Clone, Copy, PartialEq, serde::Serialize)]
+pub struct QualityMetrics {
+    /// Structural similarity, 0-1 (1 = identical)
+    pub ssim: Option<f64>,
+    /// Peak signal-to-noise ratio in dB (higher is closer; null when identical)
+    pub psnr_db: Option<f64>,
+}
+
+/// Both inputs in one pixel format, the reference scaled to the video's size,
+/// then compared frame by frame until the shorter one ends
+const QUALITY_FILTER: &str = "[0:v]format=yuv420p[main];[1:v]format=yuv420p[ref];\
+[ref][main]scale2ref=flags=bicubic[scaled][video];[video]split[m1][m2];[scaled]split[r1][r2];\
+[m1][r1]ssim=shortest=1;[m2][r2]psnr=shortest=1";
+
+/// Compare `video` with `reference` using FFmpeg's ssim and psnr filters. A
+/// reference shorter than the video (such as its first chunk) only covers the
+/// start of it.
+pub fn measure_quality(ffmpeg: &str, video: &Path, reference: &Path) -> Result<QualityMetrics> {
+    let result = Command::new(ffmpeg)
+        .args(["-hide_banner", "-nostats", "-i"])
+        .arg(video)
+        .arg("-i")
+        .arg(reference)
+        .args(["-lavfi", QUALITY_FILTER, "-f", "null", "-"])
+        .output()
+        .map_err(|e| ScreenRecError::EncodingError(format!("Failed to run FFmpeg: {}", e)))?;
+
+    let stderr = String::from_utf8_lossy(&result.stderr);
+    if !result.status.success() {
+        let last_line = stderr.lines().last().unwrap_or_default();
+        return Err(ScreenRecError::EncodingError(format!("Quality comparison failed: {}", last_line)));
+    }
+
+    let metrics = parse_quality_metrics(&stderr);
+    if metrics.ssim.is_none() && metrics.psnr_db.is_none() {
+        return Err(ScreenRecError::EncodingError("FFmpeg reported no SSIM or PSNR".to_string()));
+    }
+    Ok(metrics)
+}
+
+/// Pull the summary lines the ssim (`SSIM ... All:0.98 (17.2)`) and psnr
+/// (`PSNR ... average:42.1 ...`) filters log when they finish
+fn parse_quality_metrics(stderr: &str) -> QualityMetrics {
+    let value_after = |line: &str, key: &str| {
+        let (_, rest) = line.split_once(key)?;
+        rest.split_whitespace().next()?.parse::<f64>().ok()
+    };
+
+    let mut metrics = QualityMetrics::default();
+    for line in stderr.lines() {
+        if line.contains("SSIM ") {
+            metrics.ssim = metrics.ssim.or(value_after(line, " All:"));
+        } else if line.contains("PSNR ") {
+            metrics.psnr_db = metrics.psnr_db.or(value_after(line, " average:").filter(|psnr| psnr.is_finite()));
+        }
+    }
+    metrics
+}
+
+/// Chunks smaller than this can't hold a playable frame (likely a crash mid-write)
+const MIN_CHUNK_BYTES: u64 = 1024;
+
+/// Whether a recorded chunk can go into a concat
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChunkHealth {
+    /// Readable, with a video stream in a supported codec
+    Valid { duration: f64 },
+    Missing,
+    TooSmall(u64),
+    /// Probed but unusable, with the reasons
+    Invalid(Vec<&'static str>),
+    /// An `--encrypt` chunk, which ffprobe can't read until it is decrypted
+    Encrypted,
+}
+
+impl ChunkHealth {
+    pub fn is_valid(&self) -> bool {
+        matches!(self, ChunkHealth::Valid { .. })
+    }
+}
+
+impl std::fmt::Display for ChunkHealth {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChunkHealth::Valid { duration } => write!(f, "ok ({:.2}s)", duration),
+            ChunkHealth::Missing => write!(f, "missing"),
+            ChunkHealth::TooSmall(bytes) => write!(f, "too small ({} bytes, likely corrupted)", bytes),
+            ChunkHealth::Invalid(reasons) => write!(f, "{}", reasons.join(", ")),
+            ChunkHealth::Encrypted => write!(f, "encrypted (run decrypt to probe it)"),
+        }
+    }
+}
+
+/// File-level checks only (exists, not truncated to nothing), for when probing is skipped
+pub fn check_chunk_file(path: &Path) -> Option<ChunkHealth> {
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return Some(ChunkHealth::Missing);
+    };
+    (metadata.len() < MIN_CHUNK_BYTES).then_some(ChunkHealth::TooSmall(metadata.len()))
+}
+
+/// What ffprobe found in a video file. Fields ffprobe couldn't read are `None`/`false`;
+/// tiny files are not probed at all.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VideoFileInfo {
+    pub size: u64,
+    pub duration: Option<f64>,
+    pub codec: Option<String>,
+    pub has_video_stream: bool,
+    /// Whether FFmpeg decoded every frame without errors; None unless deep validation ran
+    pub decodes_cleanly: Option<bool>,
+}
+
+impl VideoFileInfo {
+    /// Classify the file: a plausible duration and a video stream in a supported codec
+    pub fn health(&self) -> ChunkHealth {
+        if self.size < MIN_CHUNK_BYTES {
+            return ChunkHealth::TooSmall(self.size);
+        }
+
+        let has_valid_codec = matches!(self.codec.as_deref(), Some("h264" | "hevc" | "av1"));
+        let decodes = self.decodes_cleanly != Some(false);
+        match self.duration {
+            Some(duration) if self.has_video_stream && has_valid_codec && decodes => ChunkHealth::Valid { duration },
+            _ => {
+                let mut reasons = Vec::new();
+                if !self.has_video_stream {
+                    reasons.push("no video stream");
+                }
+                if self.duration.is_none() {
+                    reasons.push("invalid/missing duration");
+                }
+                if !has_valid_codec {
+                    reasons.push("unsupported codec");
+                }
+                if !decodes {
+                    reasons.push("decode errors");
+                }
+                ChunkHealth::Invalid(reasons)
+            }
+        }
+    }
+}
+
+/// Probe a video file for its duration, video stream and codec with a single
+/// ffprobe run. Only a file that can't be read at all is an error; use
+/// [`VideoFileInfo::health`] for the verdict. Frame-level checks are left to
+/// [`decodes_cleanly`], as decoding every frame is slow.
+pub fn validate_video_file(path: &Path, ffprobe: &str) -> Result<VideoFileInfo> {
+    let metadata = std::fs::metadata(path).map_err(|e| {
+        ScreenRecError::InvalidParameter(format!("Cannot read {}: {}", path.display(), e))
+    })?;
+
+    let mut info = VideoFileInfo {
+        size: metadata.len(),
+        duration: None,
+        codec: None,
+        has_video_stream: false,
+        decodes_cleanly: None,
+    };
+    if info.size < MIN_CHUNK_BYTES {
+        return Ok(info);
+    }
+
+    let probe = Command::new(ffprobe)
+        .args(["-v", "error", "-show_format", "-show_streams", "-print_format", "json"])
+        .arg(path)
+        .output()
+        .ok()
+        .filter(|output| output.status.success());
+    if let Some(output) = probe {
+        let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap_or_default();
+        read_probe_json(&mut info, &json);
+    }
+
+    Ok(info)
+}
+
+/// Fill duration, video stream and codec from `ffprobe -show_format -show_streams` JSON
+fn read_probe_json(info: &mut VideoFileInfo, json: &serde_json::Value) {
+    // "N/A" or garbage fails to parse; a chunk is never longer than an hour
+    info.duration = json["format"]["duration"]
+        .as_str()
+        .and_then(|duration| duration.parse::<f64>().ok())
+        .filter(|duration| *duration > 0.0 && *duration < 3600.0);
+
+    let video = json["streams"]
+        .as_array()
+        .and_then(|streams| streams.iter().find(|stream| stream["codec_type"] == "video"));
+    info.has_video_stream = video.is_some();
+    info.codec = video
+        .and_then(|stream| stream["codec_name"].as_str())
+        .filter(|codec| !codec.is_empty())
+        .map(str::to_string);
+}
+
+/// Decode every frame with FFmpeg and report whether it got through without errors.
+/// Catches corruption a header probe can't see, at the cost of a full decode.
+pub fn decodes_cleanly(path: &Path, ffmpeg: &str) -> bool {
+    Command::new(ffmpeg)
+        .args(["-v", "error", "-i"])
+        .arg(path)
+        .args(["-f", "null", "-"])
+        .output()
+        .is_ok_and(|output| output.status.success() && output.stderr.trim_ascii().is_empty())
+}
+
+/// Run [`validate_video_file`] on every path using up to `concurrency` threads.
+/// With `deep_ffmpeg`, files that pass are also fully decoded with
+/// [`decodes_cleanly`]. Results come back in the order of `paths` (None for a
+/// file that can't be read); `on_probed` gets the number of files finished so
+/// far after each one.
+pub fn validate_video_files(
+    paths: &[PathBuf],
+    ffprobe: &str,
+    deep_ffmpeg: Option<&str>,
+    concurrency: usize,
+    on_probed: impl Fn(usize) + Sync,
+) -> Vec<Option<VideoFileInfo>> {
+    let next = AtomicUsize::new(0);
+    let finished = AtomicUsize::new(0);
+    let mut results: Vec<Option<VideoFileInfo>> = vec![None; paths.len()];
+
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..concurrency.clamp(1, paths.len().max(1)))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut probed = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(path) = paths.get(index) else {
+                            break;
+                        };
+                        let mut info = validate_video_file(path, ffprobe).ok();
+                        if let (Some(info), Some(ffmpeg)) = (info.as_mut(), deep_ffmpeg) {
+                            if info.health().is_valid() {
+                                info.decodes_cleanly = Some(decodes_cleanly(path, ffmpeg));
+                            }
+                        }
+                        probed.push((index, info));
+                        on_probed(finished.fetch_add(1, Ordering::Relaxed) + 1);
+                    }
+                    probed
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            // A worker only panics if on_probed does; its files stay None
+            for (index, info) in worker.join().unwrap_or_default() {
+                results[index] = info;
+            }
+        }
+    });
+
+    results
+}
+
+/// Check a recorded chunk the way concat does; an unreadable file counts as missing
+pub fn validate_chunk(path: &Path, ffprobe: &str) -> ChunkHealth {
+    if crate::encryption::is_encrypted(path) && path.exists() {
+        return ChunkHealth::Encrypted;
+    }
+    validate_video_file(path, ffprobe).map_or(ChunkHealth::Missing, |info| info.health())
+}
+
+/// Run FFmpeg with `args` (which must include `-progress pipe:1`) and turn its
+/// progress output into `[PROGRESS] <label>: N%` lines every 10%, measured
+/// against `duration` seconds of output. Returns the finished process output
+/// so callers can report failures their own way.
+pub fn run_with_progress(
+    ffmpeg_binary: &str,
+    args: &[String],
+    duration: Option<f64>,
+    label: &str,
+) -> Result<Output> {
+    log::debug!("Running FFmpeg with args: {:?}", args);
+
+    let mut child = Command::new(ffmpeg_binary)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| ScreenRecError::EncodingError(format!("Failed to run ffmpeg: {}", e)))?;
+
+    // -progress writes key=value lines to stdout; report every 10%
+    if let Some(stdout) = child.stdout.take() {
+        let mut last_reported = 0;
+        for line in BufReader::new(stdout).lines().map_while(|line| line.ok()) {
+            let (Some(duration), Some(out_time_us)) = (duration, line.strip_prefix("out_time_us=")) else {
+                continue;
+            };
+            let Ok(out_time_us) = out_time_us.trim().parse::<f64>() else {
+                continue;
+            };
+
+            let done = out_time_us / 1_000_000.0;
+            let percent = ((done / duration * 100.0) as u32).min(100);
+            if percent >= last_reported + 10 {
+                last_reported = percent - percent % 10;
+                println!("🔄 [PROGRESS] {}: {}% ({:.1}s / {:.1}s)", label, percent, done, duration);
+            }
+        }
+    }
+
+    child
+        .wait_with_output()
+        .map_err(|e| ScreenRecError::EncodingError(format!("Failed to wait for ffmpeg: {}", e)))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -143,4 +568,150 @@ mod tests {
         let result = which_command("ls");
         assert!(result.is_some());
     }
+
+    #[test]
+    fn test_chunk_file_checks_run_before_probing() {
+        let dir = tempfile::tempdir().unwrap();
+        let truncated = dir.path().join("truncated.mp4");
+        std::fs::write(&truncated, [0u8; 100]).unwrap();
+
+        // The ffprobe path is never used for these
+        assert_eq!(validate_chunk(&dir.path().join("gone.mp4"), "/nonexistent/ffprobe"), ChunkHealth::Missing);
+        assert_eq!(validate_chunk(&truncated, "/nonexistent/ffprobe"), ChunkHealth::TooSmall(100));
+
+        // Encrypted chunks are never probed, so doctor --fix can't mistake them for broken ones
+        let encrypted = dir.path().join("2025-01-14_10-30-00_0000.mp4.enc");
+        std::fs::write(&encrypted, [0u8; 100]).unwrap();
+        assert_eq!(validate_chunk(&encrypted, "/nonexistent/ffprobe"), ChunkHealth::Encrypted);
+        assert!(!validate_chunk(&encrypted, "/nonexistent/ffprobe").is_valid());
+
+        let unreadable = dir.path().join("unreadable.mp4");
+        std::fs::write(&unreadable, [0u8; 4096]).unwrap();
+        let health = validate_chunk(&unreadable, "/nonexistent/ffprobe");
+        assert!(!health.is_valid());
+        assert_eq!(health.to_string(), "no video stream, invalid/missing duration, unsupported codec");
+    }
+
+    #[test]
+    fn test_validate_video_file_classification() {
+        let dir = tempfile::tempdir().unwrap();
+        let ffprobe = which_ffprobe().unwrap_or_else(|| "/nonexistent/ffprobe".to_string());
+
+        assert!(validate_video_file(&dir.path().join("gone.mp4"), &ffprobe).is_err());
+
+        let truncated = dir.path().join("truncated.mp4");
+        std::fs::write(&truncated, b"\0\0\0\x18ftypis").unwrap();
+        let info = validate_video_file(&truncated, &ffprobe).unwrap();
+        assert_eq!(info.size, 10);
+        assert_eq!(info.health(), ChunkHealth::TooSmall(10));
+
+        let text = dir.path().join("notes.mp4");
+        std::fs::write(&text, "not a video\n".repeat(200)).unwrap();
+        let info = validate_video_file(&text, &ffprobe).unwrap();
+        assert!(!info.has_video_stream);
+        assert!(matches!(info.health(), ChunkHealth::Invalid(_)));
+
+        // One ffprobe JSON answer carries everything; the video stream needn't come first
+        let mut info = VideoFileInfo { size: 4096, ..info };
+        read_probe_json(
+            &mut info,
+            &serde_json::json!({
+                "streams": [{ "codec_type": "audio", "codec_name": "aac" }, { "codec_type": "video", "codec_name": "hevc" }],
+                "format": { "duration": "29.97" },
+            }),
+        );
+        assert_eq!(info.health(), ChunkHealth::Valid { duration: 29.97 });
+        info.decodes_cleanly = Some(false);
+        assert_eq!(info.health().to_string(), "decode errors");
+
+        // The known-good fixture is generated, so it needs FFmpeg on PATH
+        let (Some(ffmpeg), Some(_)) = (which_ffmpeg(), which_ffprobe()) else {
+            eprintln!("⚠️  Skipping known-good fixture check: ffmpeg/ffprobe not on PATH");
+            return;
+        };
+        let good = dir.path().join("good.mp4");
+        let status = Command::new(&ffmpeg)
+            .args(["-v", "error", "-f", "lavfi", "-i", "testsrc=duration=1:size=320x240:rate=10"])
+            .args(["-c:v", "libx264", "-pix_fmt", "yuv420p", "-y"])
+            .arg(&good)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let info = validate_video_file(&good, &ffprobe).unwrap();
+        assert_eq!(info.codec.as_deref(), Some("h264"));
+        match info.health() {
+            ChunkHealth::Valid { duration } => assert!((duration - 1.0).abs() < 0.2),
+            other => panic!("fixture classified as {}", other),
+        }
+        assert!(decodes_cleanly(&good, &ffmpeg));
+        assert!(!decodes_cleanly(&text, &ffmpeg));
+    }
+
+    #[test]
+    fn test_parallel_validation_keeps_chunk_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths: Vec<PathBuf> = (0..20u64)
+            .map(|i| {
+                let path = dir.path().join(format!("{:02}.mp4", i));
+                // Every third chunk is missing; the rest are tiny, with a size that encodes their index
+                if i % 3 != 0 {
+                    std::fs::write(&path, vec![0u8; i as usize]).unwrap();
+                }
+                path
+            })
+            .collect();
+
+        let calls = AtomicUsize::new(0);
+        let results = validate_video_files(&paths, "/nonexistent/ffprobe", None, 4, |_| {
+            calls.fetch_add(1, Ordering::Relaxed);
+        });
+        assert_eq!(calls.into_inner(), paths.len());
+        for (i, result) in results.iter().enumerate() {
+            match result {
+                Some(info) => assert_eq!(info.size, i as u64),
+                None => assert_eq!(i % 3, 0),
+            }
+        }
+        assert!(validate_video_files(&[], "/nonexistent/ffprobe", None, 8, |_| {}).is_empty());
+    }
+
+    #[test]
+    fn test_media_info_from_probe_json() {
+        let json = serde_json::json!({
+            "streams": [
+                {"codec_type": "audio", "codec_name": "aac", "sample_rate": "48000", "channels": 2},
+                {"codec_type": "video", "codec_name": "h264", "width": 1920, "height": 1080,
+                 "avg_frame_rate": "0/0", "r_frame_rate": "30000/1001"}
+            ],
+            "format": {"format_name": "mov,mp4,m4a,3gp,3g2,mj2", "duration": "12.345000",
+                       "size": "1048576", "bit_rate": "679542"}
+        });
+
+        let info = MediaInfo::from_probe_json(&json);
+        assert_eq!(info.stream_count, 2);
+        assert_eq!(info.duration, Some(12.345));
+        assert_eq!((info.size, info.bit_rate), (Some(1_048_576), Some(679_542)));
+        assert_eq!((info.video_codec.as_deref(), info.width, info.height), (Some("h264"), Some(1920), Some(1080)));
+        assert!((info.fps.unwrap() - 29.97).abs() < 0.01);
+        assert_eq!((info.audio_codec.as_deref(), info.sample_rate, info.channels), (Some("aac"), Some(48000), Some(2)));
+
+        assert_eq!(MediaInfo::from_probe_json(&serde_json::json!({})), MediaInfo::default());
+    }
+
+    #[test]
+    fn test_parse_quality_metrics() {
+        let stderr = "\
+Input #0, mov,mp4,m4a,3gp,3g2,mj2, from 'final.mp4':
+[Parsed_ssim_8 @ 0x600] SSIM Y:0.991234 (20.571) U:0.995 (23.0) V:0.996 (24.0) All:0.993117 (21.622171)
+[Parsed_psnr_9 @ 0x600] PSNR y:44.10 u:47.95 v:48.33 average:45.280392 min:39.81 max:51.22
+";
+        assert_eq!(parse_quality_metrics(stderr), QualityMetrics { ssim: Some(0.993117), psnr_db: Some(45.280392) });
+
+        // Identical inputs have infinite PSNR, which JSON can't hold
+        let identical = "[Parsed_ssim_8 @ 0x1] SSIM Y:1.000000 (inf) All:1.000000 (inf)\n\
+                         [Parsed_psnr_9 @ 0x1] PSNR y:inf u:inf v:inf average:inf min:inf max:inf";
+        assert_eq!(parse_quality_metrics(identical), QualityMetrics { ssim: Some(1.0), psnr_db: None });
+        assert_eq!(parse_quality_metrics("no summary"), QualityMetrics::default());
+    }
 }
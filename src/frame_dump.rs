@@ -0,0 +1,168 @@
+//! Raw frames on disk (`record --dump-frames DIR`)
+//!
+//! Every Nth frame that reaches the encoder is copied while it is still RGB and
+//! written to DIR as `<captured_at>_d<display>_<width>x<height>.png`, or `.rgb`
+//! with bare RGB24 bytes, for training data and other pipelines that want pixels
+//! rather than video. A background thread does the writing so encoding keeps
+//! pace; when it falls behind, frames are left out of the dump (never out of the
+//! recording) and counted.
+
+use crate::capture::Frame;
+use crate::cli::FrameFormat;
+use crate::error::{Result, ScreenRecError};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::thread::JoinHandle;
+
+/// Frames waiting to be written before new ones are skipped
+const DUMP_QUEUE: usize = 8;
+
+pub struct FrameDumper {
+    dir: PathBuf,
+    every: u64,
+    /// Frames to pass over before the next one is kept
+    countdown: u64,
+    frames_skipped: u64,
+    tx: Option<SyncSender<Frame>>,
+    writer: Option<JoinHandle<u64>>,
+}
+
+impl FrameDumper {
+    /// Create `dir` and start the writer thread; every `every`th frame is kept
+    pub fn start(dir: &Path, every: u64, format: FrameFormat) -> Result<Self> {
+        std::fs::create_dir_all(dir).map_err(|e| {
+            ScreenRecError::ConfigError(format!("Failed to create frame dump directory {}: {}", dir.display(), e))
+        })?;
+
+        let (tx, rx) = sync_channel::<Frame>(DUMP_QUEUE);
+        let writer_dir = dir.to_path_buf();
+        let writer = std::thread::spawn(move || {
+            let mut written = 0u64;
+            for frame in rx {
+                let path = writer_dir.join(frame_file_name(&frame, format));
+                if let Err(e) = write_frame(&path, &frame, format) {
+                    log::error!("Failed to write {}: {}; no more frames will be dumped", path.display(), e);
+                    break;
+                }
+                written += 1;
+            }
+            written
+        });
+
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            every: every.max(1),
+            countdown: 0,
+            frames_skipped: 0,
+            tx: Some(tx),
+            writer: Some(writer),
+        })
+    }
+
+    /// Queue a copy of `frame` if it is one of the frames being dumped
+    pub fn offer(&mut self, frame: &Frame) {
+        if self.countdown > 0 {
+            self.countdown -= 1;
+            return;
+        }
+        self.countdown = self.every - 1;
+        let Some(tx) = self.tx.as_ref() else {
+            return;
+        };
+
+        match tx.try_send(frame.clone()) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                if self.frames_skipped == 0 {
+                    log::warn!("⚠️  Frame dump can't keep up with the recording; skipping frames (raise --dump-every)");
+                }
+                self.frames_skipped += 1;
+            }
+            // The writer stopped after an error it already reported
+            Err(TrySendError::Disconnected(_)) => self.tx = None,
+        }
+    }
+
+    /// Write out the queued frames and wait for the writer thread, returning how
+    /// many frames were dumped. Blocks, so call it from a blocking context.
+    pub fn finish(mut self) -> u64 {
+        // Closing the channel lets the writer finish what is queued
+        self.tx = None;
+        let written = self.writer.take().map_or(0, |writer| writer.join().unwrap_or(0));
+        log::info!("🖼️  Dumped {} frames to {}", written, self.dir.display());
+        if self.frames_skipped > 0 {
+            log::warn!("⚠️  {} frames were left out of the dump because writing fell behind", self.frames_skipped);
+        }
+        written
+    }
+}
+
+impl Drop for FrameDumper {
+    fn drop(&mut self) {
+        // Without finish() (e.g. the encoder failed) the writer is left to drain
+        // the queue on its own; nothing waits for it
+        self.tx = None;
+    }
+}
+
+/// `2025-01-14_10-30-00-123_d0_1920x1080.png`: sorts by capture time, and raw
+/// files carry the size needed to read them back
+fn frame_file_name(frame: &Frame, format: FrameFormat) -> String {
+    format!(
+        "{}_d{}_{}x{}.{}",
+        frame.captured_at.format("%Y-%m-%d_%H-%M-%S-%3f"),
+        frame.display_index,
+        frame.width,
+        frame.height,
+        format
+    )
+}
+
+fn write_frame(path: &Path, frame: &Frame, format: FrameFormat) -> std::result::Result<(), String> {
+    match format {
+        FrameFormat::Png => image::save_buffer(
+            path,
+            &frame.data,
+            frame.width as u32,
+            frame.height as u32,
+            image::ColorType::Rgb8,
+        )
+        .map_err(|e| e.to_string()),
+        FrameFormat::Rgb => std::fs::write(path, &frame.data).map_err(|e| e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use std::time::Duration;
+
+    #[test]
+    fn test_every_nth_frame_is_written() {
+        let dir = tempfile::tempdir().unwrap();
+        let frame = |ms: i64| Frame {
+            data: vec![7; 4 * 2 * 3],
+            width: 4,
+            height: 2,
+            timestamp: Duration::ZERO,
+            captured_at: Utc.timestamp_millis_opt(1_736_850_600_000 + ms).unwrap(),
+            display_index: 1,
+        };
+
+        let mut dumper = FrameDumper::start(dir.path(), 2, FrameFormat::Rgb).unwrap();
+        for ms in [0, 40, 80] {
+            dumper.offer(&frame(ms));
+        }
+        assert_eq!(dumper.finish(), 2);
+
+        let mut names: Vec<String> = std::fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names, ["2025-01-14_10-30-00-000_d1_4x2.rgb", "2025-01-14_10-30-00-080_d1_4x2.rgb"]);
+        assert_eq!(std::fs::read(dir.path().join(&names[0])).unwrap(), vec![7; 24]);
+        assert_eq!(frame_file_name(&frame(0), FrameFormat::Png), "2025-01-14_10-30-00-000_d1_4x2.png");
+    }
+}
@@ -0,0 +1,123 @@
+//! Frame metadata as it is encoded
+//!
+//! With `record --frames-jsonl PATH` every encoded frame is appended to PATH as
+//! one JSON line, using the same field names as the `frames.json` that concat
+//! writes (plus `dts` and `chunk_index`). always_on recordings are never
+//! concatenated, so this is how they get frame-accurate timing. Lines are
+//! buffered and flushed at least once a second, so a crash loses at most the
+//! last second.
+
+use crate::encoder::FrameMetadata;
+use crate::error::{Result, ScreenRecError};
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+pub struct FrameLog {
+    writer: BufWriter<File>,
+    last_flush: Instant,
+}
+
+impl FrameLog {
+    /// Start appending to `path`, creating it if needed
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| {
+                ScreenRecError::ConfigError(format!("Failed to open frames file {}: {}", path.display(), e))
+            })?;
+        Ok(Self { writer: BufWriter::new(file), last_flush: Instant::now() })
+    }
+
+    /// Record frame number `offset` of the recording (counted across chunks)
+    pub fn append(&mut self, offset: u64, chunk_index: i64, captured_at: DateTime<Utc>, metadata: &FrameMetadata) {
+        let line = frame_line(offset, chunk_index, captured_at, metadata);
+        if let Err(e) = writeln!(self.writer, "{}", line) {
+            log::warn!("⚠️  Failed to write frame metadata: {}", e);
+            return;
+        }
+
+        if self.last_flush.elapsed() >= FLUSH_INTERVAL {
+            self.flush();
+        }
+    }
+
+    fn flush(&mut self) {
+        if let Err(e) = self.writer.flush() {
+            log::warn!("⚠️  Failed to flush frame metadata: {}", e);
+        }
+        self.last_flush = Instant::now();
+    }
+}
+
+fn frame_line(offset: u64, chunk_index: i64, captured_at: DateTime<Utc>, metadata: &FrameMetadata) -> serde_json::Value {
+    serde_json::json!({
+        "offset": offset,
+        "chunk_index": chunk_index,
+        "timestamp": captured_at.to_rfc3339_opts(SecondsFormat::Millis, true),
+        "pts": metadata.pts,
+        "dts": metadata.dts,
+        "is_keyframe": metadata.is_keyframe,
+        "display_index": metadata.display_index,
+        "display_width": metadata.width,
+        "display_height": metadata.height,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frames_are_appended_as_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("frames.jsonl");
+        let captured_at = DateTime::parse_from_rfc3339("2025-01-14T10:30:00.123Z").unwrap().with_timezone(&Utc);
+        let metadata = |pts: i64| FrameMetadata {
+            is_keyframe: pts == 0,
+            pts: Some(pts),
+            dts: None,
+            display_index: 1,
+            width: 1920,
+            height: 1080,
+        };
+
+        let mut log = FrameLog::open(&path).unwrap();
+        log.append(0, 0, captured_at, &metadata(0));
+        log.append(1, 0, captured_at, &metadata(1));
+        drop(log);
+
+        // Reopening appends rather than truncating
+        FrameLog::open(&path).unwrap().append(2, 1, captured_at, &metadata(2));
+
+        let lines: Vec<serde_json::Value> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            serde_json::json!({
+                "offset": 0,
+                "chunk_index": 0,
+                "timestamp": "2025-01-14T10:30:00.123Z",
+                "pts": 0,
+                "dts": null,
+                "is_keyframe": true,
+                "display_index": 1,
+                "display_width": 1920,
+                "display_height": 1080,
+            })
+        );
+        assert_eq!(lines[2]["offset"], 2);
+        assert_eq!(lines[2]["chunk_index"], 1);
+        assert_eq!(lines[2]["is_keyframe"], false);
+    }
+}
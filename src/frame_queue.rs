@@ -0,0 +1,224 @@
+//! Frame queue between the capture bridge and the encoder, bounded in bytes
+//!
+//! A frame-count limit means very different memory use at different resolutions
+//! (300 frames is ~800MB at 720p but ~7GB at 4K). This queue bounds the total size
+//! of buffered frame data instead, and the bytes are handed back as the encoder
+//! receives frames. What `send` does once the budget is used up depends on the
+//! [`OverflowPolicy`]: by default it waits, so no frame is ever lost; with
+//! `drop-oldest` it throws away the oldest queued frames to make room, so capture
+//! never stalls behind a slow encoder.
+
+use crate::capture::Frame;
+use crate::cli::OverflowPolicy;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{Notify, Semaphore, TryAcquireError};
+
+/// Create a queue that buffers at most `max_bytes` of frame data
+pub fn frame_queue(max_bytes: usize, policy: OverflowPolicy) -> (FrameSender, FrameReceiver) {
+    let max_bytes = max_bytes.max(1);
+    let shared = Arc::new(Shared {
+        frames: Mutex::new(VecDeque::new()),
+        budget: Semaphore::new(max_bytes),
+        frame_ready: Notify::new(),
+        sender_alive: AtomicBool::new(true),
+        max_bytes,
+    });
+
+    (
+        FrameSender { shared: shared.clone(), policy, dropped: AtomicU64::new(0) },
+        FrameReceiver { shared },
+    )
+}
+
+struct Shared {
+    frames: Mutex<VecDeque<Frame>>,
+    budget: Semaphore,
+    frame_ready: Notify,
+    sender_alive: AtomicBool,
+    max_bytes: usize,
+}
+
+impl Shared {
+    /// Bytes a frame holds against the budget. A frame larger than the whole budget
+    /// is charged the full budget, so it still goes through once the queue drains.
+    fn charge(&self, frame: &Frame) -> u32 {
+        frame.data.len().min(self.max_bytes).min(u32::MAX as usize) as u32
+    }
+
+    fn pop(&self) -> Option<Frame> {
+        let frame = self.frames.lock().ok()?.pop_front()?;
+        self.budget.add_permits(self.charge(&frame) as usize);
+        Some(frame)
+    }
+
+    fn buffered_bytes(&self) -> usize {
+        self.max_bytes.saturating_sub(self.budget.available_permits())
+    }
+}
+
+pub struct FrameSender {
+    shared: Arc<Shared>,
+    policy: OverflowPolicy,
+    dropped: AtomicU64,
+}
+
+impl FrameSender {
+    /// Queue a frame, waiting (or dropping the oldest frames) while buffered
+    /// frames use the whole budget. Gives the frame back if the receiver is gone.
+    pub async fn send(&self, frame: Frame) -> std::result::Result<(), Frame> {
+        let charge = self.shared.charge(&frame);
+
+        match self.policy {
+            // The receiver closes the semaphore when dropped, so this can't wait forever
+            OverflowPolicy::Block => match self.shared.budget.acquire_many(charge).await {
+                Ok(permit) => permit.forget(),
+                Err(_) => return Err(frame),
+            },
+            OverflowPolicy::DropOldest => loop {
+                match self.shared.budget.try_acquire_many(charge) {
+                    Ok(permit) => {
+                        permit.forget();
+                        break;
+                    }
+                    Err(TryAcquireError::Closed) => return Err(frame),
+                    Err(TryAcquireError::NoPermits) => {
+                        if self.shared.pop().is_some() {
+                            self.dropped.fetch_add(1, Ordering::Relaxed);
+                        } else {
+                            // Nothing left to drop: the encoder holds the budget for a moment
+                            tokio::task::yield_now().await;
+                        }
+                    }
+                }
+            },
+        }
+
+        match self.shared.frames.lock() {
+            Ok(mut frames) => frames.push_back(frame),
+            Err(_) => return Err(frame),
+        }
+        self.shared.frame_ready.notify_one();
+        Ok(())
+    }
+
+    /// Frame data currently waiting for the encoder
+    pub fn buffered_bytes(&self) -> usize {
+        self.shared.buffered_bytes()
+    }
+
+    /// Frames thrown away to make room (only with `drop-oldest`)
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for FrameSender {
+    fn drop(&mut self) {
+        // Let the receiver drain what's left and then see the end of the stream
+        self.shared.sender_alive.store(false, Ordering::Release);
+        self.shared.frame_ready.notify_one();
+    }
+}
+
+pub struct FrameReceiver {
+    shared: Arc<Shared>,
+}
+
+impl FrameReceiver {
+    /// Receive the next frame, or None once the sender is dropped and the queue is empty.
+    /// Cancel safe, so it can be used in `tokio::select!`.
+    pub async fn recv(&mut self) -> Option<Frame> {
+        loop {
+            if let Some(frame) = self.shared.pop() {
+                return Some(frame);
+            }
+            if !self.shared.sender_alive.load(Ordering::Acquire) {
+                // A last frame may have been queued just before the sender went away
+                return self.shared.pop();
+            }
+            // notify_one stores a wakeup when nobody is waiting, so none is missed
+            self.shared.frame_ready.notified().await;
+        }
+    }
+
+    /// How full the queue is, from 0.0 (empty) to 1.0 (capture is waiting on the encoder)
+    pub fn backlog(&self) -> f64 {
+        self.shared.buffered_bytes() as f64 / self.shared.max_bytes as f64
+    }
+
+    /// Frames waiting to be received
+    pub fn queued_frames(&self) -> usize {
+        self.shared.frames.lock().map(|frames| frames.len()).unwrap_or(0)
+    }
+}
+
+impl Drop for FrameReceiver {
+    fn drop(&mut self) {
+        // Wake a sender blocked on the budget so it sees the encoder is gone
+        self.shared.budget.close();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn frame(bytes: usize) -> Frame {
+        Frame {
+            data: vec![0; bytes],
+            width: 0,
+            height: 0,
+            timestamp: Duration::ZERO,
+            captured_at: chrono::Utc::now(),
+            display_index: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sender_waits_for_byte_budget() {
+        let (tx, mut rx) = frame_queue(250, OverflowPolicy::Block);
+
+        assert!(tx.send(frame(100)).await.is_ok());
+        assert!(tx.send(frame(100)).await.is_ok());
+        assert_eq!(tx.buffered_bytes(), 200);
+
+        // A third frame doesn't fit until the encoder takes one
+        let blocked = tokio::time::timeout(Duration::from_millis(50), tx.send(frame(100))).await;
+        assert!(blocked.is_err());
+
+        assert_eq!(rx.recv().await.unwrap().data.len(), 100);
+        assert!(tx.send(frame(100)).await.is_ok());
+        assert_eq!(tx.buffered_bytes(), 200);
+
+        // Oversized frames are charged the whole budget instead of blocking forever
+        rx.recv().await.unwrap();
+        rx.recv().await.unwrap();
+        assert!(tx.send(frame(1000)).await.is_ok());
+        assert_eq!(tx.buffered_bytes(), 250);
+
+        // A dropped receiver fails the send instead of hanging
+        drop(rx);
+        assert!(tx.send(frame(100)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_drop_oldest_makes_room_without_waiting() {
+        let (tx, mut rx) = frame_queue(250, OverflowPolicy::DropOldest);
+
+        for bytes in [100, 101, 102, 103] {
+            let sent = tokio::time::timeout(Duration::from_millis(50), tx.send(frame(bytes))).await;
+            assert!(matches!(sent, Ok(Ok(()))));
+        }
+        assert_eq!(tx.dropped(), 2);
+        assert_eq!(tx.buffered_bytes(), 205);
+
+        // The newest frames survive, in order, and the stream ends once the sender is gone
+        drop(tx);
+        assert_eq!(rx.recv().await.unwrap().data.len(), 102);
+        assert_eq!(rx.recv().await.unwrap().data.len(), 103);
+        assert!(rx.recv().await.is_none());
+    }
+}
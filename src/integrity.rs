@@ -0,0 +1,141 @@
+//! SHA-256 checksums of finished chunks, for showing recordings weren't altered
+//!
+//! When the chunked encoder finishes a chunk it hashes the file and writes the
+//! digest next to it as `<chunk>.mp4.sha256`, in the format `sha256sum` reads
+//! (`sha256sum -c` works on it directly), and stores it in the chunk's database
+//! row. `omgrec verify-integrity --task-id <id>` hashes every chunk of a task
+//! again and compares against both.
+
+use crate::error::Result;
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// Read buffer for hashing; chunks are streamed, never loaded whole
+const HASH_BUFFER_BYTES: usize = 64 * 1024;
+
+/// `<chunk>.mp4.sha256` next to the chunk
+pub fn sidecar_path(chunk: &Path) -> PathBuf {
+    let mut name = chunk.as_os_str().to_owned();
+    name.push(".sha256");
+    PathBuf::from(name)
+}
+
+/// Lowercase hex SHA-256 of a file
+pub fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; HASH_BUFFER_BYTES];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.finalize().iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Hash a finished chunk and write its sidecar, returning the digest
+pub fn write_checksum(chunk: &Path) -> Result<String> {
+    let sha256 = sha256_file(chunk)?;
+    let name = chunk.file_name().unwrap_or_default().to_string_lossy();
+    std::fs::write(sidecar_path(chunk), format!("{}  {}\n", sha256, name))?;
+    Ok(sha256)
+}
+
+/// Digest from a chunk's sidecar, None if it has none
+pub fn read_sidecar(chunk: &Path) -> Option<String> {
+    let contents = std::fs::read_to_string(sidecar_path(chunk)).ok()?;
+    contents.split_whitespace().next().map(str::to_lowercase)
+}
+
+/// What re-hashing a chunk found
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChunkIntegrity {
+    /// The file matches every recorded checksum
+    Verified,
+    /// The file, or the checksum recorded in `source`, was changed
+    Mismatch { source: &'static str, expected: String, actual: String },
+    Missing,
+    /// Neither the database nor a sidecar has a checksum (e.g. recorded before checksums)
+    Unrecorded,
+}
+
+impl ChunkIntegrity {
+    pub fn is_verified(&self) -> bool {
+        matches!(self, ChunkIntegrity::Verified)
+    }
+}
+
+impl std::fmt::Display for ChunkIntegrity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChunkIntegrity::Verified => write!(f, "ok"),
+            ChunkIntegrity::Mismatch { source, expected, actual } => {
+                write!(f, "mismatch ({} has {}…, file is {}…)", source, short(expected), short(actual))
+            }
+            ChunkIntegrity::Missing => write!(f, "missing"),
+            ChunkIntegrity::Unrecorded => write!(f, "no checksum recorded"),
+        }
+    }
+}
+
+/// First 12 hex digits, enough to tell digests apart in a report
+fn short(sha256: &str) -> &str {
+    sha256.get(..12).unwrap_or(sha256)
+}
+
+/// Hash `chunk` and compare it with the database's checksum and its sidecar
+pub fn verify_chunk(chunk: &Path, recorded: Option<&str>) -> Result<ChunkIntegrity> {
+    if !chunk.exists() {
+        return Ok(ChunkIntegrity::Missing);
+    }
+    let sidecar = read_sidecar(chunk);
+    let expected: Vec<(&'static str, String)> = [("database", recorded.map(str::to_lowercase)), ("sidecar", sidecar)]
+        .into_iter()
+        .filter_map(|(source, sha256)| sha256.map(|sha256| (source, sha256)))
+        .collect();
+    if expected.is_empty() {
+        return Ok(ChunkIntegrity::Unrecorded);
+    }
+
+    let actual = sha256_file(chunk)?;
+    for (source, expected) in expected {
+        if expected != actual {
+            return Ok(ChunkIntegrity::Mismatch { source, expected, actual });
+        }
+    }
+    Ok(ChunkIntegrity::Verified)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_altered_chunk_fails_verification() {
+        let dir = tempfile::tempdir().unwrap();
+        let chunk = dir.path().join("2025-01-14_10-30-00_0000.mp4");
+        std::fs::write(&chunk, b"abc").unwrap();
+
+        let sha256 = write_checksum(&chunk).unwrap();
+        assert_eq!(sha256, "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(
+            std::fs::read_to_string(sidecar_path(&chunk)).unwrap(),
+            format!("{}  2025-01-14_10-30-00_0000.mp4\n", sha256)
+        );
+        assert_eq!(verify_chunk(&chunk, Some(&sha256)).unwrap(), ChunkIntegrity::Verified);
+
+        std::fs::write(&chunk, b"abd").unwrap();
+        assert!(matches!(
+            verify_chunk(&chunk, Some(&sha256)).unwrap(),
+            ChunkIntegrity::Mismatch { source: "database", .. }
+        ));
+
+        std::fs::remove_file(sidecar_path(&chunk)).unwrap();
+        assert_eq!(verify_chunk(&chunk, None).unwrap(), ChunkIntegrity::Unrecorded);
+        std::fs::remove_file(&chunk).unwrap();
+        assert_eq!(verify_chunk(&chunk, Some(&sha256)).unwrap(), ChunkIntegrity::Missing);
+    }
+}
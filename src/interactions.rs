@@ -1,12 +1,14 @@
+use crate::display_info::{display_containing, DisplayInfo};
 use crate::error::{Result, ScreenRecError};
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use rdev::{listen, Event, EventType, Key};
 use serde::{Deserialize, Serialize};
 use std::fs::{File, OpenOptions};
 use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 #[cfg(target_os = "macos")]
 use active_win_pos_rs::get_active_window;
@@ -24,7 +26,7 @@ use windows::core::PWSTR;
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InteractionEvent {
     #[serde(rename = "type")]
-    pub event_type: String,  // "click", "move", "scroll", "keypress", "keyrelease"
+    pub event_type: String,  // "click", "move", "scroll", "keypress", "keyrelease", "paste"
     pub timestamp: String,  // ISO 8601 format
     pub timestamp_ms: u64,  // Milliseconds from recording start
     #[serde(rename = "taskId")]
@@ -33,6 +35,9 @@ pub struct InteractionEvent {
     pub process_name: String,
     #[serde(rename = "windowTitle")]
     pub window_title: String,
+    /// Display the event happened on (same index as `--display`); cursor position for key events
+    #[serde(rename = "displayIndex", skip_serializing_if = "Option::is_none")]
+    pub display_index: Option<usize>,
     // Mouse-specific fields (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub x: Option<f64>,
@@ -43,11 +48,19 @@ pub struct InteractionEvent {
     // Keyboard-specific fields (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub key: Option<String>,
+    /// Character typed, for "text" events (only with --capture-text)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
     // Scroll-specific fields (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub delta_x: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub delta_y: Option<i64>,
+    // Paste-specific fields, only with --capture-clipboard (optional)
+    #[serde(rename = "contentType", skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    #[serde(rename = "contentLength", skip_serializing_if = "Option::is_none")]
+    pub content_length: Option<usize>,
 }
 
 /// Represents a mouse event
@@ -72,7 +85,7 @@ pub struct KeyboardEvent {
     pub timestamp_ms: u64,
     /// Key name
     pub key: String,
-    /// Event type: press or release
+    /// Event type: press, release, paste, or text (with --capture-text)
     pub event_type: String,
 }
 
@@ -102,6 +115,167 @@ pub struct InteractionMetadata {
     pub total_mouse_clicks: usize,
     /// Total keyboard events captured
     pub total_keyboard_events: usize,
+    /// Paste shortcuts (Cmd/Ctrl+V) detected
+    #[serde(default)]
+    pub total_paste_events: usize,
+    /// JSONL segment files written during the session, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub jsonl_files: Vec<String>,
+}
+
+/// JSONL writer that rolls over to a new segment once a size limit is reached.
+///
+/// The first segment uses the configured path (`interactions.jsonl`); later
+/// segments are numbered (`interactions.1.jsonl`, `interactions.2.jsonl`, ...)
+/// so completed segments can be processed while recording continues. A new
+/// session in the same task appends to the newest segment instead of starting
+/// over at the first.
+struct JsonlWriter {
+    base_path: PathBuf,
+    writer: BufWriter<File>,
+    max_bytes: Option<u64>,
+    bytes_written: u64,
+    segment_index: usize,
+}
+
+impl JsonlWriter {
+    fn open(base_path: PathBuf, max_bytes: Option<u64>) -> Result<Self> {
+        let mut segment_index = 0;
+        while Self::segment_path(&base_path, segment_index + 1).exists() {
+            segment_index += 1;
+        }
+        let file = Self::open_segment(&Self::segment_path(&base_path, segment_index))?;
+        let bytes_written = file.metadata().map(|m| m.len()).unwrap_or(0);
+
+        Ok(Self {
+            base_path,
+            writer: BufWriter::new(file),
+            max_bytes,
+            bytes_written,
+            segment_index,
+        })
+    }
+
+    fn open_segment(path: &Path) -> Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| ScreenRecError::ConfigError(format!("Failed to create JSONL file: {}", e)))
+    }
+
+    fn segment_path(base_path: &Path, index: usize) -> PathBuf {
+        if index == 0 {
+            return base_path.to_path_buf();
+        }
+        let stem = base_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("interactions");
+        base_path.with_file_name(format!("{}.{}.jsonl", stem, index))
+    }
+
+    /// File names of all segments written so far
+    fn segment_names(&self) -> Vec<String> {
+        (0..=self.segment_index)
+            .filter_map(|i| {
+                Self::segment_path(&self.base_path, i)
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+            })
+            .collect()
+    }
+
+    /// Write one JSON object as a line, rotating first if it would exceed the limit
+    fn write_line(&mut self, line: &str) -> std::io::Result<()> {
+        let line_len = line.len() as u64 + 1;
+        if let Some(max_bytes) = self.max_bytes {
+            if self.bytes_written > 0 && self.bytes_written + line_len > max_bytes {
+                self.rotate()?;
+            }
+        }
+
+        writeln!(self.writer, "{}", line)?;
+        self.bytes_written += line_len;
+        Ok(())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.writer.flush()?;
+
+        let next_index = self.segment_index + 1;
+        let next_path = Self::segment_path(&self.base_path, next_index);
+        let file = OpenOptions::new().create(true).append(true).open(&next_path)?;
+
+        log::info!(
+            "Interaction log reached {} bytes, rotating to {}",
+            self.bytes_written,
+            next_path.display()
+        );
+
+        self.writer = BufWriter::new(file);
+        self.bytes_written = 0;
+        self.segment_index = next_index;
+        Ok(())
+    }
+}
+
+/// Wheel events merged into a single scroll event.
+///
+/// A trackpad swipe delivers dozens of small wheel events; they are summed until
+/// the wheel goes quiet for the debounce window or the direction reverses.
+#[derive(Debug, Clone, PartialEq)]
+struct ScrollBurst {
+    /// Time of the first wheel event in the burst
+    timestamp_ms: u64,
+    /// Wall-clock time of the first wheel event; the burst is only written once
+    /// a later event closes it
+    opened_at: DateTime<Utc>,
+    /// Process and window title at the first wheel event, when logging to JSONL
+    window: Option<(String, String)>,
+    /// Time of the most recent wheel event
+    last_ms: u64,
+    /// Pointer position at the first wheel event
+    x: f64,
+    y: f64,
+    /// Display under the pointer at the first wheel event
+    display_index: Option<usize>,
+    delta_x: i64,
+    delta_y: i64,
+}
+
+impl ScrollBurst {
+    fn new(
+        timestamp_ms: u64,
+        x: f64,
+        y: f64,
+        display_index: Option<usize>,
+        window: Option<(String, String)>,
+        delta_x: i64,
+        delta_y: i64,
+    ) -> Self {
+        Self { timestamp_ms, opened_at: Utc::now(), window, last_ms: timestamp_ms, x, y, display_index, delta_x, delta_y }
+    }
+
+    /// Whether the wheel has been quiet long enough to close the burst
+    fn is_finished(&self, now_ms: u64, debounce_ms: u64) -> bool {
+        now_ms.saturating_sub(self.last_ms) >= debounce_ms
+    }
+
+    /// Whether a wheel event scrolls against the burst on either axis
+    fn reverses(&self, delta_x: i64, delta_y: i64) -> bool {
+        self.delta_x.signum() * delta_x.signum() < 0 || self.delta_y.signum() * delta_y.signum() < 0
+    }
+
+    fn add(&mut self, timestamp_ms: u64, delta_x: i64, delta_y: i64) {
+        self.last_ms = timestamp_ms;
+        self.delta_x += delta_x;
+        self.delta_y += delta_y;
+    }
 }
 
 /// Interaction tracker that captures mouse and keyboard events
@@ -113,14 +287,26 @@ pub struct InteractionTracker {
     screen_width: usize,
     screen_height: usize,
     track_movements: bool,
-    movement_sample_rate: usize, // Capture every Nth movement to avoid too much data
+    capture_clipboard: bool, // Read the clipboard's type/length on paste (privacy opt-in)
+    capture_text: bool, // Log typed characters as text events (privacy opt-in)
+    move_sample_interval: Option<Duration>, // Minimum gap between recorded moves (None = every move)
+    scroll_debounce_ms: u64, // Quiet gap that ends a scroll burst (0 = log every wheel event)
+    pending_scroll: Arc<Mutex<Option<ScrollBurst>>>,
     task_id: Option<String>,
-    jsonl_file: Arc<Mutex<Option<BufWriter<File>>>>,
+    jsonl_file: Arc<Mutex<Option<JsonlWriter>>>,
     click_count: Arc<Mutex<usize>>,
 }
 
 impl InteractionTracker {
-    pub fn new(screen_width: usize, screen_height: usize, track_movements: bool) -> Self {
+    pub fn new(
+        screen_width: usize,
+        screen_height: usize,
+        track_movements: bool,
+        move_sample_hz: u32,
+        scroll_debounce_ms: u64,
+        capture_clipboard: bool,
+        capture_text: bool,
+    ) -> Self {
         Self {
             start_time: Arc::new(Instant::now()),
             mouse_events: Arc::new(Mutex::new(Vec::new())),
@@ -128,7 +314,11 @@ impl InteractionTracker {
             screen_width,
             screen_height,
             track_movements,
-            movement_sample_rate: 5, // Capture every 5th movement event
+            capture_clipboard,
+            capture_text,
+            move_sample_interval: move_sample_interval(move_sample_hz),
+            scroll_debounce_ms,
+            pending_scroll: Arc::new(Mutex::new(None)),
             task_id: None,
             jsonl_file: Arc::new(Mutex::new(None)),
             click_count: Arc::new(Mutex::new(0)),
@@ -139,17 +329,16 @@ impl InteractionTracker {
         screen_width: usize,
         screen_height: usize,
         track_movements: bool,
+        move_sample_hz: u32,
+        scroll_debounce_ms: u64,
+        capture_clipboard: bool,
+        capture_text: bool,
         task_id: String,
         jsonl_path: PathBuf,
+        max_jsonl_bytes: Option<u64>,
     ) -> Result<Self> {
         // Create JSONL file for click events
-        let file = OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(&jsonl_path)
-            .map_err(|e| ScreenRecError::ConfigError(format!("Failed to create JSONL file: {}", e)))?;
-
-        let writer = BufWriter::new(file);
+        let writer = JsonlWriter::open(jsonl_path, max_jsonl_bytes)?;
 
         Ok(Self {
             start_time: Arc::new(Instant::now()),
@@ -158,7 +347,11 @@ impl InteractionTracker {
             screen_width,
             screen_height,
             track_movements,
-            movement_sample_rate: 5,
+            capture_clipboard,
+            capture_text,
+            move_sample_interval: move_sample_interval(move_sample_hz),
+            scroll_debounce_ms,
+            pending_scroll: Arc::new(Mutex::new(None)),
             task_id: Some(task_id),
             jsonl_file: Arc::new(Mutex::new(Some(writer))),
             click_count: Arc::new(Mutex::new(0)),
@@ -171,15 +364,25 @@ impl InteractionTracker {
         let keyboard_events = Arc::clone(&self.keyboard_events);
         let start_time = Arc::clone(&self.start_time);
         let track_movements = self.track_movements;
-        let mut movement_counter = 0usize;
-        let movement_sample_rate = self.movement_sample_rate;
+        let capture_clipboard = self.capture_clipboard;
+        let capture_text = self.capture_text;
+        let mut last_move_sample: Option<Instant> = None;
+        let move_sample_interval = self.move_sample_interval;
+        let scroll_debounce_ms = self.scroll_debounce_ms;
+        let pending_scroll = Arc::clone(&self.pending_scroll);
         let task_id = self.task_id.clone();
         let jsonl_file = Arc::clone(&self.jsonl_file);
         let click_count = Arc::clone(&self.click_count);
 
         log::info!("Starting interaction tracking...");
         log::info!("  Track mouse movements: {}", track_movements);
-        log::info!("  Movement sample rate: 1/{}", movement_sample_rate);
+        match move_sample_interval {
+            Some(interval) => log::info!("  Movement sampling: at most one move every {:?}", interval),
+            None => log::info!("  Movement sampling: every move"),
+        }
+        log::info!("  Scroll debounce: {}ms", scroll_debounce_ms);
+        log::info!("  Clipboard capture on paste: {}", capture_clipboard);
+        log::info!("  Typed text capture: {}", capture_text);
         if task_id.is_some() {
             log::info!("  JSONL logging: enabled");
         }
@@ -189,10 +392,34 @@ impl InteractionTracker {
             // Track last mouse position within this closure
             let mut last_mouse_x = 0.0;
             let mut last_mouse_y = 0.0;
+            // Listed once here, not on every event
+            let mut displays = DisplayMap::new();
+            // Held modifiers, for spotting the paste shortcut
+            let mut ctrl_down = false;
+            let mut meta_down = false;
+            // Shift/Alt/Caps Lock, for resolving typed characters
+            let mut keymap = TextKeymap::default();
 
             let callback = move |event: Event| {
                 let timestamp_ms = start_time.elapsed().as_millis() as u64;
 
+                // Close a pending scroll burst once the wheel goes quiet, or as soon as
+                // a click or key comes in so the log stays in order
+                if let Ok(mut pending) = pending_scroll.lock() {
+                    let finished = match (&*pending, &event.event_type) {
+                        (Some(burst), EventType::Wheel { .. } | EventType::MouseMove { .. }) => {
+                            burst.is_finished(timestamp_ms, scroll_debounce_ms)
+                        }
+                        (Some(_), _) => true,
+                        (None, _) => false,
+                    };
+                    if finished {
+                        if let Some(burst) = pending.take() {
+                            record_scroll(&burst, &mouse_events, &jsonl_file, task_id.as_deref());
+                        }
+                    }
+                }
+
                 match event.event_type {
                     EventType::MouseMove { x, y } => {
                         // Update last known position
@@ -203,9 +430,15 @@ impl InteractionTracker {
                         crate::capture::update_cursor_position(x as i32, y as i32);
 
                         if track_movements {
-                            movement_counter += 1;
-                            // Only capture every Nth movement to reduce data volume
-                            if movement_counter % movement_sample_rate == 0 {
+                            // Record at most one move per sample interval to reduce data volume,
+                            // however fast the OS delivers move events
+                            let now = Instant::now();
+                            let due = match (last_move_sample, move_sample_interval) {
+                                (Some(last), Some(interval)) => now.duration_since(last) >= interval,
+                                _ => true,
+                            };
+                            if due {
+                                last_move_sample = Some(now);
                                 let mouse_event = MouseEvent {
                                     timestamp_ms,
                                     x,
@@ -248,19 +481,23 @@ impl InteractionTracker {
                                 task_id: tid.clone(),
                                 process_name,
                                 window_title,
+                                display_index: displays.display_at(x, y),
                                 x: Some(x),
                                 y: Some(y),
                                 button: Some(button_name),
                                 key: None,
+                                text: None,
                                 delta_x: None,
                                 delta_y: None,
+                                content_type: None,
+                                content_length: None,
                             };
 
                             if let Ok(mut file_opt) = jsonl_file.lock() {
                                 if let Some(ref mut writer) = *file_opt {
                                     // Write as compact single line (JSONL format: one JSON object per line)
                                     if let Ok(json) = serde_json::to_string(&interaction_event) {
-                                        match writeln!(writer, "{}", json) {
+                                        match writer.write_line(&json) {
                                             Ok(_) => {
                                                 let _ = writer.flush();
                                                 log::debug!("Click event written to JSONL");
@@ -295,56 +532,59 @@ impl InteractionTracker {
                         }
                     }
                     EventType::Wheel { delta_x, delta_y } => {
-                        // Use last known mouse position
-                        let x = last_mouse_x;
-                        let y = last_mouse_y;
-
-                        let mouse_event = MouseEvent {
-                            timestamp_ms,
-                            x,
-                            y,
-                            event_type: format!("scroll({}, {})", delta_x, delta_y),
-                            button: None,
-                        };
-                        if let Ok(mut events) = mouse_events.lock() {
-                            events.push(mouse_event);
-                        }
-
-                        // Write to JSONL if task-based tracking
-                        if let Some(ref tid) = task_id {
-                            let (process_name, window_title) = get_active_window_info();
-                            let interaction_event = InteractionEvent {
-                                event_type: "scroll".to_string(),
-                                timestamp: Utc::now().to_rfc3339(),
-                                timestamp_ms,
-                                task_id: tid.clone(),
-                                process_name,
-                                window_title,
-                                x: Some(x),
-                                y: Some(y),
-                                button: None,
-                                key: None,
-                                delta_x: Some(delta_x),
-                                delta_y: Some(delta_y),
-                            };
-
-                            if let Ok(mut file_opt) = jsonl_file.lock() {
-                                if let Some(ref mut writer) = *file_opt {
-                                    if let Ok(json) = serde_json::to_string(&interaction_event) {
-                                        if let Err(e) = writeln!(writer, "{}", json) {
-                                            log::error!("Failed to write scroll to JSONL: {}", e);
-                                        }
+                        if let Ok(mut pending) = pending_scroll.lock() {
+                            match pending.as_mut() {
+                                Some(burst) if !burst.reverses(delta_x, delta_y) => {
+                                    burst.add(timestamp_ms, delta_x, delta_y);
+                                }
+                                _ => {
+                                    if let Some(burst) = pending.take() {
+                                        record_scroll(&burst, &mouse_events, &jsonl_file, task_id.as_deref());
                                     }
+                                    // Use last known mouse position
+                                    *pending = Some(ScrollBurst::new(
+                                        timestamp_ms,
+                                        last_mouse_x,
+                                        last_mouse_y,
+                                        displays.display_at(last_mouse_x, last_mouse_y),
+                                        task_id.is_some().then(get_active_window_info),
+                                        delta_x,
+                                        delta_y,
+                                    ));
+                                }
+                            }
+
+                            if scroll_debounce_ms == 0 {
+                                if let Some(burst) = pending.take() {
+                                    record_scroll(&burst, &mouse_events, &jsonl_file, task_id.as_deref());
                                 }
                             }
                         }
                     }
                     EventType::KeyPress(key) => {
+                        set_modifier(key, true, &mut ctrl_down, &mut meta_down);
+                        keymap.update(key, true);
                         let key_name = format_key(key);
-                        let keyboard_event = KeyboardEvent {
-                            timestamp_ms,
-                            key: key_name.clone(),
-                            event_type: "press".to_string(),
+
+                        // With --capture-text, printable keys are logged as the character
+                        // they type; shortcuts and non-printing keys stay raw keypresses
+                        let typed = if capture_text && !ctrl_down && !meta_down {
+                            keymap.typed_char(key)
+                        } else {
+                            None
+                        };
+
+                        let keyboard_event = match typed {
+                            Some(c) => KeyboardEvent {
+                                timestamp_ms,
+                                key: c.to_string(),
+                                event_type: "text".to_string(),
+                            },
+                            None => KeyboardEvent {
+                                timestamp_ms,
+                                key: key_name.clone(),
+                                event_type: "press".to_string(),
+                            },
                         };
                         if let Ok(mut events) = keyboard_events.lock() {
                             events.push(keyboard_event);
@@ -354,32 +594,88 @@ impl InteractionTracker {
                         if let Some(ref tid) = task_id {
                             let (process_name, window_title) = get_active_window_info();
                             let interaction_event = InteractionEvent {
-                                event_type: "keypress".to_string(),
+                                event_type: if typed.is_some() { "text" } else { "keypress" }.to_string(),
                                 timestamp: Utc::now().to_rfc3339(),
                                 timestamp_ms,
                                 task_id: tid.clone(),
                                 process_name,
                                 window_title,
+                                display_index: displays.display_at(last_mouse_x, last_mouse_y),
                                 x: None,
                                 y: None,
                                 button: None,
-                                key: Some(key_name),
+                                key: if typed.is_some() { None } else { Some(key_name) },
+                                text: typed.map(String::from),
                                 delta_x: None,
                                 delta_y: None,
+                                content_type: None,
+                                content_length: None,
                             };
 
                             if let Ok(mut file_opt) = jsonl_file.lock() {
                                 if let Some(ref mut writer) = *file_opt {
                                     if let Ok(json) = serde_json::to_string(&interaction_event) {
-                                        if let Err(e) = writeln!(writer, "{}", json) {
+                                        if let Err(e) = writer.write_line(&json) {
                                             log::error!("Failed to write keypress to JSONL: {}", e);
                                         }
                                     }
                                 }
                             }
                         }
+
+                        if let Some(shortcut) = paste_shortcut(key, ctrl_down, meta_down) {
+                            if let Ok(mut events) = keyboard_events.lock() {
+                                events.push(KeyboardEvent {
+                                    timestamp_ms,
+                                    key: shortcut.to_string(),
+                                    event_type: "paste".to_string(),
+                                });
+                            }
+
+                            if let Some(ref tid) = task_id {
+                                // The clipboard still holds what is about to be pasted
+                                let (content_type, content_length) = if capture_clipboard {
+                                    let (content_type, content_length) = clipboard_summary();
+                                    (Some(content_type.to_string()), content_length)
+                                } else {
+                                    (None, None)
+                                };
+
+                                let (process_name, window_title) = get_active_window_info();
+                                let interaction_event = InteractionEvent {
+                                    event_type: "paste".to_string(),
+                                    timestamp: Utc::now().to_rfc3339(),
+                                    timestamp_ms,
+                                    task_id: tid.clone(),
+                                    process_name,
+                                    window_title,
+                                    display_index: displays.display_at(last_mouse_x, last_mouse_y),
+                                    x: None,
+                                    y: None,
+                                    button: None,
+                                    key: Some(shortcut.to_string()),
+                                    text: None,
+                                    delta_x: None,
+                                    delta_y: None,
+                                    content_type,
+                                    content_length,
+                                };
+
+                                if let Ok(mut file_opt) = jsonl_file.lock() {
+                                    if let Some(ref mut writer) = *file_opt {
+                                        if let Ok(json) = serde_json::to_string(&interaction_event) {
+                                            if let Err(e) = writer.write_line(&json) {
+                                                log::error!("Failed to write paste to JSONL: {}", e);
+                                            }
+                                        }
+                                    }
+                                }
+                            }
+                        }
                     }
                     EventType::KeyRelease(key) => {
+                        set_modifier(key, false, &mut ctrl_down, &mut meta_down);
+                        keymap.update(key, false);
                         let key_name = format_key(key);
                         let keyboard_event = KeyboardEvent {
                             timestamp_ms,
@@ -400,18 +696,22 @@ impl InteractionTracker {
                                 task_id: tid.clone(),
                                 process_name,
                                 window_title,
+                                display_index: displays.display_at(last_mouse_x, last_mouse_y),
                                 x: None,
                                 y: None,
                                 button: None,
                                 key: Some(key_name),
+                                text: None,
                                 delta_x: None,
                                 delta_y: None,
+                                content_type: None,
+                                content_length: None,
                             };
 
                             if let Ok(mut file_opt) = jsonl_file.lock() {
                                 if let Some(ref mut writer) = *file_opt {
                                     if let Ok(json) = serde_json::to_string(&interaction_event) {
-                                        if let Err(e) = writeln!(writer, "{}", json) {
+                                        if let Err(e) = writer.write_line(&json) {
                                             log::error!("Failed to write keyrelease to JSONL: {}", e);
                                         }
                                     }
@@ -431,9 +731,19 @@ impl InteractionTracker {
         Ok(handle)
     }
 
+    /// Record a scroll burst still waiting for its debounce window to close
+    fn flush_pending_scroll(&self) {
+        if let Ok(mut pending) = self.pending_scroll.lock() {
+            if let Some(burst) = pending.take() {
+                record_scroll(&burst, &self.mouse_events, &self.jsonl_file, self.task_id.as_deref());
+            }
+        }
+    }
+
     /// Save interaction data to a JSON file
     pub fn save(&self, output_path: &Path) -> Result<()> {
         let duration_ms = self.start_time.elapsed().as_millis() as u64;
+        self.flush_pending_scroll();
 
         let mouse_events = self
             .mouse_events
@@ -461,6 +771,24 @@ impl InteractionTracker {
             .filter(|e| e.event_type == "click")
             .count();
         let total_keyboard_events = keyboard_events.len();
+        let total_paste_events = keyboard_events
+            .iter()
+            .filter(|e| e.event_type == "paste")
+            .count();
+
+        // Flush the live JSONL segment and collect every file written so far
+        let jsonl_files = match self.jsonl_file.lock() {
+            Ok(mut file_opt) => match *file_opt {
+                Some(ref mut writer) => {
+                    if let Err(e) = writer.flush() {
+                        log::warn!("Failed to flush JSONL file: {}", e);
+                    }
+                    writer.segment_names()
+                }
+                None => Vec::new(),
+            },
+            Err(_) => Vec::new(),
+        };
 
         let interaction_data = InteractionData {
             duration_ms,
@@ -473,6 +801,8 @@ impl InteractionTracker {
                 total_mouse_moves,
                 total_mouse_clicks,
                 total_keyboard_events,
+                total_paste_events,
+                jsonl_files,
             },
         };
 
@@ -490,11 +820,274 @@ impl InteractionTracker {
         log::info!("  Mouse movements: {}", total_mouse_moves);
         log::info!("  Mouse clicks: {}", total_mouse_clicks);
         log::info!("  Keyboard events: {}", total_keyboard_events);
+        log::info!("  Paste events: {}", total_paste_events);
+
+        Ok(())
+    }
+
+    /// Save interaction data as a flat CSV: one row per mouse or keyboard event,
+    /// sorted by time, with columns `timestamp_ms,type,x,y,button,key,delta_x,delta_y`
+    pub fn save_csv(&self, output_path: &Path) -> Result<()> {
+        self.flush_pending_scroll();
+
+        let mouse_events = self
+            .mouse_events
+            .lock()
+            .map_err(|e| {
+                ScreenRecError::ConfigError(format!("Failed to lock mouse events: {}", e))
+            })?
+            .clone();
 
+        let keyboard_events = self
+            .keyboard_events
+            .lock()
+            .map_err(|e| {
+                ScreenRecError::ConfigError(format!("Failed to lock keyboard events: {}", e))
+            })?
+            .clone();
+
+        let mut file = BufWriter::new(File::create(output_path)?);
+        let rows = write_csv(&mut file, &mouse_events, &keyboard_events)?;
+        file.flush()?;
+
+        log::info!("Interaction CSV saved to: {:?} ({} rows)", output_path, rows);
         Ok(())
     }
 }
 
+/// Add a finished scroll burst to the in-memory events and, in task mode, the JSONL log
+fn record_scroll(
+    burst: &ScrollBurst,
+    mouse_events: &Mutex<Vec<MouseEvent>>,
+    jsonl_file: &Mutex<Option<JsonlWriter>>,
+    task_id: Option<&str>,
+) {
+    let mouse_event = MouseEvent {
+        timestamp_ms: burst.timestamp_ms,
+        x: burst.x,
+        y: burst.y,
+        event_type: format!("scroll({}, {})", burst.delta_x, burst.delta_y),
+        button: None,
+    };
+    if let Ok(mut events) = mouse_events.lock() {
+        events.push(mouse_event);
+    }
+
+    // Write to JSONL if task-based tracking
+    if let Some(tid) = task_id {
+        let (process_name, window_title) = burst.window.clone().unwrap_or_else(get_active_window_info);
+        let interaction_event = InteractionEvent {
+            event_type: "scroll".to_string(),
+            timestamp: burst.opened_at.to_rfc3339(),
+            timestamp_ms: burst.timestamp_ms,
+            task_id: tid.to_string(),
+            process_name,
+            window_title,
+            display_index: burst.display_index,
+            x: Some(burst.x),
+            y: Some(burst.y),
+            button: None,
+            key: None,
+            text: None,
+            delta_x: Some(burst.delta_x),
+            delta_y: Some(burst.delta_y),
+            content_type: None,
+            content_length: None,
+        };
+
+        if let Ok(mut file_opt) = jsonl_file.lock() {
+            if let Some(ref mut writer) = *file_opt {
+                if let Ok(json) = serde_json::to_string(&interaction_event) {
+                    if let Err(e) = writer.write_line(&json) {
+                        log::error!("Failed to write scroll to JSONL: {}", e);
+                    }
+                }
+            }
+        }
+    }
+}
+
+const CSV_HEADER: &str = "timestamp_ms,type,x,y,button,key,delta_x,delta_y";
+
+/// Write the CSV header and one row per event (ordered by timestamp); returns the row count
+fn write_csv(
+    out: &mut impl Write,
+    mouse_events: &[MouseEvent],
+    keyboard_events: &[KeyboardEvent],
+) -> std::io::Result<usize> {
+    let mut rows: Vec<(u64, String)> = Vec::with_capacity(mouse_events.len() + keyboard_events.len());
+
+    for event in mouse_events {
+        let (event_type, deltas) = match parse_scroll(&event.event_type) {
+            Some((dx, dy)) => ("scroll", format!("{},{}", dx, dy)),
+            None => (event.event_type.as_str(), ",".to_string()),
+        };
+        rows.push((
+            event.timestamp_ms,
+            format!(
+                "{},{},{},{},{},,{}",
+                event.timestamp_ms,
+                csv_field(event_type),
+                event.x,
+                event.y,
+                csv_field(event.button.as_deref().unwrap_or("")),
+                deltas
+            ),
+        ));
+    }
+
+    for event in keyboard_events {
+        // Same type names as the JSONL stream, so key and mouse presses stay distinct
+        let event_type = match event.event_type.as_str() {
+            "press" => "keypress",
+            "release" => "keyrelease",
+            other => other,
+        };
+        rows.push((
+            event.timestamp_ms,
+            format!(
+                "{},{},,,,{},,",
+                event.timestamp_ms,
+                csv_field(event_type),
+                csv_field(&event.key)
+            ),
+        ));
+    }
+
+    // Stable sort keeps each stream's own order for events in the same millisecond
+    rows.sort_by_key(|(timestamp_ms, _)| *timestamp_ms);
+
+    writeln!(out, "{}", CSV_HEADER)?;
+    for (_, row) in &rows {
+        writeln!(out, "{}", row)?;
+    }
+    Ok(rows.len())
+}
+
+/// Decode the `scroll(dx, dy)` event type recorded for wheel events
+fn parse_scroll(event_type: &str) -> Option<(i64, i64)> {
+    let inner = event_type.strip_prefix("scroll(")?.strip_suffix(')')?;
+    let (dx, dy) = inner.split_once(',')?;
+    Some((dx.trim().parse().ok()?, dy.trim().parse().ok()?))
+}
+
+/// Quote a CSV field if it contains a separator, quote or newline (e.g. the "," key)
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Shortest time between two listings of the displays
+const DISPLAY_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Display bounds for tagging events, so they line up with multi-monitor
+/// recordings. Listing displays is a round of OS calls, too slow for the input
+/// hook, so the list is taken when tracking starts and only taken again when a
+/// point lands on no known display, i.e. the layout has changed.
+struct DisplayMap {
+    displays: Vec<DisplayInfo>,
+    listed_at: Instant,
+}
+
+impl DisplayMap {
+    fn new() -> Self {
+        Self { displays: list_displays(), listed_at: Instant::now() }
+    }
+
+    /// Display under a screen position, or the primary one if it is on none
+    fn display_at(&mut self, x: f64, y: f64) -> Option<usize> {
+        let (x, y) = (x as i32, y as i32);
+        if let Some(index) = display_containing(&self.displays, x, y) {
+            return Some(index);
+        }
+        if self.listed_at.elapsed() >= DISPLAY_REFRESH_INTERVAL {
+            log::debug!("({}, {}) is on no known display; listing displays again", x, y);
+            *self = Self::new();
+            if let Some(index) = display_containing(&self.displays, x, y) {
+                return Some(index);
+            }
+        }
+        self.displays.iter().find(|display| display.is_primary).map(|display| display.index)
+    }
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+fn list_displays() -> Vec<DisplayInfo> {
+    crate::display_info::get_all_displays_with_bounds().unwrap_or_else(|e| {
+        log::warn!("Could not list displays for interaction events: {}", e);
+        Vec::new()
+    })
+}
+
+/// Display lookup isn't available on this platform; the field is left out
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn list_displays() -> Vec<DisplayInfo> {
+    Vec::new()
+}
+
+/// Minimum time between recorded mouse moves for a sampling rate, or None (0 Hz)
+/// to record every move
+fn move_sample_interval(move_sample_hz: u32) -> Option<Duration> {
+    (move_sample_hz > 0).then(|| Duration::from_secs_f64(1.0 / move_sample_hz as f64))
+}
+
+/// Lightweight input listener that only remembers when the user was last active.
+///
+/// Used by modes that need an idle signal (e.g. activity-only screenshots)
+/// without buffering every interaction event in memory.
+#[derive(Clone)]
+pub struct ActivityMonitor {
+    start_time: Arc<Instant>,
+    last_activity_ms: Arc<AtomicU64>,
+}
+
+impl Default for ActivityMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ActivityMonitor {
+    pub fn new() -> Self {
+        Self {
+            start_time: Arc::new(Instant::now()),
+            last_activity_ms: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Start listening for input events
+    pub fn start(&self) -> Result<std::thread::JoinHandle<()>> {
+        let start_time = Arc::clone(&self.start_time);
+        let last_activity_ms = Arc::clone(&self.last_activity_ms);
+
+        log::info!("Starting activity monitoring...");
+
+        let handle = std::thread::spawn(move || {
+            let callback = move |event: Event| {
+                if let EventType::MouseMove { x, y } = event.event_type {
+                    crate::capture::update_cursor_position(x as i32, y as i32);
+                }
+                last_activity_ms.store(start_time.elapsed().as_millis() as u64, Ordering::Relaxed);
+            };
+
+            if let Err(error) = listen(callback) {
+                log::error!("Error listening for events: {:?}", error);
+            }
+        });
+
+        Ok(handle)
+    }
+
+    /// Time since the last input event (or since the monitor was created)
+    pub fn idle_for(&self) -> Duration {
+        let last = Duration::from_millis(self.last_activity_ms.load(Ordering::Relaxed));
+        self.start_time.elapsed().saturating_sub(last)
+    }
+}
+
 /// Get current mouse position (platform-independent)
 #[allow(dead_code)]
 fn get_mouse_position() -> Option<(f64, f64)> {
@@ -539,15 +1132,21 @@ fn get_active_window_info() -> (String, String) {
     };
 
     log::debug!("get_active_window_info: Windows version called (WINDOWS_NATIVE_API)");
-    unsafe {
-        // Get the foreground window
-        let hwnd = GetForegroundWindow();
-        if hwnd.0 == 0 {
-            log::debug!("GetForegroundWindow returned NULL");
-            return ("Unknown".to_string(), "".to_string());
-        }
-        log::debug!("Got foreground window handle: {:?}", hwnd);
+    // Get the foreground window
+    let hwnd = unsafe { GetForegroundWindow() };
+    if hwnd.0 == 0 {
+        log::debug!("GetForegroundWindow returned NULL");
+        return ("Unknown".to_string(), "".to_string());
+    }
+    log::debug!("Got foreground window handle: {:?}", hwnd);
 
+    get_window_info(hwnd)
+}
+
+/// Get process name and window title for a specific window handle
+#[cfg(target_os = "windows")]
+pub(crate) fn get_window_info(hwnd: HWND) -> (String, String) {
+    unsafe {
         // Get window title
         let mut title_buffer = [0u16; 512];
         let title_len = GetWindowTextW(hwnd, &mut title_buffer);
@@ -614,12 +1213,221 @@ fn get_active_window_info() -> (String, String) {
     }
 }
 
-#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+/// X11 connection and the atoms the active-window lookup reads, opened once
+#[cfg(target_os = "linux")]
+struct X11Session {
+    conn: x11rb::rust_connection::RustConnection,
+    root: u32,
+    net_active_window: u32,
+    net_wm_name: u32,
+    net_wm_pid: u32,
+    utf8_string: u32,
+}
+
+#[cfg(target_os = "linux")]
+static X11_SESSION: std::sync::OnceLock<Option<X11Session>> = std::sync::OnceLock::new();
+
+#[cfg(target_os = "linux")]
+impl X11Session {
+    fn connect() -> Option<Self> {
+        use x11rb::connection::Connection;
+        use x11rb::protocol::xproto::ConnectionExt;
+
+        let (conn, screen_num) = match x11rb::connect(None) {
+            Ok(connection) => connection,
+            Err(e) => {
+                log::warn!("⚠️  No X11 connection ({}); window titles will be empty", e);
+                return None;
+            }
+        };
+        let root = conn.setup().roots.get(screen_num)?.root;
+        let atom = |name: &[u8]| conn.intern_atom(false, name).ok()?.reply().ok().map(|reply| reply.atom);
+
+        Some(Self {
+            net_active_window: atom(b"_NET_ACTIVE_WINDOW")?,
+            net_wm_name: atom(b"_NET_WM_NAME")?,
+            net_wm_pid: atom(b"_NET_WM_PID")?,
+            utf8_string: atom(b"UTF8_STRING")?,
+            root,
+            conn,
+        })
+    }
+
+    /// Raw value of a window property, if the window has it
+    fn property(&self, window: u32, property: u32, kind: u32, max_len: u32) -> Option<x11rb::protocol::xproto::GetPropertyReply> {
+        use x11rb::protocol::xproto::ConnectionExt;
+
+        let reply = self.conn.get_property(false, window, property, kind, 0, max_len).ok()?.reply().ok()?;
+        (!reply.value.is_empty()).then_some(reply)
+    }
+
+    /// Process name and title of the window the EWMH `_NET_ACTIVE_WINDOW` hint
+    /// points at. The title is `_NET_WM_NAME` (UTF-8) or the legacy `WM_NAME`;
+    /// the process name is `/proc/<_NET_WM_PID>/comm`.
+    fn active_window(&self) -> Option<(String, String)> {
+        use x11rb::protocol::xproto::AtomEnum;
+
+        let window = self
+            .property(self.root, self.net_active_window, AtomEnum::WINDOW.into(), 1)?
+            .value32()?
+            .next()
+            .filter(|&window| window != 0)?;
+
+        let window_title = self
+            .property(window, self.net_wm_name, self.utf8_string, 1024)
+            .or_else(|| self.property(window, AtomEnum::WM_NAME.into(), AtomEnum::ANY.into(), 1024))
+            .map(|reply| String::from_utf8_lossy(&reply.value).into_owned())
+            .unwrap_or_default();
+
+        let process_name = self
+            .property(window, self.net_wm_pid, AtomEnum::CARDINAL.into(), 1)
+            .and_then(|reply| reply.value32()?.next())
+            .and_then(|pid| std::fs::read_to_string(format!("/proc/{}/comm", pid)).ok())
+            .map(|comm| comm.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .unwrap_or_else(|| "Unknown".to_string());
+
+        log::debug!("Returning: process='{}', title='{}'", process_name, window_title);
+        Some((process_name, window_title))
+    }
+}
+
+#[cfg(target_os = "linux")]
 fn get_active_window_info() -> (String, String) {
-    log::debug!("get_active_window_info: fallback version called (not macOS or Windows)");
+    log::debug!("get_active_window_info: Linux (X11) version called");
+    X11_SESSION
+        .get_or_init(X11Session::connect)
+        .as_ref()
+        .and_then(X11Session::active_window)
+        .unwrap_or_else(|| ("Unknown".to_string(), "".to_string()))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+fn get_active_window_info() -> (String, String) {
+    log::debug!("get_active_window_info: fallback version called (not macOS, Windows or Linux)");
     ("Unknown".to_string(), "".to_string())
 }
 
+/// Track Ctrl/Cmd state from key press and release events
+fn set_modifier(key: Key, pressed: bool, ctrl_down: &mut bool, meta_down: &mut bool) {
+    match key {
+        Key::ControlLeft | Key::ControlRight => *ctrl_down = pressed,
+        Key::MetaLeft | Key::MetaRight => *meta_down = pressed,
+        _ => {}
+    }
+}
+
+/// Modifier state for turning key presses into typed characters (`--capture-text`).
+///
+/// Uses a US layout on top of `format_key`. Caps Lock is assumed off when
+/// tracking starts and toggles on every Caps Lock press.
+#[derive(Debug, Default)]
+struct TextKeymap {
+    shift: bool,
+    alt: bool,
+    caps_lock: bool,
+}
+
+impl TextKeymap {
+    fn update(&mut self, key: Key, pressed: bool) {
+        match key {
+            Key::ShiftLeft | Key::ShiftRight => self.shift = pressed,
+            Key::Alt | Key::AltGr => self.alt = pressed,
+            Key::CapsLock if pressed => self.caps_lock = !self.caps_lock,
+            _ => {}
+        }
+    }
+
+    /// Character a key press types, or None for non-printing keys and Alt combinations.
+    /// Letters follow Shift xor Caps Lock; digits and punctuation follow Shift only.
+    fn typed_char(&self, key: Key) -> Option<char> {
+        if self.alt {
+            return None;
+        }
+        if key == Key::Space {
+            return Some(' ');
+        }
+
+        // format_key names printable keys by their unshifted character
+        let name = format_key(key);
+        let mut chars = name.chars();
+        let (Some(c), None) = (chars.next(), chars.next()) else {
+            return None;
+        };
+
+        if c.is_ascii_alphabetic() {
+            let upper = self.shift != self.caps_lock;
+            return Some(if upper { c.to_ascii_uppercase() } else { c.to_ascii_lowercase() });
+        }
+        if !self.shift {
+            return Some(c);
+        }
+        Some(match c {
+            '`' => '~',
+            '1' => '!',
+            '2' => '@',
+            '3' => '#',
+            '4' => '$',
+            '5' => '%',
+            '6' => '^',
+            '7' => '&',
+            '8' => '*',
+            '9' => '(',
+            '0' => ')',
+            '-' => '_',
+            '=' => '+',
+            '[' => '{',
+            ']' => '}',
+            '\\' => '|',
+            ';' => ':',
+            '\'' => '"',
+            ',' => '<',
+            '.' => '>',
+            '/' => '?',
+            other => other,
+        })
+    }
+}
+
+/// The platform paste shortcut name if `key` completes it (Cmd+V on macOS, Ctrl+V elsewhere)
+fn paste_shortcut(key: Key, ctrl_down: bool, meta_down: bool) -> Option<&'static str> {
+    if key != Key::KeyV {
+        return None;
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let _ = ctrl_down;
+        meta_down.then_some("Cmd+V")
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = meta_down;
+        ctrl_down.then_some("Ctrl+V")
+    }
+}
+
+/// Describe what is on the clipboard without keeping it: the content type and,
+/// for text, its length in characters. The content itself is never stored.
+fn clipboard_summary() -> (&'static str, Option<usize>) {
+    let mut clipboard = match arboard::Clipboard::new() {
+        Ok(clipboard) => clipboard,
+        Err(e) => {
+            log::debug!("Clipboard unavailable: {}", e);
+            return ("unavailable", None);
+        }
+    };
+
+    if let Ok(text) = clipboard.get_text() {
+        return ("text", Some(text.chars().count()));
+    }
+    if clipboard.get_image().is_ok() {
+        return ("image", None);
+    }
+    ("other", None)
+}
+
 /// Format a key for display
 fn format_key(key: Key) -> String {
     match key {
@@ -731,3 +1539,148 @@ fn format_key(key: Key) -> String {
         Key::Unknown(code) => format!("Unknown({})", code),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_jsonl_rotates_and_resumes_at_the_newest_segment() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join("interactions.jsonl");
+        let line = "x".repeat(9); // 10 bytes with the newline
+
+        let mut writer = JsonlWriter::open(base.clone(), Some(25)).unwrap();
+        for _ in 0..5 {
+            writer.write_line(&line).unwrap();
+        }
+        writer.flush().unwrap();
+        assert_eq!(writer.segment_names(), ["interactions.jsonl", "interactions.1.jsonl", "interactions.2.jsonl"]);
+        let size = |name: &str| std::fs::metadata(dir.path().join(name)).unwrap().len();
+        assert_eq!((size("interactions.jsonl"), size("interactions.1.jsonl"), size("interactions.2.jsonl")), (20, 20, 10));
+        drop(writer);
+
+        // The next session fills up the last segment, then moves on to a new one
+        let mut writer = JsonlWriter::open(base, Some(25)).unwrap();
+        writer.write_line(&line).unwrap();
+        writer.write_line(&line).unwrap();
+        writer.flush().unwrap();
+        assert_eq!(writer.segment_names().last().unwrap(), "interactions.3.jsonl");
+        assert_eq!((size("interactions.jsonl"), size("interactions.2.jsonl"), size("interactions.3.jsonl")), (20, 20, 10));
+    }
+
+    #[test]
+    fn test_paste_shortcut_needs_platform_modifier() {
+        let (ctrl, meta) = if cfg!(target_os = "macos") { (false, true) } else { (true, false) };
+
+        assert!(paste_shortcut(Key::KeyV, ctrl, meta).is_some());
+        assert!(paste_shortcut(Key::KeyV, false, false).is_none());
+        assert!(paste_shortcut(Key::KeyC, ctrl, meta).is_none());
+        // The other platform's modifier alone is not a paste
+        assert!(paste_shortcut(Key::KeyV, !ctrl, !meta).is_none());
+    }
+
+    #[test]
+    fn test_text_keymap_resolves_shift_and_caps_lock() {
+        let mut keymap = TextKeymap::default();
+        assert_eq!(keymap.typed_char(Key::KeyA), Some('a'));
+        assert_eq!(keymap.typed_char(Key::Num1), Some('1'));
+        assert_eq!(keymap.typed_char(Key::Space), Some(' '));
+        assert_eq!(keymap.typed_char(Key::Return), None);
+        assert_eq!(keymap.typed_char(Key::Kp1), None);
+
+        keymap.update(Key::ShiftLeft, true);
+        assert_eq!(keymap.typed_char(Key::KeyA), Some('A'));
+        assert_eq!(keymap.typed_char(Key::Num1), Some('!'));
+        assert_eq!(keymap.typed_char(Key::Quote), Some('"'));
+        keymap.update(Key::ShiftLeft, false);
+
+        // Caps Lock toggles letters only, and Shift inverts it again
+        keymap.update(Key::CapsLock, true);
+        keymap.update(Key::CapsLock, false);
+        assert_eq!(keymap.typed_char(Key::KeyA), Some('A'));
+        assert_eq!(keymap.typed_char(Key::Slash), Some('/'));
+        keymap.update(Key::ShiftRight, true);
+        assert_eq!(keymap.typed_char(Key::KeyA), Some('a'));
+        assert_eq!(keymap.typed_char(Key::Slash), Some('?'));
+        keymap.update(Key::ShiftRight, false);
+
+        keymap.update(Key::Alt, true);
+        assert_eq!(keymap.typed_char(Key::KeyA), None);
+    }
+
+    #[test]
+    fn test_display_map_falls_back_to_the_primary_display() {
+        let display = |index, x, is_primary| DisplayInfo {
+            index,
+            width: 1920,
+            height: 1080,
+            x,
+            y: 0,
+            is_primary,
+            scale_factor: 1.0,
+        };
+        let mut displays = DisplayMap {
+            displays: vec![display(0, -1920, false), display(1, 0, true)],
+            listed_at: Instant::now(),
+        };
+        assert_eq!(displays.display_at(-5.0, 10.0), Some(0));
+        assert_eq!(displays.display_at(1919.5, 1079.0), Some(1));
+        // Off every display, and listed too recently to list again
+        assert_eq!(displays.display_at(5000.0, 10.0), Some(1));
+        assert_eq!(displays.displays.len(), 2);
+    }
+
+    #[test]
+    fn test_scroll_burst_sums_until_quiet_or_reversed() {
+        let before = Utc::now();
+        let window = Some(("code".to_string(), "main.rs".to_string()));
+        let mut burst = ScrollBurst::new(1000, 10.0, 20.0, Some(0), window.clone(), 0, -2);
+        for (i, delta_y) in [-3, -1, -4].into_iter().enumerate() {
+            assert!(!burst.reverses(0, delta_y));
+            burst.add(1000 + 16 * (i as u64 + 1), 0, delta_y);
+        }
+
+        // Summed deltas, first event's time and position
+        assert_eq!((burst.timestamp_ms, burst.x, burst.y, burst.delta_x, burst.delta_y), (1000, 10.0, 20.0, 0, -10));
+        // The first event's wall-clock time and window too, however late the burst closes
+        assert!(burst.opened_at >= before && burst.opened_at <= Utc::now());
+        assert_eq!(burst.window, window);
+        assert!(!burst.is_finished(1048 + 99, 100));
+        assert!(burst.is_finished(1048 + 100, 100));
+
+        // Scrolling back up, or sideways the other way, starts a new burst;
+        // a sideways nudge alongside a vertical scroll does not
+        assert!(burst.reverses(0, 1));
+        assert!(!burst.reverses(2, -1));
+        burst.add(1064, 2, -1);
+        assert!(burst.reverses(-1, 0));
+    }
+
+    #[test]
+    fn test_csv_rows_are_flat_and_time_ordered() {
+        let mouse_events = vec![
+            MouseEvent { timestamp_ms: 10, x: 5.0, y: 6.5, event_type: "click".to_string(), button: Some("left".to_string()) },
+            MouseEvent { timestamp_ms: 40, x: 5.0, y: 6.5, event_type: "scroll(0, -3)".to_string(), button: None },
+        ];
+        let keyboard_events = vec![
+            KeyboardEvent { timestamp_ms: 20, key: ",".to_string(), event_type: "press".to_string() },
+            KeyboardEvent { timestamp_ms: 30, key: "A".to_string(), event_type: "release".to_string() },
+        ];
+
+        let mut out = Vec::new();
+        assert_eq!(write_csv(&mut out, &mouse_events, &keyboard_events).unwrap(), 4);
+        let csv = String::from_utf8(out).unwrap();
+
+        assert_eq!(
+            csv.lines().collect::<Vec<_>>(),
+            [
+                CSV_HEADER,
+                "10,click,5,6.5,left,,,",
+                "20,keypress,,,,\",\",,",
+                "30,keyrelease,,,,A,,",
+                "40,scroll,5,6.5,,,0,-3",
+            ]
+        );
+    }
+}
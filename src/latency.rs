@@ -0,0 +1,133 @@
+//! Capture-to-encode latency, for telling a slow encoder from a slow capture
+//!
+//! Every `SAMPLE_EVERY`th frame the encoder task records how long ago the frame
+//! was captured when `encode_frame` returned, along with how many frames were
+//! still queued behind it. Every `LOG_INTERVAL` the p50/p95 of the samples since
+//! the last log line are printed; the whole run's figures go into the
+//! end-of-run summary next to the achieved FPS. Latency that keeps growing
+//! with a full queue means the encoder can't keep up (try a faster `--preset`);
+//! short spikes with a mostly empty queue are what `--buffer-frames` absorbs.
+
+use chrono::{DateTime, Utc};
+use std::time::{Duration, Instant};
+
+/// Frames between latency samples
+const SAMPLE_EVERY: u64 = 10;
+const LOG_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Latency over a whole recording
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+pub struct LatencyStats {
+    pub samples: usize,
+    pub p50_ms: u32,
+    pub p95_ms: u32,
+    pub max_ms: u32,
+    /// Most frames seen waiting in the encoder queue at a sample
+    pub peak_queued_frames: usize,
+}
+
+pub struct EncodeLatency {
+    /// Frames to pass over before the next sample
+    countdown: u64,
+    /// Every sample of the run, in milliseconds
+    samples: Vec<u32>,
+    /// Where the samples since the last log line start
+    window_start: usize,
+    peak_queued_frames: usize,
+    last_log: Instant,
+}
+
+impl Default for EncodeLatency {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EncodeLatency {
+    pub fn new() -> Self {
+        Self {
+            countdown: 0,
+            samples: Vec::new(),
+            window_start: 0,
+            peak_queued_frames: 0,
+            last_log: Instant::now(),
+        }
+    }
+
+    /// Call once a frame captured at `captured_at` is encoded, with the number of
+    /// frames still waiting in the queue
+    pub fn frame_encoded(&mut self, captured_at: DateTime<Utc>, queued_frames: usize) {
+        if self.countdown > 0 {
+            self.countdown -= 1;
+            return;
+        }
+        self.countdown = SAMPLE_EVERY - 1;
+
+        let latency_ms = (Utc::now() - captured_at).num_milliseconds().clamp(0, u32::MAX as i64) as u32;
+        self.samples.push(latency_ms);
+        self.peak_queued_frames = self.peak_queued_frames.max(queued_frames);
+
+        if self.last_log.elapsed() >= LOG_INTERVAL {
+            let mut window = self.samples[self.window_start..].to_vec();
+            window.sort_unstable();
+            log::info!(
+                "⏱️  Capture-to-encode latency: p50 {}ms, p95 {}ms ({} frames queued)",
+                percentile(&window, 50),
+                percentile(&window, 95),
+                queued_frames
+            );
+            self.window_start = self.samples.len();
+            self.last_log = Instant::now();
+        }
+    }
+
+    /// Figures for the whole run, or None if no frame was sampled
+    pub fn stats(&self) -> Option<LatencyStats> {
+        let mut sorted = self.samples.clone();
+        sorted.sort_unstable();
+        let max_ms = *sorted.last()?;
+        Some(LatencyStats {
+            samples: sorted.len(),
+            p50_ms: percentile(&sorted, 50),
+            p95_ms: percentile(&sorted, 95),
+            max_ms,
+            peak_queued_frames: self.peak_queued_frames,
+        })
+    }
+}
+
+/// Nearest-rank percentile of already sorted samples (0 when there are none)
+fn percentile(sorted: &[u32], pct: usize) -> u32 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = (sorted.len() * pct).div_ceil(100).max(1);
+    sorted[rank - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_every_tenth_frame_is_sampled_into_percentiles() {
+        let sorted: Vec<u32> = (1..=20).collect();
+        assert_eq!(percentile(&sorted, 50), 10);
+        assert_eq!(percentile(&sorted, 95), 19);
+        assert_eq!(percentile(&[7], 95), 7);
+        assert_eq!(percentile(&[], 50), 0);
+
+        let mut latency = EncodeLatency::new();
+        assert!(latency.stats().is_none());
+        let captured_at = Utc::now() - chrono::Duration::milliseconds(200);
+        for queued in 0..25 {
+            latency.frame_encoded(captured_at, queued);
+        }
+
+        // Frames 0, 10 and 20 were sampled
+        let stats = latency.stats().unwrap();
+        assert_eq!(stats.samples, 3);
+        assert_eq!(stats.peak_queued_frames, 20);
+        assert!(stats.p50_ms >= 200 && stats.p50_ms <= stats.p95_ms && stats.p95_ms <= stats.max_ms);
+    }
+}
@@ -0,0 +1,62 @@
+//! Screen recording with chunked video, audio and interaction tracking
+//!
+//! This is the library behind the `omgrec` binary. To record from your own
+//! program, use [`Recorder`]:
+//!
+//! ```no_run
+//! # #[tokio::main]
+//! # async fn main() -> screenrec::error::Result<()> {
+//! use screenrec::cli::Codec;
+//! use screenrec::Recorder;
+//!
+//! let recording = Recorder::new().fps(30).quality(8).codec(Codec::Hevc).output("recordings").start()?;
+//! println!("{} frames so far", recording.status().frames_encoded);
+//! let summary = recording.stop().await?;
+//! println!("Wrote {} chunk(s)", summary.chunks.len());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The other modules are what the CLI is built from; they are public so the
+//! binary can use them, and are less stable than [`Recorder`].
+
+pub mod adaptive;
+pub mod audio;
+pub mod capture;
+pub mod cli;
+pub mod config;
+mod cursor;
+pub mod db;
+pub mod display_info;
+pub mod encoder;
+#[cfg(target_os = "macos")]
+mod encoder_subprocess;
+pub mod encryption;
+pub mod error;
+pub mod export;
+pub mod ffmpeg_utils;
+pub mod frame_dump;
+pub mod frame_log;
+pub mod frame_queue;
+pub mod integrity;
+pub mod interactions;
+pub mod latency;
+mod mask;
+pub mod overlay;
+pub mod progress;
+pub mod recorder;
+pub mod retention;
+pub mod scaling;
+pub mod screenshot;
+pub mod session;
+pub mod setup;
+pub mod status;
+pub mod subtitles;
+pub mod timeline;
+pub mod transcode;
+pub mod transform;
+pub mod window_capture;
+#[cfg(not(target_os = "macos"))]
+mod yuv;
+
+pub use recorder::{Recorder, RecordingHandle, RecordingOptions, RecordingSummary, StopHandle};
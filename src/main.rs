@@ -1,52 +1,157 @@
-#[cfg(target_os = "macos")]
-mod audio;
-mod capture;
-mod cli;
-mod db;
-mod display_info;
-mod encoder;
-#[cfg(target_os = "macos")]
-mod encoder_subprocess;
-mod error;
-mod ffmpeg_utils;
-mod interactions;
-mod screenshot;
-
-#[cfg(target_os = "macos")]
-use crate::audio::AudioCapture;
-use crate::capture::ScreenCapture;
-use crate::cli::{Cli, Commands, RecordingType};
-use crate::db::Database;
-use crate::error::{Result, ScreenRecError};
-use crate::interactions::InteractionTracker;
-use clap::Parser;
-use std::sync::mpsc as std_mpsc;
+use screenrec::capture::{ScreenCapture, MAX_HIGH_FPS, MAX_STANDARD_FPS};
+use screenrec::cli::{Cli, Commands, RecordingType};
+use screenrec::db::Database;
+use screenrec::error::{Result, ScreenRecError};
+use screenrec::interactions::{ActivityMonitor, InteractionTracker};
+use screenrec::recorder::{RecordingHandle, RecordingOptions, DEFAULT_BUFFER_FRAMES};
+use screenrec::{
+    adaptive, audio, cli, config, display_info, encoder, encryption, error, export, ffmpeg_utils, frame_dump, frame_log,
+    integrity, overlay, progress, retention, scaling, screenshot, session, setup, status, subtitles, timeline, transcode,
+    transform, window_capture,
+};
+use clap::{CommandFactory, FromArgMatches};
 use std::sync::Arc;
-use tokio::sync::mpsc;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Parse command line arguments
-    let cli = Cli::parse();
-
-    // Initialize logger
-    let log_level = if cli.verbose { "debug" } else { "info" };
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level)).init();
+    // Parse command line arguments, keeping the matches to tell typed flags from defaults
+    let matches = Cli::command().get_matches();
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    // Initialize logger (safe mode always logs verbosely for troubleshooting)
+    let safe_mode = matches!(cli.command, Commands::Record { safe_mode: true, .. });
+    let log_level = if cli.verbose || safe_mode { "debug" } else { "info" };
+    let mut logger = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(log_level));
+    if cli.log_format == cli::LogFormat::Json {
+        logger.format(|buf, record| {
+            use std::io::Write;
+            let line = serde_json::json!({
+                "timestamp": chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+                "level": record.level().as_str(),
+                "target": record.target(),
+                "message": record.args().to_string(),
+            });
+            writeln!(buf, "{}", line)
+        });
+    }
+    logger.init();
 
     log::info!("🎯 Omega Focus Screen Recorder v0.1.0");
     log::info!("================================================");
 
+    // Fill record flags left at their defaults from the config file (CLI > file > built-in)
+    let mut from_config = Vec::new();
+    if let Commands::Record { config: config_path, print_config, .. } = &cli.command {
+        let print_config = *print_config;
+        let (defaults, source) = config::load(config_path.as_deref())?;
+        from_config = config::apply(&mut cli.command, &matches, &defaults);
+        if print_config {
+            print!("{}", config::render(&cli.command, source.as_deref())?);
+            return Ok(());
+        }
+        if let Some(source) = source {
+            log::info!("Using defaults from {}", source.display());
+        }
+    }
+
+    if let Some(path) = &cli.progress_json {
+        progress::init(path)?;
+    }
+
     // Execute the requested command
     match cli.command {
-        Commands::Screenshot { output, display } => {
-            log::info!("Taking screenshot...");
-            screenshot::capture_screenshot(&output, display)?;
-            println!("✅ Screenshot saved to: {}", output.display());
+        Commands::Screenshot { output, display, all_displays, interval, count, format, quality, activity_only, idle_threshold } => {
+            if quality.is_some_and(|q| !(1..=100).contains(&q)) {
+                return Err(ScreenRecError::InvalidParameter(
+                    "Screenshot quality must be between 1 and 100".to_string(),
+                ));
+            }
+
+            if let Some(interval) = interval {
+                if interval <= 0.0 {
+                    return Err(ScreenRecError::InvalidParameter(
+                        "Screenshot interval must be greater than 0".to_string(),
+                    ));
+                }
+                if count == Some(0) {
+                    return Err(ScreenRecError::InvalidParameter(
+                        "Screenshot count must be greater than 0".to_string(),
+                    ));
+                }
+
+                let activity = if activity_only {
+                    let monitor = ActivityMonitor::new();
+                    monitor.start()?;
+                    Some((monitor, std::time::Duration::from_secs(idle_threshold)))
+                } else {
+                    None
+                };
+
+                let running = Arc::new(std::sync::atomic::AtomicBool::new(true));
+                let r = running.clone();
+                ctrlc::set_handler(move || {
+                    log::info!("Received Ctrl+C, stopping screenshots...");
+                    r.store(false, std::sync::atomic::Ordering::SeqCst);
+                })
+                .map_err(|e| ScreenRecError::ConfigError(format!("Failed to set Ctrl+C handler: {}", e)))?;
+
+                log::info!("Taking a screenshot every {}s...", interval);
+                let taken = screenshot::watch_screenshots(
+                    &output,
+                    display,
+                    all_displays,
+                    std::time::Duration::from_secs_f64(interval),
+                    count,
+                    format,
+                    quality,
+                    activity.as_ref().map(|(m, idle)| (m, *idle)),
+                    running,
+                )?;
+                println!("✅ {} screenshot(s) saved to: {}", taken, output.display());
+            } else {
+                log::info!("Taking screenshot...");
+                if all_displays {
+                    screenshot::capture_all_displays_screenshot(&output, quality)?;
+                } else {
+                    screenshot::capture_screenshot(&output, display, quality)?;
+                }
+                println!("✅ Screenshot saved to: {}", output.display());
+            }
         }
 
-        Commands::Concat { task_id, output, ffmpeg_path } => {
+        Commands::Concat {
+            task_id,
+            output,
+            ffmpeg_path,
+            raw_concat,
+            dry_run,
+            probe_concurrency,
+            deep_validate,
+            verify,
+            verify_reference,
+        } => {
             log::info!("Concatenating chunks for task_id: {}", task_id);
-            concatenate_chunks(&task_id, output, ffmpeg_path).await?;
+            if probe_concurrency == Some(0) {
+                return Err(ScreenRecError::InvalidParameter("--probe-concurrency must be at least 1".to_string()));
+            }
+            let probe_concurrency = probe_concurrency
+                .unwrap_or_else(|| std::thread::available_parallelism().map_or(4, |n| n.get()));
+            let options = ConcatOptions {
+                output_path: output,
+                ffmpeg_path,
+                raw_concat,
+                deep_validate,
+                verify,
+                verify_reference,
+                dry_run,
+                probe_concurrency,
+            };
+            concatenate_chunks(&task_id, options).await?;
+        }
+
+        Commands::MergeAudio { task_id, output, ffmpeg_path } => {
+            log::info!("Merging audio for task_id: {}", task_id);
+            merge_audio(&task_id, output, ffmpeg_path).await?;
         }
 
         Commands::InspectSessions { task_id } => {
@@ -54,23 +159,169 @@ async fn main() -> Result<()> {
             inspect_sessions(&task_id).await?;
         }
 
+        Commands::Doctor { task_id, fix, ffmpeg_path } => {
+            doctor(&task_id, fix, ffmpeg_path).await?;
+        }
+
+        Commands::VerifyIntegrity { task_id } => {
+            verify_integrity(&task_id).await?;
+        }
+
+        Commands::Decrypt { task_id, key_file } => {
+            decrypt_task(&task_id, key_file).await?;
+        }
+
+        Commands::Timeline { task_id, date, gap_threshold, output } => {
+            show_timeline(task_id, date, gap_threshold, output).await?;
+        }
+
+        Commands::ExportSubtitles { task_id, output } => {
+            export_subtitles(&task_id, output).await?;
+        }
+
+        Commands::ToGif { input, task_id, output, start, duration, fps, width, format, ffmpeg_path } => {
+            let format = format
+                .or_else(|| cli::AnimationFormat::from_path(&output))
+                .ok_or_else(|| {
+                    ScreenRecError::InvalidParameter(
+                        "Cannot tell the format from the output extension; use .gif/.png or pass --format".to_string(),
+                    )
+                })?;
+
+            let input = match (input, task_id) {
+                (Some(input), _) => input,
+                (None, Some(task_id)) => {
+                    let video_path = task_output_dir(&task_id).await?.join("final.mp4");
+                    if !video_path.exists() {
+                        return Err(ScreenRecError::InvalidParameter(format!(
+                            "{} not found. Run `omgrec concat --task-id {}` first.",
+                            video_path.display(),
+                            task_id
+                        )));
+                    }
+                    video_path
+                }
+                (None, None) => {
+                    return Err(ScreenRecError::InvalidParameter("Pass --input or --task-id".to_string()))
+                }
+            };
+
+            let ffmpeg_binary = ffmpeg_utils::find_ffmpeg_binary(ffmpeg_path.as_ref())?;
+            log::info!("Exporting {} to {} ({} @ {}fps)", input.display(), output.display(), format, fps);
+            let width = (width > 0).then_some(width);
+            export::export_animation(&ffmpeg_binary, &input, &output, format, fps, width, start, duration)?;
+            println!("✅ Animation saved to: {}", output.display());
+        }
+
+        Commands::Transcode { input, output, crf, scale, codec, ffmpeg_path } => {
+            let ffmpeg_binary = ffmpeg_utils::find_ffmpeg_binary(ffmpeg_path.as_ref())?;
+            ffmpeg_utils::validate_ffmpeg(&ffmpeg_binary)?;
+            log::info!("Transcoding {} to {} ({}, crf {})", input.display(), output.display(), codec, crf);
+            transcode::transcode(&ffmpeg_binary, &input, &output, codec, crf, scale.as_deref())?;
+            println!("✅ Transcoded video saved to: {}", output.display());
+        }
+
+        Commands::Probe { input, json, ffmpeg_path } => {
+            probe(&input, json, ffmpeg_path.as_ref())?;
+        }
+
+        Commands::Cleanup { older_than_days, dry_run } => {
+            let omega_dir = dirs::home_dir()
+                .ok_or_else(|| ScreenRecError::ConfigError("Could not find home directory".to_string()))?
+                .join(".omega");
+            let db = Database::new(omega_dir.join("db.sqlite")).await?;
+
+            let report = retention::cleanup(&db, &omega_dir.join("data"), older_than_days, dry_run).await?;
+            println!(
+                "📊 {}{} file(s), {} freed, {} database row(s), {} empty task directories",
+                if dry_run { "Dry run, would remove: " } else { "Removed: " },
+                report.files_removed,
+                cli::ByteSize(report.bytes_freed),
+                report.rows_removed,
+                report.dirs_removed
+            );
+        }
+
+        Commands::Setup { config, yes } => {
+            setup::run_setup(config, yes)?;
+        }
+
+        Commands::ListDevices { json } => {
+            list_devices(json)?;
+        }
+
         Commands::Record {
             output,
             duration,
+            start_delay,
+            start_at,
             fps,
+            allow_high_fps,
             audio,
             no_audio,
             width,
             height,
             display,
             quality,
+            bitrate,
+            crf,
+            preset,
+            quality_preset,
+            keyframe_interval,
+            lossless,
+            colorspace,
+            max_encode_dimension,
             track_interactions,
             track_mouse_moves,
+            move_sample_hz,
+            scroll_debounce_ms,
+            interactions_format,
+            capture_clipboard,
+            capture_text,
             recording_type,
             task_id,
             chunk_duration,
+            chunk_size,
+            chunk_name_pattern,
+            max_disk_usage,
+            encrypt,
+            key_file,
+            frames_jsonl,
+            dump_frames,
+            dump_every,
+            frame_format,
             monitor_switch_interval,
+            multi_monitor_mode,
+            window_title,
+            window_id,
+            region,
+            mask,
+            mask_mode,
+            no_cursor,
+            rotate,
+            flip,
+            dedup_threshold,
+            overlay_timestamp,
+            overlay_position,
             ffmpeg_path,
+            codec,
+            encoder: encoder_name,
+            list_encoders,
+            mp4_layout,
+            interactions_max_size_mb,
+            frame_buffer_bytes,
+            buffer_frames,
+            on_overflow,
+            adaptive_quality,
+            min_quality,
+            max_quality,
+            scale_filter,
+            safe_mode,
+            audio_reconnect_attempts,
+            audio_device,
+            audio_channels,
+            status_port,
+            ..
         } => {
             // Handle no_audio flag
             let audio = if no_audio {
@@ -79,6 +330,157 @@ async fn main() -> Result<()> {
                 audio
             };
 
+            if !(1..=2).contains(&audio_channels) {
+                return Err(ScreenRecError::InvalidParameter(format!(
+                    "--audio-channels must be 1 or 2 (got {})",
+                    audio_channels
+                )));
+            }
+
+            // Resolve --audio-device now so a typo fails before anything is recorded
+            let audio_device = match audio_device {
+                Some(name) if audio != cli::AudioSource::None => Some(audio::resolve_input_device(&name)?),
+                _ => None,
+            };
+
+            // A quality preset fills in the encoding flags that weren't typed on the command line
+            // or set in the config file
+            let (codec, bitrate, crf, quality, preset, tuning) = match quality_preset {
+                Some(quality_preset) => {
+                    let record = matches.subcommand_matches("record");
+                    let explicit = |id: &str| {
+                        from_config.contains(&id)
+                            || record.and_then(|m| m.value_source(id)) == Some(clap::parser::ValueSource::CommandLine)
+                    };
+                    let profile = encoder::QualityProfile::for_preset(quality_preset);
+                    let rate_typed = explicit("quality") || explicit("bitrate") || explicit("crf");
+                    let (bitrate, crf, quality) = match profile.rate_control {
+                        _ if rate_typed => (bitrate, crf, quality),
+                        encoder::RateControl::Quality(quality) => (None, None, quality),
+                        encoder::RateControl::Crf(crf) => (None, Some(crf), quality),
+                        encoder::RateControl::Bitrate(bits) => (Some(bits.to_string()), None, quality),
+                    };
+                    (
+                        if explicit("codec") { codec } else { profile.codec },
+                        bitrate,
+                        crf,
+                        quality,
+                        if explicit("preset") { preset } else { Some(profile.preset) },
+                        profile.tuning,
+                    )
+                }
+                None => (codec, bitrate, crf, quality, preset, encoder::EncoderTuning::default()),
+            };
+            if keyframe_interval == Some(0) {
+                return Err(ScreenRecError::InvalidParameter(
+                    "--keyframe-interval must be at least 1 second".to_string(),
+                ));
+            }
+            if matches!(max_encode_dimension, Some(max) if max < 16) {
+                return Err(ScreenRecError::InvalidParameter(
+                    "--max-encode-dimension must be at least 16 pixels".to_string(),
+                ));
+            }
+            let tuning = encoder::EncoderTuning {
+                keyframe_interval: keyframe_interval.or(tuning.keyframe_interval),
+                lossless,
+                max_dimension: max_encode_dimension,
+                ..tuning
+            };
+
+            // Safe mode: software H.264 only, whatever codec was asked for
+            let codec = if safe_mode && codec != cli::Codec::H264 {
+                log::warn!("--safe-mode records H.264 with libx264; ignoring --codec {}", codec);
+                cli::Codec::H264
+            } else if lossless && codec != cli::Codec::H264 {
+                log::warn!("--lossless records H.264 with libx264; ignoring codec {}", codec);
+                cli::Codec::H264
+            } else {
+                codec
+            };
+
+            // Lossless output needs libx264; falling back to another encoder would lose it
+            let encoder_choice = if lossless {
+                if let Some(name) = encoder_name.filter(|name| name != "libx264") {
+                    log::warn!("--lossless records with libx264; ignoring encoder {}", name);
+                }
+                encoder::EncoderChoice::Named("libx264".to_string())
+            } else if safe_mode {
+                // A pinned encoder can still come from the config file
+                if let Some(name) = encoder_name {
+                    log::warn!("--safe-mode records with libx264; ignoring encoder {}", name);
+                }
+                encoder::EncoderChoice::SoftwareOnly
+            } else {
+                match encoder_name {
+                    Some(name) => encoder::EncoderChoice::Named(name),
+                    None => encoder::EncoderChoice::Auto,
+                }
+            };
+
+            // Validate the bitrate and CRF before any setup so typos fail fast; --crf wins over --quality
+            let rate_control = match (bitrate.as_deref(), crf) {
+                (Some(bitrate), _) => encoder::RateControl::Bitrate(encoder::parse_bitrate(bitrate)?),
+                (None, Some(crf)) if crf > encoder::MAX_CRF => {
+                    return Err(ScreenRecError::InvalidParameter(format!(
+                        "--crf must be between 0 and {} (got {})",
+                        encoder::MAX_CRF,
+                        crf
+                    )));
+                }
+                (None, Some(crf)) => encoder::RateControl::Crf(crf),
+                (None, None) => encoder::RateControl::Quality(quality),
+            };
+
+            let chunk_policy = match chunk_size {
+                Some(size) => encoder::ChunkPolicy::BySize(size.0),
+                None => encoder::ChunkPolicy::ByDuration(chunk_duration),
+            };
+            if !chunk_name_pattern.has_index() {
+                log::warn!(
+                    "--chunk-name-pattern {} has no {{index}}; chunks started in the same second get a -N suffix",
+                    chunk_name_pattern
+                );
+            }
+
+            // Evicting chunks would leave a task with nothing to concatenate
+            let max_disk_usage = if max_disk_usage.is_some() && recording_type == RecordingType::Task {
+                log::warn!("--max-disk-usage only applies to always_on recordings; ignoring it for this task");
+                None
+            } else {
+                max_disk_usage
+            };
+
+            // Load the key up front so a missing one fails before anything is recorded
+            let encryption_key = if encrypt {
+                Some(encryption::EncryptionKey::load(key_file.as_deref())?)
+            } else {
+                None
+            };
+
+            let frame_log = frames_jsonl.as_deref().map(frame_log::FrameLog::open).transpose()?;
+            if dump_every == 0 {
+                return Err(ScreenRecError::InvalidParameter("--dump-every must be at least 1".to_string()));
+            }
+            let frame_dump = dump_frames
+                .as_deref()
+                .map(|dir| frame_dump::FrameDumper::start(dir, dump_every, frame_format))
+                .transpose()?;
+
+            // Adaptive quality moves between --min-quality and --max-quality (default: --quality)
+            let adaptive = if adaptive_quality {
+                let max_quality = max_quality.unwrap_or(quality);
+                if min_quality == 0 || min_quality > max_quality || max_quality > 10 {
+                    return Err(ScreenRecError::InvalidParameter(format!(
+                        "Adaptive quality needs 1 <= --min-quality <= --max-quality <= 10 (got {} and {})",
+                        min_quality, max_quality
+                    )));
+                }
+                Some(adaptive::AdaptiveQuality::new(min_quality, max_quality, rate_control))
+            } else {
+                None
+            };
+
             // Find and validate FFmpeg binary
             let ffmpeg_binary = ffmpeg_utils::find_ffmpeg_binary(ffmpeg_path.as_ref())?;
 
@@ -91,6 +493,42 @@ async fn main() -> Result<()> {
                     return Err(e);
                 }
             }
+
+            // List encoders, or find out now which ones work (test-encoding each
+            // hardware encoder once) rather than when the first chunk opens
+            {
+                let choice = if list_encoders { &encoder::EncoderChoice::Auto } else { &encoder_choice };
+                #[cfg(target_os = "macos")]
+                let encoders = encoder::available_encoders(codec, choice, &ffmpeg_binary)?;
+                #[cfg(not(target_os = "macos"))]
+                let encoders = encoder::available_encoders(codec, choice)?;
+
+                if list_encoders {
+                    println!("🎬 Available {} encoders, in fallback order:", codec);
+                    println!("{:<20} {:<12} Priority", "Encoder", "Type");
+                    for encoder_info in &encoders {
+                        println!(
+                            "{:<20} {:<12} {}",
+                            encoder_info.name,
+                            format!("{:?}", encoder_info.encoder_type),
+                            encoder_info.priority
+                        );
+                    }
+                    if encoders.is_empty() {
+                        println!("(none found)");
+                    }
+                    let failed: Vec<_> = encoder::hardware_probe_results()
+                        .into_iter()
+                        .filter(|(_, works)| !works)
+                        .map(|(name, _)| name)
+                        .collect();
+                    if !failed.is_empty() {
+                        println!("Skipped (built into FFmpeg but failed a test encode): {}", failed.join(", "));
+                    }
+                    return Ok(());
+                }
+            }
+
             // Validate recording type requirements
             if recording_type == RecordingType::Task {
                 if task_id.is_none() {
@@ -101,11 +539,20 @@ async fn main() -> Result<()> {
             }
 
             // Validate FPS
-            if fps == 0 || fps > 60 {
+            let max_fps = if allow_high_fps { MAX_HIGH_FPS } else { MAX_STANDARD_FPS };
+            if fps == 0 || fps > max_fps {
+                let hint = if allow_high_fps { "" } else { " (pass --allow-high-fps to go higher)" };
+                return Err(error::ScreenRecError::InvalidParameter(format!(
+                    "FPS must be between 1 and {}{}",
+                    max_fps, hint
+                )));
+            }
+            if buffer_frames == Some(0) {
                 return Err(error::ScreenRecError::InvalidParameter(
-                    "FPS must be between 1 and 60".to_string(),
+                    "--buffer-frames must be at least 1".to_string(),
                 ));
             }
+            let start_at = start_at.as_deref().map(parse_start_at).transpose()?;
 
             // Set up default output directory (~/.omega/data/)
             let omega_dir = dirs::home_dir()
@@ -142,6 +589,14 @@ async fn main() -> Result<()> {
                 .and_then(|h| h.into_string().ok())
                 .unwrap_or_else(|| "unknown".to_string());
 
+            // Wait or count down before anything is recorded, so the session starts after it
+            if let Some(start_at) = start_at {
+                wait_until(start_at).await;
+            }
+            if start_delay > 0 {
+                countdown(start_delay).await;
+            }
+
             // Create recording session (capture start time)
             let session_start_time = chrono::Utc::now();
             let session_id = if recording_type == RecordingType::Task && task_id.is_some() {
@@ -159,7 +614,19 @@ async fn main() -> Result<()> {
                 log::info!("  Task ID: {}", tid);
             }
             log::info!("  Output: {}", output_dir.display());
-            log::info!("  Chunk duration: {} seconds", chunk_duration);
+            log::info!("  Chunking: {} named {}.mp4", chunk_policy, chunk_name_pattern);
+            if let Some(max_disk_usage) = max_disk_usage {
+                log::info!("  Max disk usage: {} (oldest chunks are deleted)", max_disk_usage);
+            }
+            if encrypt {
+                log::info!("  Encryption: AES-256-GCM, chunks are written as .mp4.enc");
+            }
+            if let Some(path) = &frames_jsonl {
+                log::info!("  Frame metadata: {}", path.display());
+            }
+            if let Some(dir) = &dump_frames {
+                log::info!("  Dumping every {} frame(s) as {} to {}", dump_every, frame_format, dir.display());
+            }
             log::info!("  FPS: {}", fps);
             log::info!(
                 "  Duration: {}",
@@ -169,8 +636,40 @@ async fn main() -> Result<()> {
                     "unlimited (Ctrl+C to stop)".to_string()
                 }
             );
-            log::info!("  Audio: {}", audio);
-            log::info!("  Quality: {}/10", quality);
+            log::info!("  Audio: {} ({})", audio, if audio_channels == 2 { "stereo" } else { "mono" });
+            if let Some(device) = &audio_device {
+                log::info!("  Audio device: {}", device);
+            }
+            if lossless {
+                log::info!("  Rate control: lossless (QP 0, 4:4:4)");
+            } else {
+                log::info!("  Rate control: {}", rate_control);
+            }
+            if adaptive_quality {
+                log::info!(
+                    "  Adaptive quality: quality {}-{} (bitrate may drop to a quarter)",
+                    min_quality,
+                    max_quality.unwrap_or(quality)
+                );
+            }
+            log::info!("  Codec: {}", codec);
+            if let encoder::EncoderChoice::Named(name) = &encoder_choice {
+                log::info!("  Encoder: {} (no fallback)", name);
+            }
+            log::info!("  Scale filter: {}", scale_filter);
+            if let Some(quality_preset) = quality_preset {
+                log::info!("  Quality preset: {}", quality_preset);
+            }
+            log::info!("  Keyframe every {} frames", tuning.gop_size(fps));
+            if let Some(preset) = preset {
+                log::info!("  Preset: {}", preset);
+            }
+            #[cfg(target_os = "macos")]
+            log::info!("  MP4 layout: {}", mp4_layout.unwrap_or_default());
+            #[cfg(not(target_os = "macos"))]
+            if mp4_layout.is_some() {
+                log::warn!("--mp4-layout only applies to the macOS encoder; chunks here are always regular MP4");
+            }
             log::info!(
                 "  Interaction tracking: {}",
                 if track_interactions {
@@ -180,178 +679,288 @@ async fn main() -> Result<()> {
                 }
             );
 
+            // Everything this recording was asked for, saved to session.json once the capture size is known
+            let mut session_settings = serde_json::json!({
+                "session_id": session_id,
+                "started_at": session_start_time.to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+                "device_name": device_name,
+                "app_version": env!("CARGO_PKG_VERSION"),
+                "os": std::env::consts::OS,
+                "recording_type": recording_type.to_string(),
+                "task_id": task_id,
+                "output_dir": output_dir.display().to_string(),
+                "fps": fps,
+                "duration": duration,
+                "display": display,
+                "multi_monitor_mode": multi_monitor_mode.to_string(),
+                "window_title": window_title,
+                "window_id": window_id,
+                "region": region.as_ref().map(|region| region.to_string()),
+                "masks": mask.iter().map(ToString::to_string).collect::<Vec<_>>(),
+                "mask_mode": mask_mode.to_string(),
+                "requested_width": width,
+                "requested_height": height,
+                "codec": codec.to_string(),
+                "encoder": match &encoder_choice {
+                    encoder::EncoderChoice::Auto => "auto",
+                    encoder::EncoderChoice::SoftwareOnly => "software",
+                    encoder::EncoderChoice::Named(name) => name.as_str(),
+                },
+                "rate_control": rate_control.to_string(),
+                "lossless": lossless,
+                "preset": preset.as_ref().map(|preset| preset.to_string()),
+                "quality_preset": quality_preset.as_ref().map(|preset| preset.to_string()),
+                "keyframe_interval_frames": tuning.gop_size(fps),
+                "adaptive_quality": adaptive_quality,
+                "scale_filter": scale_filter.to_string(),
+                "chunking": chunk_policy.to_string(),
+                "chunk_name_pattern": chunk_name_pattern.to_string(),
+                "max_disk_usage": max_disk_usage.as_ref().map(|size| size.to_string()),
+                "audio": audio.to_string(),
+                "audio_device": audio_device,
+                "audio_channels": audio_channels,
+                "track_interactions": track_interactions,
+                "no_cursor": no_cursor,
+                "rotate": rotate.to_string(),
+                "flip": flip.to_string(),
+                "overlay_timestamp": overlay_timestamp,
+                "dedup_threshold": dedup_threshold,
+                "safe_mode": safe_mode,
+            });
+
             // Initialize screen capture
             let monitor_switch_duration = std::time::Duration::from_secs_f64(monitor_switch_interval);
-            let screen_capture = ScreenCapture::new(display, fps, monitor_switch_duration)?;
-
-            let mut capture_width = if width > 0 {
-                width as usize
-            } else if screen_capture.is_multi_monitor() {
-                // In multi-monitor mode, use maximum dimensions across all displays
-                let (max_w, _) = screen_capture.get_max_dimensions()?;
-                log::info!("Multi-monitor mode: using maximum width {}", max_w);
-                max_w
-            } else {
-                screen_capture.width()
+            let window = match (&window_title, window_id) {
+                (Some(title), _) => Some(window_capture::find_window_by_title(title)?),
+                (None, Some(id)) => Some(window_capture::find_window_by_id(id)?),
+                (None, None) => None,
             };
-
-            let mut capture_height = if height > 0 {
-                height as usize
-            } else if screen_capture.is_multi_monitor() {
-                // In multi-monitor mode, use maximum dimensions across all displays
-                let (_, max_h) = screen_capture.get_max_dimensions()?;
-                log::info!("Multi-monitor mode: using maximum height {}", max_h);
-                max_h
-            } else {
-                screen_capture.height()
+            let capturing_window = window.is_some();
+            let mut screen_capture = match window {
+                Some(window) => ScreenCapture::new_for_window(window, fps)?,
+                None => ScreenCapture::new(display, fps, monitor_switch_duration)?,
             };
 
-            // Ensure dimensions are even (required by H.264 encoder)
-            if capture_width % 2 != 0 {
-                capture_width -= 1;
-                log::info!("Adjusted width to even number: {}", capture_width);
-            }
-            if capture_height % 2 != 0 {
-                capture_height -= 1;
-                log::info!("Adjusted height to even number: {}", capture_height);
+            // Validate the crop region against the chosen display
+            if let Some(region) = region {
+                let (display_width, display_height) = screen_capture.get_display_size()?;
+                let clamped = region.clamp_to(display_width, display_height).ok_or_else(|| {
+                    error::ScreenRecError::InvalidParameter(format!(
+                        "Region {} is outside display {} ({}x{})",
+                        region, display, display_width, display_height
+                    ))
+                })?;
+                if clamped != region {
+                    log::warn!(
+                        "Region {} adjusted to {} to fit the {}x{} display with even dimensions",
+                        region, clamped, display_width, display_height
+                    );
+                }
+                log::info!("Capturing region {} of display {}", clamped, display);
+                screen_capture.set_region(clamped);
             }
 
-            log::info!("Capture resolution: {}x{}", capture_width, capture_height);
+            // Masks are in display pixels; clip them to the display up front
+            if !mask.is_empty() {
+                let masks = if capturing_window {
+                    mask
+                } else {
+                    let (display_width, display_height) = screen_capture.get_display_size()?;
+                    mask.into_iter()
+                        .filter_map(|rect| {
+                            let clamped = rect.clamp_to(display_width, display_height);
+                            match clamped {
+                                None => log::warn!("Mask {} is outside the {}x{} display, ignoring it", rect, display_width, display_height),
+                                Some(clamped) if clamped != rect => log::warn!("Mask {} clipped to {}", rect, clamped),
+                                Some(_) => {}
+                            }
+                            clamped
+                        })
+                        .collect()
+                };
+                log::info!("Masking {} region(s) with {}", masks.len(), mask_mode);
+                screen_capture.set_masks(masks, mask_mode);
+            }
 
-            // Create channels for frame data
-            let (frame_tx_std, frame_rx_std) = std_mpsc::channel(); // Sync channel for capture thread
-            // Increased buffer from 60 to 300 frames (10 seconds at 30fps) to prevent blocking during database writes
-            let (frame_tx, frame_rx) = mpsc::channel(300); // Async channel for encoder
+            if safe_mode {
+                log::info!("Safe mode: software encoding, single display, small frame buffer, verbose logging");
+                screen_capture.set_single_display();
+            }
 
-            // Bridge: sync receiver -> async sender (NO DROPS - blocks if encoder is slow)
-            let bridge_handle = tokio::spawn(async move {
-                let mut total_frames = 0u64;
-                let mut last_log = std::time::Instant::now();
+            if screen_capture.is_multi_monitor() && multi_monitor_mode == cli::MultiMonitorMode::Composite {
+                log::info!("Multi-monitor mode: compositing all displays into one frame");
+                screen_capture.set_multi_monitor_mode(multi_monitor_mode);
+            }
 
-                while let Ok(frame) = frame_rx_std.recv() {
-                    total_frames += 1;
+            if no_cursor {
+                log::info!("Cursor drawing disabled");
+                screen_capture.set_show_cursor(false);
+            }
 
-                    // Log progress every 5 seconds
-                    if last_log.elapsed() >= std::time::Duration::from_secs(5) {
-                        log::info!("Bridge: {} frames forwarded to encoder", total_frames);
-                        last_log = std::time::Instant::now();
-                    }
+            let frame_transform = transform::FrameTransform::new(rotate, flip);
+            if !frame_transform.is_identity() {
+                log::info!("Transforming frames: rotate {}, flip {}", rotate, flip);
+                screen_capture.set_transform(frame_transform);
+            }
 
-                    // Send frame - will block if channel is full (encoder is slow)
-                    // This is CORRECT - we want to preserve all frames, not drop them!
-                    if frame_tx.send(frame).await.is_err() {
-                        log::error!("Encoder channel closed unexpectedly");
-                        break;
-                    }
+            if let Some(threshold) = dedup_threshold {
+                if capturing_window {
+                    log::warn!("--dedup-threshold only applies to display capture; ignored for window capture");
+                } else {
+                    log::info!("Reusing unchanged frames (up to {} sampled pixels may differ)", threshold);
+                    screen_capture.set_dedup_threshold(threshold);
                 }
+            }
 
-                log::info!("Bridge completed: {} total frames forwarded", total_frames);
-            });
+            if overlay_timestamp {
+                // The task ID goes under the time so reviewers can match footage to tasks
+                let label = match recording_type {
+                    RecordingType::Task => task_id.clone(),
+                    RecordingType::AlwaysOn => None,
+                };
+                log::info!("Timestamp overlay enabled ({})", overlay_position);
+                screen_capture.set_overlay(overlay::TimestampOverlay::new(overlay_position, label));
+            }
 
-            // Create shutdown channel for graceful encoder termination
-            let (shutdown_tx, shutdown_rx) = tokio::sync::oneshot::channel();
+            // Pause flag toggled by SIGUSR1/SIGUSR2 (Unix); capture and audio skip while set
+            let paused = Arc::new(std::sync::atomic::AtomicBool::new(false));
+            screen_capture.set_pause_flag(paused.clone());
 
-            // Start encoder task with chunking support
-            let db_for_encoder = db.clone();
-            let device_name_for_encoder = device_name.clone();
-            let output_dir_for_encoder = output_dir.clone();
-            let recording_type_str = recording_type.to_string();
-            let task_id_for_encoder = task_id.clone();
+            let composite_size = if screen_capture.is_multi_monitor() && multi_monitor_mode == cli::MultiMonitorMode::Composite {
+                let (composite_w, composite_h) = screen_capture.get_composite_size()?;
+                log::info!("Composite mode: using desktop bounds {}x{}", composite_w, composite_h);
+                Some((composite_w, composite_h))
+            } else {
+                None
+            };
 
-            #[cfg(target_os = "macos")]
-            let encoder_handle = {
-                let ffmpeg_path_for_encoder = Some(ffmpeg_binary.clone());
-                tokio::spawn(async move {
-                    encoder::process_frames_chunked(
-                        frame_rx,
-                        output_dir_for_encoder,
-                        capture_width,
-                        capture_height,
-                        fps,
-                        quality,
-                        chunk_duration,
-                        Some(db_for_encoder),
-                        Some(device_name_for_encoder),
-                        Some(recording_type_str),
-                        task_id_for_encoder,
-                        session_id,
-                        Some(shutdown_rx),
-                        ffmpeg_path_for_encoder,
-                    )
-                    .await
-                })
+            // What the capturer delivers, before --width/--height
+            let native_size = if let Some(composite_size) = composite_size {
+                composite_size
+            } else if screen_capture.is_multi_monitor() {
+                // In multi-monitor mode, use maximum dimensions across all displays
+                let (max_w, max_h) = screen_capture.get_max_dimensions()?;
+                log::info!("Multi-monitor mode: using maximum size {}x{}", max_w, max_h);
+                (max_w, max_h)
+            } else {
+                (screen_capture.width(), screen_capture.height())
             };
 
-            #[cfg(not(target_os = "macos"))]
-            let encoder_handle = tokio::spawn(async move {
-                encoder::process_frames_chunked(
-                    frame_rx,
-                    output_dir_for_encoder,
+            // --rotate 90/270 swaps the sides. H.264 4:2:0 needs both even; output_size
+            // rounds down, and any odd frame is scaled to that by the encoder.
+            // Lossless 4:4:4 has no chroma subsampling, so it keeps odd sizes unscaled
+            let native_size = frame_transform.output_size(native_size);
+            let keep_native_size = lossless && width == 0 && height == 0;
+            if !keep_native_size && (native_size.0 & 1 != 0 || native_size.1 & 1 != 0) {
+                log::warn!(
+                    "Rotated frames are {}x{}; odd sides are scaled to even ones for the encoder",
+                    native_size.0,
+                    native_size.1
+                );
+            }
+
+            // The encoder runs at the requested size (even, as H.264 requires); frames
+            // of any other size are scaled to it by scale_and_pad_frame
+            let (capture_width, capture_height) = if keep_native_size {
+                native_size
+            } else {
+                scaling::output_size(native_size, width, height)
+            };
+            if (capture_width, capture_height) != native_size {
+                log::info!(
+                    "Scaling {}x{} frames to {}x{}",
+                    native_size.0,
+                    native_size.1,
                     capture_width,
-                    capture_height,
-                    fps,
-                    quality,
-                    chunk_duration,
-                    Some(db_for_encoder),
-                    Some(device_name_for_encoder),
-                    Some(recording_type_str),
-                    task_id_for_encoder,
-                    session_id,
-                    Some(shutdown_rx),
-                )
-                .await
-            });
+                    capture_height
+                );
+            }
 
-            // Initialize audio capture if requested (macOS only)
-            #[cfg(target_os = "macos")]
-            let audio_handle = if audio != cli::AudioSource::None {
-                match AudioCapture::new(audio) {
-                    Ok(Some(audio_capture)) => {
-                        let (audio_tx, audio_rx) = mpsc::channel(1000);
-                        // Start audio capture in a separate thread (cpal requires non-async)
-                        let audio_tx_clone = audio_tx.clone();
-                        std::thread::spawn(move || {
-                            if let Err(e) = audio_capture.start_capture(audio_tx_clone) {
-                                log::error!("Audio capture failed: {}", e);
-                            }
-                        });
+            log::info!("Capture resolution: {}x{}", capture_width, capture_height);
 
-                        // Start audio processing task
-                        Some(tokio::spawn(async move {
-                            encoder::process_audio(audio_rx).await
-                        }))
-                    }
-                    Ok(None) => {
-                        log::info!("Audio capture disabled");
-                        None
-                    }
-                    Err(e) => {
-                        // Audio failed, but continue with video-only recording
-                        match e {
-                            ScreenRecError::AudioDeviceUnavailable(tried) => {
-                                log::warn!("Audio unavailable (tried: {:?}). Continuing with video only.", tried);
-                                println!("⚠️  Audio capture failed. Recording video only.");
-                            }
-                            _ => {
-                                log::warn!("Audio init failed: {}. Continuing with video only.", e);
-                                println!("⚠️  Audio initialization failed. Recording video only.");
-                            }
-                        }
-                        None
-                    }
+            let colorspace = colorspace.unwrap_or_else(|| encoder::default_colorspace(capture_height));
+            log::info!("Colorspace: {}", colorspace);
+            let tuning = encoder::EncoderTuning { colorspace, ..tuning };
+
+            // Bound the encoder queue by bytes so memory use doesn't depend on resolution.
+            // The default matches the old 300-frame buffer (10 seconds at 30fps) at this resolution;
+            // safe mode keeps only 60 frames so a stalled encoder can't eat memory.
+            let buffered_frames = buffer_frames.unwrap_or(if safe_mode { 60 } else { DEFAULT_BUFFER_FRAMES });
+            let frame_buffer_bytes = frame_buffer_bytes
+                .unwrap_or(cli::ByteSize((capture_width * capture_height * 3 * buffered_frames) as u64));
+            log::info!("Frame buffer budget: {} (on overflow: {})", frame_buffer_bytes, on_overflow);
+
+            session_settings["capture_width"] = capture_width.into();
+            session_settings["capture_height"] = capture_height.into();
+            session_settings["colorspace"] = colorspace.to_string().into();
+            session_settings["max_encode_dimension"] = max_encode_dimension.into();
+            session_settings["encrypt"] = encrypt.into();
+            session_settings["frame_buffer_bytes"] = frame_buffer_bytes.0.into();
+            session_settings["on_overflow"] = on_overflow.to_string().into();
+            if let Err(e) = session::append_session_settings(&output_dir, session_settings) {
+                log::warn!("⚠️  Session settings not saved: {}", e);
+            }
+
+            // Optional HTTP status endpoint, fed by the bridge and the encoder
+            let recorder_status = status::RecorderStatus::new_shared(task_id.clone());
+            let status_server = match status_port {
+                Some(port) => {
+                    let listener = status::bind(port).await?;
+                    let (stop_tx, stop_rx) = tokio::sync::oneshot::channel();
+                    let handle = tokio::spawn(status::serve(listener, recorder_status.clone(), stop_rx));
+                    println!("📊 Status available at http://127.0.0.1:{}/status", port);
+                    Some((stop_tx, handle))
                 }
+                None => None,
+            };
+
+            // Calculate target frames based on duration and fps
+            let target_frames = if duration > 0 {
+                Some(duration * fps as u64)
             } else {
                 None
             };
 
-            // Audio not supported on Windows yet
-            #[cfg(not(target_os = "macos"))]
-            let audio_handle: Option<tokio::task::JoinHandle<()>> = {
-                if audio != cli::AudioSource::None {
-                    log::warn!("Audio capture is only supported on macOS");
-                }
-                None
+            progress::emit("record", target_frames.map(|_| 0), "Recording started");
+
+            // Audio, encoder, bridge and capture thread. On Windows/Linux audio is muxed
+            // into the chunks by the encoder; on macOS it is written to session.wav.
+            let options = RecordingOptions {
+                target_frames,
+                rate_control,
+                codec,
+                preset,
+                tuning,
+                encoder_choice,
+                scale_filter,
+                adaptive,
+                chunk_policy,
+                chunk_name_pattern,
+                max_disk_usage: max_disk_usage.map(|size| size.0),
+                encryption_key,
+                frame_log,
+                frame_dump,
+                db: Some(db.clone()),
+                device_name: Some(device_name.clone()),
+                recording_type: Some(recording_type.to_string()),
+                task_id: task_id.clone(),
+                session_id,
+                frame_buffer_bytes: frame_buffer_bytes.0 as usize,
+                on_overflow,
+                audio,
+                audio_device,
+                audio_channels,
+                audio_reconnect_attempts,
+                paused: paused.clone(),
+                status: recorder_status.clone(),
+                #[cfg(target_os = "macos")]
+                ffmpeg_path: Some(ffmpeg_binary.clone()),
+                #[cfg(target_os = "macos")]
+                mp4_layout: mp4_layout.unwrap_or_default(),
+                ..RecordingOptions::new(output_dir.clone(), capture_width, capture_height, fps)
             };
+            let recording = RecordingHandle::spawn(screen_capture, options);
+            let stop = recording.stop_handle();
 
             // Initialize interaction tracker
             // For task mode: always track all interactions (clicks, keys, scrolls) to JSONL
@@ -367,21 +976,34 @@ async fn main() -> Result<()> {
                     capture_width,
                     capture_height,
                     track_mouse_moves,
+                    move_sample_hz,
+                    scroll_debounce_ms,
+                    capture_clipboard,
+                    capture_text,
                     tid.clone(),
                     jsonl_path,
+                    interactions_max_size_mb.map(|mb| mb * 1024 * 1024),
                 )?;
 
                 let tracker_handle = tracker.start()?;
                 Some((tracker, tracker_handle))
             } else if track_interactions {
                 // Always_on mode: only track if explicitly requested
-                let tracker = InteractionTracker::new(capture_width, capture_height, track_mouse_moves);
+                let tracker = InteractionTracker::new(
+                    capture_width,
+                    capture_height,
+                    track_mouse_moves,
+                    move_sample_hz,
+                    scroll_debounce_ms,
+                    capture_clipboard,
+                    capture_text,
+                );
                 let tracker_handle = tracker.start()?;
                 Some((tracker, tracker_handle))
             } else {
                 // No interaction tracking, but still need cursor updates for rendering
                 let _cursor_tracker_handle = std::thread::spawn(|| {
-                    use crate::capture::update_cursor_position;
+                    use screenrec::capture::update_cursor_position;
                     let _ = rdev::listen(move |event| {
                         if let rdev::EventType::MouseMove { x, y } = event.event_type {
                             update_cursor_position(x as i32, y as i32);
@@ -392,12 +1014,7 @@ async fn main() -> Result<()> {
             };
 
             // Set up Ctrl+C handler for graceful shutdown
-            let running = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(true));
-            let r = running.clone();
-
-            // Wrap shutdown_tx in Arc<Mutex<Option<_>>> so we can move it into the handler
-            let shutdown_tx_for_handler = Arc::new(std::sync::Mutex::new(Some(shutdown_tx)));
-            let shutdown_tx_clone = shutdown_tx_for_handler.clone();
+            let stop_for_ctrlc = stop.clone();
 
             // Clone db and session_id for signal handler
             let db_for_ctrlc = db.clone();
@@ -423,17 +1040,13 @@ async fn main() -> Result<()> {
                 }
 
                 // Signal encoder to finish current chunk
-                if let Ok(mut tx_opt) = shutdown_tx_clone.lock() {
-                    if let Some(tx) = tx_opt.take() {
-                        log::info!("Signaling encoder to finalize current chunk...");
-                        let _ = tx.send(());
-                        // Give encoder a moment to finish
-                        std::thread::sleep(std::time::Duration::from_millis(500));
-                    }
+                if stop_for_ctrlc.finish_chunk() {
+                    // Give encoder a moment to finish
+                    std::thread::sleep(std::time::Duration::from_millis(500));
                 }
 
                 // Then signal capture to stop
-                r.store(false, std::sync::atomic::Ordering::SeqCst);
+                stop_for_ctrlc.stop_capture();
             })
             .map_err(|e| {
                 error::ScreenRecError::ConfigError(format!("Failed to set Ctrl+C handler: {}", e))
@@ -442,8 +1055,7 @@ async fn main() -> Result<()> {
             // Also handle SIGTERM (Unix only) for graceful shutdown on kill
             #[cfg(unix)]
             {
-                let running_sigterm = running.clone();
-                let shutdown_tx_sigterm = shutdown_tx_for_handler.clone();
+                let stop_for_sigterm = stop.clone();
                 let db_for_sigterm = db.clone();
                 let session_id_for_sigterm = session_id;
 
@@ -466,54 +1078,73 @@ async fn main() -> Result<()> {
                     }
 
                     // Signal encoder to finish current chunk
-                    {
-                        if let Ok(mut tx_opt) = shutdown_tx_sigterm.lock() {
-                            if let Some(tx) = tx_opt.take() {
-                                log::info!("Signaling encoder to finalize current chunk...");
-                                let _ = tx.send(());
-                            }
-                        }
-                        // Guard is dropped here before the await
-                    }
+                    stop_for_sigterm.finish_chunk();
 
                     // Give encoder a moment to finish
                     tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
 
                     // Then signal capture to stop
-                    running_sigterm.store(false, std::sync::atomic::Ordering::SeqCst);
+                    stop_for_sigterm.stop_capture();
                 });
-            }
-
-            // Calculate target frames based on duration and fps
-            let target_frames = if duration > 0 {
-                Some(duration * fps as u64)
-            } else {
-                None
-            };
 
-            // Run capture in a separate OS thread (not tokio thread) because Capturer is not Send
-            let running_for_capture = running.clone();
-            let capture_handle = std::thread::spawn(move || {
-                screen_capture.start_capture_sync(frame_tx_std, target_frames, Some(running_for_capture))
-            });
+                // SIGHUP starts a new chunk without stopping, e.g. at a CI job boundary
+                let status_for_sighup = recorder_status.clone();
+                tokio::spawn(async move {
+                    use tokio::signal::unix::{signal, SignalKind};
+                    let Ok(mut sighup) = signal(SignalKind::hangup()) else {
+                        log::warn!("Failed to register SIGHUP handler; chunk rotation on signal unavailable");
+                        return;
+                    };
+                    while sighup.recv().await.is_some() {
+                        log::info!("Received SIGHUP, starting a new chunk");
+                        if let Ok(mut status) = status_for_sighup.lock() {
+                            status.request_rotation();
+                        }
+                    }
+                });
 
-            // Wait for capture to finish
-            let _capture_result = capture_handle
-                .join()
-                .map_err(|e| {
-                    error::ScreenRecError::CaptureError(format!("Capture thread panicked: {:?}", e))
-                })?
-                .map_err(|e| {
-                    error::ScreenRecError::CaptureError(format!("Capture failed: {}", e))
-                })?;
+                // SIGUSR1 pauses and SIGUSR2 resumes without ending the session
+                let paused_for_signals = paused.clone();
+                tokio::spawn(async move {
+                    use std::sync::atomic::Ordering;
+                    use tokio::signal::unix::{signal, SignalKind};
+                    let (Ok(mut sigusr1), Ok(mut sigusr2)) = (
+                        signal(SignalKind::user_defined1()),
+                        signal(SignalKind::user_defined2()),
+                    ) else {
+                        log::warn!("Failed to register SIGUSR1/SIGUSR2 handlers; pause/resume unavailable");
+                        return;
+                    };
+
+                    loop {
+                        tokio::select! {
+                            _ = sigusr1.recv() => {
+                                if !paused_for_signals.swap(true, Ordering::SeqCst) {
+                                    log::info!("Received SIGUSR1, pausing recording (send SIGUSR2 to resume)");
+                                }
+                            }
+                            _ = sigusr2.recv() => {
+                                if paused_for_signals.swap(false, Ordering::SeqCst) {
+                                    log::info!("Received SIGUSR2, resuming recording");
+                                }
+                            }
+                        }
+                    }
+                });
+            }
 
-            // Wait for bridge to finish
-            let _ = bridge_handle.await;
+            // Wait for capture to finish, then for the encoder to close the last chunk
+            let summary = recording.wait().await?;
+            let capture_stats = summary.capture;
+            let dropped_frames = summary.dropped_frames;
+            let encode_latency = summary.encode_latency;
+            let chunk_outputs = summary.chunks;
 
-            // Wait for encoder to finish and get video chunks
-            let chunk_outputs = encoder_handle.await.map_err(|e| {
-                error::ScreenRecError::EncodingError(format!("Encoder task failed: {}", e))
-            })??;
+            // The last chunk is final, so the status endpoint can go too
+            if let Some((stop_tx, handle)) = status_server {
+                let _ = stop_tx.send(());
+                let _ = handle.await;
+            }
 
             // Update recording session end time
             if let Some(sid) = session_id {
@@ -524,22 +1155,64 @@ async fn main() -> Result<()> {
                     let duration = (session_end_time - session_start_time).num_seconds();
                     log::info!("Recording session {} ended. Duration: {}s", sid, duration);
                 }
+
+                if let Err(e) = db
+                    .set_session_capture_stats(
+                        sid,
+                        capture_stats.frames,
+                        capture_stats.achieved_fps,
+                        capture_stats.late_frames,
+                        capture_stats.paused_ms,
+                    )
+                    .await
+                {
+                    log::error!("Failed to save capture stats: {}", e);
+                }
             }
 
-            // Wait for audio processing if it was started
-            if let Some(handle) = audio_handle {
-                let _ = handle.await;
+            println!(
+                "📊 Capture: {} frames in {:.1}s, {:.1}/{} fps achieved, {} late frames",
+                capture_stats.frames,
+                capture_stats.duration_secs,
+                capture_stats.achieved_fps,
+                capture_stats.target_fps,
+                capture_stats.late_frames
+            );
+            if let Some(latency) = encode_latency {
+                println!(
+                    "⏱️  Capture-to-encode latency: p50 {}ms, p95 {}ms, max {}ms ({} sampled frames, up to {} queued)",
+                    latency.p50_ms,
+                    latency.p95_ms,
+                    latency.max_ms,
+                    latency.samples,
+                    latency.peak_queued_frames
+                );
+            }
+            if dropped_frames > 0 {
+                println!("🗑️  Dropped {} frames the encoder couldn't keep up with (--on-overflow drop-oldest)", dropped_frames);
+            }
+            if capture_stats.paused_ms > 0 {
+                println!("⏸️  Paused for {:.1}s of the session", capture_stats.paused_ms as f64 / 1000.0);
             }
 
             // Save interaction data if tracking was enabled
             if let Some((tracker, _handle)) = interaction_tracker {
-                let interactions_path = output_dir.join("interactions.json");
-
                 log::info!("Saving interaction data...");
-                if let Err(e) = tracker.save(&interactions_path) {
-                    log::error!("Failed to save interaction data: {}", e);
-                } else {
-                    println!("✅ Interactions saved to: {}", interactions_path.display());
+                if interactions_format.writes_json() {
+                    let interactions_path = output_dir.join("interactions.json");
+                    if let Err(e) = tracker.save(&interactions_path) {
+                        log::error!("Failed to save interaction data: {}", e);
+                    } else {
+                        println!("✅ Interactions saved to: {}", interactions_path.display());
+                    }
+                }
+                if interactions_format.writes_csv() {
+                    let csv_path = output_dir.join("interactions.csv");
+                    if let Err(e) = tracker.save_csv(&csv_path) {
+                        log::error!("Failed to save interaction CSV: {}", e);
+                    } else {
+                        println!("✅ Interactions saved to: {}", csv_path.display());
+                    }
                 }
             }
 
@@ -547,6 +1220,8 @@ async fn main() -> Result<()> {
             log::info!("Recording completed. Chunks saved to: {}", output_dir.display());
             println!("✅ Recording saved to: {}", output_dir.display());
             println!("   {} chunk(s) created", chunk_outputs.len());
+            progress::emit("record", Some(100), &format!("{} chunk(s) created", chunk_outputs.len()));
+            progress::done(&output_dir);
 
             // If this is a task recording, inform about concatenation
             if recording_type == RecordingType::Task {
@@ -560,86 +1235,765 @@ async fn main() -> Result<()> {
         }
     }
 
-    Ok(())
+    Ok(())
+}
+
+/// Concatenate video chunks for a completed task recording
+async fn inspect_sessions(task_id: &str) -> Result<()> {
+    println!("🔍 Inspecting recording sessions for task: {}", task_id);
+
+    // Set up database path
+    let omega_dir = dirs::home_dir()
+        .ok_or_else(|| error::ScreenRecError::ConfigError("Could not find home directory".to_string()))?
+        .join(".omega");
+
+    let db_path = omega_dir.join("db.sqlite");
+    let db = Database::new(&db_path).await?;
+
+    // Get all sessions for this task
+    let sessions = db.get_sessions_for_task(task_id).await?;
+
+    if sessions.is_empty() {
+        println!("❌ No recording sessions found for task_id: {}", task_id);
+        return Ok(());
+    }
+
+    println!("\n📊 Found {} recording session(s):\n", sessions.len());
+    println!("{:<6} {:<22} {:<22} {:<12} {:<15}",
+             "ID", "Started At", "Ended At", "Duration (s)", "Status");
+    println!("{}", "=".repeat(80));
+
+    let mut total_duration = 0.0;
+
+    for session in &sessions {
+        let duration = if let Some(ended_at) = session.ended_at {
+            let duration_secs = (ended_at - session.started_at).num_seconds() as f64;
+            total_duration += duration_secs;
+            format!("{:.2}", duration_secs)
+        } else {
+            "N/A".to_string()
+        };
+
+        let status = if session.ended_at.is_some() {
+            "Completed"
+        } else {
+            "In Progress"
+        };
+
+        let ended_at_str = if let Some(ended_at) = session.ended_at {
+            ended_at.format("%Y-%m-%d %H:%M:%S").to_string()
+        } else {
+            "N/A".to_string()
+        };
+
+        println!("{:<6} {:<22} {:<22} {:<12} {:<15}",
+                 session.id,
+                 session.started_at.format("%Y-%m-%d %H:%M:%S"),
+                 ended_at_str,
+                 duration,
+                 status);
+    }
+
+    println!("{}", "=".repeat(80));
+    println!("\n✅ Total accumulated recording time: {:.2} seconds ({:.2} minutes)",
+             total_duration, total_duration / 60.0);
+
+    // Also get and display the database-calculated total for verification
+    let db_total = db.get_total_recording_time(task_id).await?;
+    println!("✅ Database calculated total: {:.2} seconds ({:.2} minutes)",
+             db_total, db_total / 60.0);
+
+    if (total_duration - db_total).abs() > 0.01 {
+        println!("⚠️  WARNING: Mismatch between manual sum and database calculation!");
+    }
+
+    Ok(())
+}
+
+/// Validate every chunk of a task and optionally drop the rows of unusable ones
+async fn doctor(task_id: &str, fix: bool, ffmpeg_path: Option<std::path::PathBuf>) -> Result<()> {
+    println!("🩺 Checking chunks for task: {}", task_id);
+
+    let ffmpeg_binary = ffmpeg_utils::find_ffmpeg_binary(ffmpeg_path.as_ref())?;
+    let ffprobe = ffmpeg_utils::find_ffprobe_binary(&ffmpeg_binary);
+
+    let omega_dir = dirs::home_dir()
+        .ok_or_else(|| error::ScreenRecError::ConfigError("Could not find home directory".to_string()))?
+        .join(".omega");
+    let data_dir = omega_dir.join("data");
+    let db = Database::new(omega_dir.join("db.sqlite")).await?;
+
+    let chunks = db.get_chunks_by_task_id(task_id).await?;
+    if chunks.is_empty() {
+        return Err(error::ScreenRecError::InvalidParameter(
+            format!("No chunks found for task_id: {}", task_id)
+        ));
+    }
+
+    println!("\n{:<6} {:<44} Health", "Chunk", "File");
+    println!("{}", "=".repeat(80));
+
+    let mut total_duration = 0.0;
+    let mut unhealthy = Vec::new();
+    let mut encrypted = 0;
+    for (idx, chunk) in chunks.iter().enumerate() {
+        let chunk_path = if std::path::Path::new(&chunk.file_path).is_absolute() {
+            std::path::PathBuf::from(&chunk.file_path)
+        } else {
+            data_dir.join(&chunk.file_path)
+        };
+
+        let health = ffmpeg_utils::validate_chunk(&chunk_path, &ffprobe);
+        let marker = match health {
+            ffmpeg_utils::ChunkHealth::Valid { .. } => "✅",
+            ffmpeg_utils::ChunkHealth::Encrypted => "🔒",
+            _ => "❌",
+        };
+        println!(
+            "{:<6} {:<44} {} {}",
+            idx + 1,
+            chunk_path.file_name().unwrap_or_default().to_string_lossy(),
+            marker,
+            health
+        );
+
+        // Encrypted chunks can't be probed, but are never broken rows for --fix to delete
+        match health {
+            ffmpeg_utils::ChunkHealth::Valid { duration } => total_duration += duration,
+            ffmpeg_utils::ChunkHealth::Encrypted => encrypted += 1,
+            _ => unhealthy.push(chunk),
+        }
+    }
+    println!("{}", "=".repeat(80));
+
+    println!(
+        "\n📊 {} healthy ({:.1}s of video), {} encrypted, {} missing or invalid",
+        chunks.len() - unhealthy.len() - encrypted,
+        total_duration,
+        encrypted,
+        unhealthy.len()
+    );
+
+    if unhealthy.is_empty() {
+        println!("✅ All chunks are usable");
+    } else if fix {
+        for chunk in &unhealthy {
+            db.delete_chunk(chunk.id).await?;
+            log::info!("Deleted database row for chunk {}", chunk.file_path);
+        }
+        println!("🔧 Removed {} chunk row(s) from the database; concat will no longer probe them", unhealthy.len());
+    } else {
+        println!("💡 Run with --fix to remove their database rows so concat stops re-probing them");
+    }
+
+    Ok(())
+}
+
+/// Re-hash every chunk of a task against its recorded checksums; fails if any
+/// chunk is missing or altered, so scripts can rely on the exit code
+async fn verify_integrity(task_id: &str) -> Result<()> {
+    println!("🔐 Verifying chunks for task: {}", task_id);
+
+    let omega_dir = dirs::home_dir()
+        .ok_or_else(|| error::ScreenRecError::ConfigError("Could not find home directory".to_string()))?
+        .join(".omega");
+    let data_dir = omega_dir.join("data");
+    let db = Database::new(omega_dir.join("db.sqlite")).await?;
+
+    let chunks = db.get_chunks_by_task_id(task_id).await?;
+    if chunks.is_empty() {
+        return Err(error::ScreenRecError::InvalidParameter(
+            format!("No chunks found for task_id: {}", task_id)
+        ));
+    }
+
+    println!("\n{:<6} {:<44} Integrity", "Chunk", "File");
+    println!("{}", "=".repeat(80));
+
+    let (mut verified, mut unrecorded, mut failed) = (0, 0, 0);
+    for (idx, chunk) in chunks.iter().enumerate() {
+        let chunk_path = if std::path::Path::new(&chunk.file_path).is_absolute() {
+            std::path::PathBuf::from(&chunk.file_path)
+        } else {
+            data_dir.join(&chunk.file_path)
+        };
+
+        let result = integrity::verify_chunk(&chunk_path, chunk.sha256.as_deref())?;
+        let marker = match result {
+            integrity::ChunkIntegrity::Verified => "✅",
+            integrity::ChunkIntegrity::Unrecorded => "➖",
+            _ => "❌",
+        };
+        println!(
+            "{:<6} {:<44} {} {}",
+            idx + 1,
+            chunk_path.file_name().unwrap_or_default().to_string_lossy(),
+            marker,
+            result
+        );
+
+        match result {
+            integrity::ChunkIntegrity::Verified => verified += 1,
+            integrity::ChunkIntegrity::Unrecorded => unrecorded += 1,
+            _ => failed += 1,
+        }
+    }
+    println!("{}", "=".repeat(80));
+
+    println!("\n📊 {} verified, {} without a checksum, {} missing or altered", verified, unrecorded, failed);
+    if failed > 0 {
+        return Err(error::ScreenRecError::InvalidParameter(format!(
+            "{} chunk(s) of task {} failed verification",
+            failed, task_id
+        )));
+    }
+    println!("✅ No chunk was altered");
+
+    Ok(())
+}
+
+/// Decrypt a task's `--encrypt` chunks back to `.mp4` files in place, updating
+/// their database rows and checksums
+async fn decrypt_task(task_id: &str, key_file: Option<std::path::PathBuf>) -> Result<()> {
+    let key = encryption::EncryptionKey::load(key_file.as_deref())?;
+
+    let omega_dir = dirs::home_dir()
+        .ok_or_else(|| error::ScreenRecError::ConfigError("Could not find home directory".to_string()))?
+        .join(".omega");
+    let data_dir = omega_dir.join("data");
+    let db = Database::new(omega_dir.join("db.sqlite")).await?;
+
+    let chunks = db.get_chunks_by_task_id(task_id).await?;
+    if chunks.is_empty() {
+        return Err(error::ScreenRecError::InvalidParameter(
+            format!("No chunks found for task_id: {}", task_id)
+        ));
+    }
+
+    let mut decrypted = 0;
+    for chunk in chunks.iter().filter(|chunk| encryption::is_encrypted(std::path::Path::new(&chunk.file_path))) {
+        let chunk_path = if std::path::Path::new(&chunk.file_path).is_absolute() {
+            std::path::PathBuf::from(&chunk.file_path)
+        } else {
+            data_dir.join(&chunk.file_path)
+        };
+
+        let (plain_path, nonce) = encryption::decrypt_chunk(&chunk_path, &key)?;
+        if chunk.encryption_nonce.as_deref().is_some_and(|recorded| recorded != nonce) {
+            log::warn!("⚠️  {} was encrypted under a different nonce than the database records", chunk_path.display());
+        }
+        let _ = std::fs::remove_file(integrity::sidecar_path(&chunk_path));
+
+        let new_file_path = std::path::Path::new(&chunk.file_path).with_extension("");
+        let new_file_path = new_file_path.to_string_lossy();
+        db.set_chunk_file(&chunk.file_path, &new_file_path, None).await?;
+        match integrity::write_checksum(&plain_path) {
+            Ok(sha256) => {
+                db.set_chunk_sha256(&new_file_path, &sha256).await?;
+            }
+            Err(e) => log::warn!("⚠️  Could not checksum {}: {}", plain_path.display(), e),
+        }
+
+        println!("🔓 {}", plain_path.file_name().unwrap_or_default().to_string_lossy());
+        decrypted += 1;
+    }
+
+    if decrypted == 0 {
+        println!("No encrypted chunks for task {}", task_id);
+    } else {
+        println!("✅ Decrypted {} chunk(s) of task {}", decrypted, task_id);
+    }
+
+    Ok(())
+}
+
+/// Build the session timeline for a task or a local date and print or save it as JSON
+async fn show_timeline(
+    task_id: Option<String>,
+    date: Option<String>,
+    gap_threshold: f64,
+    output: Option<std::path::PathBuf>,
+) -> Result<()> {
+    if gap_threshold <= 0.0 {
+        return Err(error::ScreenRecError::InvalidParameter(
+            "Gap threshold must be greater than 0".to_string(),
+        ));
+    }
+
+    let omega_dir = dirs::home_dir()
+        .ok_or_else(|| error::ScreenRecError::ConfigError("Could not find home directory".to_string()))?
+        .join(".omega");
+    let db = Database::new(omega_dir.join("db.sqlite")).await?;
+
+    let sessions = match (&task_id, &date) {
+        (Some(task_id), _) => db.get_sessions_for_task(task_id).await?,
+        (None, Some(date)) => {
+            use chrono::TimeZone;
+
+            let day = chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").map_err(|_| {
+                error::ScreenRecError::InvalidParameter(format!("Invalid date: {}. Use: YYYY-MM-DD", date))
+            })?;
+            let local_midnight = |day: chrono::NaiveDate| {
+                chrono::Local
+                    .from_local_datetime(&day.and_hms_opt(0, 0, 0).unwrap_or_default())
+                    .earliest()
+                    .map(|t| t.with_timezone(&chrono::Utc))
+                    .ok_or_else(|| error::ScreenRecError::InvalidParameter(format!("Invalid date: {}", day)))
+            };
+            let from = local_midnight(day)?;
+            let to = local_midnight(day.succ_opt().unwrap_or(day))?;
+            db.get_sessions_between(from, to).await?
+        }
+        (None, None) => {
+            return Err(error::ScreenRecError::InvalidParameter(
+                "Pass --task-id or --date".to_string(),
+            ))
+        }
+    };
+
+    let mut spans = Vec::with_capacity(sessions.len());
+    for session in sessions {
+        let frames = db.get_frame_timestamps_for_session(session.id).await?;
+        spans.push(timeline::SessionSpan {
+            session_id: session.id,
+            task_id: session.task_id,
+            device_name: session.device_name,
+            started_at: session.started_at,
+            ended_at: session.ended_at,
+            frames,
+        });
+    }
+
+    let timeline = timeline::build_timeline(&spans, gap_threshold);
+    let json = serde_json::to_string_pretty(&timeline).map_err(|e| {
+        error::ScreenRecError::EncodingError(format!("Failed to serialize timeline: {}", e))
+    })?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, json)?;
+            println!(
+                "✅ Timeline with {} session(s) saved to: {}",
+                timeline.sessions,
+                path.display()
+            );
+        }
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
+
+/// Turn a task's interaction log into final.vtt, one cue per focused window
+async fn export_subtitles(task_id: &str, output: Option<std::path::PathBuf>) -> Result<()> {
+    let task_dir = task_output_dir(task_id).await?;
+    let files = subtitles::interaction_files(&task_dir);
+    if files.is_empty() {
+        return Err(error::ScreenRecError::InvalidParameter(format!(
+            "No interactions.jsonl in {}. Record with --track-interactions to log window titles.",
+            task_dir.display()
+        )));
+    }
+
+    let events = subtitles::read_events(&files)?;
+    let cues = subtitles::window_cues(&events);
+    if cues.is_empty() {
+        log::warn!("No events with a window title in {} event(s); the subtitle file will be empty", events.len());
+    }
+
+    let output = output.unwrap_or_else(|| task_dir.join("final.vtt"));
+    std::fs::write(&output, subtitles::render_vtt(&cues))?;
+    println!("✅ {} subtitle cue(s) saved to: {}", cues.len(), output.display());
+    Ok(())
+}
+
+/// A `--start-at` time: RFC 3339, or a local date and time without an offset.
+/// Times that have already passed are rejected.
+fn parse_start_at(value: &str) -> Result<chrono::DateTime<chrono::Local>> {
+    use chrono::TimeZone;
+
+    let invalid = || {
+        error::ScreenRecError::InvalidParameter(format!(
+            "Invalid --start-at: {}. Use: YYYY-MM-DDTHH:MM:SS (local time) or RFC 3339",
+            value
+        ))
+    };
+    let start = match chrono::DateTime::parse_from_rfc3339(value) {
+        Ok(start) => start.with_timezone(&chrono::Local),
+        Err(_) => {
+            let naive = chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+                .or_else(|_| chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S"))
+                .or_else(|_| chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M"))
+                .map_err(|_| invalid())?;
+            // A time skipped by a DST change doesn't exist locally
+            chrono::Local.from_local_datetime(&naive).earliest().ok_or_else(invalid)?
+        }
+    };
+
+    if start <= chrono::Local::now() {
+        return Err(error::ScreenRecError::InvalidParameter(format!(
+            "--start-at {} is in the past",
+            start.format("%Y-%m-%d %H:%M:%S")
+        )));
+    }
+    Ok(start)
+}
+
+/// Idle until `start`. Ctrl+C still ends the process, as nothing is recording yet.
+async fn wait_until(start: chrono::DateTime<chrono::Local>) {
+    let remaining = start - chrono::Local::now();
+    println!(
+        "⏰ Waiting until {} to start recording ({}h {:02}m from now, Ctrl+C to cancel)",
+        start.format("%Y-%m-%d %H:%M:%S"),
+        remaining.num_hours(),
+        remaining.num_minutes() % 60
+    );
+
+    // Re-read the clock every minute: a monotonic sleep doesn't advance while the machine is asleep
+    while let Ok(remaining) = (start - chrono::Local::now()).to_std() {
+        tokio::time::sleep(remaining.min(std::time::Duration::from_secs(60))).await;
+    }
+    log::info!("Start time {} reached", start.format("%Y-%m-%d %H:%M:%S"));
+}
+
+/// `Recording in 3... 2... 1...` on stderr, one number per second
+async fn countdown(seconds: u64) {
+    use std::io::Write;
+
+    eprint!("⏳ Recording in");
+    for remaining in (1..=seconds).rev() {
+        eprint!(" {}...", remaining);
+        let _ = std::io::stderr().flush();
+        tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+    }
+    eprintln!();
+}
+
+/// Print what ffprobe knows about a file: a summary, or its raw JSON
+fn probe(input: &std::path::Path, json: bool, ffmpeg_path: Option<&std::path::PathBuf>) -> Result<()> {
+    let ffmpeg_binary = ffmpeg_utils::find_ffmpeg_binary(ffmpeg_path)?;
+    let ffprobe = ffmpeg_utils::find_ffprobe_binary(&ffmpeg_binary);
+    let probed = ffmpeg_utils::probe_json(&ffprobe, input)?;
+
+    if json {
+        let json = serde_json::to_string_pretty(&probed).map_err(|e| {
+            error::ScreenRecError::EncodingError(format!("Failed to serialize probe output: {}", e))
+        })?;
+        println!("{}", json);
+        return Ok(());
+    }
+
+    let info = ffmpeg_utils::MediaInfo::from_probe_json(&probed);
+    let or_unknown = |value: Option<String>| value.unwrap_or_else(|| "unknown".to_string());
+    println!("📄 {}\n", input.display());
+    println!("  Format:     {}", or_unknown(info.format));
+    println!("  Duration:   {}", or_unknown(info.duration.map(|d| format!("{:.3}s", d))));
+    println!("  Size:       {}", or_unknown(info.size.map(|s| cli::ByteSize(s).to_string())));
+    println!("  Bitrate:    {}", or_unknown(info.bit_rate.map(|b| format!("{} kb/s", b / 1000))));
+    println!("  Streams:    {}", info.stream_count);
+    match info.video_codec {
+        Some(codec) => {
+            let resolution = match (info.width, info.height) {
+                (Some(width), Some(height)) => format!("{}x{}", width, height),
+                _ => "unknown size".to_string(),
+            };
+            let fps = or_unknown(info.fps.map(|fps| format!("{:.2} fps", fps)));
+            println!("  Video:      {} {} @ {}", codec, resolution, fps);
+        }
+        None => println!("  Video:      none"),
+    }
+    match info.audio_codec {
+        Some(codec) => println!(
+            "  Audio:      {} {} Hz, {} channel(s)",
+            codec,
+            or_unknown(info.sample_rate.map(|rate| rate.to_string())),
+            or_unknown(info.channels.map(|channels| channels.to_string()))
+        ),
+        None => println!("  Audio:      none"),
+    }
+    Ok(())
+}
+
+/// Print the displays and audio inputs a recording can use
+fn list_devices(json: bool) -> Result<()> {
+    let displays = display_info::get_all_displays_with_bounds()?;
+    let audio_inputs = audio::list_input_devices();
+    // Window capture isn't available everywhere; list none rather than failing
+    let windows = window_capture::list_windows().unwrap_or_default();
+
+    if json {
+        let devices = serde_json::json!({
+            "displays": displays,
+            "windows": windows,
+            "audio_inputs": audio_inputs,
+        });
+        let json = serde_json::to_string_pretty(&devices).map_err(|e| {
+            error::ScreenRecError::EncodingError(format!("Failed to serialize devices: {}", e))
+        })?;
+        println!("{}", json);
+        return Ok(());
+    }
+
+    println!("🖥️  Displays (use with --display):\n");
+    println!("{:<6} {:<12} {:<14} {:<6} {:<8}", "Index", "Resolution", "Origin", "Scale", "Primary");
+    println!("{}", "=".repeat(49));
+    for display in &displays {
+        println!(
+            "{:<6} {:<12} {:<14} {:<6} {:<8}",
+            display.index,
+            format!("{}x{}", display.width, display.height),
+            format!("{},{}", display.x, display.y),
+            format!("{}x", display.scale_factor),
+            if display.is_primary { "yes" } else { "" }
+        );
+    }
+
+    if !windows.is_empty() {
+        println!("\n🪟 Windows (use with --window-id):\n");
+        println!("{:<12} {:<20} Title", "ID", "Application");
+        println!("{}", "=".repeat(60));
+        for window in &windows {
+            println!("{:<12} {:<20} {}", window.id, window.process_name, window.title);
+        }
+    }
+
+    println!("\n🎤 Audio inputs:\n");
+    if audio_inputs.is_empty() {
+        println!("   (none found; record with --no-audio)");
+    }
+    for device in &audio_inputs {
+        let mut tags = Vec::new();
+        if device.is_default {
+            tags.push("default, used by --audio mic");
+        }
+        if device.is_system_audio {
+            tags.push("system loopback, used by --audio system");
+        }
+        if tags.is_empty() {
+            println!("   • {}", device.name);
+        } else {
+            println!("   • {} ({})", device.name, tags.join("; "));
+        }
+    }
+
+    Ok(())
+}
+
+/// Directory holding a chunk; relative chunk paths are resolved against the data dir
+fn chunk_output_dir(chunk_path: &str, data_dir: &std::path::Path) -> Result<std::path::PathBuf> {
+    let chunk_path = std::path::Path::new(chunk_path);
+    let parent = chunk_path.parent()
+        .ok_or_else(|| error::ScreenRecError::ConfigError("Could not determine output directory".to_string()))?;
+
+    if chunk_path.is_absolute() {
+        Ok(parent.to_path_buf())
+    } else {
+        Ok(data_dir.join(parent))
+    }
+}
+
+/// Directory a task's chunks (and its final.mp4) were recorded into
+async fn task_output_dir(task_id: &str) -> Result<std::path::PathBuf> {
+    let omega_dir = dirs::home_dir()
+        .ok_or_else(|| error::ScreenRecError::ConfigError("Could not find home directory".to_string()))?
+        .join(".omega");
+    let data_dir = omega_dir.join("data");
+
+    let db = Database::new(omega_dir.join("db.sqlite")).await?;
+    let chunks = db.get_chunks_by_task_id(task_id).await?;
+    if chunks.is_empty() {
+        return Err(error::ScreenRecError::InvalidParameter(
+            format!("No chunks found for task_id: {}", task_id)
+        ));
+    }
+
+    chunk_output_dir(&chunks[0].file_path, &data_dir)
 }
 
-/// Concatenate video chunks for a completed task recording
-async fn inspect_sessions(task_id: &str) -> Result<()> {
-    println!("🔍 Inspecting recording sessions for task: {}", task_id);
+/// Mux the session.wav written on macOS into a task's final.mp4.
+/// `apad` + `-shortest` pads a short WAV with silence and cuts a long one at the video's end.
+async fn merge_audio(
+    task_id: &str,
+    output_path: Option<std::path::PathBuf>,
+    ffmpeg_path: Option<std::path::PathBuf>,
+) -> Result<()> {
+    let ffmpeg_binary = ffmpeg_utils::find_ffmpeg_binary(ffmpeg_path.as_ref())?;
+    let version = ffmpeg_utils::validate_ffmpeg(&ffmpeg_binary)?;
+    log::info!("Using FFmpeg: {}", version);
+
+    let output_dir = task_output_dir(task_id).await?;
+    let video_path = output_dir.join("final.mp4");
+    let wav_path = output_dir.join(audio::SESSION_WAV_FILE);
+
+    if !video_path.exists() {
+        return Err(error::ScreenRecError::InvalidParameter(format!(
+            "{} not found. Run `omgrec concat --task-id {}` first.",
+            video_path.display(),
+            task_id
+        )));
+    }
+    if !wav_path.exists() {
+        return Err(error::ScreenRecError::InvalidParameter(format!(
+            "No {} found for task {} (it is only written on macOS recordings with audio)",
+            audio::SESSION_WAV_FILE,
+            task_id
+        )));
+    }
 
-    // Set up database path
-    let omega_dir = dirs::home_dir()
-        .ok_or_else(|| error::ScreenRecError::ConfigError("Could not find home directory".to_string()))?
-        .join(".omega");
+    let final_output_path = output_path.unwrap_or_else(|| output_dir.join("final_with_audio.mp4"));
+    if final_output_path == video_path {
+        return Err(error::ScreenRecError::InvalidParameter(
+            "Output must differ from final.mp4, which is the merge input".to_string(),
+        ));
+    }
 
-    let db_path = omega_dir.join("db.sqlite");
-    let db = Database::new(&db_path).await?;
+    println!("🔄 [PROGRESS] Merging {} into {}...", audio::SESSION_WAV_FILE, video_path.display());
+
+    // Stretch or squeeze the audio to wall-clock time if its clock drifted
+    let mut audio_filter = "apad".to_string();
+    let timing_path = output_dir.join(audio::SESSION_TIMING_FILE);
+    if timing_path.exists() {
+        let nominal_rate = audio::wav_sample_rate(&wav_path)?;
+        let points = audio::read_timing_points(&timing_path)?;
+        if let Some(rate) = audio::measured_sample_rate(&points, nominal_rate) {
+            println!(
+                "🎚️  Audio clock ran at {:.1} Hz instead of {} Hz; correcting drift of {:.2}s/hour",
+                rate,
+                nominal_rate,
+                (nominal_rate as f64 / rate - 1.0) * 3600.0
+            );
+            audio_filter = format!("asetrate={:.3},aresample={},apad", rate, nominal_rate);
+        }
+    }
 
-    // Get all sessions for this task
-    let sessions = db.get_sessions_for_task(task_id).await?;
+    let path_str = |path: &std::path::Path| {
+        path.to_str()
+            .map(|s| s.to_string())
+            .ok_or_else(|| error::ScreenRecError::InvalidParameter(format!("Invalid path: {}", path.display())))
+    };
+    let ffmpeg_args = vec![
+        "-y".to_string(),
+        "-i".to_string(), path_str(&video_path)?,
+        "-i".to_string(), path_str(&wav_path)?,
+        "-map".to_string(), "0:v:0".to_string(),
+        "-map".to_string(), "1:a:0".to_string(),
+        "-c:v".to_string(), "copy".to_string(),
+        "-c:a".to_string(), "aac".to_string(),
+        "-b:a".to_string(), "128k".to_string(),
+        "-af".to_string(), audio_filter,
+        "-shortest".to_string(),
+        "-movflags".to_string(), "+faststart".to_string(),
+        path_str(&final_output_path)?,
+    ];
+    log::debug!("FFmpeg merge args: {:?}", ffmpeg_args);
 
-    if sessions.is_empty() {
-        println!("❌ No recording sessions found for task_id: {}", task_id);
-        return Ok(());
+    let output = std::process::Command::new(&ffmpeg_binary)
+        .args(&ffmpeg_args)
+        .output()
+        .map_err(|e| error::ScreenRecError::EncodingError(format!("Failed to run ffmpeg: {}", e)))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        log::error!("FFmpeg stderr: {}", stderr);
+        std::fs::remove_file(&final_output_path).ok();
+        return Err(error::ScreenRecError::EncodingError(format!(
+            "FFmpeg audio merge failed: {}",
+            stderr
+        )));
     }
 
-    println!("\n📊 Found {} recording session(s):\n", sessions.len());
-    println!("{:<6} {:<22} {:<22} {:<12} {:<15}",
-             "ID", "Started At", "Ended At", "Duration (s)", "Status");
-    println!("{}", "=".repeat(80));
+    println!("✅ Video with audio saved to: {}", final_output_path.display());
+    Ok(())
+}
 
-    let mut total_duration = 0.0;
+/// One `file '...'` line for the FFmpeg concat demuxer list
+fn concat_list_entry(path: &std::path::Path) -> String {
+    // Escape single quotes in the path by replacing ' with '\''
+    let path_str = path.to_string_lossy().replace("'", r"'\''");
+    format!("file '{}'\n", path_str)
+}
 
-    for session in &sessions {
-        let duration = if let Some(ended_at) = session.ended_at {
-            let duration_secs = (ended_at - session.started_at).num_seconds() as f64;
-            total_duration += duration_secs;
-            format!("{:.2}", duration_secs)
-        } else {
-            "N/A".to_string()
-        };
+/// FFmpeg arguments that join the chunks in `concat_list_path` into `output`.
+/// `normalize_to` re-encodes every chunk scaled and padded to that size;
+/// otherwise streams are copied.
+fn concat_ffmpeg_args(
+    concat_list_path: &std::path::Path,
+    output: &std::path::Path,
+    normalize_to: Option<(i64, i64)>,
+    fps: i64,
+    raw_concat: bool,
+) -> Vec<String> {
+    let mut ffmpeg_args = vec![
+        "-f".to_string(), "concat".to_string(),
+        "-safe".to_string(), "0".to_string(),
+        "-i".to_string(), concat_list_path.to_string_lossy().to_string(),
+    ];
 
-        let status = if session.ended_at.is_some() {
-            "Completed"
-        } else {
-            "In Progress"
-        };
+    if let Some((max_width, max_height)) = normalize_to {
+        // Add video filter for scaling and padding
+        let filter_string = format!(
+            "scale={}:{}:force_original_aspect_ratio=decrease,pad={}:{}:(ow-iw)/2:(oh-ih)/2:black",
+            max_width, max_height, max_width, max_height
+        );
 
-        let ended_at_str = if let Some(ended_at) = session.ended_at {
-            ended_at.format("%Y-%m-%d %H:%M:%S").to_string()
-        } else {
-            "N/A".to_string()
-        };
+        ffmpeg_args.extend(vec![
+            "-vf".to_string(), filter_string,
+            "-c:v".to_string(), "libx264".to_string(),
+            "-preset".to_string(), "medium".to_string(),
+            "-crf".to_string(), "23".to_string(),
+            // Frame rate params (only for re-encoding)
+            "-r".to_string(), fps.to_string(),
+            "-fps_mode".to_string(), "cfr".to_string(),
+        ]);
+    } else {
+        // No normalization needed, use copy mode
+        // Note: Cannot use -r or -fps_mode with -c copy as they require re-encoding
+        ffmpeg_args.extend(vec![
+            "-c".to_string(), "copy".to_string(),
+        ]);
 
-        println!("{:<6} {:<22} {:<22} {:<12} {:<15}",
-                 session.id,
-                 session.started_at.format("%Y-%m-%d %H:%M:%S"),
-                 ended_at_str,
-                 duration,
-                 status);
+        if raw_concat {
+            // Shift timestamps to start at zero so players don't see a leading gap
+            ffmpeg_args.extend(vec![
+                "-avoid_negative_ts".to_string(), "make_zero".to_string(),
+            ]);
+        }
     }
 
-    println!("{}", "=".repeat(80));
-    println!("\n✅ Total accumulated recording time: {:.2} seconds ({:.2} minutes)",
-             total_duration, total_duration / 60.0);
-
-    // Also get and display the database-calculated total for verification
-    let db_total = db.get_total_recording_time(task_id).await?;
-    println!("✅ Database calculated total: {:.2} seconds ({:.2} minutes)",
-             db_total, db_total / 60.0);
+    ffmpeg_args.push(output.to_string_lossy().to_string());
+    ffmpeg_args
+}
 
-    if (total_duration - db_total).abs() > 0.01 {
-        println!("⚠️  WARNING: Mismatch between manual sum and database calculation!");
+/// Quote an argument for copy-pasting into a POSIX shell, leaving plain words alone
+fn shell_quote(arg: &str) -> String {
+    let plain = !arg.is_empty()
+        && arg.chars().all(|c| c.is_ascii_alphanumeric() || "-_./:=+,@%".contains(c));
+    if plain {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
     }
-
-    Ok(())
 }
 
-async fn concatenate_chunks(
-    task_id: &str,
+/// `omgrec concat` flags, passed to every attempt
+#[derive(Clone)]
+struct ConcatOptions {
     output_path: Option<std::path::PathBuf>,
     ffmpeg_path: Option<std::path::PathBuf>,
-) -> Result<()> {
+    raw_concat: bool,
+    deep_validate: bool,
+    verify: bool,
+    verify_reference: Option<std::path::PathBuf>,
+    dry_run: bool,
+    probe_concurrency: usize,
+}
+
+async fn concatenate_chunks(task_id: &str, options: ConcatOptions) -> Result<()> {
+    // A plan has nothing to retry
+    if options.dry_run {
+        return concatenate_chunks_impl(task_id, options).await;
+    }
+
     const MAX_RETRIES: u32 = 3;
     let mut last_error = None;
 
@@ -647,6 +2001,7 @@ async fn concatenate_chunks(
         if attempt > 1 {
             log::info!("Retry attempt {}/{} for task {}", attempt, MAX_RETRIES, task_id);
             println!("🔄 [PROGRESS] Retry attempt {}/{}", attempt, MAX_RETRIES);
+            progress::emit("concat", None, &format!("Retry attempt {}/{}", attempt, MAX_RETRIES));
 
             // Wait before retrying (exponential backoff: 2s, 4s, 8s)
             let wait_secs = 2u64.pow(attempt - 1);
@@ -657,7 +2012,8 @@ async fn concatenate_chunks(
         println!("🔄 [PROGRESS] Starting concatenation for task: {} (attempt {}/{})", task_id, attempt, MAX_RETRIES);
         log::info!("Starting chunk concatenation for task_id: {} (attempt {}/{})", task_id, attempt, MAX_RETRIES);
 
-        match concatenate_chunks_impl(task_id, output_path.clone(), ffmpeg_path.clone()).await {
+        let attempt_result = concatenate_chunks_impl(task_id, options.clone()).await;
+        match attempt_result {
             Ok(()) => {
                 if attempt > 1 {
                     log::info!("✅ Concatenation succeeded on attempt {}/{}", attempt, MAX_RETRIES);
@@ -667,6 +2023,7 @@ async fn concatenate_chunks(
             Err(e) => {
                 log::error!("Concatenation attempt {}/{} failed: {}", attempt, MAX_RETRIES, e);
                 println!("❌ [PROGRESS] Attempt {}/{} failed: {}", attempt, MAX_RETRIES, e);
+                progress::emit("concat", None, &format!("Attempt {}/{} failed: {}", attempt, MAX_RETRIES, e));
                 last_error = Some(e);
 
                 if attempt < MAX_RETRIES {
@@ -677,19 +2034,27 @@ async fn concatenate_chunks(
     }
 
     // All retries exhausted
+    progress::emit("error", None, "All concatenation attempts failed");
     Err(last_error.unwrap_or_else(|| {
         error::ScreenRecError::EncodingError("All concatenation attempts failed".to_string())
     }))
 }
 
-async fn concatenate_chunks_impl(
-    task_id: &str,
-    output_path: Option<std::path::PathBuf>,
-    ffmpeg_path: Option<std::path::PathBuf>,
-) -> Result<()> {
+async fn concatenate_chunks_impl(task_id: &str, options: ConcatOptions) -> Result<()> {
+    let ConcatOptions {
+        output_path,
+        ffmpeg_path,
+        raw_concat,
+        deep_validate,
+        verify,
+        verify_reference,
+        dry_run,
+        probe_concurrency,
+    } = options;
 
     // Find and validate FFmpeg binary
     println!("🔄 [PROGRESS] Validating FFmpeg installation...");
+    progress::emit("concat", Some(0), "Validating FFmpeg installation");
     let ffmpeg_binary = ffmpeg_utils::find_ffmpeg_binary(ffmpeg_path.as_ref())?;
 
     // Validate FFmpeg is working
@@ -712,6 +2077,7 @@ async fn concatenate_chunks_impl(
 
     // Initialize database
     println!("🔄 [PROGRESS] Loading recording data from database...");
+    progress::emit("concat", Some(5), "Loading recording data from database");
     let db_path = omega_dir.join("db.sqlite");
     let db = Database::new(&db_path).await?;
 
@@ -725,7 +2091,19 @@ async fn concatenate_chunks_impl(
         ));
     }
 
+    let encrypted = chunks
+        .iter()
+        .filter(|chunk| encryption::is_encrypted(std::path::Path::new(&chunk.file_path)))
+        .count();
+    if encrypted > 0 {
+        return Err(error::ScreenRecError::InvalidParameter(format!(
+            "{} chunk(s) of task {} are encrypted; run `omgrec decrypt --task-id {}` first",
+            encrypted, task_id, task_id
+        )));
+    }
+
     println!("✅ [PROGRESS] Found {} video chunks to concatenate", chunks.len());
+    progress::emit("concat", Some(10), &format!("Found {} video chunks to concatenate", chunks.len()));
     log::info!("Found {} chunks to concatenate", chunks.len());
 
     // Extract FPS from chunks (use first chunk's FPS, default to 30 if not set)
@@ -735,20 +2113,13 @@ async fn concatenate_chunks_impl(
     log::info!("Using FPS: {}", fps);
 
     // Determine output directory from first chunk
-    let first_chunk_path = std::path::Path::new(&chunks[0].file_path);
-    let output_dir = if first_chunk_path.is_absolute() {
-        first_chunk_path.parent()
-            .ok_or_else(|| error::ScreenRecError::ConfigError("Could not determine output directory".to_string()))?
-            .to_path_buf()
-    } else {
-        data_dir.join(first_chunk_path.parent()
-            .ok_or_else(|| error::ScreenRecError::ConfigError("Could not determine output directory".to_string()))?)
-    };
+    let output_dir = chunk_output_dir(&chunks[0].file_path, &data_dir)?;
 
     log::info!("Output directory: {}", output_dir.display());
 
     // Check if we need normalization (multiple resolutions detected)
     println!("🔄 [PROGRESS] Analyzing video frames and resolutions...");
+    progress::emit("concat", Some(15), "Analyzing video frames and resolutions");
     let frames = db.get_frames_by_task_id(task_id).await?;
     let mut resolutions = std::collections::HashSet::new();
     for frame in &frames {
@@ -758,18 +2129,27 @@ async fn concatenate_chunks_impl(
     }
 
     let needs_normalization = resolutions.len() > 1;
+    // Mixed resolutions are scaled and padded to the largest width and height
+    let normalize_to = needs_normalization.then(|| {
+        resolutions.iter().fold((0i64, 0i64), |(max_w, max_h), &(w, h)| (max_w.max(w), max_h.max(h)))
+    });
 
-    if needs_normalization {
+    if raw_concat {
+        // Raw concat only stream-copies; mixed resolutions would produce a broken file
+        if needs_normalization {
+            return Err(error::ScreenRecError::InvalidParameter(format!(
+                "--raw-concat needs chunks with a single resolution, found {:?}",
+                resolutions
+            )));
+        }
+        println!("⚡ [PROGRESS] Raw concat: skipping chunk probing, stream copy only");
+        log::info!("Raw concat mode: chunks are assumed compatible");
+    } else if needs_normalization {
         println!("⚠️  [PROGRESS] Multiple resolutions detected - normalization required");
         log::info!("Multiple resolutions detected: {:?}", resolutions);
         log::info!("Video normalization will be applied during concatenation");
 
-        // Find the maximum dimensions across all resolutions
-        let (max_width, max_height) = resolutions.iter()
-            .fold((0i64, 0i64), |(max_w, max_h), &(w, h)| {
-                (max_w.max(w), max_h.max(h))
-            });
-
+        let (max_width, max_height) = normalize_to.unwrap_or_default();
         println!("📐 [PROGRESS] Target resolution: {}x{}", max_width, max_height);
         log::info!("Target resolution: {}x{}", max_width, max_height);
     } else {
@@ -779,147 +2159,92 @@ async fn concatenate_chunks_impl(
 
     // Create concat file list for FFmpeg
     println!("🔄 [PROGRESS] Preparing concatenation list...");
+    progress::emit("concat", Some(20), "Validating chunks");
     let concat_list_path = output_dir.join("concat_list.txt");
     let mut concat_content = String::new();
     let mut existing_chunks = 0;
     let mut missing_chunks = 0;
     let mut invalid_chunks = 0;
     let mut total_chunk_duration = 0.0;
+    let mut first_valid_chunk = None;
 
     // Get ffprobe path for validating chunks
     let ffprobe_cmd = ffmpeg_utils::find_ffprobe_binary(&ffmpeg_binary);
 
     log::info!("===== CHUNK VALIDATION =====");
 
-    for (idx, chunk) in chunks.iter().enumerate() {
-        // Build absolute path to chunk file
-        let chunk_path = if std::path::Path::new(&chunk.file_path).is_absolute() {
-            std::path::PathBuf::from(&chunk.file_path)
-        } else {
-            data_dir.join(&chunk.file_path)
-        };
-
-        // Only include files that actually exist
-        if chunk_path.exists() {
-            // Get file size first - skip extremely small files (likely corrupted)
-            let file_size = std::fs::metadata(&chunk_path)
-                .map(|m| m.len())
-                .unwrap_or(0);
-
-            if file_size < 1024 {
-                log::warn!("Skipping chunk {} - file too small ({} bytes, likely corrupted): {}",
-                    idx + 1, file_size, chunk_path.display());
-                invalid_chunks += 1;
-                continue;
+    // Build absolute paths to the chunk files
+    let chunk_paths: Vec<std::path::PathBuf> = chunks
+        .iter()
+        .map(|chunk| {
+            if std::path::Path::new(&chunk.file_path).is_absolute() {
+                std::path::PathBuf::from(&chunk.file_path)
+            } else {
+                data_dir.join(&chunk.file_path)
             }
+        })
+        .collect();
 
-            // Get chunk duration
-            let duration_result = std::process::Command::new(&ffprobe_cmd)
-                .args(&[
-                    "-v", "error",
-                    "-show_entries", "format=duration",
-                    "-of", "default=noprint_wrappers=1:nokey=1",
-                    chunk_path.to_str().unwrap()
-                ])
-                .output();
-
-            // Parse duration and validate it's a valid number
-            let duration_opt = duration_result
-                .ok()
-                .and_then(|result| String::from_utf8(result.stdout).ok())
-                .and_then(|duration_str| {
-                    let trimmed = duration_str.trim();
-                    // Check for "N/A" or invalid duration strings
-                    if trimmed == "N/A" || trimmed.is_empty() {
-                        None
-                    } else {
-                        trimmed.parse::<f64>().ok().and_then(|d| {
-                            // Duration must be positive and reasonable (< 1 hour per chunk)
-                            if d > 0.0 && d < 3600.0 {
-                                Some(d)
-                            } else {
-                                None
-                            }
-                        })
-                    }
-                });
+    // Pre-validated chunks skip the ffprobe runs, which dominate concat time;
+    // otherwise chunks are probed in parallel and reported in order below
+    let probed = if raw_concat {
+        Vec::new()
+    } else {
+        log::info!("Probing {} chunks, {} at a time", chunk_paths.len(), probe_concurrency);
+        if deep_validate {
+            println!("🔍 [PROGRESS] Deep validation: decoding every frame of every chunk");
+        }
+        // The probe threads wait on child processes, so keep them off the async workers
+        let (paths, ffprobe) = (chunk_paths.clone(), ffprobe_cmd.clone());
+        let deep_ffmpeg = deep_validate.then(|| ffmpeg_binary.clone());
+        tokio::task::spawn_blocking(move || {
+            ffmpeg_utils::validate_video_files(&paths, &ffprobe, deep_ffmpeg.as_deref(), probe_concurrency, |done| {
+                // Chunk validation covers 20-50%
+                progress::emit(
+                    "concat",
+                    Some((20 + done * 30 / paths.len()) as u8),
+                    &format!("Validated chunk {}/{}", done, paths.len()),
+                );
+            })
+        })
+        .await
+        .map_err(|e| error::ScreenRecError::EncodingError(format!("Chunk validation failed: {}", e)))?
+    };
 
-            // Validate that the chunk has valid video streams using ffprobe
-            let has_valid_stream = std::process::Command::new(&ffprobe_cmd)
-                .args(&[
-                    "-v", "quiet",
-                    "-select_streams", "v:0",
-                    "-show_entries", "stream=codec_type",
-                    "-of", "default=noprint_wrappers=1:nokey=1",
-                    chunk_path.to_str().unwrap()
-                ])
-                .output()
-                .map(|output| {
-                    output.status.success() && !output.stdout.is_empty()
-                })
-                .unwrap_or(false);
-
-            // Get video codec info to ensure it's actually H.264
-            let has_valid_codec = std::process::Command::new(&ffprobe_cmd)
-                .args(&[
-                    "-v", "quiet",
-                    "-select_streams", "v:0",
-                    "-show_entries", "stream=codec_name",
-                    "-of", "default=noprint_wrappers=1:nokey=1",
-                    chunk_path.to_str().unwrap()
-                ])
-                .output()
-                .map(|output| {
-                    if output.status.success() {
-                        let codec = String::from_utf8_lossy(&output.stdout);
-                        let codec_name = codec.trim();
-                        // Accept h264 or hevc
-                        codec_name == "h264" || codec_name == "hevc"
-                    } else {
-                        false
+    for (idx, chunk_path) in chunk_paths.iter().enumerate() {
+        let health = if raw_concat {
+            ffmpeg_utils::check_chunk_file(chunk_path).unwrap_or(ffmpeg_utils::ChunkHealth::Valid { duration: 0.0 })
+        } else {
+            match &probed[idx] {
+                Some(info) => {
+                    if let Some(codec) = &info.codec {
+                        log::debug!("Chunk {} codec: {}", idx + 1, codec);
                     }
-                })
-                .unwrap_or(false);
-
-            // Only include chunk if validations pass
-            // Note: We skip deep frame-level validation as it's too slow (decodes every frame)
-            // The 4 checks above (size, duration, stream, codec) are sufficient
-            let is_valid = has_valid_stream
-                && duration_opt.is_some()
-                && has_valid_codec;
+                    info.health()
+                }
+                None => ffmpeg_utils::ChunkHealth::Missing,
+            }
+        };
 
-            if is_valid {
-                let duration = duration_opt.unwrap();
+        match health {
+            ffmpeg_utils::ChunkHealth::Valid { duration } => {
                 total_chunk_duration += duration;
-                log::info!("Chunk {}: {:.2}s - {}", idx + 1, duration, chunk_path.file_name().unwrap_or_default().to_string_lossy());
-
-                // Escape single quotes in the path by replacing ' with '\''
-                let path_str = chunk_path.to_string_lossy().replace("'", r"'\''");
-                concat_content.push_str(&format!("file '{}'\n", path_str));
-                existing_chunks += 1;
-            } else {
-                // Detailed error reporting
-                let mut reasons = Vec::new();
-                if !has_valid_stream {
-                    reasons.push("no video stream");
-                }
-                if duration_opt.is_none() {
-                    reasons.push("invalid/missing duration");
-                }
-                if !has_valid_codec {
-                    reasons.push("unsupported codec");
+                if !raw_concat {
+                    log::info!("Chunk {}: {:.2}s - {}", idx + 1, duration, chunk_path.file_name().unwrap_or_default().to_string_lossy());
                 }
 
-                log::warn!("Skipping chunk {} ({}): {}",
-                    idx + 1,
-                    reasons.join(", "),
-                    chunk_path.display()
-                );
+                concat_content.push_str(&concat_list_entry(chunk_path));
+                first_valid_chunk.get_or_insert_with(|| chunk_path.clone());
+                existing_chunks += 1;
+            }
+            ffmpeg_utils::ChunkHealth::Missing => {
+                log::warn!("Skipping missing chunk file: {}", chunk_path.display());
+                missing_chunks += 1;
+            }
+            problem => {
+                log::warn!("Skipping chunk {} ({}): {}", idx + 1, problem, chunk_path.display());
                 invalid_chunks += 1;
             }
-        } else {
-            log::warn!("Skipping missing chunk file: {}", chunk_path.display());
-            missing_chunks += 1;
         }
     }
 
@@ -948,13 +2273,37 @@ async fn concatenate_chunks_impl(
                    missing_chunks + invalid_chunks, warning_msg, existing_chunks);
     }
 
+    // Determine final output path
+    let final_output_path = output_path.unwrap_or_else(|| output_dir.join("final.mp4"));
+    let ffmpeg_args = concat_ffmpeg_args(&concat_list_path, &final_output_path, normalize_to, fps, raw_concat);
+
+    if dry_run {
+        println!("\n📝 Dry run: nothing was deleted or written");
+        println!("Chunks: {} valid, {} missing, {} invalid", existing_chunks, missing_chunks, invalid_chunks);
+        println!("Total duration: {:.2}s ({:.1} minutes)", total_chunk_duration, total_chunk_duration / 60.0);
+        match normalize_to {
+            Some((width, height)) => println!("Mode: re-encode, normalizing {:?} to {}x{}", resolutions, width, height),
+            None => println!("Mode: stream copy"),
+        }
+        if final_output_path.exists() {
+            println!("Would replace: {}", final_output_path.display());
+        }
+        if let Some(reference) = verify_reference.or(first_valid_chunk).filter(|_| verify) {
+            println!("Verify: SSIM/PSNR against {}", reference.display());
+        }
+        println!("\nConcat list ({}):\n{}", concat_list_path.display(), concat_content);
+        println!(
+            "Command:\n{} {}",
+            shell_quote(&ffmpeg_binary),
+            ffmpeg_args.iter().map(|arg| shell_quote(arg)).collect::<Vec<_>>().join(" ")
+        );
+        return Ok(());
+    }
+
     std::fs::write(&concat_list_path, concat_content).map_err(|e| {
         error::ScreenRecError::EncodingError(format!("Failed to write concat list: {}", e))
     })?;
 
-    // Determine final output path
-    let final_output_path = output_path.unwrap_or_else(|| output_dir.join("final.mp4"));
-
     // Clean up any existing output files from previous failed attempts
     if final_output_path.exists() {
         log::warn!("Removing existing output file from previous attempt: {}", final_output_path.display());
@@ -974,47 +2323,10 @@ async fn concatenate_chunks_impl(
     }
 
     println!("🎬 [PROGRESS] Starting FFmpeg concatenation...");
+    progress::emit("concat", Some(50), "Running FFmpeg concatenation");
     println!("   Output: {}", final_output_path.display());
     log::info!("Concatenating chunks to: {}", final_output_path.display());
 
-    let mut ffmpeg_args = vec![
-        "-f".to_string(), "concat".to_string(),
-        "-safe".to_string(), "0".to_string(),
-        "-i".to_string(), concat_list_path.to_str().unwrap().to_string(),
-    ];
-
-    if needs_normalization {
-        // Find the maximum dimensions
-        let (max_width, max_height) = resolutions.iter()
-            .fold((0i64, 0i64), |(max_w, max_h), &(w, h)| {
-                (max_w.max(w), max_h.max(h))
-            });
-
-        // Add video filter for scaling and padding
-        let filter_string = format!(
-            "scale={}:{}:force_original_aspect_ratio=decrease,pad={}:{}:(ow-iw)/2:(oh-ih)/2:black",
-            max_width, max_height, max_width, max_height
-        );
-
-        ffmpeg_args.extend(vec![
-            "-vf".to_string(), filter_string,
-            "-c:v".to_string(), "libx264".to_string(),
-            "-preset".to_string(), "medium".to_string(),
-            "-crf".to_string(), "23".to_string(),
-            // Frame rate params (only for re-encoding)
-            "-r".to_string(), fps.to_string(),
-            "-fps_mode".to_string(), "cfr".to_string(),
-        ]);
-    } else {
-        // No normalization needed, use copy mode
-        // Note: Cannot use -r or -fps_mode with -c copy as they require re-encoding
-        ffmpeg_args.extend(vec![
-            "-c".to_string(), "copy".to_string(),
-        ]);
-    }
-
-    ffmpeg_args.push(final_output_path.to_str().unwrap().to_string());
-
     log::info!("Running FFmpeg concatenation: {}", ffmpeg_binary);
 
     let concat_result = std::process::Command::new(&ffmpeg_binary)
@@ -1060,6 +2372,7 @@ async fn concatenate_chunks_impl(
     }
 
     println!("✅ [PROGRESS] Video concatenation complete!");
+    progress::emit("concat", Some(80), "Video concatenation complete");
     log::info!("✅ Final video created: {}", final_output_path.display());
     println!("✅ Final video saved to: {}", final_output_path.display());
 
@@ -1067,70 +2380,44 @@ async fn concatenate_chunks_impl(
 
     // Get video metadata using ffprobe
     println!("🔄 [PROGRESS] Extracting video metadata...");
+    progress::emit("concat", Some(85), "Extracting video metadata");
     log::info!("Extracting video metadata...");
     let ffprobe_cmd = ffmpeg_utils::find_ffprobe_binary(&ffmpeg_binary);
 
-    let ffprobe_result = std::process::Command::new(&ffprobe_cmd)
-        .args(&[
-            "-v", "quiet",
-            "-print_format", "json",
-            "-show_format",
-            "-show_streams",
-            final_output_path.to_str().unwrap()
-        ])
-        .output();
-
-    let mut video_duration_secs = 0.0;
-    let mut video_bitrate = 0;
-    let mut video_codec = String::new();
-    let mut file_size_bytes = 0;
-
-    if let Ok(result) = ffprobe_result {
-        if result.status.success() {
-            let output_str = String::from_utf8_lossy(&result.stdout);
-            if let Ok(json_data) = serde_json::from_str::<serde_json::Value>(&output_str) {
-                // Get duration from format
-                if let Some(format_obj) = json_data.get("format") {
-                    if let Some(duration_str) = format_obj.get("duration").and_then(|v| v.as_str()) {
-                        video_duration_secs = duration_str.parse::<f64>().unwrap_or(0.0);
-                        log::info!("Sum of chunk durations: {:.2}s ({:.1} min)", total_chunk_duration, total_chunk_duration / 60.0);
-                        log::info!("Final video duration:    {:.2}s ({:.1} min)", video_duration_secs, video_duration_secs / 60.0);
-                        let diff = (video_duration_secs - total_chunk_duration).abs();
-                        let diff_pct = (diff / total_chunk_duration * 100.0).abs();
-                        if diff > 1.0 {
-                            log::warn!("Duration mismatch: {:.2}s difference ({:.1}%)", diff, diff_pct);
-                        } else {
-                            log::info!("Duration match: within {:.2}s ({:.2}%)", diff, diff_pct);
-                        }
-                        log::info!("==============================");
-                    }
-                    if let Some(bitrate_str) = format_obj.get("bit_rate").and_then(|v| v.as_str()) {
-                        video_bitrate = bitrate_str.parse::<i64>().unwrap_or(0);
-                    }
-                    if let Some(size_str) = format_obj.get("size").and_then(|v| v.as_str()) {
-                        file_size_bytes = size_str.parse::<i64>().unwrap_or(0);
-                    }
-                }
-                // Get codec from first video stream
-                if let Some(streams) = json_data.get("streams").and_then(|v| v.as_array()) {
-                    for stream in streams {
-                        if stream.get("codec_type").and_then(|v| v.as_str()) == Some("video") {
-                            video_codec = stream.get("codec_name")
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("unknown")
-                                .to_string();
-                            break;
-                        }
-                    }
-                }
-            }
+    let media = match ffmpeg_utils::probe_json(&ffprobe_cmd, &final_output_path) {
+        Ok(json) => ffmpeg_utils::MediaInfo::from_probe_json(&json),
+        Err(e) => {
+            log::warn!("Failed to probe {}: {}", final_output_path.display(), e);
+            ffmpeg_utils::MediaInfo::default()
+        }
+    };
+    let video_duration_secs = media.duration.unwrap_or(0.0);
+    let video_bitrate = media.bit_rate.unwrap_or(0) as i64;
+    let file_size_bytes = media.size.unwrap_or(0) as i64;
+    let video_codec = media.video_codec.clone().unwrap_or_default();
+
+    if media.duration.is_some() {
+        log::info!("Sum of chunk durations: {:.2}s ({:.1} min)", total_chunk_duration, total_chunk_duration / 60.0);
+        log::info!("Final video duration:    {:.2}s ({:.1} min)", video_duration_secs, video_duration_secs / 60.0);
+        let diff = (video_duration_secs - total_chunk_duration).abs();
+        let diff_pct = (diff / total_chunk_duration * 100.0).abs();
+        if raw_concat {
+            // Chunk durations weren't probed in raw mode
+            log::info!("Duration comparison skipped (raw concat)");
+        } else if diff > 1.0 {
+            log::warn!("Duration mismatch: {:.2}s difference ({:.1}%)", diff, diff_pct);
+        } else {
+            log::info!("Duration match: within {:.2}s ({:.2}%)", diff, diff_pct);
         }
+        log::info!("==============================");
     }
 
-    // Get device name and recording settings from first chunk
-    let device_name = "unknown".to_string(); // We don't have this info readily available
-    let fps = 30; // Default, we don't have this stored per task
-    let quality = 8; // Default
+    // Recording settings from the first chunk (null for chunks recorded before they were stored)
+    let first_chunk = chunks.first();
+    let device_name = first_chunk.map(|c| c.device_name.clone()).unwrap_or_else(|| "unknown".to_string());
+    let fps = first_chunk.and_then(|c| c.fps);
+    let quality = first_chunk.and_then(|c| c.quality);
+    let encoder_name = first_chunk.and_then(|c| c.encoder_name.clone());
 
     println!("🔄 [PROGRESS] Calculating recording statistics...");
 
@@ -1155,6 +2442,10 @@ async fn concatenate_chunks_impl(
     let chunk_details: Vec<serde_json::Value> = chunks.iter().map(|c| {
         serde_json::json!({
             "chunk_index": c.chunk_index,
+            "session_id": c.session_id,
+            "fps": c.fps,
+            "encoder": c.encoder_name,
+            "quality": c.quality,
             "file_path": c.file_path,
             "created_at": c.created_at.to_rfc3339(),
         })
@@ -1180,6 +2471,8 @@ async fn concatenate_chunks_impl(
         }
     }
 
+    let paused_secs = sessions.iter().filter_map(|s| s.paused_ms).sum::<i64>() as f64 / 1000.0;
+
     // Get total recording time from database
     log::debug!("Querying total recording time for task {}", task_id);
     let total_recording_time_secs = match db.get_total_recording_time(task_id).await {
@@ -1198,11 +2491,52 @@ async fn concatenate_chunks_impl(
         }
     };
 
+    // Compare the result against a reference so a bad normalize or concat shows up
+    const MIN_VERIFY_SSIM: f64 = 0.9;
+    let quality_metrics = match verify_reference.or(first_valid_chunk).filter(|_| verify) {
+        Some(reference) => {
+            println!("🔄 [PROGRESS] Verifying quality against {}...", reference.display());
+            progress::emit("concat", Some(88), "Verifying quality");
+            match ffmpeg_utils::measure_quality(&ffmpeg_binary, &final_output_path, &reference) {
+                Ok(metrics) => {
+                    println!(
+                        "📏 SSIM: {}, PSNR: {}",
+                        metrics.ssim.map(|ssim| format!("{:.4}", ssim)).unwrap_or_else(|| "n/a".to_string()),
+                        metrics.psnr_db.map(|psnr| format!("{:.2} dB", psnr)).unwrap_or_else(|| "identical".to_string())
+                    );
+                    if metrics.ssim.is_some_and(|ssim| ssim < MIN_VERIFY_SSIM) {
+                        log::warn!("⚠️  SSIM below {}: the output differs noticeably from {}", MIN_VERIFY_SSIM, reference.display());
+                    }
+                    Some(serde_json::json!({
+                        "reference": reference.to_string_lossy(),
+                        "ssim": metrics.ssim,
+                        "psnr_db": metrics.psnr_db,
+                    }))
+                }
+                Err(e) => {
+                    // The video itself is fine; record why it couldn't be measured
+                    log::warn!("⚠️  Quality verification failed: {}", e);
+                    Some(serde_json::json!({
+                        "reference": reference.to_string_lossy(),
+                        "error": e.to_string(),
+                    }))
+                }
+            }
+        }
+        None => {
+            if verify {
+                log::warn!("⚠️  --verify: no reference to compare against");
+            }
+            None
+        }
+    };
+
     // Export comprehensive metadata to JSON
     println!("🔄 [PROGRESS] Generating metadata files...");
+    progress::emit("concat", Some(90), "Generating metadata files");
     log::info!("Exporting comprehensive metadata to JSON...");
 
-    let metadata_output = serde_json::json!({
+    let mut metadata_output = serde_json::json!({
         "version": "1.0",
         "task_id": task_id,
         "device_name": device_name,
@@ -1225,6 +2559,9 @@ async fn concatenate_chunks_impl(
             } else {
                 0.0
             },
+            // Paused time (SIGUSR1) is part of the session wall time but not of the video
+            "paused_seconds": paused_secs,
+            "active_seconds": (total_recording_time_secs - paused_secs).max(0.0),
         },
         "video": {
             "final_video_path": final_output_path.file_name().and_then(|n| n.to_str()).unwrap_or("final.mp4"),
@@ -1240,6 +2577,7 @@ async fn concatenate_chunks_impl(
             "bitrate_bps": video_bitrate,
             "fps": fps,
             "quality": quality,
+            "encoder": encoder_name,
         },
         "focused_time": {
             "total_seconds": video_duration_secs,
@@ -1255,6 +2593,18 @@ async fn concatenate_chunks_impl(
             "total_count": chunks.len(),
             "details": chunk_details,
         },
+        "capture": {
+            "late_frames": sessions.iter().filter_map(|s| s.late_frames).sum::<i64>(),
+            "sessions": sessions.iter().filter(|s| s.captured_frames.is_some()).map(|s| {
+                serde_json::json!({
+                    "session_id": s.id,
+                    "captured_frames": s.captured_frames,
+                    "achieved_fps": s.achieved_fps,
+                    "late_frames": s.late_frames,
+                    "paused_ms": s.paused_ms,
+                })
+            }).collect::<Vec<_>>(),
+        },
         "frames": {
             "total_count": frames.len(),
             "keyframe_count": keyframe_count,
@@ -1290,6 +2640,9 @@ async fn concatenate_chunks_impl(
             },
         },
     });
+    if let Some(quality_metrics) = quality_metrics {
+        metadata_output["quality_metrics"] = quality_metrics;
+    }
 
     let metadata_path = output_dir.join("metadata.json");
     std::fs::write(&metadata_path, serde_json::to_string_pretty(&metadata_output).unwrap())
@@ -1303,6 +2656,7 @@ async fn concatenate_chunks_impl(
 
     // Also export detailed frame metadata to a separate JSON
     println!("🔄 [PROGRESS] Exporting frame-level data...");
+    progress::emit("concat", Some(95), "Exporting frame-level data");
     log::info!("Exporting detailed frame metadata to JSON...");
 
     let frames_output = serde_json::json!({
@@ -1332,6 +2686,8 @@ async fn concatenate_chunks_impl(
     println!("   📄 {}", frames_path.display());
 
     println!("\n🎉 [PROGRESS] Concatenation complete!");
+    progress::emit("concat", Some(100), "Concatenation complete");
+    progress::done(&final_output_path);
     println!("   Video Duration: {:.1}s | Size: {:.2}MB | Frames: {}",
         video_duration_secs,
         file_size_bytes as f64 / 1024.0 / 1024.0,
@@ -1354,3 +2710,21 @@ async fn concatenate_chunks_impl(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_raw_concat_only_adds_timestamp_shift_to_copy() {
+        let (list, output) = (Path::new("/tmp/task/concat_list.txt"), Path::new("/tmp/task/final.mp4"));
+        let copy = ["-f", "concat", "-safe", "0", "-i", "/tmp/task/concat_list.txt", "-c", "copy"];
+
+        assert_eq!(
+            concat_ffmpeg_args(list, output, None, 30, true),
+            [&copy[..], &["-avoid_negative_ts", "make_zero", "/tmp/task/final.mp4"]].concat()
+        );
+        assert_eq!(concat_ffmpeg_args(list, output, None, 30, false), [&copy[..], &["/tmp/task/final.mp4"]].concat());
+    }
+}
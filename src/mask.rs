@@ -0,0 +1,166 @@
+//! Privacy masks: hide parts of the screen before frames reach the encoder
+//!
+//! `--mask` rectangles are painted over right after the BGRA→RGB conversion, so
+//! the masked content is never written to disk. Blackout fills them with black;
+//! blur runs a few passes of a wide box blur, which approximates a gaussian and
+//! leaves text unreadable while keeping the rough layout visible.
+
+use crate::cli::{MaskMode, MaskRect};
+
+/// Box blur radius in pixels
+const BLUR_RADIUS: usize = 12;
+/// Three box passes approximate a gaussian blur
+const BLUR_PASSES: usize = 3;
+
+/// Hide `masks` (in display pixels) in an RGB24 frame that shows the display
+/// starting at `origin` (non-zero when capturing a region). Masks are clipped to
+/// the frame; ones that miss it are skipped.
+pub fn apply_masks(
+    rgb: &mut [u8],
+    width: usize,
+    height: usize,
+    origin: (usize, usize),
+    masks: &[MaskRect],
+    mode: MaskMode,
+) {
+    for rect in frame_masks(masks, origin, width, height) {
+        match mode {
+            MaskMode::Blackout => blackout(rgb, width, &rect),
+            MaskMode::Blur => blur(rgb, width, &rect),
+        }
+    }
+}
+
+/// `masks` (in display pixels) in the coordinates of a `width`x`height` frame
+/// showing the display from `origin`, clipped to it; ones that miss it are dropped
+pub fn frame_masks(masks: &[MaskRect], origin: (usize, usize), width: usize, height: usize) -> Vec<MaskRect> {
+    masks
+        .iter()
+        .filter_map(|mask| {
+            let left = mask.x.max(origin.0);
+            let top = mask.y.max(origin.1);
+            let right = (mask.x + mask.width).min(origin.0 + width);
+            let bottom = (mask.y + mask.height).min(origin.1 + height);
+            (left < right && top < bottom).then(|| MaskRect {
+                x: left - origin.0,
+                y: top - origin.1,
+                width: right - left,
+                height: bottom - top,
+            })
+        })
+        .collect()
+}
+
+fn blackout(rgb: &mut [u8], width: usize, rect: &MaskRect) {
+    for y in rect.y..rect.y + rect.height {
+        let row = (y * width + rect.x) * 3;
+        rgb[row..row + rect.width * 3].fill(0);
+    }
+}
+
+fn blur(rgb: &mut [u8], width: usize, rect: &MaskRect) {
+    // Blur a copy of the rectangle so nothing outside it is read or written
+    let mut pixels = Vec::with_capacity(rect.width * rect.height * 3);
+    for y in rect.y..rect.y + rect.height {
+        let row = (y * width + rect.x) * 3;
+        pixels.extend_from_slice(&rgb[row..row + rect.width * 3]);
+    }
+
+    let mut line = Vec::new();
+    for _ in 0..BLUR_PASSES {
+        for y in 0..rect.height {
+            box_blur_line(&mut pixels, y * rect.width, 1, rect.width, &mut line);
+        }
+        for x in 0..rect.width {
+            box_blur_line(&mut pixels, x, rect.width, rect.height, &mut line);
+        }
+    }
+
+    for (y, blurred_row) in pixels.chunks_exact(rect.width * 3).enumerate() {
+        let row = ((rect.y + y) * width + rect.x) * 3;
+        rgb[row..row + rect.width * 3].copy_from_slice(blurred_row);
+    }
+}
+
+/// Running-sum box blur along one row or column (`len` pixels, `step` apart),
+/// repeating the edge pixels past either end
+fn box_blur_line(pixels: &mut [u8], start: usize, step: usize, len: usize, line: &mut Vec<[u8; 3]>) {
+    line.clear();
+    line.extend((0..len).map(|i| {
+        let p = (start + i * step) * 3;
+        [pixels[p], pixels[p + 1], pixels[p + 2]]
+    }));
+
+    let radius = BLUR_RADIUS as isize;
+    let window = 2 * BLUR_RADIUS as u32 + 1;
+    let at = |i: isize| line[i.clamp(0, len as isize - 1) as usize];
+
+    let mut sum = [0u32; 3];
+    for i in -radius..=radius {
+        for (total, value) in sum.iter_mut().zip(at(i)) {
+            *total += value as u32;
+        }
+    }
+
+    for i in 0..len {
+        let p = (start + i * step) * 3;
+        for (out, total) in pixels[p..p + 3].iter_mut().zip(sum) {
+            *out = ((total + window / 2) / window) as u8;
+        }
+
+        let (entering, leaving) = (at(i as isize + radius + 1), at(i as isize - radius));
+        for ((total, add), sub) in sum.iter_mut().zip(entering).zip(leaving) {
+            *total = *total + add as u32 - sub as u32;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_masks_change_only_pixels_inside() {
+        let (width, height) = (20, 10);
+        // Checkerboard, so a blur changes every pixel it touches
+        let frame: Vec<u8> = (0..width * height)
+            .flat_map(|i| {
+                let v = if (i % width + i / width) % 2 == 0 { 255 } else { 0 };
+                [v, v, v]
+            })
+            .collect();
+
+        // Capturing a region that starts at (100, 50) of the display; the second
+        // mask hangs off the bottom-right corner and is clipped
+        let masks = [
+            MaskRect { x: 105, y: 52, width: 6, height: 4 },
+            MaskRect { x: 117, y: 58, width: 50, height: 50 },
+        ];
+        let inside = |x: usize, y: usize| ((5..11).contains(&x) && (2..6).contains(&y)) || (x >= 17 && y >= 8);
+
+        for mode in [MaskMode::Blackout, MaskMode::Blur] {
+            let mut rgb = frame.clone();
+            apply_masks(&mut rgb, width, height, (100, 50), &masks, mode);
+
+            for y in 0..height {
+                for x in 0..width {
+                    let i = (y * width + x) * 3;
+                    let (before, after) = (&frame[i..i + 3], &rgb[i..i + 3]);
+                    if !inside(x, y) {
+                        assert_eq!(after, before, "{} touched ({}, {})", mode, x, y);
+                    } else if mode == MaskMode::Blackout {
+                        assert_eq!(after, [0, 0, 0], "({}, {}) not blacked out", x, y);
+                    } else {
+                        assert_ne!(after, before, "({}, {}) not blurred", x, y);
+                    }
+                }
+            }
+        }
+
+        // A mask outside the captured region does nothing
+        let mut rgb = frame.clone();
+        let elsewhere = [MaskRect { x: 0, y: 0, width: 50, height: 50 }];
+        apply_masks(&mut rgb, width, height, (100, 50), &elsewhere, MaskMode::Blackout);
+        assert_eq!(rgb, frame);
+    }
+}
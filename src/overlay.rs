@@ -0,0 +1,176 @@
+//! Wall-clock timestamp burned into captured frames
+//!
+//! For compliance review the time (and task ID, when recording a task) is drawn
+//! into a corner of every frame with a small built-in 5x7 bitmap font, on a
+//! darkened box so it stays readable over any content. Glyphs are scaled up on
+//! large frames. Only uppercase letters, digits and a little punctuation are
+//! included; anything else is drawn as `?`.
+
+use crate::cli::OverlayPosition;
+use chrono::{DateTime, Local};
+
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+
+/// Rows of a glyph, top first; bit 4 is the leftmost pixel
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT] {
+    match c.to_ascii_uppercase() {
+        '0' => [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E],
+        '1' => [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        '2' => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F],
+        '3' => [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E],
+        '4' => [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02],
+        '5' => [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E],
+        '6' => [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E],
+        '7' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+        '8' => [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E],
+        '9' => [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C],
+        'A' => [0x0E, 0x11, 0x11, 0x11, 0x1F, 0x11, 0x11],
+        'B' => [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E],
+        'C' => [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E],
+        'D' => [0x1C, 0x12, 0x11, 0x11, 0x11, 0x12, 0x1C],
+        'E' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F],
+        'F' => [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10],
+        'G' => [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F],
+        'H' => [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11],
+        'I' => [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E],
+        'J' => [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C],
+        'K' => [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11],
+        'L' => [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F],
+        'M' => [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11],
+        'N' => [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11],
+        'O' => [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'P' => [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10],
+        'Q' => [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D],
+        'R' => [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11],
+        'S' => [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E],
+        'T' => [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04],
+        'U' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E],
+        'V' => [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04],
+        'W' => [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A],
+        'X' => [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11],
+        'Y' => [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04],
+        'Z' => [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F],
+        ':' => [0x00, 0x0C, 0x0C, 0x00, 0x0C, 0x0C, 0x00],
+        '-' => [0x00, 0x00, 0x00, 0x1F, 0x00, 0x00, 0x00],
+        '.' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C],
+        '_' => [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x1F],
+        '/' => [0x00, 0x01, 0x02, 0x04, 0x08, 0x10, 0x00],
+        ' ' => [0x00; GLYPH_HEIGHT],
+        _ => [0x0E, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04], // '?'
+    }
+}
+
+/// Timestamp (and optional label such as the task ID) drawn into a frame corner
+pub struct TimestampOverlay {
+    position: OverlayPosition,
+    label: Option<String>,
+}
+
+impl TimestampOverlay {
+    pub fn new(position: OverlayPosition, label: Option<String>) -> Self {
+        Self { position, label }
+    }
+
+    /// Draw the overlay for `now` into an RGB24 frame
+    pub fn draw(&self, rgb: &mut [u8], width: usize, height: usize, now: DateTime<Local>) {
+        let mut lines = vec![now.format("%Y-%m-%d %H:%M:%S").to_string()];
+        if let Some(label) = &self.label {
+            lines.push(label.clone());
+        }
+        draw_text_box(rgb, width, height, &lines, self.position);
+    }
+}
+
+/// Draw white text lines on a half-darkened box in one corner, clipped to the frame
+fn draw_text_box(rgb: &mut [u8], width: usize, height: usize, lines: &[String], position: OverlayPosition) {
+    // 1x up to 1080p-ish frames, 2x at 1440p/4K
+    let scale = (height / 1000).max(1);
+    let padding = 3 * scale;
+    let margin = 8 * scale;
+    let advance = (GLYPH_WIDTH + 1) * scale;
+    let line_height = (GLYPH_HEIGHT + 3) * scale;
+
+    let longest = lines.iter().map(|line| line.chars().count()).max().unwrap_or(0);
+    let box_width = longest * advance - scale + 2 * padding;
+    let box_height = lines.len() * line_height - 3 * scale + 2 * padding;
+    if box_width + margin > width || box_height + margin > height {
+        return;
+    }
+
+    let box_x = match position {
+        OverlayPosition::TopLeft | OverlayPosition::BottomLeft => margin,
+        OverlayPosition::TopRight | OverlayPosition::BottomRight => width - margin - box_width,
+    };
+    let box_y = match position {
+        OverlayPosition::TopLeft | OverlayPosition::TopRight => margin,
+        OverlayPosition::BottomLeft | OverlayPosition::BottomRight => height - margin - box_height,
+    };
+
+    // Semi-transparent background: darken to half brightness
+    for y in box_y..box_y + box_height {
+        let row = (y * width + box_x) * 3;
+        for value in &mut rgb[row..row + box_width * 3] {
+            *value /= 2;
+        }
+    }
+
+    for (line_index, line) in lines.iter().enumerate() {
+        let top = box_y + padding + line_index * line_height;
+        for (char_index, c) in line.chars().enumerate() {
+            let left = box_x + padding + char_index * advance;
+            for (gy, bits) in glyph(c).iter().enumerate() {
+                for gx in 0..GLYPH_WIDTH {
+                    if bits & (0x10 >> gx) == 0 {
+                        continue;
+                    }
+                    for dy in 0..scale {
+                        let row = ((top + gy * scale + dy) * width + left + gx * scale) * 3;
+                        rgb[row..row + scale * 3].fill(255);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_box_stays_in_its_corner() {
+        let (width, height) = (320, 200);
+        let lines = vec!["12:34:56".to_string(), "TASK-1".to_string()];
+
+        for position in [OverlayPosition::TopLeft, OverlayPosition::BottomRight] {
+            let mut rgb = vec![100u8; width * height * 3];
+            draw_text_box(&mut rgb, width, height, &lines, position);
+
+            let touched: Vec<(usize, usize)> = rgb
+                .chunks_exact(3)
+                .enumerate()
+                .filter(|(_, pixel)| pixel[0] != 100)
+                .map(|(i, _)| (i % width, i / width))
+                .collect();
+            assert!(touched.iter().any(|(x, y)| rgb[(y * width + x) * 3] == 255), "no text drawn");
+            assert!(touched.iter().all(|(x, y)| {
+                let value = rgb[(y * width + x) * 3];
+                value == 255 || value == 50
+            }));
+
+            // 8 glyphs * 6px - 1 + 2 * 3px padding = 53 wide, 2 lines * 10px - 3 + 6 = 23 tall
+            let (min_x, min_y) = (touched.iter().map(|p| p.0).min().unwrap(), touched.iter().map(|p| p.1).min().unwrap());
+            let (max_x, max_y) = (touched.iter().map(|p| p.0).max().unwrap(), touched.iter().map(|p| p.1).max().unwrap());
+            match position {
+                OverlayPosition::TopLeft => assert_eq!((min_x, min_y, max_x, max_y), (8, 8, 60, 30)),
+                _ => assert_eq!((min_x, min_y, max_x, max_y), (259, 169, 311, 191)),
+            }
+        }
+
+        // Too small to fit: left untouched
+        let mut tiny = vec![100u8; 20 * 10 * 3];
+        draw_text_box(&mut tiny, 20, 10, &lines, OverlayPosition::TopLeft);
+        assert!(tiny.iter().all(|&v| v == 100));
+    }
+}
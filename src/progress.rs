@@ -0,0 +1,54 @@
+//! Machine-readable progress events
+//!
+//! The emoji `[PROGRESS]` lines on stdout are for people. With the global
+//! `--progress-json PATH` flag the same milestones are also appended to PATH as
+//! newline-delimited JSON (`{"stage":"concat","pct":42,"msg":"..."}`), ending with
+//! `{"stage":"done","output":"..."}`. PATH may be a file or an inherited file
+//! descriptor such as `/dev/fd/3`. Without the flag, `emit` does nothing.
+
+use crate::error::{Result, ScreenRecError};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+static SINK: OnceLock<Mutex<File>> = OnceLock::new();
+
+/// Start appending progress events to `path`
+pub fn init(path: &Path) -> Result<()> {
+    let file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| {
+            ScreenRecError::ConfigError(format!("Failed to open progress file {}: {}", path.display(), e))
+        })?;
+    let _ = SINK.set(Mutex::new(file));
+    Ok(())
+}
+
+/// Report a step of `stage`; `pct` is overall completion when it is known
+pub fn emit(stage: &str, pct: Option<u8>, msg: &str) {
+    let mut event = serde_json::json!({ "stage": stage, "msg": msg });
+    if let Some(pct) = pct {
+        event["pct"] = pct.min(100).into();
+    }
+    write_event(&event);
+}
+
+/// Final event, pointing at what was produced
+pub fn done(output: &Path) {
+    write_event(&serde_json::json!({ "stage": "done", "output": output.to_string_lossy() }));
+}
+
+fn write_event(event: &serde_json::Value) {
+    let Some(sink) = SINK.get() else {
+        return;
+    };
+    if let Ok(mut file) = sink.lock() {
+        // One write per line so readers never see half an event
+        if let Err(e) = file.write_all(format!("{}\n", event).as_bytes()) {
+            log::debug!("Failed to write progress event: {}", e);
+        }
+    }
+}
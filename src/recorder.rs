@@ -0,0 +1,701 @@
+//! Recording from Rust code
+//!
+//! [`Recorder`] is the entry point for embedding the recorder: configure it with
+//! builder calls, `start()` it inside a tokio runtime, then `stop()` the returned
+//! [`RecordingHandle`]. It runs the same pipeline as `omgrec record`: a capture
+//! thread sends frames to a bridge task, which forwards them to the chunked
+//! encoder through a byte-bounded queue. Audio, when enabled, is muxed into the
+//! chunks (on macOS it goes to `session.wav` instead).
+//!
+//! `omgrec record` starts the pipeline with [`RecordingHandle::spawn`] and a full
+//! [`RecordingOptions`] directly, because it also configures masks, overlays, the
+//! database and the other flags the builder leaves at their defaults.
+
+use crate::adaptive::AdaptiveQuality;
+use crate::audio::{AudioCapture, AudioSample};
+use crate::capture::{CaptureStats, ScreenCapture, MAX_HIGH_FPS, MAX_STANDARD_FPS};
+use crate::cli::{AudioSource, ByteSize, ChunkNamePattern, Codec, OverflowPolicy, Preset, ScaleFilter};
+use crate::db::Database;
+use crate::encoder::{self, ChunkFinished, ChunkPolicy, EncoderChoice, EncoderTuning, RateControl, RecordingOutput};
+use crate::encryption::EncryptionKey;
+use crate::error::{Result, ScreenRecError};
+use crate::frame_dump::FrameDumper;
+use crate::frame_log::FrameLog;
+use crate::latency::LatencyStats;
+use crate::status::{RecorderStatus, SharedStatus};
+use crate::{frame_queue, progress, scaling};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+/// Frames the default queue holds at the recording's resolution (10 seconds at 30fps)
+pub const DEFAULT_BUFFER_FRAMES: usize = 300;
+
+/// How long `stop` gives the encoder to close its chunk before capture stops
+const FINISH_CHUNK_GRACE: Duration = Duration::from_millis(500);
+
+/// Builder for a screen recording
+///
+/// ```no_run
+/// # async fn record() -> screenrec::error::Result<()> {
+/// use screenrec::Recorder;
+///
+/// let recording = Recorder::new()
+///     .fps(30)
+///     .quality(8)
+///     .output("recordings")
+///     .on_chunk_finished(|path, index| println!("chunk {} ready: {}", index, path.display()))
+///     .start()?;
+/// tokio::time::sleep(std::time::Duration::from_secs(10)).await;
+/// let summary = recording.stop().await?;
+/// println!("{} chunks in {}", summary.chunks.len(), summary.output_dir.display());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone)]
+pub struct Recorder {
+    output: Option<PathBuf>,
+    fps: u32,
+    allow_high_fps: bool,
+    quality: u8,
+    codec: Codec,
+    encoder: EncoderChoice,
+    display: usize,
+    duration: Option<Duration>,
+    chunk_duration: u64,
+    audio: AudioSource,
+    audio_device: Option<String>,
+    audio_channels: u16,
+    keyframe_interval: Option<u32>,
+    on_chunk_finished: Option<ChunkFinished>,
+    #[cfg(target_os = "macos")]
+    ffmpeg_path: Option<PathBuf>,
+}
+
+impl Default for Recorder {
+    fn default() -> Self {
+        Self {
+            output: None,
+            fps: 30,
+            allow_high_fps: false,
+            quality: 10,
+            codec: Codec::H264,
+            encoder: EncoderChoice::Auto,
+            display: 0,
+            duration: None,
+            chunk_duration: 10,
+            audio: AudioSource::None,
+            audio_device: None,
+            audio_channels: 1,
+            keyframe_interval: None,
+            on_chunk_finished: None,
+            #[cfg(target_os = "macos")]
+            ffmpeg_path: None,
+        }
+    }
+}
+
+impl Recorder {
+    /// 30fps at quality 10, H.264 in 10-second chunks, no audio
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Directory the chunks are written to (required)
+    pub fn output(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.output = Some(dir.into());
+        self
+    }
+
+    /// Frames per second, 1-60 (or up to 240 with `allow_high_fps`)
+    pub fn fps(mut self, fps: u32) -> Self {
+        self.fps = fps;
+        self
+    }
+
+    /// Accept frame rates above 60, for high-refresh displays
+    pub fn allow_high_fps(mut self, allow: bool) -> Self {
+        self.allow_high_fps = allow;
+        self
+    }
+
+    /// Quality 1-10, higher is better
+    pub fn quality(mut self, quality: u8) -> Self {
+        self.quality = quality;
+        self
+    }
+
+    pub fn codec(mut self, codec: Codec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Use exactly this FFmpeg encoder (e.g. `libx264`) instead of the fallback chain
+    pub fn encoder(mut self, name: impl Into<String>) -> Self {
+        self.encoder = EncoderChoice::Named(name.into());
+        self
+    }
+
+    /// Display to start on; with several displays the recording follows the cursor
+    pub fn display(mut self, index: usize) -> Self {
+        self.display = index;
+        self
+    }
+
+    /// Stop by itself after this long (otherwise it runs until `stop`)
+    pub fn duration(mut self, duration: Duration) -> Self {
+        self.duration = Some(duration);
+        self
+    }
+
+    /// Seconds of video per chunk file
+    pub fn chunk_duration(mut self, secs: u64) -> Self {
+        self.chunk_duration = secs;
+        self
+    }
+
+    pub fn audio(mut self, source: AudioSource) -> Self {
+        self.audio = source;
+        self
+    }
+
+    /// Record from the input device with this name (or a unique part of it)
+    pub fn audio_device(mut self, name: impl Into<String>) -> Self {
+        self.audio_device = Some(name.into());
+        self
+    }
+
+    /// Seconds between keyframes (default 2)
+    pub fn keyframe_interval(mut self, secs: u32) -> Self {
+        self.keyframe_interval = Some(secs);
+        self
+    }
+
+    /// 1 (mono, the default) or 2 (stereo) audio channels
+    pub fn audio_channels(mut self, channels: u16) -> Self {
+        self.audio_channels = channels;
+        self
+    }
+
+    /// Call `callback` with each chunk's path and index as soon as the chunk is
+    /// complete, while recording continues (e.g. to start uploading it). It runs
+    /// on the encoder task: hand slow work off to another task or thread.
+    pub fn on_chunk_finished(mut self, callback: impl Fn(&Path, i64) + Send + Sync + 'static) -> Self {
+        self.on_chunk_finished = Some(Arc::new(callback));
+        self
+    }
+
+    /// FFmpeg binary for the macOS encoder (found on PATH or next to the executable otherwise)
+    #[cfg(target_os = "macos")]
+    pub fn ffmpeg_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.ffmpeg_path = Some(path.into());
+        self
+    }
+
+    /// Start capturing and encoding. Must be called inside a tokio runtime.
+    pub fn start(self) -> Result<RecordingHandle> {
+        let output_dir = self.output.ok_or_else(|| {
+            ScreenRecError::InvalidParameter("Recorder needs an output directory (Recorder::output)".to_string())
+        })?;
+        let max_fps = if self.allow_high_fps { MAX_HIGH_FPS } else { MAX_STANDARD_FPS };
+        if self.fps == 0 || self.fps > max_fps {
+            return Err(ScreenRecError::InvalidParameter(format!("FPS must be between 1 and {}", max_fps)));
+        }
+        if !(1..=10).contains(&self.quality) {
+            return Err(ScreenRecError::InvalidParameter(format!(
+                "Quality must be between 1 and 10 (got {})",
+                self.quality
+            )));
+        }
+        if self.chunk_duration == 0 {
+            return Err(ScreenRecError::InvalidParameter("Chunk duration must be at least 1 second".to_string()));
+        }
+        if self.keyframe_interval == Some(0) {
+            return Err(ScreenRecError::InvalidParameter("Keyframe interval must be at least 1 second".to_string()));
+        }
+        if !(1..=2).contains(&self.audio_channels) {
+            return Err(ScreenRecError::InvalidParameter(format!(
+                "Audio channels must be 1 or 2 (got {})",
+                self.audio_channels
+            )));
+        }
+        let audio_device = match self.audio_device.as_deref() {
+            Some(name) if self.audio != AudioSource::None => Some(crate::audio::resolve_input_device(name)?),
+            _ => None,
+        };
+        std::fs::create_dir_all(&output_dir).map_err(|e| {
+            ScreenRecError::ConfigError(format!("Failed to create output directory: {}", e))
+        })?;
+
+        #[cfg(target_os = "macos")]
+        let ffmpeg_binary = {
+            let binary = crate::ffmpeg_utils::find_ffmpeg_binary(self.ffmpeg_path.as_ref())?;
+            crate::ffmpeg_utils::validate_ffmpeg(&binary)?;
+            binary
+        };
+
+        let mut screen_capture = ScreenCapture::new(self.display, self.fps, Duration::from_secs(1))?;
+        let paused = Arc::new(AtomicBool::new(false));
+        screen_capture.set_pause_flag(paused.clone());
+
+        let native_size = if screen_capture.is_multi_monitor() {
+            screen_capture.get_max_dimensions()?
+        } else {
+            (screen_capture.width(), screen_capture.height())
+        };
+        let (width, height) = scaling::output_size(native_size, 0, 0);
+        let target_frames = self.duration.map(|duration| (duration.as_secs_f64() * self.fps as f64).round() as u64);
+
+        let options = RecordingOptions {
+            target_frames,
+            rate_control: RateControl::Quality(self.quality),
+            codec: self.codec,
+            tuning: EncoderTuning {
+                keyframe_interval: self.keyframe_interval,
+                colorspace: encoder::default_colorspace(height),
+                ..EncoderTuning::default()
+            },
+            encoder_choice: self.encoder,
+            chunk_policy: ChunkPolicy::ByDuration(self.chunk_duration),
+            on_chunk_finished: self.on_chunk_finished,
+            audio: self.audio,
+            audio_device,
+            audio_channels: self.audio_channels,
+            paused,
+            #[cfg(target_os = "macos")]
+            ffmpeg_path: Some(ffmpeg_binary),
+            ..RecordingOptions::new(output_dir, width, height, self.fps)
+        };
+        Ok(RecordingHandle::spawn(screen_capture, options))
+    }
+}
+
+/// Everything [`RecordingHandle::spawn`] needs besides the configured capture
+pub struct RecordingOptions {
+    /// Stop after this many frames (otherwise capture runs until stopped)
+    pub target_frames: Option<u64>,
+    pub output_dir: PathBuf,
+    /// Encoder size; frames of any other size are scaled to it
+    pub width: usize,
+    pub height: usize,
+    pub fps: u32,
+    pub rate_control: RateControl,
+    pub codec: Codec,
+    pub preset: Option<Preset>,
+    pub tuning: EncoderTuning,
+    pub encoder_choice: EncoderChoice,
+    pub scale_filter: ScaleFilter,
+    pub adaptive: Option<AdaptiveQuality>,
+    pub chunk_policy: ChunkPolicy,
+    pub chunk_name_pattern: ChunkNamePattern,
+    /// Delete the oldest chunks to stay under this many bytes
+    pub max_disk_usage: Option<u64>,
+    pub encryption_key: Option<EncryptionKey>,
+    pub frame_log: Option<FrameLog>,
+    pub frame_dump: Option<FrameDumper>,
+    pub on_chunk_finished: Option<ChunkFinished>,
+    pub db: Option<Arc<Database>>,
+    pub device_name: Option<String>,
+    pub recording_type: Option<String>,
+    pub task_id: Option<String>,
+    pub session_id: Option<i64>,
+    /// Byte budget of the queue between capture and encoder
+    pub frame_buffer_bytes: usize,
+    pub on_overflow: OverflowPolicy,
+    pub audio: AudioSource,
+    pub audio_device: Option<String>,
+    pub audio_channels: u16,
+    pub audio_reconnect_attempts: u32,
+    /// The pause flag the capture was given, shared with audio
+    pub paused: Arc<AtomicBool>,
+    pub status: SharedStatus,
+    #[cfg(target_os = "macos")]
+    pub ffmpeg_path: Option<String>,
+    #[cfg(target_os = "macos")]
+    pub mp4_layout: crate::cli::Mp4Layout,
+}
+
+impl RecordingOptions {
+    /// Quality 10 H.264 in 10-second chunks without audio, a database or any of
+    /// the optional extras, queueing up to `DEFAULT_BUFFER_FRAMES` frames
+    pub fn new(output_dir: PathBuf, width: usize, height: usize, fps: u32) -> Self {
+        Self {
+            target_frames: None,
+            output_dir,
+            width,
+            height,
+            fps,
+            rate_control: RateControl::Quality(10),
+            codec: Codec::H264,
+            preset: None,
+            tuning: EncoderTuning::default(),
+            encoder_choice: EncoderChoice::Auto,
+            scale_filter: ScaleFilter::default(),
+            adaptive: None,
+            chunk_policy: ChunkPolicy::ByDuration(10),
+            chunk_name_pattern: ChunkNamePattern::default(),
+            max_disk_usage: None,
+            encryption_key: None,
+            frame_log: None,
+            frame_dump: None,
+            on_chunk_finished: None,
+            db: None,
+            device_name: None,
+            recording_type: None,
+            task_id: None,
+            session_id: None,
+            frame_buffer_bytes: width * height * 3 * DEFAULT_BUFFER_FRAMES,
+            on_overflow: OverflowPolicy::default(),
+            audio: AudioSource::None,
+            audio_device: None,
+            audio_channels: 1,
+            audio_reconnect_attempts: 3,
+            paused: Arc::new(AtomicBool::new(false)),
+            status: RecorderStatus::new_shared(None),
+            #[cfg(target_os = "macos")]
+            ffmpeg_path: None,
+            #[cfg(target_os = "macos")]
+            mp4_layout: crate::cli::Mp4Layout::default(),
+        }
+    }
+}
+
+/// Stops a running recording; cheap to clone into signal handlers
+#[derive(Clone)]
+pub struct StopHandle {
+    running: Arc<AtomicBool>,
+    shutdown_tx: Arc<Mutex<Option<oneshot::Sender<()>>>>,
+}
+
+impl StopHandle {
+    /// Ask the encoder to finish the chunk it is writing. Returns false if that
+    /// was already asked for.
+    pub fn finish_chunk(&self) -> bool {
+        let tx = self.shutdown_tx.lock().ok().and_then(|mut tx| tx.take());
+        match tx {
+            Some(tx) => {
+                log::info!("Signaling encoder to finalize current chunk...");
+                let _ = tx.send(());
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stop capturing; the queued frames are still encoded
+    pub fn stop_capture(&self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+/// What a finished recording produced
+#[derive(Debug)]
+pub struct RecordingSummary {
+    pub output_dir: PathBuf,
+    /// Chunk files, in order
+    pub chunks: Vec<PathBuf>,
+    pub capture: CaptureStats,
+    /// Frames `OverflowPolicy::DropOldest` discarded because the encoder fell behind
+    pub dropped_frames: u64,
+    /// How long sampled frames took from capture to encoded, None if none were
+    pub encode_latency: Option<LatencyStats>,
+}
+
+/// A running recording
+pub struct RecordingHandle {
+    output_dir: PathBuf,
+    status: SharedStatus,
+    paused: Arc<AtomicBool>,
+    stop: StopHandle,
+    capture: std::thread::JoinHandle<Result<CaptureStats>>,
+    bridge: tokio::task::JoinHandle<u64>,
+    encoder: tokio::task::JoinHandle<Result<(Vec<RecordingOutput>, Option<LatencyStats>)>>,
+    audio_running: Arc<AtomicBool>,
+    audio: Option<tokio::task::JoinHandle<Result<()>>>,
+}
+
+impl RecordingHandle {
+    /// Start audio, the encoder, the bridge and the capture thread for an already
+    /// configured `screen_capture`. Must be called inside a tokio runtime.
+    pub fn spawn(screen_capture: ScreenCapture, options: RecordingOptions) -> Self {
+        let RecordingOptions {
+            target_frames,
+            output_dir,
+            width,
+            height,
+            fps,
+            rate_control,
+            codec,
+            preset,
+            tuning,
+            encoder_choice,
+            scale_filter,
+            adaptive,
+            chunk_policy,
+            chunk_name_pattern,
+            max_disk_usage,
+            encryption_key,
+            frame_log,
+            frame_dump,
+            on_chunk_finished,
+            db,
+            device_name,
+            recording_type,
+            task_id,
+            session_id,
+            frame_buffer_bytes,
+            on_overflow,
+            audio,
+            audio_device,
+            audio_channels,
+            audio_reconnect_attempts,
+            paused,
+            status,
+            #[cfg(target_os = "macos")]
+            ffmpeg_path,
+            #[cfg(target_os = "macos")]
+            mp4_layout,
+        } = options;
+        let (frame_tx_std, frame_rx_std) = std::sync::mpsc::channel(); // Sync channel for capture thread
+        let (frame_tx, frame_rx) = frame_queue::frame_queue(frame_buffer_bytes, on_overflow); // Async queue for encoder
+
+        // Bridge: sync receiver -> async sender. Blocks if the encoder is slow, unless
+        // --on-overflow drop-oldest trades the oldest queued frames for a steady capture.
+        let status_for_bridge = status.clone();
+        let bridge = tokio::spawn(async move {
+            let mut total_frames = 0u64;
+            let mut peak_buffered = 0usize;
+            let mut last_log = std::time::Instant::now();
+
+            while let Ok(frame) = frame_rx_std.recv() {
+                total_frames += 1;
+                if let Ok(mut status) = status_for_bridge.lock() {
+                    status.frames_captured = total_frames;
+                }
+
+                // Log progress every 5 seconds
+                if last_log.elapsed() >= Duration::from_secs(5) {
+                    log::info!(
+                        "Bridge: {} frames forwarded to encoder ({} buffered, {} dropped)",
+                        total_frames,
+                        ByteSize(frame_tx.buffered_bytes() as u64),
+                        frame_tx.dropped()
+                    );
+                    let pct = target_frames.map(|target| (total_frames * 100 / target.max(1)).min(99) as u8);
+                    progress::emit("record", pct, &format!("{} frames captured", total_frames));
+                    last_log = std::time::Instant::now();
+                }
+
+                // Send frame - with the default policy this blocks if the byte budget is
+                // used up (encoder is slow), preserving every frame
+                if frame_tx.send(frame).await.is_err() {
+                    log::error!("Encoder channel closed unexpectedly");
+                    break;
+                }
+                peak_buffered = peak_buffered.max(frame_tx.buffered_bytes());
+            }
+
+            log::info!(
+                "Bridge completed: {} total frames forwarded, peak buffer {}",
+                total_frames,
+                ByteSize(peak_buffered as u64)
+            );
+            frame_tx.dropped()
+        });
+
+        // On Windows/Linux the samples are muxed into the chunks by the encoder;
+        // on macOS they are written to session.wav.
+        let audio_running = Arc::new(AtomicBool::new(true));
+        let audio_rx = start_audio(
+            audio,
+            audio_device.as_deref(),
+            audio_channels,
+            audio_reconnect_attempts,
+            paused.clone(),
+            audio_running.clone(),
+        );
+
+        #[cfg(target_os = "macos")]
+        let audio_task = audio_rx.map(|audio_rx| {
+            let wav_path = output_dir.join(crate::audio::SESSION_WAV_FILE);
+            tokio::spawn(async move { encoder::process_audio(audio_rx, wav_path).await })
+        });
+        #[cfg(not(target_os = "macos"))]
+        let audio_task = None;
+
+        // Create shutdown channel for graceful encoder termination
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+
+        let output_dir_for_encoder = output_dir.clone();
+        let status_for_encoder = status.clone();
+        let encoder = tokio::spawn(async move {
+            encoder::process_frames_chunked(
+                frame_rx,
+                output_dir_for_encoder,
+                width,
+                height,
+                fps,
+                rate_control,
+                codec,
+                preset,
+                tuning,
+                encoder_choice,
+                scale_filter,
+                adaptive,
+                chunk_policy,
+                chunk_name_pattern,
+                max_disk_usage,
+                encryption_key,
+                frame_log,
+                frame_dump,
+                on_chunk_finished,
+                db,
+                device_name,
+                recording_type,
+                task_id,
+                session_id,
+                Some(shutdown_rx),
+                Some(status_for_encoder),
+                #[cfg(target_os = "macos")]
+                ffmpeg_path,
+                #[cfg(target_os = "macos")]
+                mp4_layout,
+                #[cfg(not(target_os = "macos"))]
+                audio_rx,
+                #[cfg(not(target_os = "macos"))]
+                audio_channels,
+            )
+            .await
+        });
+
+        // Run capture in a separate OS thread (not tokio thread) because Capturer is not Send
+        let running = Arc::new(AtomicBool::new(true));
+        let running_for_capture = running.clone();
+        let capture = std::thread::spawn(move || {
+            screen_capture.start_capture_sync(frame_tx_std, target_frames, Some(running_for_capture))
+        });
+
+        Self {
+            output_dir,
+            status,
+            paused,
+            stop: StopHandle { running, shutdown_tx: Arc::new(Mutex::new(Some(shutdown_tx))) },
+            capture,
+            bridge,
+            encoder,
+            audio_running,
+            audio: audio_task,
+        }
+    }
+
+    pub fn output_dir(&self) -> &Path {
+        &self.output_dir
+    }
+
+    /// Counters so far (frames captured and encoded, current chunk)
+    pub fn status(&self) -> RecorderStatus {
+        self.status.lock().map(|status| status.clone()).unwrap_or_else(|e| e.into_inner().clone())
+    }
+
+    pub fn stop_handle(&self) -> StopHandle {
+        self.stop.clone()
+    }
+
+    /// Finish the current chunk at the next frame and carry on in a new one
+    pub fn rotate_chunk(&self) {
+        if let Ok(mut status) = self.status.lock() {
+            status.request_rotation();
+        }
+    }
+
+    /// Stop grabbing frames (and audio) until `resume`; the video skips the pause
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+    }
+
+    /// Finish the current chunk, stop capturing and wait for everything to be written
+    pub async fn stop(self) -> Result<RecordingSummary> {
+        if self.stop.finish_chunk() {
+            tokio::time::sleep(FINISH_CHUNK_GRACE).await;
+        }
+        self.stop.stop_capture();
+        self.wait().await
+    }
+
+    /// Wait for the recording to end, by `duration`, a `StopHandle` or a capture error
+    pub async fn wait(self) -> Result<RecordingSummary> {
+        let capture = self.capture;
+        let capture_stats = tokio::task::spawn_blocking(move || capture.join())
+            .await
+            .map_err(|e| ScreenRecError::CaptureError(format!("Capture thread failed: {}", e)))?
+            .map_err(|e| ScreenRecError::CaptureError(format!("Capture thread panicked: {:?}", e)))?
+            .map_err(|e| ScreenRecError::CaptureError(format!("Capture failed: {}", e)))?;
+
+        let dropped_frames = self.bridge.await.unwrap_or(0);
+
+        let (chunk_outputs, encode_latency) = self.encoder.await.map_err(|e| {
+            ScreenRecError::EncodingError(format!("Encoder task failed: {}", e))
+        })??;
+
+        // Stop audio capture and wait for audio processing if it was started
+        self.audio_running.store(false, Ordering::SeqCst);
+        if let Some(audio) = self.audio {
+            let _ = audio.await;
+        }
+
+        Ok(RecordingSummary {
+            output_dir: self.output_dir,
+            chunks: chunk_outputs.into_iter().map(|output| output.video_file).collect(),
+            capture: capture_stats,
+            dropped_frames,
+            encode_latency,
+        })
+    }
+}
+
+/// Open `source` on its own thread (cpal streams aren't async). A failure leaves
+/// the recording video-only rather than stopping it.
+fn start_audio(
+    source: AudioSource,
+    device: Option<&str>,
+    channels: u16,
+    reconnect_attempts: u32,
+    paused: Arc<AtomicBool>,
+    running: Arc<AtomicBool>,
+) -> Option<mpsc::Receiver<AudioSample>> {
+    if source == AudioSource::None {
+        return None;
+    }
+
+    match AudioCapture::new(source, device) {
+        Ok(Some(mut audio_capture)) => {
+            audio_capture.set_pause_flag(paused);
+            audio_capture.set_output_channels(channels);
+            let (audio_tx, audio_rx) = mpsc::channel(1000);
+            std::thread::spawn(move || {
+                if let Err(e) = audio_capture.run_with_reconnect(audio_tx, running, reconnect_attempts) {
+                    log::error!("Audio capture failed: {}", e);
+                }
+            });
+            Some(audio_rx)
+        }
+        Ok(None) => {
+            log::info!("Audio capture disabled");
+            None
+        }
+        Err(ScreenRecError::AudioDeviceUnavailable(tried)) => {
+            log::warn!("⚠️  Audio unavailable (tried: {:?}). Recording video only.", tried);
+            None
+        }
+        Err(e) => {
+            log::warn!("⚠️  Audio init failed: {}. Recording video only.", e);
+            None
+        }
+    }
+}
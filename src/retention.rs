@@ -0,0 +1,340 @@
+//! Deleting old recordings
+//!
+//! With `--max-disk-usage`, every time the encoder starts a new chunk the chunks
+//! in the output directory are totalled and the oldest ones are deleted (file,
+//! checksum sidecar and database rows) until the directory fits the budget again. The chunk being
+//! written is counted but never deleted, so the budget can be exceeded by at most
+//! one chunk.
+//!
+//! `omgrec cleanup` works on the whole data directory instead: it deletes chunks
+//! older than a cutoff, database rows whose file is gone, chunk files no row
+//! points to, and task directories left empty.
+
+use crate::cli::ByteSize;
+use crate::db::Database;
+use crate::error::Result;
+use crate::integrity;
+use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+/// Orphans younger than this may belong to a recording that is still running
+/// (the chunk file and its row are not created at the same instant)
+const ORPHAN_GRACE_SECS: i64 = 3600;
+
+/// A chunk file on disk
+struct ChunkFile {
+    path: PathBuf,
+    size: u64,
+    modified: SystemTime,
+}
+
+/// Delete the oldest chunks in `dir` until they total at most `max_bytes`
+pub async fn enforce_disk_budget(dir: &Path, current_chunk: &Path, max_bytes: u64, db: Option<&Arc<Database>>) {
+    let chunks = match list_chunks(dir) {
+        Ok(chunks) => chunks,
+        Err(e) => {
+            log::warn!("⚠️  Could not check disk usage of {}: {}", dir.display(), e);
+            return;
+        }
+    };
+
+    for chunk in chunks_to_evict(chunks, current_chunk, max_bytes) {
+        if let Err(e) = std::fs::remove_file(&chunk.path) {
+            log::warn!("⚠️  Failed to evict {}: {}", chunk.path.display(), e);
+            continue;
+        }
+        remove_sidecar(&chunk.path, false);
+        log::info!(
+            "🗑️  Evicted {} ({}) to stay under --max-disk-usage {}",
+            chunk.path.display(),
+            ByteSize(chunk.size),
+            ByteSize(max_bytes)
+        );
+
+        if let Some(db) = db {
+            if let Err(e) = db.delete_chunk_by_path(&chunk.path.to_string_lossy()).await {
+                log::error!("Failed to delete evicted chunk from database: {}", e);
+            }
+        }
+    }
+}
+
+fn list_chunks(dir: &Path) -> std::io::Result<Vec<ChunkFile>> {
+    let mut chunks = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        if chunk_stem(&path).is_none() {
+            continue;
+        }
+        let metadata = entry.metadata()?;
+        if metadata.is_file() {
+            chunks.push(ChunkFile {
+                path,
+                size: metadata.len(),
+                modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            });
+        }
+    }
+    Ok(chunks)
+}
+
+/// Oldest chunks first until the rest fit `max_bytes`, never `current_chunk`
+fn chunks_to_evict(mut chunks: Vec<ChunkFile>, current_chunk: &Path, max_bytes: u64) -> Vec<ChunkFile> {
+    let mut total: u64 = chunks.iter().map(|chunk| chunk.size).sum();
+    if total <= max_bytes {
+        return Vec::new();
+    }
+
+    // Chunk names are timestamps, which break ties between equal modification times
+    chunks.sort_by(|a, b| a.modified.cmp(&b.modified).then_with(|| a.path.cmp(&b.path)));
+
+    let mut evict = Vec::new();
+    for chunk in chunks {
+        if total <= max_bytes {
+            break;
+        }
+        if chunk.path == current_chunk {
+            continue;
+        }
+        total -= chunk.size;
+        evict.push(chunk);
+    }
+    evict
+}
+
+/// What `cleanup` removed (or would remove, on a dry run)
+#[derive(Debug, Default)]
+pub struct CleanupReport {
+    pub files_removed: u64,
+    pub bytes_freed: u64,
+    pub rows_removed: u64,
+    pub dirs_removed: u64,
+}
+
+/// Delete chunks older than `older_than_days` (files and rows), orphaned rows and
+/// files, and task directories left empty. A dry run only prints what it would do.
+pub async fn cleanup(db: &Database, data_dir: &Path, older_than_days: Option<u32>, dry_run: bool) -> Result<CleanupReport> {
+    let now = Utc::now();
+    let cutoff = older_than_days.map(|days| now - chrono::Duration::days(days as i64));
+    let orphan_cutoff = now - chrono::Duration::seconds(ORPHAN_GRACE_SECS);
+    let verb = if dry_run { "Would remove" } else { "Removed" };
+
+    let mut report = CleanupReport::default();
+    let mut tracked = HashSet::new();
+    let mut removed = HashSet::new();
+
+    for chunk in db.get_all_chunks().await? {
+        let path = if Path::new(&chunk.file_path).is_absolute() {
+            PathBuf::from(&chunk.file_path)
+        } else {
+            data_dir.join(&chunk.file_path)
+        };
+        tracked.insert(path.clone());
+
+        let expired = cutoff.is_some_and(|cutoff| chunk.created_at < cutoff);
+        let missing = !path.exists();
+        let orphaned = missing && chunk.created_at < orphan_cutoff;
+        if !expired && !orphaned {
+            continue;
+        }
+
+        if missing {
+            println!("🗑️  {} database row for missing {}", verb, path.display());
+        } else {
+            let Some(size) = remove_file(&path, dry_run) else {
+                continue;
+            };
+            println!("🗑️  {} {} ({})", verb, path.display(), ByteSize(size));
+            report.files_removed += 1;
+            report.bytes_freed += size;
+            removed.extend(remove_sidecar(&path, dry_run));
+            removed.insert(path);
+        }
+
+        if !dry_run {
+            db.delete_chunk(chunk.id).await?;
+        }
+        report.rows_removed += 1;
+    }
+
+    for path in chunk_files_on_disk(data_dir) {
+        if tracked.contains(&path) {
+            continue;
+        }
+        let modified = std::fs::metadata(&path)
+            .and_then(|metadata| metadata.modified())
+            .map(DateTime::<Utc>::from)
+            .unwrap_or(now);
+        if modified >= orphan_cutoff {
+            continue;
+        }
+        if let Some(size) = remove_file(&path, dry_run) {
+            println!("🗑️  {} untracked {} ({})", verb, path.display(), ByteSize(size));
+            report.files_removed += 1;
+            report.bytes_freed += size;
+            removed.extend(remove_sidecar(&path, dry_run));
+            removed.insert(path);
+        }
+    }
+
+    for dir in task_dirs(data_dir) {
+        if !is_empty_after(&dir, &removed) {
+            continue;
+        }
+        if !dry_run {
+            if let Err(e) = std::fs::remove_dir(&dir) {
+                log::warn!("⚠️  Failed to remove {}: {}", dir.display(), e);
+                continue;
+            }
+        }
+        println!("🗑️  {} empty task directory {}", verb, dir.display());
+        report.dirs_removed += 1;
+    }
+
+    Ok(report)
+}
+
+/// Size of the file, after deleting it unless this is a dry run
+fn remove_file(path: &Path, dry_run: bool) -> Option<u64> {
+    let size = std::fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0);
+    if !dry_run {
+        if let Err(e) = std::fs::remove_file(path) {
+            log::warn!("⚠️  Failed to remove {}: {}", path.display(), e);
+            return None;
+        }
+    }
+    Some(size)
+}
+
+/// The chunk's `.sha256` sidecar, if it had one, after deleting it unless this is a dry run
+fn remove_sidecar(chunk: &Path, dry_run: bool) -> Option<PathBuf> {
+    let sidecar = integrity::sidecar_path(chunk);
+    if !sidecar.exists() {
+        return None;
+    }
+    remove_file(&sidecar, dry_run)?;
+    Some(sidecar)
+}
+
+fn task_dirs(data_dir: &Path) -> Vec<PathBuf> {
+    std::fs::read_dir(data_dir.join("tasks"))
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.is_dir())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Chunk files (named by their start time and index, like `2025-01-14_10-30-00_0003.mp4`,
+/// or just the start time for older recordings) in `always_on/` and every task
+/// directory. Exports such as `final.mp4` are skipped, and so are chunks named
+/// with a custom `--chunk-name-pattern`.
+fn chunk_files_on_disk(data_dir: &Path) -> Vec<PathBuf> {
+    let mut dirs = task_dirs(data_dir);
+    dirs.push(data_dir.join("always_on"));
+
+    let is_chunk = |path: &Path| chunk_stem(path).is_some_and(is_chunk_stem);
+
+    let mut files: Vec<PathBuf> = dirs
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && is_chunk(path))
+        .collect();
+    files.sort();
+    files
+}
+
+/// File name without `.mp4`, or `.mp4.enc` for `--encrypt` chunks; None for other files
+fn chunk_stem(path: &Path) -> Option<&str> {
+    let name = path.file_name()?.to_str()?;
+    name.strip_suffix(".mp4.enc").or_else(|| name.strip_suffix(".mp4"))
+}
+
+/// `<start time>` or `<start time>_<index>`
+fn is_chunk_stem(stem: &str) -> bool {
+    let (timestamp, index) = match stem.split_at_checked(19) {
+        Some((timestamp, rest)) => (timestamp, rest),
+        None => return false,
+    };
+    let index_ok = index.is_empty()
+        || index
+            .strip_prefix('_')
+            .is_some_and(|digits| !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()));
+    index_ok && chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d_%H-%M-%S").is_ok()
+}
+
+/// Whether `dir` holds nothing but files in `removed`
+fn is_empty_after(dir: &Path, removed: &HashSet<PathBuf>) -> bool {
+    std::fs::read_dir(dir)
+        .map(|mut entries| entries.all(|entry| entry.is_ok_and(|entry| removed.contains(&entry.path()))))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_evicts_oldest_but_never_the_current_chunk() {
+        let at = |secs: u64| SystemTime::UNIX_EPOCH + Duration::from_secs(secs);
+        let chunk = |name: &str, size: u64, secs: u64| ChunkFile { path: PathBuf::from(name), size, modified: at(secs) };
+        let names = |chunks: Vec<ChunkFile>| chunks.into_iter().map(|c| c.path).collect::<Vec<_>>();
+
+        let chunks = || vec![chunk("c.mp4", 40, 30), chunk("a.mp4", 40, 10), chunk("b.mp4", 40, 20), chunk("d.mp4", 40, 40)];
+
+        assert!(chunks_to_evict(chunks(), Path::new("d.mp4"), 160).is_empty());
+        assert_eq!(names(chunks_to_evict(chunks(), Path::new("d.mp4"), 120)), [PathBuf::from("a.mp4")]);
+        assert_eq!(
+            names(chunks_to_evict(chunks(), Path::new("d.mp4"), 50)),
+            [PathBuf::from("a.mp4"), PathBuf::from("b.mp4"), PathBuf::from("c.mp4")]
+        );
+
+        // The oldest file is still being written (clock skew): skip it, evict the next ones
+        assert_eq!(
+            names(chunks_to_evict(chunks(), Path::new("a.mp4"), 90)),
+            [PathBuf::from("b.mp4"), PathBuf::from("c.mp4")]
+        );
+
+        // A budget smaller than the current chunk evicts everything else
+        assert_eq!(chunks_to_evict(chunks(), Path::new("d.mp4"), 10).len(), 3);
+    }
+
+    #[test]
+    fn test_finds_chunk_files_and_empty_task_dirs() {
+        let data = tempfile::tempdir().unwrap();
+        let data_dir = data.path();
+        let task = data_dir.join("tasks").join("demo");
+        std::fs::create_dir_all(&task).unwrap();
+        std::fs::create_dir_all(data_dir.join("always_on")).unwrap();
+        for file in [
+            "always_on/2025-01-14_10-30-00.mp4",
+            "always_on/notes.mp4",
+            "tasks/demo/2025-01-14_11-00-00_0001.mp4",
+            "tasks/demo/2025-01-14_11-00-00_final.mp4",
+            "tasks/demo/final.mp4",
+        ] {
+            std::fs::write(data_dir.join(file), b"x").unwrap();
+        }
+
+        assert_eq!(
+            chunk_files_on_disk(data_dir),
+            [data_dir.join("always_on/2025-01-14_10-30-00.mp4"), task.join("2025-01-14_11-00-00_0001.mp4")]
+        );
+
+        // Exports keep the task directory alive
+        let removed: HashSet<PathBuf> = chunk_files_on_disk(data_dir).into_iter().collect();
+        assert!(!is_empty_after(&task, &removed));
+        std::fs::remove_file(task.join("final.mp4")).unwrap();
+        std::fs::remove_file(task.join("2025-01-14_11-00-00_final.mp4")).unwrap();
+        assert!(is_empty_after(&task, &removed));
+    }
+}
@@ -0,0 +1,246 @@
+//! Fit frames of a different size into the encoder's frame
+//!
+//! Used by both encoders when a frame doesn't match the encoder dimensions (e.g.
+//! switching to a smaller monitor, or `--width`/`--height` asking for a smaller
+//! video than the display). The frame is scaled to fit while keeping its aspect
+//! ratio and centered on a black canvas.
+
+use crate::cli::ScaleFilter;
+use crate::error::{Result, ScreenRecError};
+
+/// Encoder size for `--width`/`--height` (0 = not given) and a `source`-sized capture.
+/// Giving only one follows the source aspect ratio, so the video isn't letterboxed;
+/// both are rounded down to even numbers for the YUV 4:2:0 encoders.
+pub fn output_size(source: (usize, usize), width: u32, height: u32) -> (usize, usize) {
+    let (source_width, source_height) = (source.0.max(1), source.1.max(1));
+    let (width, height) = match (width as usize, height as usize) {
+        (0, 0) => (source_width, source_height),
+        (0, height) => (height * source_width / source_height, height),
+        (width, 0) => (width, width * source_height / source_width),
+        size => size,
+    };
+    ((width & !1).max(2), (height & !1).max(2))
+}
+
+/// `size` scaled down so neither side exceeds `max`, keeping the aspect ratio and
+/// rounding down to even numbers; sizes that already fit are returned as they are
+pub fn fit_within(size: (usize, usize), max: usize) -> (usize, usize) {
+    let (width, height) = size;
+    let longest = width.max(height);
+    if longest <= max {
+        return size;
+    }
+    let (width, height) = (width * max / longest, height * max / longest);
+    ((width & !1).max(2), (height & !1).max(2))
+}
+
+/// Scale and pad an RGB24 frame to target dimensions (center with black bars)
+pub fn scale_and_pad_frame(
+    rgb: &[u8],
+    src_width: usize,
+    src_height: usize,
+    target_width: usize,
+    target_height: usize,
+    filter: ScaleFilter,
+) -> Result<Vec<u8>> {
+    // Calculate scaling to fit within target while preserving aspect ratio
+    let width_ratio = target_width as f32 / src_width as f32;
+    let height_ratio = target_height as f32 / src_height as f32;
+    let scale_ratio = width_ratio.min(height_ratio);
+
+    let scaled_width = (src_width as f32 * scale_ratio) as usize;
+    let scaled_height = (src_height as f32 * scale_ratio) as usize;
+
+    // Calculate padding to center the scaled image
+    let pad_x = (target_width - scaled_width) / 2;
+    let pad_y = (target_height - scaled_height) / 2;
+
+    // Create black canvas
+    let mut result = vec![0u8; target_width * target_height * 3];
+
+    let area = ScaledArea { pad_x, pad_y, scaled_width, scaled_height, scale_ratio };
+    match filter {
+        ScaleFilter::Nearest => {
+            scale_nearest(rgb, src_width, src_height, &mut result, target_width, target_height, &area)
+        }
+        ScaleFilter::Bilinear => {
+            if rgb.len() < src_width * src_height * 3 {
+                return Err(ScreenRecError::EncodingError(format!(
+                    "Frame data too short for {}x{}: {} bytes",
+                    src_width,
+                    src_height,
+                    rgb.len()
+                )));
+            }
+            scale_bilinear(rgb, src_width, src_height, &mut result, target_width, &area)
+        }
+    }
+
+    Ok(result)
+}
+
+/// Where the scaled image sits inside the target frame
+struct ScaledArea {
+    pad_x: usize,
+    pad_y: usize,
+    scaled_width: usize,
+    scaled_height: usize,
+    scale_ratio: f32,
+}
+
+/// Simple nearest-neighbor scaling and placement
+fn scale_nearest(
+    rgb: &[u8],
+    src_width: usize,
+    src_height: usize,
+    result: &mut [u8],
+    target_width: usize,
+    target_height: usize,
+    area: &ScaledArea,
+) {
+    for target_y in 0..target_height {
+        for target_x in 0..target_width {
+            // Check if we're in the scaled image area
+            if target_x >= area.pad_x
+                && target_x < area.pad_x + area.scaled_width
+                && target_y >= area.pad_y
+                && target_y < area.pad_y + area.scaled_height
+            {
+                // Map to source coordinates
+                let src_x = ((target_x - area.pad_x) as f32 / area.scale_ratio) as usize;
+                let src_y = ((target_y - area.pad_y) as f32 / area.scale_ratio) as usize;
+
+                // Bounds check
+                if src_x < src_width && src_y < src_height {
+                    let src_idx = (src_y * src_width + src_x) * 3;
+                    let dst_idx = (target_y * target_width + target_x) * 3;
+
+                    if src_idx + 2 < rgb.len() && dst_idx + 2 < result.len() {
+                        result[dst_idx] = rgb[src_idx]; // R
+                        result[dst_idx + 1] = rgb[src_idx + 1]; // G
+                        result[dst_idx + 2] = rgb[src_idx + 2]; // B
+                    }
+                }
+            }
+            // Else: leave as black (already initialized to 0)
+        }
+    }
+}
+
+/// Two source samples and the 8-bit weight of the second one
+#[derive(Clone, Copy)]
+struct Tap {
+    lo: usize,
+    hi: usize,
+    weight: u32,
+}
+
+/// Source taps for each output position along one axis, sampling at pixel centers
+fn taps(scaled_len: usize, src_len: usize, scale_ratio: f32) -> Vec<Tap> {
+    (0..scaled_len)
+        .map(|i| {
+            let pos = ((i as f32 + 0.5) / scale_ratio - 0.5).clamp(0.0, (src_len - 1) as f32);
+            let lo = pos as usize;
+            Tap {
+                lo,
+                hi: (lo + 1).min(src_len - 1),
+                weight: ((pos - lo as f32) * 256.0) as u32,
+            }
+        })
+        .collect()
+}
+
+/// Bilinear scaling: each output pixel blends the four nearest source pixels.
+/// Fixed-point weights sum to exactly 1, so flat areas keep their exact color.
+fn scale_bilinear(
+    rgb: &[u8],
+    src_width: usize,
+    src_height: usize,
+    result: &mut [u8],
+    target_width: usize,
+    area: &ScaledArea,
+) {
+    let x_taps = taps(area.scaled_width, src_width, area.scale_ratio);
+    let y_taps = taps(area.scaled_height, src_height, area.scale_ratio);
+
+    for (row, y_tap) in y_taps.iter().enumerate() {
+        let top = &rgb[y_tap.lo * src_width * 3..];
+        let bottom = &rgb[y_tap.hi * src_width * 3..];
+        let dst_row = ((area.pad_y + row) * target_width + area.pad_x) * 3;
+
+        for (col, x_tap) in x_taps.iter().enumerate() {
+            let (l, r) = (x_tap.lo * 3, x_tap.hi * 3);
+            let dst_idx = dst_row + col * 3;
+
+            for c in 0..3 {
+                let blend = |row: &[u8]| {
+                    row[l + c] as u32 * (256 - x_tap.weight) + row[r + c] as u32 * x_tap.weight
+                };
+                let value = blend(top) * (256 - y_tap.weight) + blend(bottom) * y_tap.weight;
+                result[dst_idx + c] = ((value + (1 << 15)) >> 16) as u8;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bilinear_keeps_solid_color_without_edge_artifacts() {
+        let color = [200u8, 30, 90];
+
+        // Downscale with letterboxing, and upscale with pillarboxing
+        for (src, target) in [((1920, 1080), (1280, 1024)), ((800, 600), (1920, 1080))] {
+            let rgb = color.repeat(src.0 * src.1);
+            let out = scale_and_pad_frame(&rgb, src.0, src.1, target.0, target.1, ScaleFilter::Bilinear)
+                .unwrap();
+
+            let nearest = scale_and_pad_frame(&rgb, src.0, src.1, target.0, target.1, ScaleFilter::Nearest)
+                .unwrap();
+
+            // Every pixel is either the exact source color or black padding, in the
+            // same places the nearest-neighbor path puts them
+            for (i, (pixel, expected)) in out.chunks(3).zip(nearest.chunks(3)).enumerate() {
+                assert!(
+                    pixel == color || pixel == [0, 0, 0],
+                    "pixel {} of {:?} -> {:?} is {:?}",
+                    i, src, target, pixel
+                );
+                assert_eq!(pixel, expected, "padding differs at pixel {}", i);
+            }
+        }
+    }
+
+    #[test]
+    fn test_requested_size_smaller_than_native_capture() {
+        // --width alone on a 2560x1440 display: height follows, nothing is padded
+        assert_eq!(output_size((2560, 1440), 1280, 0), (1280, 720));
+        assert_eq!(output_size((2560, 1440), 0, 360), (640, 360));
+        assert_eq!(output_size((2560, 1440), 0, 0), (2560, 1440));
+        assert_eq!(output_size((1366, 768), 1001, 0), (1000, 562));
+        assert_eq!(output_size((2560, 1440), 800, 800), (800, 800));
+
+        assert_eq!(fit_within((6016, 3384), 4096), (4096, 2304));
+        assert_eq!(fit_within((3384, 6016), 4096), (2304, 4096));
+        assert_eq!(fit_within((1920, 1080), 4096), (1920, 1080));
+
+        // Every native frame then differs from the encoder size and is scaled down
+        // to fill it exactly: left half red, right half blue stays that way
+        let (src_w, src_h) = (8, 4);
+        let rgb: Vec<u8> = (0..src_w * src_h)
+            .flat_map(|i| if i % src_w < src_w / 2 { [255, 0, 0] } else { [0, 0, 255] })
+            .collect();
+        let (width, height) = output_size((src_w, src_h), 4, 0);
+        assert_eq!((width, height), (4, 2));
+        for filter in [ScaleFilter::Nearest, ScaleFilter::Bilinear] {
+            let out = scale_and_pad_frame(&rgb, src_w, src_h, width, height, filter).unwrap();
+            assert_eq!(out.len(), width * height * 3);
+            for (i, pixel) in out.chunks(3).enumerate() {
+                let expected = if i % width < width / 2 { [255, 0, 0] } else { [0, 0, 255] };
+                assert_eq!(pixel, expected, "{:?} pixel {}", filter, i);
+            }
+        }
+    }
+}
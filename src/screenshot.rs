@@ -1,33 +1,115 @@
+use crate::capture::bgra_to_rgba;
+use crate::cli::ScreenshotFormat;
+use crate::display_info::{display_by_index, get_all_displays_with_bounds, DisplayInfo};
 use crate::error::{Result, ScreenRecError};
-use image::{ImageBuffer, RgbaImage};
-use scrap::{Capturer, Display};
+use crate::interactions::ActivityMonitor;
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::webp::WebPEncoder;
+use image::{ColorType, ImageBuffer, ImageEncoder, Rgba, RgbaImage};
+use scrap::Capturer;
+use std::fs::File;
+use std::io::BufWriter;
 use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-pub fn capture_screenshot(output_path: &Path, display_index: usize) -> Result<()> {
+pub fn capture_screenshot(output_path: &Path, display_index: usize, quality: Option<u8>) -> Result<()> {
     log::info!("Capturing screenshot to: {:?}", output_path);
 
-    // Get the specified display
-    let displays = Display::all().map_err(|e| {
-        ScreenRecError::CaptureError(format!("Failed to enumerate displays: {}", e))
+    let img = capture_display_image(display_index)?;
+    save_image(img, output_path, quality)?;
+
+    log::info!("Screenshot saved successfully");
+    Ok(())
+}
+
+/// Capture every display into one image, each placed at its position in the
+/// desktop layout; gaps between displays are black
+pub fn capture_all_displays_screenshot(output_path: &Path, quality: Option<u8>) -> Result<()> {
+    log::info!("Capturing all displays to: {:?}", output_path);
+
+    let displays = get_all_displays_with_bounds()?;
+    let layout = panorama_layout(&displays).ok_or_else(|| {
+        ScreenRecError::CaptureError("No displays found".to_string())
     })?;
 
-    if displays.is_empty() {
-        return Err(ScreenRecError::CaptureError(
-            "No displays found".to_string(),
-        ));
-    }
+    log::info!(
+        "Stitching {} display(s) into a {}x{} image",
+        layout.placements.len(),
+        layout.width,
+        layout.height
+    );
 
-    if display_index >= displays.len() {
-        return Err(ScreenRecError::CaptureError(format!(
-            "Display {} not found (only {} displays available)",
-            display_index,
-            displays.len()
-        )));
+    let mut canvas = RgbaImage::from_pixel(layout.width, layout.height, Rgba([0, 0, 0, 255]));
+    for (index, x, y) in layout.placements {
+        let img = capture_display_image(index)?;
+        image::imageops::replace(&mut canvas, &img, x as i64, y as i64);
     }
 
-    let display = displays.into_iter().nth(display_index).ok_or_else(|| {
-        ScreenRecError::CaptureError(format!("Display {} not found", display_index))
-    })?;
+    save_image(canvas, output_path, quality)?;
+
+    log::info!("Screenshot saved successfully");
+    Ok(())
+}
+
+/// Where each display goes in an all-displays screenshot
+#[derive(Debug, PartialEq)]
+struct PanoramaLayout {
+    width: u32,
+    height: u32,
+    /// (display index, x, y) on the canvas
+    placements: Vec<(usize, u32, u32)>,
+}
+
+/// The bounding box of all displays, shifted so the top-left-most corner is
+/// (0, 0). Desktop positions are scaled by the largest display scale factor so
+/// Retina displays keep their full resolution. Returns None when there are no displays.
+fn panorama_layout(displays: &[DisplayInfo]) -> Option<PanoramaLayout> {
+    let first = displays.first()?;
+    let scale = displays.iter().map(|d| d.scale_factor).reduce(f64::max)?;
+    let pixels = |desktop: i64| (desktop as f64 * scale).round() as u32;
+
+    // Without real bounds every display reports the same origin; lay them out
+    // left to right instead of stacking them on top of each other
+    let origins: Vec<(i64, i64)> = if displays.len() > 1
+        && displays.iter().all(|d| (d.x, d.y) == (first.x, first.y))
+    {
+        log::warn!("Display positions unknown; placing displays side by side");
+        let mut next_x = 0i64;
+        displays
+            .iter()
+            .map(|d| {
+                let origin = (next_x, 0);
+                next_x += d.desktop_width() as i64;
+                origin
+            })
+            .collect()
+    } else {
+        displays.iter().map(|d| (d.x as i64, d.y as i64)).collect()
+    };
+
+    let min_x = origins.iter().map(|o| o.0).min()?;
+    let min_y = origins.iter().map(|o| o.1).min()?;
+    let max_x = displays.iter().zip(&origins).map(|(d, o)| o.0 + d.desktop_width() as i64).max()?;
+    let max_y = displays.iter().zip(&origins).map(|(d, o)| o.1 + d.desktop_height() as i64).max()?;
+
+    let placements = displays
+        .iter()
+        .zip(&origins)
+        .map(|(d, o)| (d.index, pixels(o.0 - min_x), pixels(o.1 - min_y)))
+        .collect();
+    Some(PanoramaLayout {
+        width: pixels(max_x - min_x),
+        height: pixels(max_y - min_y),
+        placements,
+    })
+}
+
+/// Capture one frame of a display as RGBA
+fn capture_display_image(display_index: usize) -> Result<RgbaImage> {
+    // Same display list and indexing as `record`
+    let display = display_by_index(display_index)?;
 
     log::info!(
         "Display dimensions: {}x{}",
@@ -60,41 +142,172 @@ pub fn capture_screenshot(output_path: &Path, display_index: usize) -> Result<()
         }
     };
 
-    // Convert BGRA to RGBA
-    let mut rgba_data = Vec::with_capacity(width * height * 4);
-    for chunk in frame.chunks_exact(4) {
-        rgba_data.push(chunk[2]); // R
-        rgba_data.push(chunk[1]); // G
-        rgba_data.push(chunk[0]); // B
-        rgba_data.push(chunk[3]); // A
-    }
+    // Convert BGRA to RGBA, dropping any row padding (a padded buffer would make
+    // from_raw fail or shear the image)
+    let rgba_data = bgra_to_rgba(&frame, width, height);
 
     // Create image buffer
-    let img: RgbaImage = ImageBuffer::from_raw(width as u32, height as u32, rgba_data)
-        .ok_or_else(|| ScreenRecError::CaptureError("Failed to create image buffer".to_string()))?;
+    ImageBuffer::from_raw(width as u32, height as u32, rgba_data)
+        .ok_or_else(|| ScreenRecError::CaptureError("Failed to create image buffer".to_string()))
+}
 
+/// Save in the format the file extension names (PNG, JPEG, WebP or AVIF).
+/// `quality` (1-100) applies to the lossy formats, JPEG and AVIF.
+fn save_image(img: RgbaImage, output_path: &Path, quality: Option<u8>) -> Result<()> {
     // Determine output format from extension
     let extension = output_path
         .extension()
         .and_then(|e| e.to_str())
         .unwrap_or("png")
         .to_lowercase();
+    let (width, height) = img.dimensions();
 
     // Save image
     match extension.as_str() {
         "png" => img.save(output_path)?,
         "jpg" | "jpeg" => {
             let rgb_img = image::DynamicImage::ImageRgba8(img).to_rgb8();
-            rgb_img.save(output_path)?;
+            match quality {
+                Some(quality) => {
+                    let file = BufWriter::new(File::create(output_path)?);
+                    JpegEncoder::new_with_quality(file, quality)
+                        .write_image(rgb_img.as_raw(), width, height, ColorType::Rgb8)?;
+                }
+                None => rgb_img.save(output_path)?,
+            }
+        }
+        "webp" => {
+            // The image crate's built-in WebP encoder only writes lossless files
+            if quality.is_some() {
+                log::warn!("--quality does not apply to WebP screenshots, which are lossless");
+            }
+            let file = BufWriter::new(File::create(output_path)?);
+            WebPEncoder::new_lossless(file).write_image(img.as_raw(), width, height, ColorType::Rgba8)?;
         }
+        "avif" => save_avif(img, output_path, quality)?,
         _ => {
             return Err(ScreenRecError::InvalidParameter(format!(
-                "Unsupported image format: {}. Use .png, .jpg, or .jpeg",
+                "Unsupported image format: {}. Use .png, .jpg, .jpeg, .webp or .avif",
                 extension
             )))
         }
     }
 
-    log::info!("Screenshot saved successfully");
     Ok(())
 }
+
+#[cfg(feature = "avif")]
+fn save_avif(img: RgbaImage, output_path: &Path, quality: Option<u8>) -> Result<()> {
+    use image::codecs::avif::AvifEncoder;
+
+    let (width, height) = img.dimensions();
+    let rgb_img = image::DynamicImage::ImageRgba8(img).to_rgb8();
+    let file = BufWriter::new(File::create(output_path)?);
+    // Speed 6 of 10: a still encodes in about a second without much size penalty
+    AvifEncoder::new_with_speed_quality(file, 6, quality.unwrap_or(80))
+        .write_image(rgb_img.as_raw(), width, height, ColorType::Rgb8)?;
+    Ok(())
+}
+
+#[cfg(not(feature = "avif"))]
+fn save_avif(_img: RgbaImage, _output_path: &Path, _quality: Option<u8>) -> Result<()> {
+    Err(ScreenRecError::InvalidParameter(
+        "AVIF output is not included in this build; rebuild with `--features avif`".to_string(),
+    ))
+}
+
+/// Take a screenshot every `interval` into `output_dir` until `running` is cleared
+/// or `count` screenshots have been taken.
+///
+/// Files are numbered `shot_000000.png`, `shot_000001.png`, ... When an activity
+/// monitor is given, shots are skipped while the user has been idle for longer
+/// than the threshold, so idle periods are compressed out of the timelapse.
+pub fn watch_screenshots(
+    output_dir: &Path,
+    display_index: usize,
+    all_displays: bool,
+    interval: Duration,
+    count: Option<usize>,
+    format: ScreenshotFormat,
+    quality: Option<u8>,
+    activity: Option<(&ActivityMonitor, Duration)>,
+    running: Arc<AtomicBool>,
+) -> Result<usize> {
+    std::fs::create_dir_all(output_dir)?;
+
+    let mut shot_count = 0usize;
+    let mut skipped_count = 0usize;
+
+    while running.load(Ordering::SeqCst) {
+        let shot_start = Instant::now();
+
+        let is_idle = activity
+            .map(|(monitor, idle_threshold)| monitor.idle_for() >= idle_threshold)
+            .unwrap_or(false);
+
+        if is_idle {
+            skipped_count += 1;
+            log::debug!("User idle, skipping screenshot");
+        } else {
+            let shot_path = output_dir.join(format!("shot_{:06}.{}", shot_count, format.extension()));
+            if all_displays {
+                capture_all_displays_screenshot(&shot_path, quality)?;
+            } else {
+                capture_screenshot(&shot_path, display_index, quality)?;
+            }
+            shot_count += 1;
+
+            match count {
+                Some(count) => println!("📸 Screenshot {}/{}: {}", shot_count, count, shot_path.display()),
+                None => println!("📸 Screenshot {}: {}", shot_count, shot_path.display()),
+            }
+            if count.is_some_and(|count| shot_count >= count) {
+                break;
+            }
+        }
+
+        // Sleep for the rest of the interval, waking up early on stop
+        while running.load(Ordering::SeqCst) && shot_start.elapsed() < interval {
+            let remaining = interval.saturating_sub(shot_start.elapsed());
+            std::thread::sleep(remaining.min(Duration::from_millis(100)));
+        }
+    }
+
+    log::info!(
+        "Screenshot watch stopped: {} taken, {} skipped while idle",
+        shot_count,
+        skipped_count
+    );
+    Ok(shot_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn display(index: usize, width: usize, height: usize, x: i32, y: i32) -> DisplayInfo {
+        DisplayInfo { index, width, height, x, y, is_primary: index == 0, scale_factor: 1.0 }
+    }
+
+    #[test]
+    fn test_panorama_layout_normalizes_negative_origins() {
+        // A laptop, a monitor to its left (negative x) and one above-right of it
+        let displays = [
+            display(0, 1440, 900, 0, 0),
+            display(1, 1920, 1080, -1920, -180),
+            display(2, 1280, 1024, 1440, -1024),
+        ];
+
+        let layout = panorama_layout(&displays).unwrap();
+        assert_eq!((layout.width, layout.height), (1920 + 1440 + 1280, 1024 + 900));
+        assert_eq!(layout.placements, vec![(0, 1920, 1024), (1, 0, 844), (2, 3360, 0)]);
+
+        // Unknown positions (all at the same origin) are laid out side by side
+        let displays = [display(0, 1920, 1080, 0, 0), display(1, 1280, 720, 0, 0)];
+        let layout = panorama_layout(&displays).unwrap();
+        assert_eq!((layout.width, layout.height), (3200, 1080));
+        assert_eq!(layout.placements, vec![(0, 0, 0), (1, 1920, 0)]);
+
+        assert!(panorama_layout(&[]).is_none());
+    }
+}
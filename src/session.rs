@@ -0,0 +1,54 @@
+//! Settings of every recording started in an output directory
+//!
+//! always_on and task directories are shared by many recordings, so
+//! `session.json` is an array that each new recording appends its settings to,
+//! oldest first.
+
+use crate::error::{Result, ScreenRecError};
+use std::path::Path;
+
+pub const SESSION_FILE: &str = "session.json";
+
+/// Add one recording's settings to the output directory's session.json
+pub fn append_session_settings(output_dir: &Path, settings: serde_json::Value) -> Result<()> {
+    let path = output_dir.join(SESSION_FILE);
+    let mut sessions = match std::fs::read_to_string(&path) {
+        Ok(contents) => match serde_json::from_str::<Vec<serde_json::Value>>(&contents) {
+            Ok(sessions) => sessions,
+            // Don't overwrite what may be the only record of earlier sessions
+            Err(e) => {
+                return Err(ScreenRecError::ConfigError(format!("{} is not a JSON array: {}", path.display(), e)));
+            }
+        },
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(ScreenRecError::ConfigError(format!("Failed to read {}: {}", path.display(), e))),
+    };
+    sessions.push(settings);
+
+    let json = serde_json::to_string_pretty(&sessions)
+        .map_err(|e| ScreenRecError::ConfigError(format!("Failed to serialize session settings: {}", e)))?;
+    std::fs::write(&path, json)
+        .map_err(|e| ScreenRecError::ConfigError(format!("Failed to write {}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_sessions_are_appended_and_bad_files_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        append_session_settings(dir.path(), json!({"session_id": 1})).unwrap();
+        append_session_settings(dir.path(), json!({"session_id": 2})).unwrap();
+        let contents = std::fs::read_to_string(dir.path().join(SESSION_FILE)).unwrap();
+        let sessions: serde_json::Value = serde_json::from_str(&contents).unwrap();
+        assert_eq!(sessions, json!([{"session_id": 1}, {"session_id": 2}]));
+
+        // A file that isn't an array is left alone rather than replaced
+        std::fs::write(dir.path().join(SESSION_FILE), "{\"session_id\": 0}").unwrap();
+        assert!(append_session_settings(dir.path(), json!({"session_id": 3})).is_err());
+        let contents = std::fs::read_to_string(dir.path().join(SESSION_FILE)).unwrap();
+        assert_eq!(contents, "{\"session_id\": 0}");
+    }
+}
@@ -0,0 +1,274 @@
+//! Interactive first-run setup
+//!
+//! Walks a new user through everything a first recording needs: an FFmpeg binary,
+//! the macOS Screen Recording and Accessibility permissions, a test capture and
+//! audio device detection, then writes the result to a default config file.
+
+use crate::error::{Result, ScreenRecError};
+use crate::ffmpeg_utils;
+use crate::screenshot;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[cfg(target_os = "macos")]
+const SCREEN_RECORDING_PANE: &str =
+    "x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture";
+#[cfg(target_os = "macos")]
+const ACCESSIBILITY_PANE: &str =
+    "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility";
+
+/// Settings discovered during setup, written out as the default config
+#[derive(Debug)]
+struct SetupResult {
+    ffmpeg_path: Option<String>,
+    audio: &'static str,
+}
+
+/// Default config location: ~/.omega/config.toml
+pub fn default_config_path() -> Result<PathBuf> {
+    Ok(dirs::home_dir()
+        .ok_or_else(|| ScreenRecError::ConfigError("Could not find home directory".to_string()))?
+        .join(".omega")
+        .join("config.toml"))
+}
+
+/// Run the guided setup. With `assume_yes`, every prompt takes its default answer.
+pub fn run_setup(config_path: Option<PathBuf>, assume_yes: bool) -> Result<()> {
+    let config_path = match config_path {
+        Some(path) => path,
+        None => default_config_path()?,
+    };
+    let prompt = Prompt { assume_yes };
+
+    println!("👋 Welcome to omgrec! Let's get you ready to record.\n");
+
+    println!("Step 1/4: FFmpeg");
+    let ffmpeg_path = setup_ffmpeg(&prompt);
+
+    println!("\nStep 2/4: Permissions");
+    setup_permissions(&prompt);
+
+    println!("\nStep 3/4: Test capture");
+    test_capture();
+
+    println!("\nStep 4/4: Audio devices");
+    let audio = detect_audio();
+
+    let result = SetupResult { ffmpeg_path, audio };
+
+    if config_path.exists()
+        && !prompt.confirm(
+            &format!("\n{} already exists. Overwrite it?", config_path.display()),
+            false,
+        )
+    {
+        println!("Keeping existing config: {}", config_path.display());
+        return Ok(());
+    }
+
+    write_config(&config_path, &result)?;
+    println!("\n✅ Setup complete. Default config written to: {}", config_path.display());
+    println!("   Start recording with: omgrec record --duration 10");
+
+    Ok(())
+}
+
+/// Yes/no prompts on stdin
+struct Prompt {
+    assume_yes: bool,
+}
+
+impl Prompt {
+    fn confirm(&self, question: &str, default: bool) -> bool {
+        if self.assume_yes {
+            return default;
+        }
+
+        let hint = if default { "[Y/n]" } else { "[y/N]" };
+        print!("{} {} ", question, hint);
+        let _ = std::io::stdout().flush();
+
+        let mut answer = String::new();
+        if std::io::stdin().lock().read_line(&mut answer).is_err() {
+            return default;
+        }
+
+        match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" => true,
+            "n" | "no" => false,
+            _ => default,
+        }
+    }
+
+    #[cfg(target_os = "macos")]
+    fn wait_for_enter(&self, message: &str) {
+        if self.assume_yes {
+            return;
+        }
+        print!("{} ", message);
+        let _ = std::io::stdout().flush();
+        let mut line = String::new();
+        let _ = std::io::stdin().lock().read_line(&mut line);
+    }
+}
+
+/// Find FFmpeg, offering to install it through the platform package manager
+fn setup_ffmpeg(prompt: &Prompt) -> Option<String> {
+    if let Some(path) = check_ffmpeg() {
+        return Some(path);
+    }
+
+    println!("⚠️  FFmpeg was not found on your PATH.");
+
+    let Some((program, args)) = ffmpeg_install_command() else {
+        println!("   Install it with your package manager, e.g.: sudo apt-get install ffmpeg");
+        return None;
+    };
+
+    let command_line = format!("{} {}", program, args.join(" "));
+    if !prompt.confirm(&format!("   Install it now with `{}`?", command_line), true) {
+        println!("   Skipped. Install FFmpeg later or pass --ffmpeg-path when recording.");
+        return None;
+    }
+
+    match Command::new(program).args(args).status() {
+        Ok(status) if status.success() => check_ffmpeg(),
+        Ok(status) => {
+            println!("⚠️  `{}` exited with {}", command_line, status);
+            None
+        }
+        Err(e) => {
+            println!("⚠️  Could not run `{}`: {}", command_line, e);
+            None
+        }
+    }
+}
+
+fn check_ffmpeg() -> Option<String> {
+    let path = ffmpeg_utils::find_ffmpeg_binary(None).ok()?;
+    match ffmpeg_utils::validate_ffmpeg(&path) {
+        Ok(version) => {
+            println!("✅ {} ({})", version, path);
+            Some(path)
+        }
+        Err(e) => {
+            println!("⚠️  FFmpeg at {} is not working: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Package manager command that installs FFmpeg on this platform
+fn ffmpeg_install_command() -> Option<(&'static str, &'static [&'static str])> {
+    #[cfg(target_os = "macos")]
+    {
+        Some(("brew", &["install", "ffmpeg"]))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        Some(("winget", &["install", "--id", "Gyan.FFmpeg", "-e"]))
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    {
+        None
+    }
+}
+
+/// Open the System Settings panes for the permissions recording relies on
+#[cfg(target_os = "macos")]
+fn setup_permissions(prompt: &Prompt) {
+    let panes = [
+        ("Screen Recording", SCREEN_RECORDING_PANE, "needed to capture the screen"),
+        ("Accessibility", ACCESSIBILITY_PANE, "needed for interaction tracking and window titles"),
+    ];
+
+    for (name, pane, reason) in panes {
+        if !prompt.confirm(&format!("   Open {} settings ({})?", name, reason), true) {
+            continue;
+        }
+        if let Err(e) = Command::new("open").arg(pane).status() {
+            println!("⚠️  Could not open System Settings: {}", e);
+            println!("   Go to System Settings → Privacy & Security → {}", name);
+            continue;
+        }
+        prompt.wait_for_enter(&format!("   Add your terminal to {} and press Enter to continue...", name));
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+fn setup_permissions(_prompt: &Prompt) {
+    println!("✅ No extra permissions are needed on this platform");
+}
+
+/// Take a throwaway screenshot to confirm capture works
+fn test_capture() {
+    let test_path = std::env::temp_dir().join("omgrec_setup_test.png");
+
+    match screenshot::capture_screenshot(&test_path, 0, None) {
+        Ok(()) => {
+            println!("✅ Captured the primary display");
+            let _ = std::fs::remove_file(&test_path);
+        }
+        Err(e) => {
+            println!("⚠️  Test capture failed: {}", e);
+            #[cfg(target_os = "macos")]
+            println!("   If you just granted Screen Recording, restart your terminal and run setup again.");
+        }
+    }
+}
+
+/// List audio input devices and pick a default `--audio` source
+fn detect_audio() -> &'static str {
+    let devices = crate::audio::list_input_devices();
+    if devices.is_empty() {
+        println!("⚠️  No audio input devices found; recordings will be video only");
+        return "none";
+    }
+
+    for device in &devices {
+        let mut tags = Vec::new();
+        if device.is_default {
+            tags.push("default");
+        }
+        if device.is_system_audio {
+            tags.push("system audio");
+        }
+        if tags.is_empty() {
+            println!("   • {}", device.name);
+        } else {
+            println!("   • {} ({})", device.name, tags.join(", "));
+        }
+    }
+
+    if devices.iter().any(|d| d.is_system_audio) {
+        println!("✅ System audio device found");
+        "system"
+    } else {
+        println!("⚠️  No system audio loopback device found; defaulting to microphone audio");
+        "mic"
+    }
+}
+
+fn render_config(result: &SetupResult) -> String {
+    let mut config = String::from("# omgrec defaults, written by `omgrec setup`\n\n[record]\n");
+    config.push_str("fps = 30\n");
+    config.push_str("quality = 10\n");
+    config.push_str("chunk_duration = 10\n");
+    config.push_str(&format!("audio = \"{}\"\n", result.audio));
+    match &result.ffmpeg_path {
+        Some(path) => config.push_str(&format!("ffmpeg_path = {:?}\n", path)),
+        None => config.push_str("# ffmpeg_path = \"/path/to/ffmpeg\"\n"),
+    }
+    config
+}
+
+fn write_config(path: &Path, result: &SetupResult) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, render_config(result))?;
+    Ok(())
+}
@@ -0,0 +1,197 @@
+//! Live recorder status over HTTP
+//!
+//! With `--status-port`, `record` serves `GET /status` on localhost so a
+//! dashboard can poll progress instead of scraping the `[PROGRESS]` lines. The
+//! bridge task and the encoder update a shared [`RecorderStatus`]; the server
+//! only reads it, except for `POST /rotate`, which asks the encoder to start a
+//! new chunk (as SIGHUP does). The server runs on the recording's tokio runtime and stops
+//! once the encoder has finished its last chunk.
+
+use crate::error::{Result, ScreenRecError};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Largest request head we read; anything past it is ignored
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Counters for the running recording
+#[derive(Debug, Clone)]
+pub struct RecorderStatus {
+    task_id: Option<String>,
+    started_at: Instant,
+    pub frames_captured: u64,
+    pub frames_encoded: u64,
+    pub chunk_index: i64,
+    /// A new chunk was asked for and the encoder hasn't started it yet
+    rotation_requested: bool,
+}
+
+pub type SharedStatus = Arc<Mutex<RecorderStatus>>;
+
+impl RecorderStatus {
+    pub fn new_shared(task_id: Option<String>) -> SharedStatus {
+        Arc::new(Mutex::new(Self {
+            task_id,
+            started_at: Instant::now(),
+            frames_captured: 0,
+            frames_encoded: 0,
+            chunk_index: 0,
+            rotation_requested: false,
+        }))
+    }
+
+    /// Ask the encoder to finish the current chunk at the next frame
+    pub fn request_rotation(&mut self) {
+        self.rotation_requested = true;
+    }
+
+    /// Whether a new chunk was asked for since the last call
+    pub fn take_rotation_request(&mut self) -> bool {
+        std::mem::take(&mut self.rotation_requested)
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let elapsed = self.started_at.elapsed().as_secs_f64();
+        let achieved_fps = if elapsed > 0.0 { self.frames_captured as f64 / elapsed } else { 0.0 };
+        serde_json::json!({
+            "task_id": self.task_id,
+            "elapsed_seconds": (elapsed * 10.0).round() / 10.0,
+            "frames_captured": self.frames_captured,
+            "frames_encoded": self.frames_encoded,
+            "chunk_index": self.chunk_index,
+            "rotation_pending": self.rotation_requested,
+            "achieved_fps": (achieved_fps * 100.0).round() / 100.0,
+        })
+    }
+}
+
+/// Bind the status server to `127.0.0.1:port`, so a busy port fails before recording starts
+pub async fn bind(port: u16) -> Result<TcpListener> {
+    TcpListener::bind(("127.0.0.1", port)).await.map_err(|e| {
+        ScreenRecError::ConfigError(format!("Failed to bind status server to port {}: {}", port, e))
+    })
+}
+
+/// Answer requests until `shutdown` fires (or its sender is dropped)
+pub async fn serve(listener: TcpListener, status: SharedStatus, mut shutdown: tokio::sync::oneshot::Receiver<()>) {
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, _)) => {
+                    let status = status.clone();
+                    tokio::spawn(async move {
+                        if let Err(e) = handle_connection(stream, status).await {
+                            log::debug!("Status request failed: {}", e);
+                        }
+                    });
+                }
+                Err(e) => log::warn!("Status server accept failed: {}", e),
+            },
+            _ = &mut shutdown => break,
+        }
+    }
+    log::info!("Status server stopped");
+}
+
+async fn handle_connection(mut stream: TcpStream, status: SharedStatus) -> std::io::Result<()> {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < MAX_REQUEST_BYTES {
+        let n = stream.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        request.extend_from_slice(&buf[..n]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let request_line = request.lines().next().unwrap_or("");
+    let body = status.lock().ok().map(|mut status| {
+        if is_rotate_request(request_line) {
+            log::info!("Chunk rotation requested over HTTP");
+            status.request_rotation();
+        }
+        status.to_json().to_string()
+    });
+    stream.write_all(response(request_line, body).as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Method and path of a request line, without any query string
+fn route(request_line: &str) -> (&str, &str) {
+    let mut parts = request_line.split_whitespace();
+    let (method, path) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+    (method, path.split('?').next().unwrap_or(""))
+}
+
+fn is_rotate_request(request_line: &str) -> bool {
+    route(request_line) == ("POST", "/rotate")
+}
+
+/// Full HTTP response for a request line; `status_json` is None if the status lock was poisoned
+fn response(request_line: &str, status_json: Option<String>) -> String {
+    let (method, path) = route(request_line);
+
+    let (code, body) = match (method, path, status_json) {
+        ("GET", "/status", Some(json)) => ("200 OK", json),
+        ("POST", "/rotate", Some(json)) => ("202 Accepted", json),
+        ("GET", "/status", None) | ("POST", "/rotate", None) => {
+            ("500 Internal Server Error", r#"{"error":"status unavailable"}"#.to_string())
+        }
+        ("GET", _, _) => ("404 Not Found", r#"{"error":"not found"}"#.to_string()),
+        _ => ("405 Method Not Allowed", r#"{"error":"method not allowed"}"#.to_string()),
+    };
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nCache-Control: no-store\r\nConnection: close\r\n\r\n{}",
+        code,
+        body.len(),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_route() {
+        let status = RecorderStatus::new_shared(Some("task-1".to_string()));
+        {
+            let mut status = status.lock().unwrap();
+            status.frames_captured = 90;
+            status.frames_encoded = 85;
+            status.chunk_index = 2;
+        }
+        let json = status.lock().unwrap().to_json();
+        assert_eq!(json["task_id"], "task-1");
+        assert_eq!(json["frames_encoded"], 85);
+        assert_eq!(json["chunk_index"], 2);
+
+        let ok = response("GET /status?pretty HTTP/1.1", Some(json.to_string()));
+        assert!(ok.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(ok.ends_with(&json.to_string()));
+        assert!(ok.contains(&format!("Content-Length: {}\r\n", json.to_string().len())));
+
+        assert!(response("GET / HTTP/1.1", None).starts_with("HTTP/1.1 404"));
+        assert!(response("POST /status HTTP/1.1", None).starts_with("HTTP/1.1 405"));
+        assert!(response("GET /rotate HTTP/1.1", None).starts_with("HTTP/1.1 404"));
+        assert!(response("", None).starts_with("HTTP/1.1 405"));
+    }
+
+    #[test]
+    fn test_rotate_request_is_taken_once() {
+        let status = RecorderStatus::new_shared(None);
+        assert!(is_rotate_request("POST /rotate HTTP/1.1"));
+        assert!(!is_rotate_request("GET /rotate HTTP/1.1"));
+        assert!(response("POST /rotate HTTP/1.1", Some("{}".to_string())).starts_with("HTTP/1.1 202"));
+
+        let mut status = status.lock().unwrap();
+        assert!(!status.take_rotation_request());
+        status.request_rotation();
+        assert_eq!(status.to_json()["rotation_pending"], true);
+        assert!(status.take_rotation_request());
+        assert!(!status.take_rotation_request());
+    }
+}
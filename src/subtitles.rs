@@ -0,0 +1,176 @@
+//! WebVTT sidecar of the focused window
+//!
+//! `omgrec export-subtitles --task-id` reads a task's interactions.jsonl (and its
+//! numbered segments), collapses consecutive events with the same window title
+//! into one cue each and writes `final.vtt` next to `final.mp4`, so a player can
+//! show which application was in front at every moment.
+//!
+//! Cue times come from `timestamp_ms`, which restarts at zero with every
+//! recording session. When it goes backwards the new session is placed right
+//! after the last event of the previous one, matching how concat joins sessions
+//! back to back.
+
+use crate::error::{Result, ScreenRecError};
+use crate::interactions::InteractionEvent;
+use std::path::{Path, PathBuf};
+
+/// How long the last cue stays up when nothing follows it
+const LAST_CUE_MS: u64 = 1000;
+
+/// One stretch of time with the same window in front
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cue {
+    pub start_ms: u64,
+    pub end_ms: u64,
+    pub text: String,
+}
+
+/// interactions.jsonl followed by its numbered segments (interactions.1.jsonl, ...)
+pub fn interaction_files(dir: &Path) -> Vec<PathBuf> {
+    let first = dir.join("interactions.jsonl");
+    if !first.exists() {
+        return Vec::new();
+    }
+
+    let mut files = vec![first];
+    for index in 1.. {
+        let segment = dir.join(format!("interactions.{}.jsonl", index));
+        if !segment.exists() {
+            break;
+        }
+        files.push(segment);
+    }
+    files
+}
+
+/// Read events from JSONL files in order, skipping lines that don't parse
+pub fn read_events(files: &[PathBuf]) -> Result<Vec<InteractionEvent>> {
+    let mut events = Vec::new();
+    for file in files {
+        let contents = std::fs::read_to_string(file).map_err(|e| {
+            ScreenRecError::InvalidParameter(format!("Failed to read {}: {}", file.display(), e))
+        })?;
+        for (line_number, line) in contents.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str(line) {
+                Ok(event) => events.push(event),
+                Err(e) => log::warn!("Skipping {}:{}: {}", file.display(), line_number + 1, e),
+            }
+        }
+    }
+    Ok(events)
+}
+
+/// Collapse events into cues: each lasts until the title changes. Events without
+/// a window title (the tracker couldn't read it) are skipped.
+pub fn window_cues(events: &[InteractionEvent]) -> Vec<Cue> {
+    let mut cues: Vec<Cue> = Vec::new();
+    let mut session_offset = 0;
+    let mut last_raw_ms = 0;
+    let mut last_ms = 0;
+
+    for event in events {
+        // A new recording session starts its clock from zero again
+        if event.timestamp_ms < last_raw_ms {
+            session_offset = last_ms;
+        }
+        last_raw_ms = event.timestamp_ms;
+        let ms = session_offset + event.timestamp_ms;
+        last_ms = ms;
+
+        let text = event.window_title.trim();
+        if text.is_empty() {
+            continue;
+        }
+
+        match cues.last_mut() {
+            Some(cue) if cue.text == text => cue.end_ms = ms,
+            previous => {
+                if let Some(cue) = previous {
+                    cue.end_ms = ms;
+                }
+                cues.push(Cue { start_ms: ms, end_ms: ms, text: text.to_string() });
+            }
+        }
+    }
+
+    if let Some(cue) = cues.last_mut() {
+        cue.end_ms = cue.end_ms.max(cue.start_ms + LAST_CUE_MS);
+    }
+    cues.retain(|cue| cue.end_ms > cue.start_ms);
+    cues
+}
+
+/// Render cues as a WebVTT file
+pub fn render_vtt(cues: &[Cue]) -> String {
+    let mut vtt = String::from("WEBVTT\n");
+    for cue in cues {
+        // "-->" would end the timing line early; it can't appear in cue text
+        let text = cue.text.replace("-->", "->");
+        vtt.push_str(&format!("\n{} --> {}\n{}\n", vtt_time(cue.start_ms), vtt_time(cue.end_ms), text));
+    }
+    vtt
+}
+
+/// `HH:MM:SS.mmm`
+fn vtt_time(ms: u64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        ms % 1000
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(timestamp_ms: u64, window_title: &str) -> InteractionEvent {
+        serde_json::from_value(serde_json::json!({
+            "type": "click",
+            "timestamp": "2025-01-14T10:30:00Z",
+            "timestamp_ms": timestamp_ms,
+            "taskId": "demo",
+            "processName": "app",
+            "windowTitle": window_title,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_window_title_changes_become_cues() {
+        let events = [
+            event(5000, "Figma"),
+            event(9000, "Figma"),
+            event(12000, "Slack --> #general"),
+            event(12500, ""),
+            event(20000, "Figma"),
+            // Second session: its clock restarts, so it follows on from 20s
+            event(1000, "Figma"),
+            event(3000, "Terminal"),
+        ];
+
+        let cues = window_cues(&events);
+        let spans: Vec<_> = cues.iter().map(|c| (c.start_ms, c.end_ms, c.text.as_str())).collect();
+        assert_eq!(
+            spans,
+            [
+                (5000, 12000, "Figma"),
+                (12000, 20000, "Slack --> #general"),
+                (20000, 23000, "Figma"),
+                (23000, 24000, "Terminal"),
+            ]
+        );
+
+        let vtt = render_vtt(&cues[..2]);
+        assert_eq!(
+            vtt,
+            "WEBVTT\n\n00:00:05.000 --> 00:00:12.000\nFigma\n\n00:00:12.000 --> 00:00:20.000\nSlack -> #general\n"
+        );
+        assert_eq!(vtt_time(3_723_004), "01:02:03.004");
+    }
+}
@@ -0,0 +1,188 @@
+//! Session timeline for Gantt-style visualization
+//!
+//! Each recording session becomes a row of intervals. Inside a session, time is
+//! `active` while frames keep arriving and `paused` wherever frames stop for
+//! longer than the gap threshold; time between sessions is `idle`.
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+/// One recording session and the capture times of its frames
+pub struct SessionSpan {
+    pub session_id: i64,
+    pub task_id: String,
+    pub device_name: String,
+    pub started_at: DateTime<Utc>,
+    /// None while the session is still recording (or if the recorder died)
+    pub ended_at: Option<DateTime<Utc>>,
+    /// Sorted frame timestamps
+    pub frames: Vec<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IntervalState {
+    Active,
+    Paused,
+    Idle,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimelineInterval {
+    /// None for idle time between sessions
+    pub session_id: Option<i64>,
+    pub task_id: Option<String>,
+    pub device_name: Option<String>,
+    pub state: IntervalState,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub duration_secs: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Timeline {
+    pub start: Option<DateTime<Utc>>,
+    pub end: Option<DateTime<Utc>>,
+    pub total_active_secs: f64,
+    pub total_paused_secs: f64,
+    pub total_idle_secs: f64,
+    pub sessions: usize,
+    pub intervals: Vec<TimelineInterval>,
+}
+
+/// Build the timeline. Sessions without an end time stop at their last frame.
+pub fn build_timeline(sessions: &[SessionSpan], gap_threshold_secs: f64) -> Timeline {
+    let gap_threshold = chrono::Duration::milliseconds((gap_threshold_secs * 1000.0) as i64);
+    let mut intervals: Vec<TimelineInterval> = Vec::new();
+    let mut previous_end: Option<DateTime<Utc>> = None;
+
+    for session in sessions {
+        let end = session
+            .ended_at
+            .or_else(|| session.frames.last().copied())
+            .unwrap_or(session.started_at)
+            .max(session.started_at);
+
+        if let Some(previous_end) = previous_end {
+            if session.started_at > previous_end {
+                push_interval(&mut intervals, None, IntervalState::Idle, previous_end, session.started_at);
+            }
+        }
+        previous_end = Some(previous_end.map_or(end, |p| p.max(end)));
+
+        // Walk session start -> frames -> session end; long gaps between points are pauses
+        let points = std::iter::once(session.started_at)
+            .chain(session.frames.iter().copied().filter(|t| *t >= session.started_at && *t <= end))
+            .chain(std::iter::once(end));
+
+        let mut last = session.started_at;
+        for point in points.skip(1) {
+            let state = if point - last > gap_threshold {
+                IntervalState::Paused
+            } else {
+                IntervalState::Active
+            };
+            push_interval(&mut intervals, Some(session), state, last, point);
+            last = point;
+        }
+    }
+
+    let total = |state: IntervalState| {
+        intervals
+            .iter()
+            .filter(|i| i.state == state)
+            .map(|i| i.duration_secs)
+            .sum::<f64>()
+    };
+
+    Timeline {
+        start: intervals.first().map(|i| i.start),
+        end: intervals.iter().map(|i| i.end).max(),
+        total_active_secs: total(IntervalState::Active),
+        total_paused_secs: total(IntervalState::Paused),
+        total_idle_secs: total(IntervalState::Idle),
+        sessions: sessions.len(),
+        intervals,
+    }
+}
+
+/// Append an interval, extending the last one when it continues the same state
+fn push_interval(
+    intervals: &mut Vec<TimelineInterval>,
+    session: Option<&SessionSpan>,
+    state: IntervalState,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) {
+    if end <= start {
+        return;
+    }
+
+    let session_id = session.map(|s| s.session_id);
+
+    if let Some(last) = intervals.last_mut() {
+        if last.session_id == session_id && last.state == state && last.end == start {
+            last.end = end;
+            last.duration_secs = (end - last.start).num_milliseconds() as f64 / 1000.0;
+            return;
+        }
+    }
+
+    intervals.push(TimelineInterval {
+        session_id,
+        task_id: session.map(|s| s.task_id.clone()),
+        device_name: session.map(|s| s.device_name.clone()),
+        state,
+        start,
+        end,
+        duration_secs: (end - start).num_milliseconds() as f64 / 1000.0,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_timeline_splits_pauses_and_idle() {
+        let t0 = DateTime::parse_from_rfc3339("2025-01-14T10:00:00Z").unwrap().with_timezone(&Utc);
+        let at = |secs: i64| t0 + chrono::Duration::seconds(secs);
+
+        let sessions = vec![
+            SessionSpan {
+                session_id: 1,
+                task_id: "demo".to_string(),
+                device_name: "host".to_string(),
+                started_at: at(0),
+                ended_at: Some(at(30)),
+                // Frames stop between 10s and 20s
+                frames: (0..=10).chain(20..=30).map(at).collect(),
+            },
+            SessionSpan {
+                session_id: 2,
+                task_id: "demo".to_string(),
+                device_name: "host".to_string(),
+                started_at: at(60),
+                ended_at: Some(at(70)),
+                frames: (60..=70).map(at).collect(),
+            },
+        ];
+
+        let timeline = build_timeline(&sessions, 5.0);
+        let states: Vec<(IntervalState, f64)> =
+            timeline.intervals.iter().map(|i| (i.state, i.duration_secs)).collect();
+
+        assert_eq!(
+            states,
+            vec![
+                (IntervalState::Active, 10.0),
+                (IntervalState::Paused, 10.0),
+                (IntervalState::Active, 10.0),
+                (IntervalState::Idle, 30.0),
+                (IntervalState::Active, 10.0),
+            ]
+        );
+        assert_eq!(timeline.total_active_secs, 30.0);
+        assert_eq!(timeline.end, Some(at(70)));
+    }
+}
@@ -0,0 +1,170 @@
+//! Re-encode an existing recording through FFmpeg
+//!
+//! The same kind of re-encode concat falls back to when chunk resolutions differ,
+//! as a standalone tool: pick a codec and CRF, optionally resize, and get a
+//! smaller or web-friendly copy. FFmpeg's `-progress` output is turned into the
+//! usual `[PROGRESS]` lines.
+
+use crate::cli::TranscodeCodec;
+use crate::error::{Result, ScreenRecError};
+use crate::export::validate_output;
+use crate::ffmpeg_utils;
+use std::path::Path;
+
+/// Re-encode `input` into `output` with the given codec and CRF.
+/// `scale` is an FFmpeg scale expression such as `1280:-2`.
+pub fn transcode(
+    ffmpeg_binary: &str,
+    input: &Path,
+    output: &Path,
+    codec: TranscodeCodec,
+    crf: u8,
+    scale: Option<&str>,
+) -> Result<()> {
+    if !input.exists() {
+        return Err(ScreenRecError::InvalidParameter(format!(
+            "Input file not found: {}",
+            input.display()
+        )));
+    }
+    if output.exists() && same_file(input, output) {
+        return Err(ScreenRecError::InvalidParameter(
+            "Output must be a different file than the input".to_string(),
+        ));
+    }
+    if crf > max_crf(codec) {
+        return Err(ScreenRecError::InvalidParameter(format!(
+            "CRF for {} must be 0-{} (got {})",
+            codec,
+            max_crf(codec),
+            crf
+        )));
+    }
+
+    let ffprobe = ffmpeg_utils::find_ffprobe_binary(ffmpeg_binary);
+    let duration = ffmpeg_utils::probe_video_duration(&ffprobe, input)?;
+
+    let args = transcode_args(input, output, codec, crf, scale)?;
+
+    println!("🔄 [PROGRESS] Transcoding to {} (crf {})...", codec, crf);
+    let result = ffmpeg_utils::run_with_progress(ffmpeg_binary, &args, duration, "Transcoding")?;
+
+    if !result.status.success() {
+        let stderr = String::from_utf8_lossy(&result.stderr);
+        println!("❌ [PROGRESS] Transcoding failed");
+        log::error!("FFmpeg stderr: {}", stderr);
+        return Err(ScreenRecError::EncodingError(format!(
+            "FFmpeg transcode failed: {}",
+            stderr
+        )));
+    }
+
+    validate_output(output)?;
+    println!("✅ [PROGRESS] Transcoding complete!");
+    Ok(())
+}
+
+/// Highest CRF the codec's encoder accepts
+fn max_crf(codec: TranscodeCodec) -> u8 {
+    match codec {
+        TranscodeCodec::H264 | TranscodeCodec::Hevc => 51,
+        TranscodeCodec::Vp9 => 63,
+    }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn transcode_args(
+    input: &Path,
+    output: &Path,
+    codec: TranscodeCodec,
+    crf: u8,
+    scale: Option<&str>,
+) -> Result<Vec<String>> {
+    let mut args: Vec<String> = vec![
+        "-hide_banner".into(),
+        "-loglevel".into(),
+        "error".into(),
+        "-y".into(),
+        "-i".into(),
+        path_arg(input)?,
+    ];
+
+    if let Some(scale) = scale {
+        args.extend(["-vf".into(), format!("scale={}", scale)]);
+    }
+
+    match codec {
+        TranscodeCodec::H264 => {
+            args.extend(["-c:v".into(), "libx264".into(), "-preset".into(), "medium".into()]);
+            args.extend(["-crf".into(), crf.to_string(), "-c:a".into(), "copy".into()]);
+        }
+        TranscodeCodec::Hevc => {
+            args.extend(["-c:v".into(), "libx265".into(), "-preset".into(), "medium".into()]);
+            args.extend(["-crf".into(), crf.to_string(), "-c:a".into(), "copy".into()]);
+            // QuickTime only plays HEVC tagged as hvc1
+            args.extend(["-tag:v".into(), "hvc1".into()]);
+        }
+        TranscodeCodec::Vp9 => {
+            // -b:v 0 makes libvpx use pure constant quality
+            args.extend(["-c:v".into(), "libvpx-vp9".into(), "-crf".into(), crf.to_string()]);
+            args.extend(["-b:v".into(), "0".into(), "-row-mt".into(), "1".into()]);
+            // WebM can't hold AAC, so audio is re-encoded to Opus
+            args.extend(["-c:a".into(), "libopus".into()]);
+        }
+    }
+    args.extend(["-pix_fmt".into(), "yuv420p".into()]);
+
+    // Web-friendly MP4: index at the front so playback starts before the download ends
+    let is_mp4 = output
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| matches!(ext.to_lowercase().as_str(), "mp4" | "m4v" | "mov"));
+    if is_mp4 {
+        args.extend(["-movflags".into(), "+faststart".into()]);
+    }
+
+    args.extend(["-progress".into(), "pipe:1".into(), "-nostats".into()]);
+    args.push(path_arg(output)?);
+    Ok(args)
+}
+
+fn path_arg(path: &Path) -> Result<String> {
+    path.to_str()
+        .map(|s| s.to_string())
+        .ok_or_else(|| ScreenRecError::InvalidParameter(format!("Invalid path: {}", path.display())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcode_args() {
+        let args = transcode_args(
+            Path::new("final.mp4"),
+            Path::new("web.webm"),
+            TranscodeCodec::Vp9,
+            32,
+            Some("1280:-2"),
+        )
+        .unwrap()
+        .join(" ");
+
+        assert!(args.contains("-i final.mp4 -vf scale=1280:-2 -c:v libvpx-vp9 -crf 32 -b:v 0"));
+        assert!(!args.contains("faststart"), "WebM has no moov atom");
+        assert!(args.ends_with("-progress pipe:1 -nostats web.webm"));
+
+        let args = transcode_args(Path::new("a.mp4"), Path::new("b.mp4"), TranscodeCodec::H264, 28, None)
+            .unwrap()
+            .join(" ");
+        assert!(args.contains("-c:v libx264 -preset medium -crf 28 -c:a copy"));
+        assert!(args.contains("-movflags +faststart"));
+        assert!(!args.contains("-vf"));
+    }
+}
@@ -0,0 +1,130 @@
+//! Rotating and mirroring captured frames (`--rotate`, `--flip`)
+//!
+//! Display capture folds the transform into the BGRA→RGB conversion: every
+//! source pixel is written straight to its final position, so the frame isn't
+//! walked a second time. Masks and the cursor hotspot are mapped the same way
+//! (the cursor image itself stays upright) and the timestamp overlay is drawn on
+//! the result, so it reads normally. Window and composite capture build their
+//! RGB frame first and transform it with [`FrameTransform::apply_rgb`].
+
+use crate::cli::{Flip, MaskRect, Rotation};
+
+/// `--rotate` followed by `--flip`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FrameTransform {
+    pub rotation: Rotation,
+    pub flip: Flip,
+}
+
+impl FrameTransform {
+    pub fn new(rotation: Rotation, flip: Flip) -> Self {
+        Self { rotation, flip }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.rotation == Rotation::None && self.flip == Flip::None
+    }
+
+    /// Size of a `width`x`height` frame after the transform
+    pub fn output_size(&self, (width, height): (usize, usize)) -> (usize, usize) {
+        match self.rotation {
+            Rotation::Cw90 | Rotation::Cw270 => (height, width),
+            Rotation::None | Rotation::Cw180 => (width, height),
+        }
+    }
+
+    /// Where pixel (`x`, `y`) of a `width`x`height` frame ends up. Points off the
+    /// frame (a cursor hotspot past the edge) are mapped the same way.
+    pub fn map_point(&self, x: i64, y: i64, width: usize, height: usize) -> (i64, i64) {
+        let (w, h) = (width as i64, height as i64);
+        let (x, y) = match self.rotation {
+            Rotation::None => (x, y),
+            Rotation::Cw90 => (h - 1 - y, x),
+            Rotation::Cw180 => (w - 1 - x, h - 1 - y),
+            Rotation::Cw270 => (y, w - 1 - x),
+        };
+
+        let (out_width, out_height) = self.output_size((width, height));
+        match self.flip {
+            Flip::None => (x, y),
+            Flip::Horizontal => (out_width as i64 - 1 - x, y),
+            Flip::Vertical => (x, out_height as i64 - 1 - y),
+        }
+    }
+
+    /// A rectangle inside a `width`x`height` frame, after the transform
+    pub fn map_rect(&self, rect: &MaskRect, width: usize, height: usize) -> MaskRect {
+        if rect.width == 0 || rect.height == 0 {
+            return *rect;
+        }
+        let (x0, y0) = self.map_point(rect.x as i64, rect.y as i64, width, height);
+        let (x1, y1) = self.map_point(
+            (rect.x + rect.width - 1) as i64,
+            (rect.y + rect.height - 1) as i64,
+            width,
+            height,
+        );
+        MaskRect {
+            x: x0.min(x1) as usize,
+            y: y0.min(y1) as usize,
+            width: x0.abs_diff(x1) as usize + 1,
+            height: y0.abs_diff(y1) as usize + 1,
+        }
+    }
+
+    /// Output pixel index of the first pixel of source row `y`, and the step
+    /// between neighbouring pixels of that row (both in pixels, not bytes)
+    pub fn row_layout(&self, y: usize, width: usize, height: usize) -> (isize, isize) {
+        let out_width = self.output_size((width, height)).0 as i64;
+        let index = |(x, y): (i64, i64)| (y * out_width + x) as isize;
+        let start = index(self.map_point(0, y as i64, width, height));
+        let next = index(self.map_point(1, y as i64, width, height));
+        (start, next - start)
+    }
+
+    /// Transform a packed RGB24 frame
+    pub fn apply_rgb(&self, rgb: &[u8], width: usize, height: usize) -> Vec<u8> {
+        let mut out = vec![0u8; width * height * 3];
+        for (y, row) in rgb.chunks_exact(width * 3).take(height).enumerate() {
+            let (mut dst, step) = self.row_layout(y, width, height);
+            for pixel in row.chunks_exact(3) {
+                let i = dst as usize * 3;
+                out[i..i + 3].copy_from_slice(pixel);
+                dst += step;
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotations_and_flips() {
+        // 3x2 frame, one byte value per pixel: 0 1 2 / 3 4 5
+        let (width, height) = (3, 2);
+        let rgb: Vec<u8> = (0..6).flat_map(|v| [v, v, v]).collect();
+        let pixels = |transform: FrameTransform| {
+            transform.apply_rgb(&rgb, width, height).chunks_exact(3).map(|p| p[0]).collect::<Vec<_>>()
+        };
+        let rotate = |rotation| FrameTransform::new(rotation, Flip::None);
+
+        assert_eq!(pixels(FrameTransform::default()), [0, 1, 2, 3, 4, 5]);
+        assert_eq!(pixels(rotate(Rotation::Cw90)), [3, 0, 4, 1, 5, 2]);
+        assert_eq!(pixels(rotate(Rotation::Cw180)), [5, 4, 3, 2, 1, 0]);
+        assert_eq!(pixels(rotate(Rotation::Cw270)), [2, 5, 1, 4, 0, 3]);
+        assert_eq!(pixels(FrameTransform::new(Rotation::None, Flip::Horizontal)), [2, 1, 0, 5, 4, 3]);
+        assert_eq!(pixels(FrameTransform::new(Rotation::None, Flip::Vertical)), [3, 4, 5, 0, 1, 2]);
+        // Flipping happens after rotating
+        assert_eq!(pixels(FrameTransform::new(Rotation::Cw90, Flip::Horizontal)), [0, 3, 1, 4, 2, 5]);
+
+        assert_eq!(rotate(Rotation::Cw270).output_size((1920, 1080)), (1080, 1920));
+        assert_eq!(rotate(Rotation::Cw180).output_size((1920, 1080)), (1920, 1080));
+
+        // The top row of a 3x2 frame becomes the right column after 90 degrees
+        let top_row = MaskRect { x: 0, y: 0, width: 3, height: 1 };
+        assert_eq!(rotate(Rotation::Cw90).map_rect(&top_row, width, height), MaskRect { x: 1, y: 0, width: 1, height: 3 });
+    }
+}
@@ -0,0 +1,339 @@
+//! Window-level capture through the OS compositor
+//!
+//! Screen capture only sees what is visible on the display, so a window that is
+//! partly covered by other windows records the overlapping content too. This
+//! module reads a single window's composited content straight from the window
+//! server instead, which stays clean regardless of what sits on top of it:
+//! - macOS: `CGWindowListCreateImage` for the window's backing store
+//! - Windows: `PrintWindow` with `PW_RENDERFULLCONTENT`, rendered by DWM
+
+use crate::capture::bgra_to_rgb;
+use crate::error::{Result, ScreenRecError};
+use serde::Serialize;
+
+#[cfg(target_os = "macos")]
+use core_foundation::base::{CFType, TCFType};
+#[cfg(target_os = "macos")]
+use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+#[cfg(target_os = "macos")]
+use core_foundation::number::CFNumber;
+#[cfg(target_os = "macos")]
+use core_foundation::string::{CFString, CFStringRef};
+#[cfg(target_os = "macos")]
+use core_graphics::geometry::{CGPoint, CGRect, CGSize};
+#[cfg(target_os = "macos")]
+use core_graphics::window::{
+    copy_window_info, create_image, kCGNullWindowID, kCGWindowImageBoundsIgnoreFraming,
+    kCGWindowImageNominalResolution, kCGWindowLayer, kCGWindowListExcludeDesktopElements,
+    kCGWindowListOptionAll, kCGWindowListOptionIncludingWindow, kCGWindowName,
+    kCGWindowNumber, kCGWindowOwnerName,
+};
+
+#[cfg(target_os = "windows")]
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM, RECT};
+#[cfg(target_os = "windows")]
+use windows::Win32::Graphics::Gdi::{
+    CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, GetDC, GetDIBits,
+    ReleaseDC, SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS,
+};
+#[cfg(target_os = "windows")]
+use windows::Win32::Storage::Xps::{PrintWindow, PRINT_WINDOW_FLAGS};
+#[cfg(target_os = "windows")]
+use windows::Win32::UI::WindowsAndMessaging::{
+    EnumWindows, GetWindowRect, IsIconic, IsWindowVisible, PW_RENDERFULLCONTENT,
+};
+
+/// A top-level window that can be captured
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowInfo {
+    /// Native window identifier (CGWindowID on macOS, HWND on Windows)
+    pub id: u64,
+    pub title: String,
+    pub process_name: String,
+}
+
+/// Find a window whose title contains `title` (case-insensitive).
+/// An exact title match wins over partial matches.
+pub fn find_window_by_title(title: &str) -> Result<WindowInfo> {
+    let needle = title.to_lowercase();
+    let windows = list_windows()?;
+
+    let exact = windows
+        .iter()
+        .find(|w| w.title.to_lowercase() == needle)
+        .cloned();
+
+    exact
+        .or_else(|| {
+            windows
+                .into_iter()
+                .find(|w| w.title.to_lowercase().contains(&needle))
+        })
+        .ok_or_else(|| {
+            ScreenRecError::CaptureError(format!("No window found with title matching '{}'", title))
+        })
+}
+
+/// Find a window by its native id, as printed by `list-devices`
+pub fn find_window_by_id(id: u64) -> Result<WindowInfo> {
+    list_windows()?
+        .into_iter()
+        .find(|w| w.id == id)
+        .ok_or_else(|| ScreenRecError::CaptureError(format!("No window found with id {}", id)))
+}
+
+/// Captures a single window and produces RGB frames
+pub struct WindowCapturer {
+    window: WindowInfo,
+    width: usize,
+    height: usize,
+}
+
+impl WindowCapturer {
+    pub fn new(window: WindowInfo) -> Result<Self> {
+        // Grab one frame to learn the window's current pixel size
+        let (_, width, height) = capture_window_bgra(window.id)?;
+
+        log::info!(
+            "Window capture target: '{}' ({}) id={} {}x{}",
+            window.title,
+            window.process_name,
+            window.id,
+            width,
+            height
+        );
+
+        Ok(Self { window, width, height })
+    }
+
+    pub fn window(&self) -> &WindowInfo {
+        &self.window
+    }
+
+    /// Window width when the capturer was created
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Window height when the capturer was created
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Capture the window's current content as tightly packed RGB.
+    /// Returns the data with its actual dimensions, which change if the window is resized.
+    pub fn frame(&mut self) -> Result<(Vec<u8>, usize, usize)> {
+        let (bgra, width, height) = capture_window_bgra(self.window.id)?;
+        Ok((bgra_to_rgb(&bgra, width, height), width, height))
+    }
+}
+
+/// List capturable top-level windows
+#[cfg(target_os = "macos")]
+pub fn list_windows() -> Result<Vec<WindowInfo>> {
+    let window_list = copy_window_info(
+        kCGWindowListOptionAll | kCGWindowListExcludeDesktopElements,
+        kCGNullWindowID,
+    )
+    .ok_or_else(|| ScreenRecError::CaptureError("Failed to list windows".to_string()))?;
+
+    let mut windows = Vec::new();
+    for item in window_list.iter() {
+        let dict: CFDictionary<CFString, CFType> =
+            unsafe { CFDictionary::wrap_under_get_rule(*item as CFDictionaryRef) };
+
+        // Layer 0 is the normal application window layer (skip menus, docks, overlays)
+        let layer = unsafe { dict_number(&dict, kCGWindowLayer) }.unwrap_or(-1);
+        if layer != 0 {
+            continue;
+        }
+
+        let Some(id) = (unsafe { dict_number(&dict, kCGWindowNumber) }) else {
+            continue;
+        };
+        let title = unsafe { dict_string(&dict, kCGWindowName) }.unwrap_or_default();
+        let process_name =
+            unsafe { dict_string(&dict, kCGWindowOwnerName) }.unwrap_or_else(|| "Unknown".to_string());
+
+        windows.push(WindowInfo {
+            id: id as u64,
+            title,
+            process_name,
+        });
+    }
+
+    Ok(windows)
+}
+
+#[cfg(target_os = "macos")]
+unsafe fn dict_string(dict: &CFDictionary<CFString, CFType>, key: CFStringRef) -> Option<String> {
+    let key = CFString::wrap_under_get_rule(key);
+    dict.find(&key)
+        .and_then(|value| value.downcast::<CFString>())
+        .map(|s| s.to_string())
+}
+
+#[cfg(target_os = "macos")]
+unsafe fn dict_number(dict: &CFDictionary<CFString, CFType>, key: CFStringRef) -> Option<i64> {
+    let key = CFString::wrap_under_get_rule(key);
+    dict.find(&key)
+        .and_then(|value| value.downcast::<CFNumber>())
+        .and_then(|n| n.to_i64())
+}
+
+/// Capture a window into a BGRA buffer, returning (data, width, height)
+#[cfg(target_os = "macos")]
+fn capture_window_bgra(window_id: u64) -> Result<(Vec<u8>, usize, usize)> {
+    // CGRectNull asks for the window's own bounds
+    let null_rect = CGRect::new(
+        &CGPoint::new(f64::INFINITY, f64::INFINITY),
+        &CGSize::new(0.0, 0.0),
+    );
+
+    let image = create_image(
+        null_rect,
+        kCGWindowListOptionIncludingWindow,
+        window_id as u32,
+        kCGWindowImageBoundsIgnoreFraming | kCGWindowImageNominalResolution,
+    )
+    .ok_or_else(|| {
+        ScreenRecError::CaptureError(format!(
+            "Failed to capture window {} (closed, or screen recording permission missing)",
+            window_id
+        ))
+    })?;
+
+    if image.bits_per_pixel() != 32 {
+        return Err(ScreenRecError::CaptureError(format!(
+            "Unsupported window image format: {} bits per pixel",
+            image.bits_per_pixel()
+        )));
+    }
+
+    let width = image.width();
+    let height = image.height();
+    let data = image.data();
+
+    Ok((data.bytes().to_vec(), width, height))
+}
+
+/// List capturable top-level windows
+#[cfg(target_os = "windows")]
+pub fn list_windows() -> Result<Vec<WindowInfo>> {
+    unsafe extern "system" fn collect(hwnd: HWND, lparam: LPARAM) -> BOOL {
+        let handles = &mut *(lparam.0 as *mut Vec<HWND>);
+        if IsWindowVisible(hwnd).as_bool() {
+            handles.push(hwnd);
+        }
+        BOOL(1)
+    }
+
+    let mut handles: Vec<HWND> = Vec::new();
+    unsafe {
+        EnumWindows(Some(collect), LPARAM(&mut handles as *mut Vec<HWND> as isize)).map_err(|e| {
+            ScreenRecError::CaptureError(format!("Failed to enumerate windows: {}", e))
+        })?;
+    }
+
+    Ok(handles
+        .into_iter()
+        .filter_map(|hwnd| {
+            let (process_name, title) = crate::interactions::get_window_info(hwnd);
+            if title.is_empty() {
+                return None;
+            }
+            Some(WindowInfo {
+                id: hwnd.0 as u64,
+                title,
+                process_name,
+            })
+        })
+        .collect())
+}
+
+/// Capture a window into a BGRA buffer, returning (data, width, height)
+#[cfg(target_os = "windows")]
+fn capture_window_bgra(window_id: u64) -> Result<(Vec<u8>, usize, usize)> {
+    let hwnd = HWND(window_id as isize);
+
+    unsafe {
+        if IsIconic(hwnd).as_bool() {
+            return Err(ScreenRecError::CaptureError(
+                "Window is minimized and cannot be captured".to_string(),
+            ));
+        }
+
+        let mut rect = RECT::default();
+        GetWindowRect(hwnd, &mut rect).map_err(|e| {
+            ScreenRecError::CaptureError(format!("Failed to get window bounds: {}", e))
+        })?;
+
+        let width = (rect.right - rect.left).max(0) as usize;
+        let height = (rect.bottom - rect.top).max(0) as usize;
+        if width == 0 || height == 0 {
+            return Err(ScreenRecError::CaptureError(
+                "Window has no visible area".to_string(),
+            ));
+        }
+
+        let screen_dc = GetDC(HWND::default());
+        let mem_dc = CreateCompatibleDC(screen_dc);
+        let bitmap = CreateCompatibleBitmap(screen_dc, width as i32, height as i32);
+        let previous = SelectObject(mem_dc, bitmap);
+
+        // PW_RENDERFULLCONTENT makes DWM render the window even when it is occluded
+        let printed = PrintWindow(hwnd, mem_dc, PRINT_WINDOW_FLAGS(PW_RENDERFULLCONTENT)).as_bool();
+
+        let mut info = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: width as i32,
+                biHeight: -(height as i32), // Negative height = top-down rows
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let mut buffer = vec![0u8; width * height * 4];
+        let lines = GetDIBits(
+            mem_dc,
+            bitmap,
+            0,
+            height as u32,
+            Some(buffer.as_mut_ptr() as *mut std::ffi::c_void),
+            &mut info,
+            DIB_RGB_COLORS,
+        );
+
+        SelectObject(mem_dc, previous);
+        let _ = DeleteObject(bitmap);
+        let _ = DeleteDC(mem_dc);
+        ReleaseDC(HWND::default(), screen_dc);
+
+        if !printed || lines == 0 {
+            return Err(ScreenRecError::CaptureError(
+                "PrintWindow failed to capture window content".to_string(),
+            ));
+        }
+
+        Ok((buffer, width, height))
+    }
+}
+
+/// List capturable top-level windows
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+pub fn list_windows() -> Result<Vec<WindowInfo>> {
+    Err(ScreenRecError::PlatformNotSupported(
+        "Window capture is only supported on macOS and Windows".to_string(),
+    ))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+fn capture_window_bgra(_window_id: u64) -> Result<(Vec<u8>, usize, usize)> {
+    Err(ScreenRecError::PlatformNotSupported(
+        "Window capture is only supported on macOS and Windows".to_string(),
+    ))
+}
@@ -0,0 +1,117 @@
+//! RGB24 → YUV420P (and YUV444P, for `--lossless`) conversion for the FFmpeg
+//! library encoder
+//!
+//! Rows are independent once the plane strides are known, so the conversion is
+//! split across threads with rayon: one task per Y row and one per U/V row pair.
+//! The math is the same fixed-point approximation as the serial version, which
+//! is kept as the reference the parallel output is tested against. Either the
+//! BT.601 or the BT.709 matrix is used (`--colorspace`), both full range.
+
+use rayon::prelude::*;
+
+/// Destination planes of a YUV frame (U and V share a stride)
+pub struct YuvPlanes<'a> {
+    pub y: &'a mut [u8],
+    pub y_stride: usize,
+    pub u: &'a mut [u8],
+    pub v: &'a mut [u8],
+    pub uv_stride: usize,
+}
+
+/// RGB → YUV weights in 1/256ths (full range, chroma centred on 128)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Matrix {
+    y: [i32; 3],
+    u: [i32; 3],
+    v: [i32; 3],
+}
+
+/// Y = 0.299*R + 0.587*G + 0.114*B, U = -0.169*R - 0.331*G + 0.500*B,
+/// V = 0.500*R - 0.419*G - 0.081*B
+pub const BT601: Matrix = Matrix { y: [77, 150, 29], u: [-43, -85, 128], v: [128, -107, -21] };
+
+/// Y = 0.2126*R + 0.7152*G + 0.0722*B, U = -0.115*R - 0.385*G + 0.500*B,
+/// V = 0.500*R - 0.454*G - 0.046*B
+pub const BT709: Matrix = Matrix { y: [54, 183, 19], u: [-29, -99, 128], v: [128, -116, -12] };
+
+impl Matrix {
+    #[inline]
+    pub fn luma(&self, r: i32, g: i32, b: i32) -> u8 {
+        ((self.y[0] * r + self.y[1] * g + self.y[2] * b) >> 8) as u8
+    }
+
+    #[inline]
+    pub fn chroma(&self, r: i32, g: i32, b: i32) -> (u8, u8) {
+        let u = (((self.u[0] * r + self.u[1] * g + self.u[2] * b) >> 8) + 128).clamp(0, 255) as u8;
+        let v = (((self.v[0] * r + self.v[1] * g + self.v[2] * b) >> 8) + 128).clamp(0, 255) as u8;
+        (u, v)
+    }
+}
+
+fn convert_y_row(rgb_row: &[u8], y_row: &mut [u8], width: usize, matrix: &Matrix) {
+    for (x, y_val) in y_row[..width].iter_mut().enumerate() {
+        let p = &rgb_row[x * 3..x * 3 + 3];
+        *y_val = matrix.luma(p[0] as i32, p[1] as i32, p[2] as i32);
+    }
+}
+
+/// Chroma for one output row, sampled from the top-left pixel of each 2x2 block
+fn convert_uv_row(rgb_row: &[u8], u_row: &mut [u8], v_row: &mut [u8], width: usize, matrix: &Matrix) {
+    let uv_width = width.div_ceil(2);
+    for (uv_x, (u_val, v_val)) in u_row[..uv_width].iter_mut().zip(&mut v_row[..uv_width]).enumerate() {
+        let p = &rgb_row[uv_x * 6..uv_x * 6 + 3];
+        (*u_val, *v_val) = matrix.chroma(p[0] as i32, p[1] as i32, p[2] as i32);
+    }
+}
+
+/// Convert a packed RGB24 frame into YUV420P planes, in parallel over rows
+pub fn rgb_to_yuv420p(rgb: &[u8], width: usize, height: usize, matrix: &Matrix, planes: YuvPlanes) {
+    let rgb_stride = width * 3;
+
+    planes
+        .y
+        .par_chunks_mut(planes.y_stride)
+        .take(height)
+        .enumerate()
+        .for_each(|(y, y_row)| {
+            convert_y_row(&rgb[y * rgb_stride..], y_row, width, matrix);
+        });
+
+    planes
+        .u
+        .par_chunks_mut(planes.uv_stride)
+        .zip(planes.v.par_chunks_mut(planes.uv_stride))
+        .take(height.div_ceil(2))
+        .enumerate()
+        .for_each(|(uv_y, (u_row, v_row))| {
+            convert_uv_row(&rgb[uv_y * 2 * rgb_stride..], u_row, v_row, width, matrix);
+        });
+}
+
+/// Convert a packed RGB24 frame into YUV444P planes, with chroma for every pixel
+pub fn rgb_to_yuv444p(rgb: &[u8], width: usize, height: usize, matrix: &Matrix, planes: YuvPlanes) {
+    let rgb_stride = width * 3;
+
+    planes
+        .y
+        .par_chunks_mut(planes.y_stride)
+        .take(height)
+        .enumerate()
+        .for_each(|(y, y_row)| {
+            convert_y_row(&rgb[y * rgb_stride..], y_row, width, matrix);
+        });
+
+    planes
+        .u
+        .par_chunks_mut(planes.uv_stride)
+        .zip(planes.v.par_chunks_mut(planes.uv_stride))
+        .take(height)
+        .enumerate()
+        .for_each(|(y, (u_row, v_row))| {
+            let rgb_row = &rgb[y * rgb_stride..];
+            for (x, (u_val, v_val)) in u_row[..width].iter_mut().zip(&mut v_row[..width]).enumerate() {
+                let p = &rgb_row[x * 3..x * 3 + 3];
+                (*u_val, *v_val) = matrix.chroma(p[0] as i32, p[1] as i32, p[2] as i32);
+            }
+        });
+}
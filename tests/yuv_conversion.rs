@@ -0,0 +1,193 @@
+//! Benchmark and correctness check for the parallel RGB -> YUV420P conversion
+//!
+//! The converter module is private to the library, so it is compiled into this
+//! test directly. The 4K benchmark is ignored by default; run it to see the timings:
+//! ```bash
+//! cargo test --release --test yuv_conversion -- --ignored --nocapture
+//! ```
+
+#[path = "../src/yuv.rs"]
+mod yuv;
+
+use std::time::Instant;
+use yuv::{rgb_to_yuv420p, rgb_to_yuv444p, YuvPlanes, BT601, BT709};
+
+const WIDTH: usize = 3840;
+const HEIGHT: usize = 2160;
+/// FFmpeg pads plane rows for alignment; use padded strides like a real frame
+const Y_STRIDE: usize = WIDTH + 64;
+const UV_STRIDE: usize = WIDTH / 2 + 32;
+
+/// Deterministic noise so every pixel value and chroma sample gets exercised
+fn test_frame() -> Vec<u8> {
+    let mut state = 0x2545_f491_u32;
+    (0..WIDTH * HEIGHT * 3)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            (state >> 24) as u8
+        })
+        .collect()
+}
+
+/// Single-threaded reference for `rgb_to_yuv420p`, written as the original
+/// per-pixel loop so the parallel row split can be checked against it
+fn rgb_to_yuv420p_serial(rgb: &[u8], width: usize, height: usize, matrix: &yuv::Matrix, planes: YuvPlanes) {
+    // Process Y plane (all pixels)
+    for y in 0..height {
+        let y_row_offset = y * planes.y_stride;
+        let rgb_row_offset = y * width * 3;
+
+        for x in 0..width {
+            let rgb_idx = rgb_row_offset + x * 3;
+            let r = rgb[rgb_idx] as i32;
+            let g = rgb[rgb_idx + 1] as i32;
+            let b = rgb[rgb_idx + 2] as i32;
+
+            planes.y[y_row_offset + x] = matrix.luma(r, g, b);
+        }
+    }
+
+    // Process U and V planes (2x2 subsampling)
+    for y in (0..height).step_by(2) {
+        let uv_row_offset = (y / 2) * planes.uv_stride;
+        let rgb_row_offset = y * width * 3;
+
+        for x in (0..width).step_by(2) {
+            let rgb_idx = rgb_row_offset + x * 3;
+            let r = rgb[rgb_idx] as i32;
+            let g = rgb[rgb_idx + 1] as i32;
+            let b = rgb[rgb_idx + 2] as i32;
+
+            let (u_val, v_val) = matrix.chroma(r, g, b);
+            planes.u[uv_row_offset + x / 2] = u_val;
+            planes.v[uv_row_offset + x / 2] = v_val;
+        }
+    }
+}
+
+struct Planes {
+    y: Vec<u8>,
+    u: Vec<u8>,
+    v: Vec<u8>,
+}
+
+impl Planes {
+    fn new() -> Self {
+        Self {
+            y: vec![0; Y_STRIDE * HEIGHT],
+            u: vec![0; UV_STRIDE * HEIGHT / 2],
+            v: vec![0; UV_STRIDE * HEIGHT / 2],
+        }
+    }
+
+    fn as_planes(&mut self) -> YuvPlanes<'_> {
+        YuvPlanes {
+            y: &mut self.y,
+            y_stride: Y_STRIDE,
+            u: &mut self.u,
+            v: &mut self.v,
+            uv_stride: UV_STRIDE,
+        }
+    }
+}
+
+#[test]
+#[ignore = "4K benchmark; run with --release --ignored --nocapture"]
+fn test_parallel_conversion_matches_serial_at_4k() {
+    let rgb = test_frame();
+
+    let mut serial = Planes::new();
+    let started = Instant::now();
+    rgb_to_yuv420p_serial(&rgb, WIDTH, HEIGHT, &BT601, serial.as_planes());
+    let serial_time = started.elapsed();
+
+    // Start rayon's thread pool outside the timed run
+    let mut parallel = Planes::new();
+    rgb_to_yuv420p(&rgb, WIDTH, HEIGHT, &BT601, parallel.as_planes());
+
+    let started = Instant::now();
+    rgb_to_yuv420p(&rgb, WIDTH, HEIGHT, &BT601, parallel.as_planes());
+    let parallel_time = started.elapsed();
+
+    println!(
+        "{}x{} RGB -> YUV420P: serial {:?}, parallel {:?} ({:.1}x)",
+        WIDTH,
+        HEIGHT,
+        serial_time,
+        parallel_time,
+        serial_time.as_secs_f64() / parallel_time.as_secs_f64()
+    );
+
+    assert!(serial.y == parallel.y, "Y plane differs");
+    assert!(serial.u == parallel.u, "U plane differs");
+    assert!(serial.v == parallel.v, "V plane differs");
+}
+
+#[test]
+fn test_parallel_conversion_handles_odd_dimensions() {
+    // Odd sizes leave a partial 2x2 block on the right and bottom edges
+    let (width, height) = (7, 5);
+    let rgb: Vec<u8> = (0..width * height * 3).map(|i| (i * 37 % 256) as u8).collect();
+
+    let mut outputs = Vec::new();
+    for parallel in [false, true] {
+        let (mut y, mut u, mut v) = (vec![0; 8 * height], vec![0; 4 * 3], vec![0; 4 * 3]);
+        let planes = YuvPlanes { y: &mut y, y_stride: 8, u: &mut u, v: &mut v, uv_stride: 4 };
+        if parallel {
+            rgb_to_yuv420p(&rgb, width, height, &BT601, planes);
+        } else {
+            rgb_to_yuv420p_serial(&rgb, width, height, &BT601, planes);
+        }
+        outputs.push((y, u, v));
+    }
+
+    assert_eq!(outputs[0], outputs[1]);
+}
+
+#[test]
+fn test_444_conversion_keeps_chroma_for_every_pixel() {
+    let (width, height) = (7, 5);
+    let mut rgb: Vec<u8> = (0..width * height * 3).map(|i| (i * 37 % 256) as u8).collect();
+    // A black and a red pixel side by side, in one 2x2 block
+    rgb[..6].copy_from_slice(&[0, 0, 0, 255, 0, 0]);
+
+    let (mut y, mut u, mut v) = (vec![0; 8 * height], vec![0; 8 * height], vec![0; 8 * height]);
+    rgb_to_yuv444p(&rgb, width, height, &BT601, YuvPlanes { y: &mut y, y_stride: 8, u: &mut u, v: &mut v, uv_stride: 8 });
+
+    let (mut y420, mut u420, mut v420) = (vec![0; 8 * height], vec![0; 4 * 3], vec![0; 4 * 3]);
+    rgb_to_yuv420p(&rgb, width, height, &BT601, YuvPlanes { y: &mut y420, y_stride: 8, u: &mut u420, v: &mut v420, uv_stride: 4 });
+
+    // Same luma; 4:2:0 chroma is the 4:4:4 chroma of each block's top-left pixel
+    assert_eq!(y, y420);
+    for (uv_y, row) in (0..height).step_by(2).enumerate() {
+        for (uv_x, col) in (0..width).step_by(2).enumerate() {
+            assert_eq!(u[row * 8 + col], u420[uv_y * 4 + uv_x]);
+            assert_eq!(v[row * 8 + col], v420[uv_y * 4 + uv_x]);
+        }
+    }
+    // The red pixel keeps its own chroma instead of sharing the black one's
+    assert_eq!((u[0], v[0]), (128, 128));
+    assert!(v[1] > 200, "V of red was {}", v[1]);
+}
+
+#[test]
+fn test_matrices_convert_known_colors() {
+    // (RGB, BT.601 YUV, BT.709 YUV), full range
+    let cases = [
+        ([255, 255, 255], [255, 128, 128], [255, 128, 128]),
+        ([0, 0, 0], [0, 128, 128], [0, 128, 128]),
+        ([255, 0, 0], [76, 85, 255], [53, 99, 255]),
+        ([0, 255, 0], [149, 43, 21], [182, 29, 12]),
+        ([0, 0, 255], [28, 255, 107], [18, 255, 116]),
+    ];
+
+    for (rgb, bt601, bt709) in cases {
+        for (matrix, expected) in [(&BT601, bt601), (&BT709, bt709)] {
+            let (mut y, mut u, mut v) = ([0], [0], [0]);
+            rgb_to_yuv420p(&rgb, 1, 1, matrix, YuvPlanes { y: &mut y, y_stride: 1, u: &mut u, v: &mut v, uv_stride: 1 });
+            assert_eq!([y[0], u[0], v[0]], expected, "RGB {:?} with {:?}", rgb, matrix);
+        }
+    }
+}